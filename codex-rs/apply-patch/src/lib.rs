@@ -1,6 +1,8 @@
 mod parser;
+mod partial;
 mod seek_sequence;
 mod standalone_executable;
+mod unified_diff;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -15,6 +17,7 @@ pub use parser::ParseError;
 use parser::ParseError::*;
 use parser::UpdateFileChunk;
 pub use parser::parse_patch;
+use seek_sequence::FuzzyMatch;
 use similar::TextDiff;
 use thiserror::Error;
 use tree_sitter::LanguageError;
@@ -123,7 +126,7 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ApplyPatchFileChange {
     Add {
         content: String,
@@ -136,6 +139,8 @@ pub enum ApplyPatchFileChange {
         move_path: Option<PathBuf>,
         /// new_content that will result after the unified_diff is applied.
         new_content: String,
+        /// The content of the file before the update.
+        original_content: String,
     },
 }
 
@@ -214,6 +219,17 @@ impl ApplyPatchAction {
 /// cwd must be an absolute path so that we can resolve relative paths in the
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+    maybe_parse_apply_patch_verified_with_reader(argv, cwd, &|path| std::fs::read_to_string(path))
+}
+
+/// Same as [`maybe_parse_apply_patch_verified`], but reads the files that the
+/// patch deletes or updates with `read_to_string`, e.g. to verify the patch
+/// against a view of the file system that differs from the real one.
+pub fn maybe_parse_apply_patch_verified_with_reader(
+    argv: &[String],
+    cwd: &Path,
+    read_to_string: &dyn Fn(&Path) -> std::io::Result<String>,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a bash -lc
     // script. In these cases, report an explicit error rather than applying the patch.
     match argv {
@@ -259,7 +275,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Add { content: contents });
                     }
                     Hunk::DeleteFile { .. } => {
-                        let content = match std::fs::read_to_string(&path) {
+                        let content = match read_to_string(&path) {
                             Ok(content) => content,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(
@@ -275,10 +291,29 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                     Hunk::UpdateFile {
                         move_path, chunks, ..
                     } => {
+                        let original_contents = match read_to_string(&path) {
+                            Ok(content) => content,
+                            Err(e) => {
+                                return MaybeApplyPatchVerified::CorrectnessError(
+                                    ApplyPatchError::IoError(IoError {
+                                        context: format!(
+                                            "Failed to read file to update {}",
+                                            path.display()
+                                        ),
+                                        source: e,
+                                    }),
+                                );
+                            }
+                        };
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match unified_diff_from_contents(
+                            &path,
+                            original_contents.clone(),
+                            &chunks,
+                            1,
+                        ) {
                             Ok(diff) => diff,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
@@ -290,6 +325,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 unified_diff,
                                 move_path: move_path.map(|p| cwd.join(p)),
                                 new_content: contents,
+                                original_content: original_contents,
                             },
                         );
                     }
@@ -498,6 +534,17 @@ pub fn apply_patch(
 
     apply_hunks(&hunks, stdout, stderr)?;
 
+    let lines: Vec<&str> = patch.trim().lines().collect();
+    let modes = unified_diff::changed_modes(&lines);
+    if !modes.is_empty() {
+        writeln!(
+            stdout,
+            "File modes are not changed by apply_patch; use chmod for: {}",
+            modes.join(", ")
+        )
+        .map_err(ApplyPatchError::from)?;
+    }
+
     Ok(())
 }
 
@@ -561,6 +608,19 @@ pub struct AffectedPaths {
     pub added: Vec<PathBuf>,
     pub modified: Vec<PathBuf>,
     pub deleted: Vec<PathBuf>,
+    /// Hunks that did not match their file exactly.
+    pub fuzzy_hunks: Vec<FuzzyHunk>,
+}
+
+/// A hunk whose lines did not match the file exactly and that was applied
+/// where the fuzzy matcher placed it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzyHunk {
+    pub path: PathBuf,
+    /// The 1-based line of the original file where the hunk was applied.
+    pub line: usize,
+    /// How similar the patch's lines were to the file's, from 0.0 to 1.0.
+    pub confidence: f64,
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
@@ -573,6 +633,7 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
+    let mut fuzzy_hunks: Vec<FuzzyHunk> = Vec::new();
     for hunk in hunks {
         match hunk {
             Hunk::AddFile { path, contents } => {
@@ -597,8 +658,12 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                 move_path,
                 chunks,
             } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
+                let AppliedPatch {
+                    new_contents,
+                    fuzzy_hunks: file_fuzzy_hunks,
+                    ..
+                } = derive_new_contents_from_chunks(path, chunks)?;
+                fuzzy_hunks.extend(file_fuzzy_hunks);
                 if let Some(dest) = move_path {
                     if let Some(parent) = dest.parent()
                         && !parent.as_os_str().is_empty()
@@ -624,12 +689,14 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
         added,
         modified,
         deleted,
+        fuzzy_hunks,
     })
 }
 
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
+    fuzzy_hunks: Vec<FuzzyHunk>,
 }
 
 /// Return *only* the new file contents (joined into a single `String`) after
@@ -647,7 +714,16 @@ fn derive_new_contents_from_chunks(
             }));
         }
     };
+    derive_new_contents_from_original(path, original_contents, chunks)
+}
 
+/// Same as [`derive_new_contents_from_chunks`] for a file whose contents are
+/// `original_contents`.
+fn derive_new_contents_from_original(
+    path: &Path,
+    original_contents: String,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<AppliedPatch, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
 
     // Drop the trailing empty element that results from the final newline so
@@ -656,7 +732,8 @@ fn derive_new_contents_from_chunks(
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let mut fuzzy_hunks = Vec::new();
+    let replacements = compute_replacements(&original_lines, path, chunks, &mut fuzzy_hunks)?;
     let new_lines = apply_replacements(original_lines, &replacements);
     let mut new_lines = new_lines;
     if !new_lines.last().is_some_and(String::is_empty) {
@@ -666,16 +743,19 @@ fn derive_new_contents_from_chunks(
     Ok(AppliedPatch {
         original_contents,
         new_contents,
+        fuzzy_hunks,
     })
 }
 
 /// Compute a list of replacements needed to transform `original_lines` into the
 /// new lines, given the patch `chunks`. Each replacement is returned as
-/// `(start_index, old_len, new_lines)`.
+/// `(start_index, old_len, new_lines)`. Chunks that only matched by fuzzy
+/// matching are added to `fuzzy_hunks`.
 fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
+    fuzzy_hunks: &mut Vec<FuzzyHunk>,
 ) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
     let mut line_index: usize = 0;
@@ -683,20 +763,32 @@ fn compute_replacements(
     for chunk in chunks {
         // If a chunk has a `change_context`, we use seek_sequence to find it, then
         // adjust our `line_index` to continue from there.
+        let mut confidence: f64 = 1.0;
         if let Some(ctx_line) = &chunk.change_context {
-            if let Some(idx) = seek_sequence::seek_sequence(
-                original_lines,
-                std::slice::from_ref(ctx_line),
-                line_index,
-                false,
-            ) {
+            let ctx_pattern = std::slice::from_ref(ctx_line);
+            if let Some(idx) =
+                seek_sequence::seek_sequence(original_lines, ctx_pattern, line_index, false)
+            {
                 line_index = idx + 1;
             } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
-                )));
+                match seek_sequence::fuzzy_seek_sequence(
+                    original_lines,
+                    ctx_pattern,
+                    line_index,
+                    false,
+                ) {
+                    FuzzyMatch::Found(candidate) => {
+                        line_index = candidate.index + 1;
+                        confidence = candidate.score;
+                    }
+                    FuzzyMatch::Ambiguous(..) | FuzzyMatch::NotFound(_) => {
+                        return Err(ApplyPatchError::ComputeReplacements(format!(
+                            "Failed to find context '{}' in {}",
+                            ctx_line,
+                            path.display()
+                        )));
+                    }
+                }
             }
         }
 
@@ -724,8 +816,13 @@ fn compute_replacements(
         // located reliably.
 
         let mut pattern: &[String] = &chunk.old_lines;
-        let mut found =
-            seek_sequence::seek_sequence(original_lines, pattern, line_index, chunk.is_end_of_file);
+        let mut found = seek_sequence::seek_sequence_near(
+            original_lines,
+            pattern,
+            line_index,
+            chunk.is_end_of_file,
+            chunk.line_hint,
+        );
 
         let mut new_slice: &[String] = &chunk.new_lines;
 
@@ -737,23 +834,77 @@ fn compute_replacements(
                 new_slice = &new_slice[..new_slice.len() - 1];
             }
 
-            found = seek_sequence::seek_sequence(
+            found = seek_sequence::seek_sequence_near(
                 original_lines,
                 pattern,
                 line_index,
                 chunk.is_end_of_file,
+                chunk.line_hint,
             );
         }
 
+        let mut new_lines = new_slice.to_vec();
+        if found.is_none() {
+            // The file may have drifted since the patch was written.
+            match seek_sequence::fuzzy_seek_sequence(
+                original_lines,
+                pattern,
+                line_index,
+                chunk.change_context.is_some(),
+            ) {
+                FuzzyMatch::Found(candidate) => {
+                    let matched = &original_lines[candidate.index..candidate.index + pattern.len()];
+                    new_lines = adapt_to_file(matched, pattern, new_slice);
+                    confidence = confidence.min(candidate.score);
+                    found = Some(candidate.index);
+                }
+                FuzzyMatch::Ambiguous(first, second) => {
+                    let (first, second) = if first.index < second.index {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+                    return Err(ApplyPatchError::ComputeReplacements(format!(
+                        "Expected lines match {} equally well at line {} ({}) and line {} ({}); add more context or an @@ line to tell them apart:\n{}",
+                        path.display(),
+                        first.index + 1,
+                        format_confidence(first.score),
+                        second.index + 1,
+                        format_confidence(second.score),
+                        chunk.old_lines.join("\n"),
+                    )));
+                }
+                FuzzyMatch::NotFound(nearest) => {
+                    let mut message = format!(
+                        "Failed to find expected lines in {}:\n{}",
+                        path.display(),
+                        chunk.old_lines.join("\n"),
+                    );
+                    if let Some(nearest) = nearest {
+                        let end = nearest.index + pattern.len();
+                        message.push_str(&format!(
+                            "\nNearest candidate, lines {}-{} ({}):\n{}",
+                            nearest.index + 1,
+                            end,
+                            format_confidence(nearest.score),
+                            original_lines[nearest.index..end].join("\n"),
+                        ));
+                    }
+                    return Err(ApplyPatchError::ComputeReplacements(message));
+                }
+            }
+        }
+
         if let Some(start_idx) = found {
-            replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
+            if confidence < 1.0 {
+                fuzzy_hunks.push(FuzzyHunk {
+                    path: path.to_path_buf(),
+                    line: start_idx + 1,
+                    confidence,
+                });
+            }
+            replacements.push((start_idx, pattern.len(), new_lines));
             line_index = start_idx + pattern.len();
-        } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
-                path.display(),
-                chunk.old_lines.join("\n"),
-            )));
         }
     }
 
@@ -762,6 +913,64 @@ fn compute_replacements(
     Ok(replacements)
 }
 
+/// Adapts a chunk's `new_lines` to the file lines its `pattern` fuzzily
+/// `matched`: the chunk's context lines keep the file's version of them, and
+/// added lines are indented like the file.
+fn adapt_to_file(matched: &[String], pattern: &[String], new_lines: &[String]) -> Vec<String> {
+    let mut adapted = reindent(matched, pattern, new_lines);
+    for op in similar::capture_diff_slices(similar::Algorithm::Myers, pattern, new_lines) {
+        if let similar::DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            adapted[new_index..new_index + len]
+                .clone_from_slice(&matched[old_index..old_index + len]);
+        }
+    }
+    adapted
+}
+
+/// When the file indents the `matched` lines more deeply than the patch's
+/// `pattern`, by the same prefix throughout, indents `new_lines` the same way.
+fn reindent(matched: &[String], pattern: &[String], new_lines: &[String]) -> Vec<String> {
+    let indentation = |line: &str| line.len() - line.trim_start().len();
+    let mut prefix: Option<&str> = None;
+    for (file_line, pattern_line) in matched.iter().zip(pattern) {
+        if file_line.trim().is_empty() || pattern_line.trim().is_empty() {
+            continue;
+        }
+        let file_indent = &file_line[..indentation(file_line)];
+        let pattern_indent = &pattern_line[..indentation(pattern_line)];
+        let Some(line_prefix) = file_indent.strip_suffix(pattern_indent) else {
+            return new_lines.to_vec();
+        };
+        if prefix.is_some_and(|prefix| prefix != line_prefix) {
+            return new_lines.to_vec();
+        }
+        prefix = Some(line_prefix);
+    }
+    match prefix {
+        Some(prefix) if !prefix.is_empty() => new_lines
+            .iter()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    line.clone()
+                } else {
+                    format!("{prefix}{line}")
+                }
+            })
+            .collect(),
+        _ => new_lines.to_vec(),
+    }
+}
+
+/// `confidence` as a percentage for messages.
+fn format_confidence(confidence: f64) -> String {
+    format!("{:.0}% match", confidence * 100.0)
+}
+
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
 /// returning the modified file contents as a vector of lines.
 fn apply_replacements(
@@ -812,13 +1021,44 @@ pub fn unified_diff_from_chunks_with_context(
     let AppliedPatch {
         original_contents,
         new_contents,
+        ..
     } = derive_new_contents_from_chunks(path, chunks)?;
+    Ok(unified_diff_between(
+        original_contents,
+        new_contents,
+        context,
+    ))
+}
+
+fn unified_diff_from_contents(
+    path: &Path,
+    original_contents: String,
+    chunks: &[UpdateFileChunk],
+    context: usize,
+) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
+    let AppliedPatch {
+        original_contents,
+        new_contents,
+        ..
+    } = derive_new_contents_from_original(path, original_contents, chunks)?;
+    Ok(unified_diff_between(
+        original_contents,
+        new_contents,
+        context,
+    ))
+}
+
+fn unified_diff_between(
+    original_contents: String,
+    new_contents: String,
+    context: usize,
+) -> ApplyPatchFileUpdate {
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
-    Ok(ApplyPatchFileUpdate {
+    ApplyPatchFileUpdate {
         unified_diff,
         content: new_contents,
-    })
+    }
 }
 
 /// Print the summary of changes in git-style format.
@@ -837,6 +1077,21 @@ pub fn print_summary(
     for path in &affected.deleted {
         writeln!(out, "D {}", path.display())?;
     }
+    if !affected.fuzzy_hunks.is_empty() {
+        writeln!(
+            out,
+            "Some hunks did not match exactly and were applied at the closest match:"
+        )?;
+        for hunk in &affected.fuzzy_hunks {
+            writeln!(
+                out,
+                "  {}:{} ({})",
+                hunk.path.display(),
+                hunk.line,
+                format_confidence(hunk.confidence)
+            )?;
+        }
+    }
     Ok(())
 }
 
@@ -1175,6 +1430,65 @@ PATCH"#,
         assert_eq!(contents, "line2\n");
     }
 
+    #[test]
+    fn test_unified_diff_updates_and_adds_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&path, "one\ntwo\nthree\nfour\n").unwrap();
+        let patch = format!(
+            r#"--- {path}	2025-01-01 00:00:00
++++ {path}	2025-01-02 00:00:00
+@@ -2,2 +2,2 @@
+ two
+-three
++THREE
+--- /dev/null
++++ {added}
+@@ -0,0 +1 @@
++new"#,
+            path = path.display(),
+            added = added.display(),
+        );
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        let stdout_str = String::from_utf8(stdout).unwrap();
+        let expected_out = format!(
+            "Success. Updated the following files:\nA {}\nM {}\n",
+            added.display(),
+            path.display()
+        );
+        assert_eq!(stdout_str, expected_out);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "one\ntwo\nTHREE\nfour\n"
+        );
+        assert_eq!(fs::read_to_string(&added).unwrap(), "new\n");
+    }
+
+    #[test]
+    fn test_unified_diff_applies_repeated_lines_at_their_line_number() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("repeated.txt");
+        fs::write(&path, "a\nx = 1\nb\nx = 1\nc\n").unwrap();
+        let patch = format!(
+            r#"--- {path}
++++ {path}
+@@ -4 +4 @@
+-x = 1
++x = 2"#,
+            path = path.display(),
+        );
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "a\nx = 1\nb\nx = 2\nc\n"
+        );
+    }
+
     /// Verify that a single `Update File` hunk with multiple change chunks can update different
     /// parts of a file and that the file is listed only once in the summary.
     #[test]
@@ -1337,6 +1651,64 @@ PATCH"#,
         assert_eq!(String::from_utf8(stderr).unwrap(), "");
     }
 
+    #[test]
+    fn test_fuzzy_match_applies_hunk_to_drifted_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        fs::write(
+            &path,
+            "fn main() {\n    let total = compute(1, 2);\n    println!(\"{total}\");\n}\n",
+        )
+        .unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ let total = compute(1, 2, 3);
+-println!("{{total}}");
++println!("total: {{total}}");"#,
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        let expected_out = format!(
+            "Success. Updated the following files:\nM {path}\nSome hunks did not match exactly and were applied at the closest match:\n  {path}:2 (95% match)\n",
+            path = path.display()
+        );
+        assert_eq!(String::from_utf8(stdout).unwrap(), expected_out);
+        assert_eq!(String::from_utf8(stderr).unwrap(), "");
+        // The context line keeps the file's version, and the new line is
+        // indented like the file.
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn main() {\n    let total = compute(1, 2);\n    println!(\"total: {total}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_refusal_shows_nearest_candidate() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("greek.txt");
+        fs::write(&path, "alpha\nbeta\ngamma\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ alpha
+-delta
++epsilon"#,
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        assert!(apply_patch(&patch, &mut stdout, &mut stderr).is_err());
+        let expected_err = format!(
+            "Failed to find expected lines in {}:\nalpha\ndelta\nNearest candidate, lines 1-2 (80% match):\nalpha\nbeta\n",
+            path.display()
+        );
+        assert_eq!(String::from_utf8(stderr).unwrap(), expected_err);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alpha\nbeta\ngamma\n");
+    }
+
     #[test]
     fn test_unified_diff() {
         // Start with a file containing four lines.
@@ -1594,6 +1966,7 @@ g
                         .to_string(),
                         move_path: None,
                         new_content: "updated session directory content\n".to_string(),
+                        original_content: "session directory content\n".to_string(),
                     },
                 )]),
                 patch: argv[1].clone(),
@@ -22,7 +22,12 @@
 //!
 //! The parser below is a little more lenient than the explicit spec and allows for
 //! leading/trailing whitespace around patch markers.
+//!
+//! Unified diffs, as produced by `diff -u` and `git diff`, are also accepted
+//! and parsed into the same hunks by [`crate::unified_diff`].
 use crate::ApplyPatchArgs;
+use crate::unified_diff::is_unified_diff;
+use crate::unified_diff::parse_unified_diff;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -101,6 +106,11 @@ pub struct UpdateFileChunk {
     /// If set to true, `old_lines` must occur at the end of the source file.
     /// (Tolerance around trailing newlines should be encouraged.)
     pub is_end_of_file: bool,
+
+    /// The 0-based line at which the patch expects `old_lines` to start,
+    /// which picks among several places they match. Unified diffs give it in
+    /// their `@@` headers.
+    pub line_hint: Option<usize>,
 }
 
 pub fn parse_patch(patch: &str) -> Result<ApplyPatchArgs, ParseError> {
@@ -153,6 +163,13 @@ enum ParseMode {
 
 fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, ParseError> {
     let lines: Vec<&str> = patch.trim().lines().collect();
+    if let Some(diff_lines) = unified_diff_lines(&lines, &mode) {
+        return Ok(ApplyPatchArgs {
+            hunks: parse_unified_diff(diff_lines)?,
+            patch: diff_lines.join("\n"),
+            workdir: None,
+        });
+    }
     let lines: &[&str] = match check_patch_boundaries_strict(&lines) {
         Ok(()) => &lines,
         Err(e) => match mode {
@@ -182,6 +199,25 @@ fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, Pars
     })
 }
 
+/// Returns the lines of a unified diff, if `lines` hold one instead of a
+/// `*** Begin Patch` patch. In lenient mode the diff may be wrapped in a
+/// heredoc, as for patches.
+fn unified_diff_lines<'a>(lines: &'a [&'a str], mode: &ParseMode) -> Option<&'a [&'a str]> {
+    if is_unified_diff(lines) {
+        return Some(lines);
+    }
+    match (mode, lines) {
+        (ParseMode::Lenient, [first, inner @ .., last])
+            if (first == &"<<EOF" || first == &"<<'EOF'" || first == &"<<\"EOF\"")
+                && last.ends_with("EOF")
+                && is_unified_diff(inner) =>
+        {
+            Some(inner)
+        }
+        _ => None,
+    }
+}
+
 /// Checks the start and end lines of the patch text for `apply_patch`,
 /// returning an error if they do not match the expected markers.
 fn check_patch_boundaries_strict(lines: &[&str]) -> Result<(), ParseError> {
@@ -377,6 +413,7 @@ fn parse_update_file_chunk(
         old_lines: Vec::new(),
         new_lines: Vec::new(),
         is_end_of_file: false,
+        line_hint: None,
     };
     let mut parsed_lines = 0;
     for line in &lines[start_index..] {
@@ -498,7 +535,8 @@ fn test_parse_patch() {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
                     new_lines: vec!["    return 123".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    line_hint: None,
                 }]
             }
         ]
@@ -525,7 +563,8 @@ fn test_parse_patch() {
                     change_context: None,
                     old_lines: vec![],
                     new_lines: vec!["line".to_string()],
-                    is_end_of_file: false
+                    is_end_of_file: false,
+                    line_hint: None,
                 }],
             },
             AddFile {
@@ -556,6 +595,7 @@ fn test_parse_patch() {
                 old_lines: vec!["import foo".to_string()],
                 new_lines: vec!["import foo".to_string(), "bar".to_string()],
                 is_end_of_file: false,
+                line_hint: None,
             }],
         }]
     );
@@ -576,6 +616,7 @@ fn test_parse_patch_lenient() {
             old_lines: vec!["import foo".to_string()],
             new_lines: vec!["import foo".to_string(), "bar".to_string()],
             is_end_of_file: false,
+            line_hint: None,
         }],
     }];
     let expected_error =
@@ -721,7 +762,8 @@ fn test_update_file_chunk() {
                     "add".to_string(),
                     "context2".to_string()
                 ],
-                is_end_of_file: false
+                is_end_of_file: false,
+                line_hint: None,
             }),
             6
         ))
@@ -733,7 +775,8 @@ fn test_update_file_chunk() {
                 change_context: None,
                 old_lines: vec![],
                 new_lines: vec!["line".to_string()],
-                is_end_of_file: true
+                is_end_of_file: true,
+                line_hint: None,
             }),
             3
         ))
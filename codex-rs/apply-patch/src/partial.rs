@@ -0,0 +1,260 @@
+//! Narrowing an [`ApplyPatchAction`] down to some of its hunks, for patches
+//! the user approved only in part.
+//!
+//! Hunks are numbered the way they appear in each file's `unified_diff`:
+//! added and deleted files have a single hunk 0, and so do updates that only
+//! move a file. The narrowed action carries a fresh `*** Begin Patch` text
+//! whose chunks are checked against the content they should produce; when
+//! the chunks do not reproduce it exactly, the file's whole content is
+//! replaced instead.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use similar::DiffOp;
+use similar::TextDiff;
+
+use crate::AppliedPatch;
+use crate::ApplyPatchAction;
+use crate::ApplyPatchFileChange;
+use crate::Hunk;
+use crate::derive_new_contents_from_original;
+use crate::parse_patch;
+use crate::unified_diff_between;
+
+/// Context lines of the hunks in `ApplyPatchFileChange::Update::unified_diff`.
+const HUNK_CONTEXT: usize = 1;
+
+impl ApplyPatchAction {
+    /// The action applying only the hunks for which `keep(path, index)`
+    /// returns true, or `None` when none of them are kept.
+    pub fn retain_hunks(&self, keep: impl Fn(&Path, usize) -> bool) -> Option<ApplyPatchAction> {
+        let mut paths: Vec<&PathBuf> = self.changes.keys().collect();
+        paths.sort();
+
+        let mut changes = HashMap::new();
+        let mut patch = String::from("*** Begin Patch\n");
+        for path in paths {
+            let Some(change) = self.changes.get(path) else {
+                continue;
+            };
+            let section = match change {
+                ApplyPatchFileChange::Add { content } => keep(path, 0).then(|| {
+                    let mut section = format!("*** Add File: {}\n", path.display());
+                    for line in content.lines() {
+                        let _ = writeln!(section, "+{line}");
+                    }
+                    (change.clone(), section)
+                }),
+                ApplyPatchFileChange::Delete { .. } => keep(path, 0).then(|| {
+                    (
+                        change.clone(),
+                        format!("*** Delete File: {}\n", path.display()),
+                    )
+                }),
+                ApplyPatchFileChange::Update {
+                    move_path,
+                    new_content,
+                    original_content,
+                    ..
+                } => retain_update_hunks(
+                    path,
+                    move_path.as_deref(),
+                    original_content,
+                    new_content,
+                    |index| keep(path, index),
+                ),
+            };
+            if let Some((change, section)) = section {
+                patch.push_str(&section);
+                changes.insert(path.clone(), change);
+            }
+        }
+        patch.push_str("*** End Patch");
+
+        if changes.is_empty() {
+            return None;
+        }
+        Some(ApplyPatchAction {
+            changes,
+            patch,
+            cwd: self.cwd.clone(),
+        })
+    }
+}
+
+/// The update keeping only the hunks for which `keep(index)` returns true,
+/// with its section of the patch.
+fn retain_update_hunks(
+    path: &Path,
+    move_path: Option<&Path>,
+    original: &str,
+    new: &str,
+    keep: impl Fn(usize) -> bool,
+) -> Option<(ApplyPatchFileChange, String)> {
+    let diff = TextDiff::from_lines(original, new);
+    let groups = diff.grouped_ops(HUNK_CONTEXT);
+    let kept: Vec<bool> = (0..groups.len()).map(&keep).collect();
+    let keeps_move = groups.is_empty() && move_path.is_some() && keep(0);
+    if !keeps_move && !kept.contains(&true) {
+        return None;
+    }
+
+    let old_lines = diff.old_slices();
+    let new_lines = diff.new_slices();
+    let mut content = String::new();
+    let mut position = 0;
+    for (group, kept) in groups.iter().zip(&kept) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        content.extend(old_lines[position..old_range.start].iter().copied());
+        if *kept {
+            let new_range = first.new_range().start..last.new_range().end;
+            content.extend(new_lines[new_range].iter().copied());
+        } else {
+            content.extend(old_lines[old_range.clone()].iter().copied());
+        }
+        position = old_range.end;
+    }
+    content.extend(old_lines[position..].iter().copied());
+    // `apply_patch` always ends updated files with a newline.
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+
+    let mut header = format!("*** Update File: {}\n", path.display());
+    if let Some(move_path) = move_path {
+        let _ = writeln!(header, "*** Move to: {}", move_path.display());
+    }
+    let mut section = header.clone();
+    for (group, _) in groups.iter().zip(&kept).filter(|(_, kept)| **kept) {
+        section.push_str(&chunk_text(&diff, group, old_lines.len()));
+    }
+    if !reproduces(&section, original, &content) {
+        section = header;
+        section.push_str("@@\n");
+        for line in original.lines() {
+            let _ = writeln!(section, "-{line}");
+        }
+        for line in content.lines() {
+            let _ = writeln!(section, "+{line}");
+        }
+        section.push_str("*** End of File\n");
+    }
+
+    let update = unified_diff_between(original.to_string(), content, HUNK_CONTEXT);
+    Some((
+        ApplyPatchFileChange::Update {
+            unified_diff: update.unified_diff,
+            move_path: move_path.map(Path::to_path_buf),
+            new_content: update.content,
+            original_content: original.to_string(),
+        },
+        section,
+    ))
+}
+
+/// One hunk of `diff` as a `*** Begin Patch` chunk.
+fn chunk_text(diff: &TextDiff<'_, '_, '_, str>, group: &[DiffOp], old_len: usize) -> String {
+    let mut chunk = String::from("@@\n");
+    for op in group {
+        for change in diff.iter_changes(op) {
+            let sign = match change.tag() {
+                similar::ChangeTag::Equal => ' ',
+                similar::ChangeTag::Delete => '-',
+                similar::ChangeTag::Insert => '+',
+            };
+            let _ = writeln!(chunk, "{sign}{}", change.value().trim_end_matches('\n'));
+        }
+    }
+    if group.last().is_some_and(|op| op.old_range().end == old_len) {
+        chunk.push_str("*** End of File\n");
+    }
+    chunk
+}
+
+/// Whether applying the update `section` to `original` yields `expected`.
+fn reproduces(section: &str, original: &str, expected: &str) -> bool {
+    let Ok(parsed) = parse_patch(&format!("*** Begin Patch\n{section}*** End Patch")) else {
+        return false;
+    };
+    let [Hunk::UpdateFile { path, chunks, .. }] = parsed.hunks.as_slice() else {
+        return false;
+    };
+    matches!(
+        derive_new_contents_from_original(path, original.to_string(), chunks),
+        Ok(AppliedPatch { new_contents, .. }) if new_contents == expected
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MaybeApplyPatchVerified;
+    use crate::maybe_parse_apply_patch_verified;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn action(dir: &Path, patch: &str) -> ApplyPatchAction {
+        let argv = vec!["apply_patch".to_string(), patch.to_string()];
+        match maybe_parse_apply_patch_verified(&argv, dir) {
+            MaybeApplyPatchVerified::Body(action) => action,
+            other => panic!("expected a patch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn keeps_only_selected_hunks_and_files() {
+        let dir = tempdir().unwrap();
+        let lines: Vec<String> = (1..=12).map(|n| format!("line {n}")).collect();
+        fs::write(dir.path().join("a.txt"), lines.join("\n") + "\n").unwrap();
+        let action = action(
+            dir.path(),
+            "*** Begin Patch
+*** Update File: a.txt
+@@
+-line 2
++line two
+@@
+-line 11
++line eleven
+*** Add File: b.txt
++new file
+*** End Patch",
+        );
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+
+        let partial = action
+            .retain_hunks(|path, index| path == a && index == 1)
+            .expect("a hunk is kept");
+        assert_eq!(partial.changes().len(), 1);
+        let Some(ApplyPatchFileChange::Update { new_content, .. }) = partial.changes().get(&a)
+        else {
+            panic!("expected an update of a.txt");
+        };
+        let mut expected = lines;
+        expected[10] = "line eleven".to_string();
+        assert_eq!(new_content, &(expected.join("\n") + "\n"));
+
+        // The narrowed patch applies to the file as the selection says.
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        crate::apply_patch(&partial.patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), *new_content);
+        assert!(!b.exists());
+
+        assert_eq!(
+            action
+                .retain_hunks(|path, _| path == b)
+                .map(|p| p.changes().len()),
+            Some(1)
+        );
+        assert!(action.retain_hunks(|_, _| false).is_none());
+    }
+}
@@ -31,93 +31,283 @@ pub(crate) fn seek_sequence(
     } else {
         start
     };
-    // Exact match first.
-    for i in search_start..=lines.len().saturating_sub(pattern.len()) {
-        if lines[i..i + pattern.len()] == *pattern {
-            return Some(i);
+    for same in LINE_COMPARISONS {
+        for i in search_start..=lines.len().saturating_sub(pattern.len()) {
+            if matches_at(lines, pattern, i, same) {
+                return Some(i);
+            }
         }
     }
-    // Then rstrip match.
-    for i in search_start..=lines.len().saturating_sub(pattern.len()) {
-        let mut ok = true;
-        for (p_idx, pat) in pattern.iter().enumerate() {
-            if lines[i + p_idx].trim_end() != pat.trim_end() {
-                ok = false;
-                break;
+    None
+}
+
+fn matches_at(
+    lines: &[String],
+    pattern: &[String],
+    i: usize,
+    same: fn(&str, &str) -> bool,
+) -> bool {
+    lines[i..i + pattern.len()]
+        .iter()
+        .zip(pattern)
+        .all(|(line, pat)| same(line, pat))
+}
+
+/// The ways [`seek_sequence`] compares lines, from the strictest. The last
+/// one normalises common Unicode punctuation to its ASCII equivalent, so that
+/// diffs authored with plain ASCII characters still apply to files that
+/// contain typographic dashes, quotes, etc., like the fuzzy behaviour of
+/// `git apply`.
+const LINE_COMPARISONS: [fn(&str, &str) -> bool; 4] = [
+    |line, pat| line == pat,
+    |line, pat| line.trim_end() == pat.trim_end(),
+    |line, pat| line.trim() == pat.trim(),
+    |line, pat| normalise(line) == normalise(pat),
+];
+
+/// Like [`seek_sequence`], but when `pattern` occurs more than once, picks
+/// the occurrence nearest to `hint`, the line where the patch expects it,
+/// among those that match as strictly as the first one.
+pub(crate) fn seek_sequence_near(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    eof: bool,
+    hint: Option<usize>,
+) -> Option<usize> {
+    let first = seek_sequence(lines, pattern, start, eof)?;
+    let Some(hint) = hint else {
+        return Some(first);
+    };
+    if pattern.is_empty() || eof {
+        return Some(first);
+    }
+    let Some(same) = LINE_COMPARISONS
+        .into_iter()
+        .find(|same| matches_at(lines, pattern, first, *same))
+    else {
+        return Some(first);
+    };
+    (start..=lines.len() - pattern.len())
+        .filter(|&i| matches_at(lines, pattern, i, same))
+        .min_by_key(|&i| i.abs_diff(hint))
+}
+
+/// `s` trimmed, with typographic punctuation and odd spaces replaced by their
+/// ASCII equivalents.
+fn normalise(s: &str) -> String {
+    s.trim()
+        .chars()
+        .map(|c| match c {
+            // Various dash / hyphen code-points → ASCII '-'
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+            | '\u{2212}' => '-',
+            // Fancy single quotes → '\''
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            // Fancy double quotes → '"'
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            // Non-breaking space and other odd spaces → normal space
+            '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
+            | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
+            | '\u{3000}' => ' ',
+            other => other,
+        })
+        .collect::<String>()
+}
+
+/// Lowest average line similarity at which [`fuzzy_seek_sequence`] accepts a
+/// match.
+const MIN_FUZZY_SCORE: f64 = 0.85;
+
+/// Lowest similarity of any single line within an accepted match.
+const MIN_LINE_SCORE: f64 = 0.5;
+
+/// Candidates whose scores are this close to the best one make the match
+/// ambiguous.
+const AMBIGUITY_MARGIN: f64 = 0.05;
+
+/// Lowest score of a candidate reported as the nearest one when nothing
+/// matches. Lower scores are not worth computing precisely.
+const MIN_NEAREST_SCORE: f64 = 0.5;
+
+/// Where a pattern could go, with the average similarity of its lines, from
+/// 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Candidate {
+    pub index: usize,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FuzzyMatch {
+    /// The best candidate, clearly ahead of any other.
+    Found(Candidate),
+    /// The two best candidates scored about the same.
+    Ambiguous(Candidate, Candidate),
+    /// No candidate scored high enough. Holds the nearest one, if any.
+    NotFound(Option<Candidate>),
+}
+
+/// Scored search for `pattern` within `lines` at or after `start`, for when
+/// [`seek_sequence`] finds nothing. Lines are compared ignoring indentation
+/// and runs of whitespace, and each pair is scored by its edit distance, so
+/// that small edits to the file since the patch was written are tolerated.
+///
+/// When the patch anchored the search with an `@@` context line, `anchored`
+/// resolves a tie in favor of the candidate closest after the anchor.
+pub(crate) fn fuzzy_seek_sequence(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    anchored: bool,
+) -> FuzzyMatch {
+    if pattern.is_empty() || start + pattern.len() > lines.len() {
+        return FuzzyMatch::NotFound(None);
+    }
+
+    let pattern: Vec<Vec<char>> = pattern.iter().map(|line| fuzzy_normalise(line)).collect();
+    let lines: Vec<Vec<char>> = lines.iter().map(|line| fuzzy_normalise(line)).collect();
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut best_score = 0.0_f64;
+    for index in start..=lines.len() - pattern.len() {
+        // Windows that cannot come close to the best one are not worth
+        // finishing.
+        let bound = (best_score - AMBIGUITY_MARGIN).max(MIN_NEAREST_SCORE);
+        let mut total = 0.0;
+        let mut min_line_score = 1.0_f64;
+        let mut pruned = false;
+        for (offset, pat) in pattern.iter().enumerate() {
+            let remaining = (pattern.len() - offset - 1) as f64;
+            let line_bound = (bound * pattern.len() as f64 - total - remaining).max(0.0);
+            match line_similarity(&lines[index + offset], pat, line_bound) {
+                Some(score) => {
+                    total += score;
+                    min_line_score = min_line_score.min(score);
+                }
+                None => {
+                    pruned = true;
+                    break;
+                }
             }
         }
-        if ok {
-            return Some(i);
+        if pruned {
+            continue;
         }
-    }
-    // Finally, trim both sides to allow more lenience.
-    for i in search_start..=lines.len().saturating_sub(pattern.len()) {
-        let mut ok = true;
-        for (p_idx, pat) in pattern.iter().enumerate() {
-            if lines[i + p_idx].trim() != pat.trim() {
-                ok = false;
-                break;
-            }
+        let mut score = total / pattern.len() as f64;
+        // A line that has nothing in common with the pattern is an edit the
+        // patch does not know about.
+        if min_line_score < MIN_LINE_SCORE {
+            score = score.min(MIN_FUZZY_SCORE - AMBIGUITY_MARGIN);
         }
-        if ok {
-            return Some(i);
+        if score >= bound {
+            best_score = best_score.max(score);
+            candidates.push(Candidate { index, score });
         }
     }
 
-    // ------------------------------------------------------------------
-    // Final, most permissive pass – attempt to match after *normalising*
-    // common Unicode punctuation to their ASCII equivalents so that diffs
-    // authored with plain ASCII characters can still be applied to source
-    // files that contain typographic dashes / quotes, etc.  This mirrors the
-    // fuzzy behaviour of `git apply` which ignores minor byte-level
-    // differences when locating context lines.
-    // ------------------------------------------------------------------
-
-    fn normalise(s: &str) -> String {
-        s.trim()
-            .chars()
-            .map(|c| match c {
-                // Various dash / hyphen code-points → ASCII '-'
-                '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
-                | '\u{2212}' => '-',
-                // Fancy single quotes → '\''
-                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
-                // Fancy double quotes → '"'
-                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
-                // Non-breaking space and other odd spaces → normal space
-                '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
-                | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
-                | '\u{3000}' => ' ',
-                other => other,
-            })
-            .collect::<String>()
-    }
-
-    for i in search_start..=lines.len().saturating_sub(pattern.len()) {
-        let mut ok = true;
-        for (p_idx, pat) in pattern.iter().enumerate() {
-            if normalise(&lines[i + p_idx]) != normalise(pat) {
-                ok = false;
-                break;
-            }
-        }
-        if ok {
-            return Some(i);
+    candidates.retain(|candidate| candidate.score >= best_score - AMBIGUITY_MARGIN);
+    let Some(best) = candidates
+        .iter()
+        .copied()
+        .max_by(|a, b| a.score.total_cmp(&b.score))
+    else {
+        return FuzzyMatch::NotFound(None);
+    };
+    if best.score < MIN_FUZZY_SCORE {
+        return FuzzyMatch::NotFound(Some(best));
+    }
+    if anchored {
+        // Candidates are in file order, so the first is closest to the anchor.
+        let closest = candidates
+            .iter()
+            .copied()
+            .find(|candidate| candidate.score >= MIN_FUZZY_SCORE)
+            .unwrap_or(best);
+        return FuzzyMatch::Found(closest);
+    }
+    match candidates
+        .iter()
+        .copied()
+        .find(|candidate| candidate.index != best.index)
+    {
+        Some(runner_up) => FuzzyMatch::Ambiguous(best, runner_up),
+        None => FuzzyMatch::Found(best),
+    }
+}
+
+/// `line` without indentation and with runs of whitespace collapsed, for
+/// [`fuzzy_seek_sequence`].
+fn fuzzy_normalise(line: &str) -> Vec<char> {
+    let mut normalised = Vec::new();
+    for word in normalise(line).split_whitespace() {
+        if !normalised.is_empty() {
+            normalised.push(' ');
         }
+        normalised.extend(word.chars());
     }
+    normalised
+}
 
-    None
+/// Similarity of two lines from 0.0 to 1.0, one minus their edit distance
+/// relative to the longer line. Returns `None` as soon as it is clear that
+/// the similarity is below `at_least`.
+fn line_similarity(a: &[char], b: &[char], at_least: f64) -> Option<f64> {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return Some(1.0);
+    }
+    // The distance is at least the difference in length.
+    let similarity = |distance: usize| 1.0 - distance as f64 / longest as f64;
+    if similarity(a.len().abs_diff(b.len())) < at_least {
+        return None;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let score = similarity(previous[b.len()]);
+    (score >= at_least).then_some(score)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Candidate;
+    use super::FuzzyMatch;
+    use super::fuzzy_seek_sequence;
     use super::seek_sequence;
+    use super::seek_sequence_near;
     use std::string::ToString;
 
     fn to_vec(strings: &[&str]) -> Vec<String> {
         strings.iter().map(ToString::to_string).collect()
     }
 
+    #[test]
+    fn test_hint_picks_the_nearest_occurrence() {
+        let lines = to_vec(&["x", "dup", "y", "dup", "z", "dup"]);
+        let pattern = to_vec(&["dup"]);
+        assert_eq!(
+            seek_sequence_near(&lines, &pattern, 0, false, None),
+            Some(1)
+        );
+        assert_eq!(
+            seek_sequence_near(&lines, &pattern, 0, false, Some(4)),
+            Some(3)
+        );
+        assert_eq!(
+            seek_sequence_near(&lines, &pattern, 4, false, Some(0)),
+            Some(5)
+        );
+    }
+
     #[test]
     fn test_exact_match_finds_sequence() {
         let lines = to_vec(&["foo", "bar", "baz"]);
@@ -148,4 +338,51 @@ mod tests {
         // Should not panic – must return None when pattern cannot possibly fit.
         assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
     }
+
+    #[test]
+    fn test_fuzzy_match_tolerates_indentation_and_small_edits() {
+        let lines = to_vec(&["fn main() {", "    let x = f(1, 2);", "    g(x);", "}"]);
+        let pattern = to_vec(&["let x = f(1, 2, 3);", "g(x);"]);
+        let FuzzyMatch::Found(Candidate { index, score }) =
+            fuzzy_seek_sequence(&lines, &pattern, 0, false)
+        else {
+            panic!("expected a fuzzy match");
+        };
+        assert_eq!(index, 1);
+        assert!(score > 0.85 && score < 1.0, "score: {score}");
+    }
+
+    #[test]
+    fn test_fuzzy_match_is_ambiguous_unless_anchored() {
+        let lines = to_vec(&[
+            "fn a() {",
+            "    x += 1;",
+            "}",
+            "fn b() {",
+            "    x += 1;",
+            "}",
+        ]);
+        let pattern = to_vec(&["x += 2;", "}"]);
+        assert!(matches!(
+            fuzzy_seek_sequence(&lines, &pattern, 0, false),
+            FuzzyMatch::Ambiguous(..)
+        ));
+        assert!(matches!(
+            fuzzy_seek_sequence(&lines, &pattern, 3, true),
+            FuzzyMatch::Found(Candidate { index: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn test_fuzzy_match_reports_nearest_candidate() {
+        let lines = to_vec(&["alpha", "beta", "gamma"]);
+        let pattern = to_vec(&["alpha", "delta"]);
+        let FuzzyMatch::NotFound(Some(Candidate { index, score })) =
+            fuzzy_seek_sequence(&lines, &pattern, 0, false)
+        else {
+            panic!("expected no match");
+        };
+        assert_eq!(index, 0);
+        assert!((score - 0.8).abs() < 1e-9, "score: {score}");
+    }
 }
@@ -0,0 +1,762 @@
+//! Parses standard unified diffs, as produced by `diff -u` and `git diff`,
+//! into the same [`Hunk`]s as the `*** Begin Patch` format.
+//!
+//! Each `@@` hunk becomes an [`UpdateFileChunk`] located by its lines rather
+//! than by its line numbers, so that patches apply the same way in either
+//! format. `/dev/null` sides turn into added and deleted files, and git's
+//! `rename from`/`rename to` headers into moves. File modes are not tracked
+//! by `apply_patch`, so `old mode`/`new mode` headers are accepted but do not
+//! change the file's permissions; [`changed_modes`] lists them so that the
+//! caller can say so.
+use std::path::PathBuf;
+
+use crate::parser::Hunk;
+use crate::parser::ParseError;
+use crate::parser::ParseError::*;
+use crate::parser::UpdateFileChunk;
+
+const GIT_DIFF_MARKER: &str = "diff --git ";
+const OLD_FILE_MARKER: &str = "--- ";
+const NEW_FILE_MARKER: &str = "+++ ";
+const HUNK_MARKER: &str = "@@ ";
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file";
+const DEV_NULL: &str = "/dev/null";
+
+/// Whether `lines` start like a unified diff rather than a `*** Begin Patch`
+/// patch.
+pub(crate) fn is_unified_diff(lines: &[&str]) -> bool {
+    match lines {
+        [first, ..] if first.starts_with(GIT_DIFF_MARKER) => true,
+        [first, second, ..] => {
+            first.starts_with(OLD_FILE_MARKER) && second.starts_with(NEW_FILE_MARKER)
+        }
+        _ => false,
+    }
+}
+
+/// What the headers of one file's section say about it.
+#[derive(Default)]
+struct FileHeader {
+    /// Paths from the `diff --git` line, used when there are no `---`/`+++`
+    /// lines, as for empty files.
+    git_paths: Option<(String, String)>,
+    old_path: Option<String>,
+    new_path: Option<String>,
+    rename_from: Option<String>,
+    rename_to: Option<String>,
+    new_file: bool,
+    deleted_file: bool,
+}
+
+pub(crate) fn parse_unified_diff(lines: &[&str]) -> Result<Vec<Hunk>, ParseError> {
+    let mut hunks = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        if starts_file(lines, index) {
+            let (hunk, parsed_lines) = parse_file(lines, index)?;
+            hunks.extend(hunk);
+            index += parsed_lines;
+        } else {
+            // Like `git apply`, skip anything between files, such as the
+            // `Index:` lines of other tools.
+            index += 1;
+        }
+    }
+    if hunks.is_empty() {
+        let modes = changed_modes(lines);
+        if !modes.is_empty() {
+            return Err(InvalidPatchError(format!(
+                "The diff only changes the mode of {}, which apply_patch cannot do; use chmod instead",
+                modes.join(", ")
+            )));
+        }
+        return Err(InvalidPatchError(
+            "The diff does not change any files".to_string(),
+        ));
+    }
+    Ok(hunks)
+}
+
+/// The files whose mode the `old mode`/`new mode` headers of the diff in
+/// `lines` change, which `apply_patch` leaves as they are.
+pub(crate) fn changed_modes(lines: &[&str]) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut git_paths = None;
+    for line in lines {
+        if let Some(header) = line.strip_prefix(GIT_DIFF_MARKER) {
+            git_paths = split_git_paths(header);
+        } else if line.starts_with(OLD_FILE_MARKER) || line.starts_with(HUNK_MARKER) {
+            // Extended headers end here.
+            git_paths = None;
+        } else if line.starts_with("new mode ")
+            && let Some((_, new)) = git_paths.take()
+        {
+            paths.push(new);
+        }
+    }
+    paths
+}
+
+fn starts_file(lines: &[&str], index: usize) -> bool {
+    lines[index].starts_with(GIT_DIFF_MARKER)
+        || (lines[index].starts_with(OLD_FILE_MARKER)
+            && lines
+                .get(index + 1)
+                .is_some_and(|line| line.starts_with(NEW_FILE_MARKER)))
+}
+
+/// Parses the section of the file starting at `lines[start]`. Returns its
+/// hunk, if it changes the file's contents or name, and the number of lines
+/// parsed.
+fn parse_file(lines: &[&str], start: usize) -> Result<(Option<Hunk>, usize), ParseError> {
+    let mut header = FileHeader::default();
+    let mut index = start;
+    if let Some(paths) = lines[index].strip_prefix(GIT_DIFF_MARKER) {
+        header.git_paths = split_git_paths(paths);
+        index += 1;
+        // Extended headers, up to the first `---` line or the next file.
+        while let Some(line) = lines.get(index) {
+            if line.starts_with(OLD_FILE_MARKER) || line.starts_with(GIT_DIFF_MARKER) {
+                break;
+            }
+            if let Some(path) = line.strip_prefix("rename from ") {
+                header.rename_from = Some(unquote(path));
+            } else if let Some(path) = line.strip_prefix("rename to ") {
+                header.rename_to = Some(unquote(path));
+            } else if line.starts_with("new file mode ") {
+                header.new_file = true;
+            } else if line.starts_with("deleted file mode ") {
+                header.deleted_file = true;
+            } else if line.starts_with("copy from ") || line.starts_with("copy to ") {
+                return Err(invalid_hunk(
+                    "Copies are not supported; add the new file instead",
+                    index,
+                ));
+            } else if line.starts_with("GIT binary patch") || line.starts_with("Binary files ") {
+                return Err(invalid_hunk("Binary diffs are not supported", index));
+            } else if line.starts_with(HUNK_MARKER) {
+                return Err(invalid_hunk("Expected '---' and '+++' lines", index));
+            }
+            index += 1;
+        }
+    }
+
+    let mut chunks = Vec::new();
+    // Lines of an added file, and whether it ends without a newline.
+    let mut added_lines = Vec::new();
+    let mut added_without_newline = false;
+    if lines
+        .get(index)
+        .is_some_and(|line| line.starts_with(OLD_FILE_MARKER))
+    {
+        header.old_path = lines[index]
+            .strip_prefix(OLD_FILE_MARKER)
+            .map(parse_header_path);
+        header.new_path = lines
+            .get(index + 1)
+            .and_then(|line| line.strip_prefix(NEW_FILE_MARKER))
+            .map(parse_header_path);
+        if header.new_path.is_none() {
+            return Err(invalid_hunk("Expected a '+++' line", index + 1));
+        }
+        index += 2;
+
+        while lines
+            .get(index)
+            .is_some_and(|line| line.starts_with(HUNK_MARKER))
+        {
+            let (chunk, no_newline, parsed_lines) = parse_chunk(lines, index)?;
+            added_lines.extend(chunk.new_lines.iter().cloned());
+            added_without_newline = no_newline;
+            chunks.push(chunk);
+            index += parsed_lines;
+        }
+    }
+
+    let parsed_lines = index - start;
+    let old_path = header.old_path.as_deref();
+    let new_path = header.new_path.as_deref();
+    let hunk = if header.new_file || old_path == Some(DEV_NULL) {
+        let Some(path) = new_path
+            .filter(|path| *path != DEV_NULL)
+            .map(strip_new_prefix)
+            .or_else(|| header.git_paths.as_ref().map(|(_, new)| new.clone()))
+        else {
+            return Err(invalid_hunk("Missing the path of the added file", start));
+        };
+        let mut contents = added_lines.join("\n");
+        if !added_lines.is_empty() && !added_without_newline {
+            contents.push('\n');
+        }
+        Some(Hunk::AddFile {
+            path: PathBuf::from(path),
+            contents,
+        })
+    } else if header.deleted_file || new_path == Some(DEV_NULL) {
+        let Some(path) = old_path
+            .filter(|path| *path != DEV_NULL)
+            .map(strip_old_prefix)
+            .or_else(|| header.git_paths.as_ref().map(|(old, _)| old.clone()))
+        else {
+            return Err(invalid_hunk("Missing the path of the deleted file", start));
+        };
+        Some(Hunk::DeleteFile {
+            path: PathBuf::from(path),
+        })
+    } else {
+        let path = header
+            .rename_from
+            .clone()
+            .or_else(|| old_path.map(strip_old_prefix))
+            .or_else(|| header.git_paths.as_ref().map(|(old, _)| old.clone()));
+        let move_to = header
+            .rename_to
+            .clone()
+            .or_else(|| new_path.map(strip_new_prefix))
+            .filter(|move_to| Some(move_to) != path.as_ref());
+        match path {
+            // Only the mode changed, which `apply_patch` does not track.
+            Some(_) if chunks.is_empty() && move_to.is_none() => None,
+            Some(path) => Some(Hunk::UpdateFile {
+                path: PathBuf::from(path),
+                move_path: move_to.map(PathBuf::from),
+                chunks,
+            }),
+            None => return Err(invalid_hunk("Missing the path of the file", start)),
+        }
+    };
+    Ok((hunk, parsed_lines))
+}
+
+/// The line numbers of a `@@ -a,b +c,d @@` header.
+struct HunkRange {
+    /// The first line of the old side, counting from 1, or 0 when the hunk
+    /// adds lines to an empty file.
+    old_start: usize,
+    old_count: usize,
+    new_count: usize,
+}
+
+/// A line of a hunk's body.
+enum BodyLine<'a> {
+    /// A context (` `), removed (`-`) or added (`+`) line.
+    Line(char, &'a str),
+    /// `\ No newline at end of file`, about the line before it.
+    NoNewline,
+}
+
+fn parse_body_line(line: &str) -> Option<BodyLine<'_>> {
+    if line.starts_with(NO_NEWLINE_MARKER) {
+        return Some(BodyLine::NoNewline);
+    }
+    match line.chars().next() {
+        // Editors and models drop the space of blank context lines.
+        None => Some(BodyLine::Line(' ', "")),
+        Some(kind @ (' ' | '-' | '+')) => Some(BodyLine::Line(kind, &line[1..])),
+        Some(_) => None,
+    }
+}
+
+/// Parses the `@@` hunk at `lines[start]`. Returns it as a chunk, whether the
+/// new file ends without a newline after it, and the number of lines parsed.
+fn parse_chunk(lines: &[&str], start: usize) -> Result<(UpdateFileChunk, bool, usize), ParseError> {
+    let Some(range) = parse_hunk_range(lines[start]) else {
+        return Err(invalid_hunk(
+            &format!("Invalid hunk header '{}'", lines[start]),
+            start,
+        ));
+    };
+    let (body, parsed_lines) = match counted_body(lines, start, &range) {
+        Some(counted) => counted,
+        None => uncounted_body(lines, start, &range),
+    };
+
+    let mut chunk = UpdateFileChunk {
+        change_context: None,
+        old_lines: Vec::new(),
+        new_lines: Vec::new(),
+        is_end_of_file: false,
+        line_hint: range.old_start.checked_sub(1),
+    };
+    let mut no_newline = false;
+    let mut last_kind = ' ';
+    for line in body {
+        match line {
+            BodyLine::NoNewline => {
+                chunk.is_end_of_file = true;
+                no_newline = last_kind != '-';
+            }
+            BodyLine::Line(kind, text) => {
+                if kind != '+' {
+                    chunk.old_lines.push(text.to_string());
+                }
+                if kind != '-' {
+                    chunk.new_lines.push(text.to_string());
+                }
+                last_kind = kind;
+            }
+        }
+    }
+
+    if chunk.old_lines.is_empty() && range.old_start > 0 {
+        return Err(invalid_hunk(
+            "Hunks that only add lines need context lines to locate them",
+            start,
+        ));
+    }
+    Ok((chunk, no_newline, parsed_lines))
+}
+
+/// The body of the hunk at `lines[start]`, bounded by the line counts of its
+/// header, so that removed `-- ` and added `++ ` lines are not mistaken for
+/// file headers. Returns it with the number of lines parsed, or `None` if the
+/// counts do not fit the lines that follow.
+fn counted_body<'a>(
+    lines: &[&'a str],
+    start: usize,
+    range: &HunkRange,
+) -> Option<(Vec<BodyLine<'a>>, usize)> {
+    let mut body = Vec::new();
+    let (mut old_lines, mut new_lines) = (0, 0);
+    let mut index = start + 1;
+    while old_lines < range.old_count || new_lines < range.new_count {
+        let line = parse_body_line(lines.get(index)?)?;
+        if let BodyLine::Line(kind, _) = line {
+            if kind != '+' {
+                old_lines += 1;
+            }
+            if kind != '-' {
+                new_lines += 1;
+            }
+            if old_lines > range.old_count || new_lines > range.new_count {
+                return None;
+            }
+        }
+        body.push(line);
+        index += 1;
+    }
+    while lines
+        .get(index)
+        .is_some_and(|line| line.starts_with(NO_NEWLINE_MARKER))
+    {
+        body.push(BodyLine::NoNewline);
+        index += 1;
+    }
+    // Blank lines past the counts are left over from blank context lines.
+    let mut end = index;
+    while lines.get(end).is_some_and(|line| line.is_empty()) {
+        end += 1;
+    }
+    let ends_hunk = match lines.get(end) {
+        None => true,
+        Some(line) => {
+            line.starts_with(HUNK_MARKER)
+                || starts_file(lines, end)
+                || parse_body_line(line).is_none()
+        }
+    };
+    ends_hunk.then_some((body, end - start))
+}
+
+/// The body of the hunk at `lines[start]` when its line counts are wrong, as
+/// models often get them: it ends where the next hunk or file starts, or at
+/// the first line that cannot belong to a hunk. The counts then only settle
+/// whether trailing blank lines belong to the hunk.
+fn uncounted_body<'a>(
+    lines: &[&'a str],
+    start: usize,
+    range: &HunkRange,
+) -> (Vec<BodyLine<'a>>, usize) {
+    let mut body = Vec::new();
+    let (mut old_lines, mut new_lines) = (0_usize, 0_usize);
+    let mut trailing_blank_lines = 0;
+    let mut index = start + 1;
+    while let Some(line) = lines.get(index) {
+        if starts_file(lines, index) || line.starts_with(HUNK_MARKER) {
+            break;
+        }
+        let Some(body_line) = parse_body_line(line) else {
+            break;
+        };
+        if let BodyLine::Line(kind, _) = body_line {
+            if line.is_empty() {
+                trailing_blank_lines += 1;
+            } else {
+                trailing_blank_lines = 0;
+            }
+            if kind != '+' {
+                old_lines += 1;
+            }
+            if kind != '-' {
+                new_lines += 1;
+            }
+        }
+        body.push(body_line);
+        index += 1;
+    }
+
+    let excess = usize::min(
+        old_lines.saturating_sub(range.old_count),
+        new_lines.saturating_sub(range.new_count),
+    )
+    .min(trailing_blank_lines);
+    let mut dropped = 0;
+    while dropped < excess
+        && let Some(line) = body.pop()
+    {
+        if let BodyLine::Line(..) = line {
+            dropped += 1;
+        }
+    }
+    (body, index - start)
+}
+
+/// The line numbers of a `@@ -a,b +c,d @@` header, where a missing count
+/// means one line.
+fn parse_hunk_range(header: &str) -> Option<HunkRange> {
+    let mut ranges = header.strip_prefix(HUNK_MARKER)?.split(' ');
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+    let parse = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse(old)?;
+    let (_, new_count) = parse(new)?;
+    Some(HunkRange {
+        old_start,
+        old_count,
+        new_count,
+    })
+}
+
+/// The path of a `---` or `+++` line, without the timestamp `diff -u` adds
+/// after a tab.
+fn parse_header_path(path: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    unquote(path)
+}
+
+fn strip_old_prefix(path: &str) -> String {
+    path.strip_prefix("a/").unwrap_or(path).to_string()
+}
+
+fn strip_new_prefix(path: &str) -> String {
+    path.strip_prefix("b/").unwrap_or(path).to_string()
+}
+
+/// Splits the `a/old b/new` paths of a `diff --git` line. When the paths
+/// contain spaces, the split that names the same file on both sides wins.
+fn split_git_paths(paths: &str) -> Option<(String, String)> {
+    if paths.starts_with('"') {
+        let end = closing_quote(paths)?;
+        let (old, new) = paths.split_at(end + 1);
+        let (old, new) = (unquote(old), unquote(new.trim_start()));
+        return Some((strip_old_prefix(&old), strip_new_prefix(&new)));
+    }
+    let splits: Vec<usize> = paths.match_indices(" b/").map(|(i, _)| i).collect();
+    let split = splits
+        .iter()
+        .copied()
+        .find(|&i| paths[..i].strip_prefix("a/") == Some(&paths[i + 3..]))
+        .or_else(|| splits.first().copied())?;
+    let (old, new) = (&paths[..split], &paths[split + 1..]);
+    Some((strip_old_prefix(old), strip_new_prefix(&unquote(new))))
+}
+
+fn closing_quote(quoted: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in quoted.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Undoes git's C-style quoting of paths with unusual characters.
+fn unquote(path: &str) -> String {
+    let Some(inner) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return path.to_string();
+    };
+    let mut bytes = Vec::new();
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some(digit @ '0'..='7') => {
+                let mut value = digit.to_digit(8).unwrap_or(0);
+                for _ in 0..2 {
+                    if let Some(digit) = chars.peek().and_then(|c| c.to_digit(8)) {
+                        value = value * 8 + digit;
+                        chars.next();
+                    }
+                }
+                bytes.push(value as u8);
+            }
+            Some(other) => {
+                let mut buf = [0; 4];
+                bytes.extend_from_slice(other.encode_utf8(&mut buf).as_bytes());
+            }
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn invalid_hunk(message: &str, index: usize) -> ParseError {
+    InvalidHunkError {
+        message: message.to_string(),
+        line_number: index + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse(diff: &str) -> Result<Vec<Hunk>, ParseError> {
+        let lines: Vec<&str> = diff.lines().collect();
+        parse_unified_diff(&lines)
+    }
+
+    #[test]
+    fn test_git_diff_with_add_delete_rename_and_update() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ mod a;
+ fn a() {}
+-fn b() {}
++fn b() -> u8 { 1 }
+ fn c() {}
+@@ -10,2 +10,3 @@ fn c() {}
+ fn x() {}
++fn y() {}
+ fn z() {}
+diff --git a/new.txt b/new.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/new.txt
+@@ -0,0 +1,2 @@
++hello
++world
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 4444444..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/from.rs b/to.rs
+similarity index 90%
+rename from from.rs
+rename to to.rs
+index 5555555..6666666 100644
+--- a/from.rs
++++ b/to.rs
+@@ -1 +1 @@
+-old
++new
+diff --git a/same.rs b/renamed.rs
+similarity index 100%
+rename from same.rs
+rename to renamed.rs
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![
+                Hunk::UpdateFile {
+                    path: PathBuf::from("src/lib.rs"),
+                    move_path: None,
+                    chunks: vec![
+                        UpdateFileChunk {
+                            change_context: None,
+                            old_lines: vec![
+                                "fn a() {}".to_string(),
+                                "fn b() {}".to_string(),
+                                "fn c() {}".to_string(),
+                            ],
+                            new_lines: vec![
+                                "fn a() {}".to_string(),
+                                "fn b() -> u8 { 1 }".to_string(),
+                                "fn c() {}".to_string(),
+                            ],
+                            is_end_of_file: false,
+                            line_hint: Some(0),
+                        },
+                        UpdateFileChunk {
+                            change_context: None,
+                            old_lines: vec!["fn x() {}".to_string(), "fn z() {}".to_string()],
+                            new_lines: vec![
+                                "fn x() {}".to_string(),
+                                "fn y() {}".to_string(),
+                                "fn z() {}".to_string(),
+                            ],
+                            is_end_of_file: false,
+                            line_hint: Some(9),
+                        },
+                    ],
+                },
+                Hunk::AddFile {
+                    path: PathBuf::from("new.txt"),
+                    contents: "hello\nworld\n".to_string(),
+                },
+                Hunk::DeleteFile {
+                    path: PathBuf::from("old.txt"),
+                },
+                Hunk::UpdateFile {
+                    path: PathBuf::from("from.rs"),
+                    move_path: Some(PathBuf::from("to.rs")),
+                    chunks: vec![UpdateFileChunk {
+                        change_context: None,
+                        old_lines: vec!["old".to_string()],
+                        new_lines: vec!["new".to_string()],
+                        is_end_of_file: false,
+                        line_hint: Some(0),
+                    }],
+                },
+                Hunk::UpdateFile {
+                    path: PathBuf::from("same.rs"),
+                    move_path: Some(PathBuf::from("renamed.rs")),
+                    chunks: Vec::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_plain_unified_diff_with_timestamps_and_missing_newline() {
+        let diff = "\
+--- notes.txt\t2025-01-01 00:00:00.000000000 +0000
++++ notes.txt\t2025-01-02 00:00:00.000000000 +0000
+@@ -1,2 +1,2 @@
+ first
+
+-last
+\\ No newline at end of file
++final
+\\ No newline at end of file
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("notes.txt"),
+                move_path: None,
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: vec!["first".to_string(), String::new(), "last".to_string()],
+                    new_lines: vec!["first".to_string(), String::new(), "final".to_string()],
+                    is_end_of_file: true,
+                    line_hint: Some(0),
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_mode_change_only_is_skipped() {
+        let diff = "\
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+diff --git \"a/with space.txt\" \"b/with space.txt\"
+new file mode 100644
+index 0000000..e69de29
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::AddFile {
+                path: PathBuf::from("with space.txt"),
+                contents: String::new(),
+            }])
+        );
+        let lines: Vec<&str> = diff.lines().collect();
+        assert_eq!(changed_modes(&lines), vec!["run.sh".to_string()]);
+    }
+
+    #[test]
+    fn test_mode_changes_alone_are_reported() {
+        let diff = "\
+diff --git a/run.sh b/run.sh
+old mode 100644
+new mode 100755
+";
+        assert_eq!(
+            parse(diff),
+            Err(InvalidPatchError(
+                "The diff only changes the mode of run.sh, which apply_patch cannot do; use chmod instead"
+                    .to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_hunk_counts_keep_header_like_lines_in_the_hunk() {
+        let diff = "\
+--- a/notes.md
++++ b/notes.md
+@@ -5,3 +5,3 @@
+ intro
+--- old rule
++++ new rule
+ outro
+";
+        assert_eq!(
+            parse(diff),
+            Ok(vec![Hunk::UpdateFile {
+                path: PathBuf::from("notes.md"),
+                move_path: None,
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: vec![
+                        "intro".to_string(),
+                        "-- old rule".to_string(),
+                        "outro".to_string(),
+                    ],
+                    new_lines: vec![
+                        "intro".to_string(),
+                        "++ new rule".to_string(),
+                        "outro".to_string(),
+                    ],
+                    is_end_of_file: false,
+                    line_hint: Some(4),
+                }],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_rejects_binary_diffs() {
+        let diff = "\
+diff --git a/logo.png b/logo.png
+index 1111111..2222222 100644
+Binary files a/logo.png and b/logo.png differ
+";
+        assert_eq!(
+            parse(diff),
+            Err(InvalidHunkError {
+                message: "Binary diffs are not supported".to_string(),
+                line_number: 3,
+            })
+        );
+    }
+}
@@ -0,0 +1,487 @@
+//! Parsing of shell scripts, such as the argument to `bash -lc`, with
+//! tree-sitter-bash.
+//!
+//! Callers only get commands out of scripts that are plain enough to be
+//! understood without running them: simple commands of literal words joined by
+//! `&&`, `||`, `;` and `|`, optionally redirected to or from files.
+
+use tree_sitter::Node;
+use tree_sitter::Parser;
+use tree_sitter::Tree;
+use tree_sitter_bash::LANGUAGE as BASH;
+
+/// Parse the provided bash source using tree-sitter-bash, returning a Tree on
+/// success or None if parsing failed.
+pub fn try_parse_bash(bash_lc_arg: &str) -> Option<Tree> {
+    let lang = BASH.into();
+    let mut parser = Parser::new();
+    #[expect(clippy::expect_used)]
+    parser.set_language(&lang).expect("load bash grammar");
+    let old_tree: Option<&Tree> = None;
+    parser.parse(bash_lc_arg, old_tree)
+}
+
+/// Parse a script which may contain multiple simple commands joined only by
+/// the safe logical/pipe/sequencing operators: `&&`, `||`, `;`, `|`.
+///
+/// Returns `Some(Vec<command_words>)` if every command is a plain word‑only
+/// command and the parse tree does not contain disallowed constructs
+/// (parentheses, redirections, substitutions, control flow, etc.). Otherwise
+/// returns `None`.
+pub fn try_parse_word_only_commands_sequence(tree: &Tree, src: &str) -> Option<Vec<Vec<String>>> {
+    if tree.root_node().has_error() {
+        return None;
+    }
+
+    // List of allowed (named) node kinds for a "word only commands sequence".
+    // If we encounter a named node that is not in this list we reject.
+    const ALLOWED_KINDS: &[&str] = &[
+        // top level containers
+        "program",
+        "list",
+        "pipeline",
+        // commands & words
+        "command",
+        "command_name",
+        "word",
+        "string",
+        "string_content",
+        "raw_string",
+        "number",
+    ];
+    // Allow only safe punctuation / operator tokens; anything else causes reject.
+    const ALLOWED_PUNCT_TOKENS: &[&str] = &["&&", "||", ";", "|", "\"", "'"];
+
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let mut stack = vec![root];
+    let mut command_nodes = Vec::new();
+    while let Some(node) = stack.pop() {
+        let kind = node.kind();
+        if node.is_named() {
+            if !ALLOWED_KINDS.contains(&kind) {
+                return None;
+            }
+            if kind == "command" {
+                command_nodes.push(node);
+            }
+        } else {
+            // Reject any punctuation / operator tokens that are not explicitly allowed.
+            if kind.chars().any(|c| "&;|".contains(c)) && !ALLOWED_PUNCT_TOKENS.contains(&kind) {
+                return None;
+            }
+            if !(ALLOWED_PUNCT_TOKENS.contains(&kind) || kind.trim().is_empty()) {
+                // If it's a quote token or operator it's allowed above; we also allow whitespace tokens.
+                // Any other punctuation like parentheses, braces, redirects, backticks, etc are rejected.
+                return None;
+            }
+        }
+        for child in node.children(&mut cursor) {
+            stack.push(child);
+        }
+    }
+
+    // Walk uses a stack (LIFO), so re-sort by position to restore source order.
+    command_nodes.sort_by_key(Node::start_byte);
+
+    let mut commands = Vec::new();
+    for node in command_nodes {
+        if let Some(words) = parse_plain_command_from_node(node, src) {
+            commands.push(words);
+        } else {
+            return None;
+        }
+    }
+    Some(commands)
+}
+
+/// Returns the sequence of plain commands within a `bash -lc "..."` invocation
+/// when the script only contains word-only commands joined by safe operators.
+pub fn parse_bash_lc_plain_commands(command: &[String]) -> Option<Vec<Vec<String>>> {
+    let [bash, flag, script] = command else {
+        return None;
+    };
+
+    if bash != "bash" || flag != "-lc" {
+        return None;
+    }
+
+    let tree = try_parse_bash(script)?;
+    try_parse_word_only_commands_sequence(&tree, script)
+}
+
+fn parse_plain_command_from_node(cmd: Node, src: &str) -> Option<Vec<String>> {
+    if cmd.kind() != "command" {
+        return None;
+    }
+    let mut words = Vec::new();
+    let mut cursor = cmd.walk();
+    for child in cmd.named_children(&mut cursor) {
+        match child.kind() {
+            "command_name" => {
+                let word_node = child.named_child(0)?;
+                if word_node.kind() != "word" {
+                    return None;
+                }
+                words.push(word_node.utf8_text(src.as_bytes()).ok()?.to_owned());
+            }
+            "word" | "number" | "string" | "raw_string" => words.push(word_text(child, src)?),
+            _ => return None,
+        }
+    }
+    Some(words)
+}
+
+/// Operators that may join the commands of a script.
+const CONNECTORS: &[&str] = &["&&", "||", ";", "|"];
+
+/// Why [`parse_commands`] rejected a script.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScriptError {
+    /// The script is not valid bash.
+    Parse,
+    /// The script uses syntax whose effect depends on running it, such as an
+    /// expansion, a substitution or control flow.
+    Unsupported {
+        /// The tree-sitter node kind, e.g. `command_substitution`.
+        kind: String,
+        /// The offending source text.
+        text: String,
+    },
+}
+
+/// One simple command of a script.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimpleCommand {
+    /// The program as written, with quotes removed.
+    pub program: String,
+    /// The arguments, with quotes removed.
+    pub args: Vec<String>,
+    /// Redirections applied to the command, in source order.
+    pub redirects: Vec<FileRedirect>,
+}
+
+/// A redirection such as `2>> log.txt` or `< input`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileRedirect {
+    /// Operator as written, including any file descriptor, e.g. `2>>`.
+    pub operator: String,
+    /// The target word, e.g. a path, `&1`'s `1` or `-`.
+    pub target: String,
+}
+
+/// Split `script` into its simple commands, in the order they appear.
+///
+/// Redirections that follow a pipeline or list apply, as in bash, to its last
+/// command. Comments are skipped.
+pub fn parse_commands(script: &str) -> Result<Vec<SimpleCommand>, ScriptError> {
+    let tree = try_parse_bash(script)
+        .filter(|tree| !tree.root_node().has_error())
+        .ok_or(ScriptError::Parse)?;
+    let mut commands = Vec::new();
+    collect_commands(tree.root_node(), script, &mut commands)?;
+    Ok(commands)
+}
+
+fn collect_commands(
+    node: Node,
+    src: &str,
+    commands: &mut Vec<SimpleCommand>,
+) -> Result<(), ScriptError> {
+    match node.kind() {
+        "program" | "list" | "pipeline" => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                if child.is_named() {
+                    collect_commands(child, src, commands)?;
+                } else if !CONNECTORS.contains(&child.kind()) && !child.kind().trim().is_empty() {
+                    return Err(unsupported(child, src));
+                }
+            }
+        }
+        "redirected_statement" => {
+            // tree-sitter-bash attaches redirections that follow a pipeline or
+            // list to the whole statement, but bash applies them to its last
+            // command.
+            let body = node
+                .child_by_field_name("body")
+                .filter(|body| matches!(body.kind(), "command" | "pipeline" | "list"))
+                .ok_or_else(|| unsupported(node, src))?;
+            let first = commands.len();
+            collect_commands(body, src, commands)?;
+            let last = commands
+                .get_mut(first..)
+                .and_then(<[SimpleCommand]>::last_mut)
+                .ok_or_else(|| unsupported(node, src))?;
+            let mut cursor = node.walk();
+            for redirect in node.children_by_field_name("redirect", &mut cursor) {
+                last.redirects.push(parse_redirect(redirect, src)?);
+            }
+        }
+        "command" => commands.push(parse_command(node, src)?),
+        "comment" => {}
+        _ => return Err(unsupported(node, src)),
+    }
+    Ok(())
+}
+
+fn parse_command(node: Node, src: &str) -> Result<SimpleCommand, ScriptError> {
+    let mut words = Vec::new();
+    let mut redirects = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "command_name" => {
+                let name = child
+                    .named_child(0)
+                    .ok_or_else(|| unsupported(child, src))?;
+                words.push(literal_word(name, src)?);
+            }
+            "file_redirect" => redirects.push(parse_redirect(child, src)?),
+            _ if child.is_named() => words.push(literal_word(child, src)?),
+            kind if kind.trim().is_empty() => {}
+            _ => return Err(unsupported(child, src)),
+        }
+    }
+    let (program, args) = words.split_first().ok_or_else(|| unsupported(node, src))?;
+    Ok(SimpleCommand {
+        program: program.clone(),
+        args: args.to_vec(),
+        redirects,
+    })
+}
+
+fn parse_redirect(node: Node, src: &str) -> Result<FileRedirect, ScriptError> {
+    if node.kind() != "file_redirect" {
+        return Err(unsupported(node, src));
+    }
+
+    let mut descriptor = "";
+    let mut operator = None;
+    let mut target = None;
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.kind() == "file_descriptor" {
+            descriptor = child.utf8_text(src.as_bytes()).unwrap_or_default();
+        } else if !child.is_named() {
+            operator = Some(child.kind());
+        } else if target.is_none() {
+            target = Some(literal_word(child, src)?);
+        } else {
+            return Err(unsupported(node, src));
+        }
+    }
+    let (Some(operator), Some(target)) = (operator, target) else {
+        return Err(unsupported(node, src));
+    };
+    Ok(FileRedirect {
+        operator: format!("{descriptor}{operator}"),
+        target,
+    })
+}
+
+/// The literal value of a word, number or quoted string without expansions.
+fn word_text(node: Node, src: &str) -> Option<String> {
+    match node.kind() {
+        "word" | "number" => Some(node.utf8_text(src.as_bytes()).ok()?.to_owned()),
+        "string" => {
+            if node.child_count() == 3
+                && node.child(0)?.kind() == "\""
+                && node.child(1)?.kind() == "string_content"
+                && node.child(2)?.kind() == "\""
+            {
+                Some(node.child(1)?.utf8_text(src.as_bytes()).ok()?.to_owned())
+            } else {
+                None
+            }
+        }
+        "raw_string" => node
+            .utf8_text(src.as_bytes())
+            .ok()?
+            .strip_prefix('\'')
+            .and_then(|s| s.strip_suffix('\''))
+            .map(str::to_owned),
+        _ => None,
+    }
+}
+
+/// Like [`word_text`], but rejects unquoted words that bash would still
+/// expand: a `~` at the start or after `=` or `:`, glob patterns, brace
+/// expansions and backslash escapes.
+fn literal_word(node: Node, src: &str) -> Result<String, ScriptError> {
+    let text = word_text(node, src).ok_or_else(|| unsupported(node, src))?;
+    let expands = node.kind() == "word"
+        && (text.starts_with('~')
+            || text.contains("=~")
+            || text.contains(":~")
+            || text.contains(['*', '?', '[', ']', '{', '}', '\\']));
+    if expands {
+        return Err(ScriptError::Unsupported {
+            kind: "word_expansion".to_string(),
+            text,
+        });
+    }
+    Ok(text)
+}
+
+fn unsupported(node: Node, src: &str) -> ScriptError {
+    ScriptError::Unsupported {
+        kind: node.kind().to_string(),
+        text: node
+            .utf8_text(src.as_bytes())
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn parse_seq(src: &str) -> Option<Vec<Vec<String>>> {
+        let tree = try_parse_bash(src)?;
+        try_parse_word_only_commands_sequence(&tree, src)
+    }
+
+    #[test]
+    fn accepts_single_simple_command() {
+        let cmds = parse_seq("ls -1").unwrap();
+        assert_eq!(cmds, vec![vec!["ls".to_string(), "-1".to_string()]]);
+    }
+
+    #[test]
+    fn accepts_multiple_commands_with_allowed_operators() {
+        let src = "ls && pwd; echo 'hi there' | wc -l";
+        let cmds = parse_seq(src).unwrap();
+        let expected: Vec<Vec<String>> = vec![
+            vec!["ls".to_string()],
+            vec!["pwd".to_string()],
+            vec!["echo".to_string(), "hi there".to_string()],
+            vec!["wc".to_string(), "-l".to_string()],
+        ];
+        assert_eq!(cmds, expected);
+    }
+
+    #[test]
+    fn extracts_double_and_single_quoted_strings() {
+        let cmds = parse_seq("echo \"hello world\"").unwrap();
+        assert_eq!(
+            cmds,
+            vec![vec!["echo".to_string(), "hello world".to_string()]]
+        );
+
+        let cmds2 = parse_seq("echo 'hi there'").unwrap();
+        assert_eq!(
+            cmds2,
+            vec![vec!["echo".to_string(), "hi there".to_string()]]
+        );
+    }
+
+    #[test]
+    fn accepts_numbers_as_words() {
+        let cmds = parse_seq("echo 123 456").unwrap();
+        assert_eq!(
+            cmds,
+            vec![vec![
+                "echo".to_string(),
+                "123".to_string(),
+                "456".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn rejects_parentheses_and_subshells() {
+        assert!(parse_seq("(ls)").is_none());
+        assert!(parse_seq("ls || (pwd && echo hi)").is_none());
+    }
+
+    #[test]
+    fn rejects_redirections_and_unsupported_operators() {
+        assert!(parse_seq("ls > out.txt").is_none());
+        assert!(parse_seq("echo hi & echo bye").is_none());
+    }
+
+    #[test]
+    fn rejects_command_and_process_substitutions_and_expansions() {
+        assert!(parse_seq("echo $(pwd)").is_none());
+        assert!(parse_seq("echo `pwd`").is_none());
+        assert!(parse_seq("echo $HOME").is_none());
+        assert!(parse_seq("echo \"hi $USER\"").is_none());
+    }
+
+    #[test]
+    fn rejects_variable_assignment_prefix() {
+        assert!(parse_seq("FOO=bar ls").is_none());
+    }
+
+    #[test]
+    fn rejects_trailing_operator_parse_error() {
+        assert!(parse_seq("ls &&").is_none());
+    }
+
+    #[test]
+    fn parse_commands_applies_trailing_redirects_to_the_last_command() {
+        let commands = parse_commands("grep -r foo src | sort 2>&1 > out.txt").unwrap();
+        assert_eq!(
+            vec![
+                SimpleCommand {
+                    program: "grep".into(),
+                    args: vec!["-r".into(), "foo".into(), "src".into()],
+                    redirects: Vec::new(),
+                },
+                SimpleCommand {
+                    program: "sort".into(),
+                    args: Vec::new(),
+                    redirects: vec![
+                        FileRedirect {
+                            operator: "2>&".into(),
+                            target: "1".into(),
+                        },
+                        FileRedirect {
+                            operator: ">".into(),
+                            target: "out.txt".into(),
+                        },
+                    ],
+                },
+            ],
+            commands
+        );
+    }
+
+    #[test]
+    fn parse_commands_rejects_words_bash_would_expand() {
+        for script in [
+            "cat ~/.ssh/id_rsa",
+            "echo x > ~/.bashrc",
+            "ls --dir=~/src",
+            "ls *.rs",
+            "cat src/?.rs",
+            "ls [ab].txt",
+            "touch {a,b}.txt",
+            "cat \\~/x",
+            "~/bin/tool",
+        ] {
+            assert!(
+                matches!(parse_commands(script), Err(ScriptError::Unsupported { .. })),
+                "expected {script:?} to be rejected"
+            );
+        }
+        let commands = parse_commands("git show HEAD~1 -- '*.rs' \"~/x\"").unwrap();
+        assert_eq!(
+            vec!["show", "HEAD~1", "--", "*.rs", "~/x"],
+            commands[0].args
+        );
+    }
+
+    #[test]
+    fn parse_commands_reports_unsupported_syntax() {
+        assert_eq!(Err(ScriptError::Parse), parse_commands("ls &&"));
+        let Err(ScriptError::Unsupported { kind, text }) = parse_commands("echo $(pwd)") else {
+            panic!("expected a rejection");
+        };
+        assert_eq!("command_substitution", kind);
+        assert_eq!("$(pwd)", text);
+    }
+}
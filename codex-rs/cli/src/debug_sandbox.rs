@@ -3,10 +3,13 @@ use std::path::PathBuf;
 use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config_types::LinuxSandboxBackend;
 use codex_core::exec_env::create_env;
+use codex_core::landlock::spawn_command_under_linux_namespaces;
 use codex_core::landlock::spawn_command_under_linux_sandbox;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
+use codex_core::spawn::release_exec_cgroup;
 use codex_protocol::config_types::SandboxMode;
 
 use crate::LandlockCommand;
@@ -38,6 +41,7 @@ pub async fn run_command_under_landlock(
 ) -> anyhow::Result<()> {
     let LandlockCommand {
         full_auto,
+        namespaces,
         config_overrides,
         command,
     } = command;
@@ -46,7 +50,11 @@ pub async fn run_command_under_landlock(
         command,
         config_overrides,
         codex_linux_sandbox_exe,
-        SandboxType::Landlock,
+        if namespaces {
+            SandboxType::LinuxNamespaces
+        } else {
+            SandboxType::Landlock
+        },
     )
     .await
 }
@@ -54,6 +62,7 @@ pub async fn run_command_under_landlock(
 enum SandboxType {
     Seatbelt,
     Landlock,
+    LinuxNamespaces,
 }
 
 async fn run_command_under_sandbox(
@@ -86,6 +95,11 @@ async fn run_command_under_sandbox(
     let stdio_policy = StdioPolicy::Inherit;
     let env = create_env(&config.shell_environment_policy);
 
+    let sandbox_type = match (sandbox_type, config.linux_sandbox_backend) {
+        (SandboxType::Landlock, LinuxSandboxBackend::Namespaces) => SandboxType::LinuxNamespaces,
+        (sandbox_type, _) => sandbox_type,
+    };
+
     let mut child = match sandbox_type {
         SandboxType::Seatbelt => {
             spawn_command_under_seatbelt(
@@ -95,6 +109,7 @@ async fn run_command_under_sandbox(
                 sandbox_policy_cwd.as_path(),
                 stdio_policy,
                 env,
+                config.sandbox_resource_limits,
             )
             .await?
         }
@@ -111,11 +126,33 @@ async fn run_command_under_sandbox(
                 sandbox_policy_cwd.as_path(),
                 stdio_policy,
                 env,
+                config.sandbox_resource_limits,
+            )
+            .await?
+        }
+        SandboxType::LinuxNamespaces => {
+            #[expect(clippy::expect_used)]
+            let codex_linux_sandbox_exe = config
+                .codex_linux_sandbox_exe
+                .expect("codex-linux-sandbox executable not found");
+            spawn_command_under_linux_namespaces(
+                codex_linux_sandbox_exe,
+                command,
+                cwd,
+                &config.sandbox_policy,
+                sandbox_policy_cwd.as_path(),
+                stdio_policy,
+                env,
+                config.sandbox_resource_limits,
             )
             .await?
         }
     };
+    let pid = child.id();
     let status = child.wait().await?;
+    if let Some(pid) = pid {
+        release_exec_cgroup(pid);
+    }
 
     handle_exit_status(status);
 }
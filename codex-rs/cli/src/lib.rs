@@ -26,6 +26,11 @@ pub struct LandlockCommand {
     #[arg(long = "full-auto", default_value_t = false)]
     pub full_auto: bool,
 
+    /// Isolate the command with user, mount, PID and network namespaces
+    /// instead of Landlock (same as `-c linux_sandbox_backend=namespaces`).
+    #[arg(long = "namespaces", default_value_t = false)]
+    pub namespaces: bool,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
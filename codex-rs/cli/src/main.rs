@@ -15,6 +15,7 @@ use codex_cli::login::run_logout;
 use codex_cli::proto;
 use codex_common::CliConfigOverrides;
 use codex_exec::Cli as ExecCli;
+use codex_mcp_server::ServeArgs;
 use codex_responses_api_proxy::Args as ResponsesApiProxyArgs;
 use codex_tui::AppExitInfo;
 use codex_tui::Cli as TuiCli;
@@ -68,6 +69,9 @@ enum Subcommand {
     /// [experimental] Run Codex as an MCP server and manage MCP servers.
     Mcp(McpCli),
 
+    /// [experimental] Serve Codex conversations over WebSocket and HTTP.
+    Serve(ServeArgs),
+
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
     Proto(ProtoCli),
@@ -85,6 +89,9 @@ enum Subcommand {
     /// Resume a previous interactive session (picker by default; use --last to continue the most recent).
     Resume(ResumeCommand),
 
+    /// Attach to a session running in another terminal's TUI.
+    Attach(AttachCommand),
+
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
@@ -116,6 +123,17 @@ struct ResumeCommand {
     config_overrides: TuiCli,
 }
 
+#[derive(Debug, Parser)]
+struct AttachCommand {
+    /// Conversation/session id (UUID) of the running session.
+    #[arg(value_name = "SESSION_ID")]
+    session_id: String,
+
+    /// Only watch the session; do not send input or approvals.
+    #[arg(long = "read-only", default_value_t = false)]
+    read_only: bool,
+}
+
 #[derive(Debug, Parser)]
 struct DebugArgs {
     #[command(subcommand)]
@@ -264,6 +282,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run(codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Serve(mut serve_args)) => {
+            prepend_config_flags(
+                &mut serve_args.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_mcp_server::run_serve_main(serve_args, codex_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::Resume(ResumeCommand {
             session_id,
             last,
@@ -278,6 +303,18 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
         }
+        Some(Subcommand::Attach(AttachCommand {
+            session_id,
+            read_only,
+        })) => {
+            interactive.attach_session_id = Some(session_id);
+            interactive.attach_read_only = read_only;
+            prepend_config_flags(
+                &mut interactive.config_overrides,
+                root_config_overrides.clone(),
+            );
+            codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+        }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(
                 &mut login_cli.config_overrides,
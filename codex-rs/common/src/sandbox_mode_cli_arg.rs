@@ -15,6 +15,7 @@ pub enum SandboxModeCliArg {
     ReadOnly,
     WorkspaceWrite,
     DangerFullAccess,
+    Overlay,
 }
 
 impl From<SandboxModeCliArg> for SandboxMode {
@@ -23,6 +24,7 @@ impl From<SandboxModeCliArg> for SandboxMode {
             SandboxModeCliArg::ReadOnly => SandboxMode::ReadOnly,
             SandboxModeCliArg::WorkspaceWrite => SandboxMode::WorkspaceWrite,
             SandboxModeCliArg::DangerFullAccess => SandboxMode::DangerFullAccess,
+            SandboxModeCliArg::Overlay => SandboxMode::Overlay,
         }
     }
 }
@@ -1,14 +1,15 @@
 use codex_core::protocol::SandboxPolicy;
 
 pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
-    match sandbox_policy {
+    let mut summary = match sandbox_policy {
         SandboxPolicy::DangerFullAccess => "danger-full-access".to_string(),
-        SandboxPolicy::ReadOnly => "read-only".to_string(),
+        SandboxPolicy::ReadOnly { .. } => "read-only".to_string(),
         SandboxPolicy::WorkspaceWrite {
             writable_roots,
             network_access,
             exclude_tmpdir_env_var,
             exclude_slash_tmp,
+            deny_read: _,
         } => {
             let mut summary = "workspace-write".to_string();
 
@@ -32,5 +33,22 @@ pub fn summarize_sandbox_policy(sandbox_policy: &SandboxPolicy) -> String {
             }
             summary
         }
+        SandboxPolicy::Overlay { network_access, .. } => {
+            let mut summary = "overlay".to_string();
+            if *network_access {
+                summary.push_str(" (network access enabled)");
+            }
+            summary
+        }
+    };
+
+    let deny_read = sandbox_policy.deny_read();
+    if !deny_read.is_empty() {
+        let denied: Vec<String> = deny_read
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        summary.push_str(&format!(" (no reads: {})", denied.join(", ")));
     }
+    summary
 }
@@ -2,12 +2,14 @@ use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::function_tool::FunctionCallError;
 use crate::protocol::FileChange;
+use crate::protocol::RejectedPatchHunk;
 use crate::protocol::ReviewDecision;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
 pub const CODEX_APPLY_PATCH_ARG1: &str = "--codex-run-as-apply-patch";
@@ -30,6 +32,9 @@ pub(crate) enum InternalApplyPatchInvocation {
 pub(crate) struct ApplyPatchExec {
     pub(crate) action: ApplyPatchAction,
     pub(crate) user_explicitly_approved_this_action: bool,
+    /// Tells the model which parts of the patch the user rejected, when the
+    /// user approved only part of it.
+    pub(crate) rejected_message: Option<String>,
 }
 
 pub(crate) async fn apply_patch(
@@ -49,6 +54,7 @@ pub(crate) async fn apply_patch(
             InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                 action,
                 user_explicitly_approved_this_action: false,
+                rejected_message: None,
             })
         }
         SafetyCheck::AskUser => {
@@ -67,9 +73,34 @@ pub(crate) async fn apply_patch(
                     InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                         action,
                         user_explicitly_approved_this_action: true,
+                        rejected_message: None,
                     })
                 }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
+                ReviewDecision::ApprovedPartially { rejected } => {
+                    let message = rejected_hunks_message(&action, &rejected);
+                    let is_rejected = |path: &Path, index: usize| {
+                        rejected.iter().any(|hunk| {
+                            hunk.path == path && hunk.hunk.is_none_or(|hunk| hunk == index)
+                        })
+                    };
+                    match action.retain_hunks(|path, index| !is_rejected(path, index)) {
+                        Some(action) => {
+                            InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                                action,
+                                user_explicitly_approved_this_action: true,
+                                rejected_message: Some(message),
+                            })
+                        }
+                        None => InternalApplyPatchInvocation::Output(Err(
+                            FunctionCallError::RespondToModel(format!(
+                                "patch rejected by user\n{message}"
+                            )),
+                        )),
+                    }
+                }
+                ReviewDecision::Denied
+                | ReviewDecision::Abort
+                | ReviewDecision::ApprovedWithEdit { .. } => {
                     InternalApplyPatchInvocation::Output(Err(FunctionCallError::RespondToModel(
                         "patch rejected by user".to_string(),
                     )))
@@ -82,6 +113,44 @@ pub(crate) async fn apply_patch(
     }
 }
 
+/// Lists the files and hunks the user rejected, with the lines of each hunk,
+/// so the model knows exactly what was not applied.
+fn rejected_hunks_message(action: &ApplyPatchAction, rejected: &[RejectedPatchHunk]) -> String {
+    let mut message =
+        "The user rejected these parts of the patch, which were not applied:".to_string();
+    for hunk in rejected {
+        let path = hunk.path.strip_prefix(&action.cwd).unwrap_or(&hunk.path);
+        let unified_diff = match action.changes().get(&hunk.path) {
+            Some(ApplyPatchFileChange::Update { unified_diff, .. }) => Some(unified_diff),
+            _ => None,
+        };
+        match (hunk.hunk, unified_diff) {
+            (Some(index), Some(unified_diff)) => {
+                message.push_str(&format!("\n- {}, hunk {}:", path.display(), index + 1));
+                for line in hunk_lines(unified_diff, index) {
+                    message.push_str(&format!("\n  {line}"));
+                }
+            }
+            _ => message.push_str(&format!("\n- {} (the whole file)", path.display())),
+        }
+    }
+    message
+}
+
+/// The lines of the hunk at `index` in `unified_diff`, starting with its
+/// `@@` header.
+fn hunk_lines(unified_diff: &str, index: usize) -> Vec<&str> {
+    let mut hunks = Vec::new();
+    for line in unified_diff.lines() {
+        if line.starts_with("@@") {
+            hunks.push(vec![line]);
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.push(line);
+        }
+    }
+    hunks.into_iter().nth(index).unwrap_or_default()
+}
+
 pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
 ) -> HashMap<PathBuf, FileChange> {
@@ -98,7 +167,7 @@ pub(crate) fn convert_apply_patch_to_protocol(
             ApplyPatchFileChange::Update {
                 unified_diff,
                 move_path,
-                new_content: _new_content,
+                ..
             } => FileChange::Update {
                 unified_diff: unified_diff.clone(),
                 move_path: move_path.clone(),
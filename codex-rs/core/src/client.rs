@@ -216,7 +216,7 @@ impl ModelClient {
             input: &input_with_instructions,
             tools: &tools_json,
             tool_choice: "auto",
-            parallel_tool_calls: false,
+            parallel_tool_calls: self.config.max_parallel_tool_calls > 1,
             reasoning,
             store: azure_workaround,
             stream: true,
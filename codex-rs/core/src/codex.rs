@@ -17,9 +17,11 @@ use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::MaybeApplyPatchVerified;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_apply_patch::maybe_parse_apply_patch_verified_with_reader;
 use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::protocol::ConversationPathResponseEvent;
 use codex_protocol::protocol::ExitedReviewModeEvent;
+use codex_protocol::protocol::NO_TURN_TO_STEER_MESSAGE;
 use codex_protocol::protocol::ReviewRequest;
 use codex_protocol::protocol::RolloutItem;
 use codex_protocol::protocol::TaskStartedEvent;
@@ -49,8 +51,11 @@ use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::config::Config;
+use crate::config_types::ResourceLimits;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::TurnLimits;
 use crate::conversation_history::ConversationHistory;
+use crate::conversation_manager::fork_origin;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
@@ -61,6 +66,7 @@ use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
+use crate::exec::find_denied_read_path;
 use crate::exec::process_exec_tool_call;
 use crate::exec_command::EXEC_COMMAND_TOOL_NAME;
 use crate::exec_command::ExecCommandParams;
@@ -76,6 +82,8 @@ use crate::openai_tools::ApplyPatchToolArgs;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
+use crate::overlay;
+use crate::overlay::OverlayLayer;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
@@ -96,6 +104,8 @@ use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
 use crate::protocol::Op;
+use crate::protocol::OverlayDecision;
+use crate::protocol::OverlayResolvedEvent;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
 use crate::protocol::RateLimitSnapshot;
@@ -114,6 +124,7 @@ use crate::rollout::RolloutRecorderParams;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::safety::get_platform_sandbox;
 use crate::shell;
 use crate::state::ActiveTurn;
 use crate::state::SessionServices;
@@ -137,8 +148,13 @@ use codex_protocol::models::ShellToolCallParams;
 use codex_protocol::protocol::InitialHistory;
 
 pub mod compact;
+mod parallel_tool_calls;
 use self::compact::build_compacted_history;
 use self::compact::collect_user_messages;
+use self::parallel_tool_calls::handle_parallel_response_items;
+use self::parallel_tool_calls::is_parallel_safe_call;
+use crate::tasks::TurnLimitTracker;
+use crate::tasks::finish_task_at_limit;
 
 /// The high-level interface to the Codex system.
 /// It operates as a queue pair where you send submissions and receive events.
@@ -280,6 +296,7 @@ pub(crate) struct TurnContext {
     pub(crate) approval_policy: AskForApproval,
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) shell_environment_policy: ShellEnvironmentPolicy,
+    pub(crate) resource_limits: ResourceLimits,
     pub(crate) tools_config: ToolsConfig,
     pub(crate) is_review_mode: bool,
     pub(crate) final_output_json_schema: Option<Value>,
@@ -353,18 +370,32 @@ impl Session {
         }
 
         let (conversation_id, rollout_params) = match &initial_history {
-            InitialHistory::New | InitialHistory::Forked(_) => {
+            InitialHistory::New => {
                 let conversation_id = ConversationId::default();
                 (
                     conversation_id,
                     RolloutRecorderParams::new(conversation_id, user_instructions.clone()),
                 )
             }
+            InitialHistory::Forked(items) => {
+                let conversation_id = ConversationId::default();
+                let params = match fork_origin(items) {
+                    Some(origin) => RolloutRecorderParams::fork(
+                        conversation_id,
+                        user_instructions.clone(),
+                        origin,
+                    ),
+                    None => RolloutRecorderParams::new(conversation_id, user_instructions.clone()),
+                };
+                (conversation_id, params)
+            }
             InitialHistory::Resumed(resumed_history) => (
                 resumed_history.conversation_id,
                 RolloutRecorderParams::resume(resumed_history.rollout_path.clone()),
             ),
         };
+        let sandbox_policy =
+            overlay::with_session_layer(sandbox_policy, &config.codex_home, conversation_id);
 
         // Error messages to dispatch after SessionConfigured is sent.
         let mut post_session_configured_error_events = Vec::<Event>::new();
@@ -422,6 +453,19 @@ impl Session {
             }
         }
 
+        // `RLIMIT_NPROC` counts every process of the user, so the process limit
+        // is only enforced with a cgroup.
+        if config.sandbox_resource_limits.max_processes.is_some()
+            && !crate::spawn::process_limit_supported()
+        {
+            let message = "sandbox_resource_limits.max_processes is not supported here: it needs cgroup v2 with the pids controller enabled for Codex, so commands run without a process limit".to_string();
+            warn!("{message}");
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::Error(ErrorEvent { message }),
+            });
+        }
+
         // Now that the conversation id is final (may have been updated by resume),
         // construct the model client.
         let client = ModelClient::new(
@@ -448,6 +492,7 @@ impl Session {
             approval_policy,
             sandbox_policy,
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.sandbox_resource_limits,
             cwd,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -459,8 +504,11 @@ impl Session {
             notifier: notify,
             rollout: Mutex::new(Some(rollout_recorder)),
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            linux_sandbox_backend: config.linux_sandbox_backend,
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            turn_limits: config.turn_limits,
+            max_parallel_tool_calls: config.max_parallel_tool_calls,
         };
 
         let sess = Arc::new(Session {
@@ -545,6 +593,30 @@ impl Session {
         }
     }
 
+    /// Sends the diff of the turn so far. Each diff supersedes the previous
+    /// one, so only the last is recorded in the rollout, by
+    /// [`Self::record_final_turn_diff`] once the task ends or is aborted.
+    async fn send_turn_diff(&self, sub_id: &str, unified_diff: String) {
+        self.state.lock().await.last_turn_diff = Some(unified_diff.clone());
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::TurnDiff(TurnDiffEvent { unified_diff }),
+        };
+        if let Err(e) = self.tx_event.send(event).await {
+            error!("failed to send turn diff event: {e}");
+        }
+    }
+
+    /// Records the last diff sent for the turn in the rollout.
+    pub(crate) async fn record_final_turn_diff(&self) {
+        let last_turn_diff = self.state.lock().await.last_turn_diff.take();
+        if let Some(unified_diff) = last_turn_diff {
+            let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
+            self.persist_rollout_items(&[RolloutItem::EventMsg(msg)])
+                .await;
+        }
+    }
+
     pub async fn request_command_approval(
         &self,
         sub_id: String,
@@ -649,7 +721,7 @@ impl Session {
 
     /// Records input items: always append to conversation history and
     /// persist these response items to rollout.
-    async fn record_conversation_items(&self, items: &[ResponseItem]) {
+    pub(crate) async fn record_conversation_items(&self, items: &[ResponseItem]) {
         self.record_into_history(items).await;
         self.persist_rollout_response_items(items).await;
     }
@@ -883,16 +955,16 @@ impl Session {
         self.send_event(event).await;
 
         // If this is an apply_patch, after we emit the end patch, emit a second event
-        // with the full turn diff if there is one.
-        if is_apply_patch {
-            let unified_diff = turn_diff_tracker.get_unified_diff();
+        // with the full turn diff if there is one. Under an overlay policy any
+        // command may have changed the layer, so emit the diff if it changed.
+        if is_apply_patch || turn_diff_tracker.has_overlay() {
+            let unified_diff = if is_apply_patch {
+                turn_diff_tracker.get_unified_diff()
+            } else {
+                turn_diff_tracker.get_changed_unified_diff()
+            };
             if let Ok(Some(unified_diff)) = unified_diff {
-                let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                let event = Event {
-                    id: sub_id.into(),
-                    msg,
-                };
-                self.send_event(event).await;
+                self.send_turn_diff(sub_id, unified_diff).await;
             }
         }
     }
@@ -926,7 +998,9 @@ impl Session {
         let output_stderr;
         let borrowed: &ExecToolCallOutput = match &result {
             Ok(output) => output,
-            Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => output,
+            Err(CodexErr::Sandbox(
+                SandboxErr::Timeout { output } | SandboxErr::ResourceLimit { output, .. },
+            )) => output,
             Err(e) => {
                 output_stderr = ExecToolCallOutput {
                     exit_code: -1,
@@ -954,7 +1028,7 @@ impl Session {
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
-    async fn notify_background_event(&self, sub_id: &str, message: impl Into<String>) {
+    pub(crate) async fn notify_background_event(&self, sub_id: &str, message: impl Into<String>) {
         let event = Event {
             id: sub_id.to_string(),
             msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
@@ -997,6 +1071,30 @@ impl Session {
         }
     }
 
+    /// Adds `input` to the running turn, which keeps going until the model
+    /// has responded to it. Returns the input back if no turn is running.
+    pub async fn steer_input(&self, input: Vec<InputItem>) -> Result<(), Vec<InputItem>> {
+        let mut active = self.active_turn.lock().await;
+        match active.as_mut() {
+            Some(at) => {
+                let mut ts = at.turn_state.lock().await;
+                ts.push_pending_steer(input);
+                Ok(())
+            }
+            None => Err(input),
+        }
+    }
+
+    /// Whether a steer is waiting for the model at the next tool-call
+    /// boundary.
+    pub async fn has_pending_steer(&self) -> bool {
+        let active = self.active_turn.lock().await;
+        match active.as_ref() {
+            Some(at) => at.turn_state.lock().await.has_pending_steer(),
+            None => false,
+        }
+    }
+
     pub async fn get_pending_input(&self) -> Vec<ResponseInputItem> {
         let mut active = self.active_turn.lock().await;
         match active.as_mut() {
@@ -1042,6 +1140,10 @@ impl Session {
         &self.services.notifier
     }
 
+    pub(crate) fn turn_limits(&self) -> TurnLimits {
+        self.services.turn_limits
+    }
+
     fn user_shell(&self) -> &shell::Shell {
         &self.services.user_shell
     }
@@ -1132,9 +1234,14 @@ async fn submission_loop(
                 );
 
                 let new_approval_policy = approval_policy.unwrap_or(prev.approval_policy);
-                let new_sandbox_policy = sandbox_policy
-                    .clone()
-                    .unwrap_or(prev.sandbox_policy.clone());
+                let new_sandbox_policy = match sandbox_policy.clone() {
+                    Some(policy) => overlay::with_session_layer(
+                        policy,
+                        &config.codex_home,
+                        sess.conversation_id,
+                    ),
+                    None => prev.sandbox_policy.clone(),
+                };
                 let new_cwd = cwd.clone().unwrap_or_else(|| prev.cwd.clone());
 
                 let tools_config = ToolsConfig::new(&ToolsConfigParams {
@@ -1155,6 +1262,7 @@ async fn submission_loop(
                     approval_policy: new_approval_policy,
                     sandbox_policy: new_sandbox_policy.clone(),
                     shell_environment_policy: prev.shell_environment_policy.clone(),
+                    resource_limits: prev.resource_limits,
                     cwd: new_cwd.clone(),
                     is_review_mode: false,
                     final_output_json_schema: None,
@@ -1175,6 +1283,17 @@ async fn submission_loop(
                     .await;
                 }
             }
+            Op::Steer { items } => {
+                if sess.steer_input(items).await.is_err() {
+                    let event = Event {
+                        id: sub.id.clone(),
+                        msg: EventMsg::Error(ErrorEvent {
+                            message: NO_TURN_TO_STEER_MESSAGE.to_string(),
+                        }),
+                    };
+                    sess.send_event(event).await;
+                }
+            }
             Op::UserInput { items } => {
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items).await {
@@ -1238,8 +1357,13 @@ async fn submission_loop(
                         user_instructions: turn_context.user_instructions.clone(),
                         base_instructions: turn_context.base_instructions.clone(),
                         approval_policy,
-                        sandbox_policy,
+                        sandbox_policy: overlay::with_session_layer(
+                            sandbox_policy,
+                            &config.codex_home,
+                            sess.conversation_id,
+                        ),
                         shell_environment_policy: turn_context.shell_environment_policy.clone(),
+                        resource_limits: turn_context.resource_limits,
                         cwd,
                         is_review_mode: false,
                         final_output_json_schema,
@@ -1303,13 +1427,44 @@ async fn submission_loop(
                             crate::protocol::GetHistoryEntryResponseEvent {
                                 offset,
                                 log_id,
-                                entry: entry_opt.map(|e| {
-                                    codex_protocol::message_history::HistoryEntry {
-                                        conversation_id: e.session_id,
-                                        ts: e.ts,
-                                        text: e.text,
-                                    }
-                                }),
+                                entry: entry_opt.map(Into::into),
+                            },
+                        ),
+                    };
+
+                    sess_clone.send_event(event).await;
+                });
+            }
+            Op::SearchHistoryRequest {
+                cwd,
+                conversation_id,
+                limit,
+            } => {
+                let config = config.clone();
+                let sess_clone = sess.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    // Run the search in a blocking thread because it does file IO + locking.
+                    let (cwd, conversation_id, entries) = tokio::task::spawn_blocking(move || {
+                        let entries = crate::message_history::search(
+                            &config,
+                            cwd.as_deref(),
+                            conversation_id.as_ref(),
+                            limit,
+                        );
+                        (cwd, conversation_id, entries)
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::SearchHistoryResponse(
+                            crate::protocol::SearchHistoryResponseEvent {
+                                cwd,
+                                conversation_id,
+                                entries: entries.into_iter().map(Into::into).collect(),
                             },
                         ),
                     };
@@ -1417,6 +1572,35 @@ async fn submission_loop(
                 };
                 sess.send_event(event).await;
             }
+            Op::ResolveOverlay { decision } => {
+                let msg = match OverlayLayer::from_policy(
+                    &turn_context.sandbox_policy,
+                    &turn_context.cwd,
+                ) {
+                    Some(overlay) => {
+                        let result = tokio::task::spawn_blocking(move || match decision {
+                            OverlayDecision::Commit => overlay.commit(),
+                            OverlayDecision::Discard => overlay.discard(),
+                        })
+                        .await
+                        .map_err(std::io::Error::other)
+                        .and_then(|result| result);
+                        match result {
+                            Ok(changed_files) => EventMsg::OverlayResolved(OverlayResolvedEvent {
+                                decision,
+                                changed_files,
+                            }),
+                            Err(e) => EventMsg::Error(ErrorEvent {
+                                message: format!("failed to resolve the overlay: {e}"),
+                            }),
+                        }
+                    }
+                    None => EventMsg::Error(ErrorEvent {
+                        message: "the sandbox policy has no overlay to resolve".to_string(),
+                    }),
+                };
+                sess.send_event(Event { id: sub.id, msg }).await;
+            }
             Op::Review { review_request } => {
                 spawn_review_thread(
                     sess.clone(),
@@ -1490,6 +1674,7 @@ async fn spawn_review_thread(
         approval_policy: parent_turn_context.approval_policy,
         sandbox_policy: parent_turn_context.sandbox_policy.clone(),
         shell_environment_policy: parent_turn_context.shell_environment_policy.clone(),
+        resource_limits: parent_turn_context.resource_limits,
         cwd: parent_turn_context.cwd.clone(),
         is_review_mode: true,
         final_output_json_schema: None,
@@ -1535,6 +1720,7 @@ pub(crate) async fn run_task(
     turn_context: Arc<TurnContext>,
     sub_id: String,
     input: Vec<InputItem>,
+    turn_limits: &mut TurnLimitTracker,
 ) -> Option<String> {
     if input.is_empty() {
         return None;
@@ -1565,7 +1751,11 @@ pub(crate) async fn run_task(
     let mut last_agent_message: Option<String> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
-    let mut turn_diff_tracker = TurnDiffTracker::new();
+    let mut turn_diff_tracker =
+        match OverlayLayer::from_policy(&turn_context.sandbox_policy, &turn_context.cwd) {
+            Some(overlay) => TurnDiffTracker::with_overlay(overlay),
+            None => TurnDiffTracker::new(),
+        };
     let mut auto_compact_recently_attempted = false;
 
     loop {
@@ -1616,6 +1806,7 @@ pub(crate) async fn run_task(
             &sess,
             turn_context.as_ref(),
             &mut turn_diff_tracker,
+            turn_limits,
             sub_id.clone(),
             turn_input,
         )
@@ -1764,6 +1955,12 @@ pub(crate) async fn run_task(
 
                 auto_compact_recently_attempted = false;
 
+                // A steer that arrived while the model was answering gets a
+                // response before the turn ends.
+                if responses.is_empty() && sess.has_pending_steer().await {
+                    continue;
+                }
+
                 if responses.is_empty() {
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
@@ -1776,6 +1973,20 @@ pub(crate) async fn run_task(
                         });
                     break;
                 }
+
+                if let Some(exceeded) = turn_limits.record_tool_turn() {
+                    last_agent_message = finish_task_at_limit(
+                        &sess,
+                        turn_context.as_ref(),
+                        &mut turn_diff_tracker,
+                        turn_limits,
+                        &sub_id,
+                        exceeded,
+                        is_review_mode.then_some(&mut review_thread_history),
+                    )
+                    .await;
+                    break;
+                }
                 continue;
             }
             Err(e) => {
@@ -1793,6 +2004,8 @@ pub(crate) async fn run_task(
         }
     }
 
+    sess.record_final_turn_diff().await;
+
     // If this was a review thread and we have a final assistant message,
     // try to parse it as a ReviewOutput.
     //
@@ -1841,6 +2054,7 @@ async fn run_turn(
     sess: &Session,
     turn_context: &TurnContext,
     turn_diff_tracker: &mut TurnDiffTracker,
+    turn_limits: &mut TurnLimitTracker,
     sub_id: String,
     input: Vec<ResponseItem>,
 ) -> CodexResult<TurnRunResult> {
@@ -1856,16 +2070,46 @@ async fn run_turn(
         output_schema: turn_context.final_output_json_schema.clone(),
     };
 
+    run_prompt(
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        turn_limits,
+        &sub_id,
+        &prompt,
+    )
+    .await
+}
+
+/// Stream `prompt` to the model, retrying transient stream failures within
+/// the provider's retry budget.
+pub(crate) async fn run_prompt(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_limits: &mut TurnLimitTracker,
+    sub_id: &str,
+    prompt: &Prompt,
+) -> CodexResult<TurnRunResult> {
     let mut retries = 0;
     loop {
-        match try_run_turn(sess, turn_context, turn_diff_tracker, &sub_id, &prompt).await {
+        match try_run_turn(
+            sess,
+            turn_context,
+            turn_diff_tracker,
+            turn_limits,
+            sub_id,
+            prompt,
+        )
+        .await
+        {
             Ok(output) => return Ok(output),
             Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
             Err(CodexErr::EnvVar(var)) => return Err(CodexErr::EnvVar(var)),
             Err(CodexErr::UsageLimitReached(e)) => {
                 let rate_limits = e.rate_limits.clone();
                 if let Some(rate_limits) = rate_limits {
-                    sess.update_rate_limits(&sub_id, rate_limits).await;
+                    sess.update_rate_limits(sub_id, rate_limits).await;
                 }
                 return Err(CodexErr::UsageLimitReached(e));
             }
@@ -1887,7 +2131,7 @@ async fn run_turn(
                     // user understands what is happening instead of staring
                     // at a seemingly frozen screen.
                     sess.notify_stream_error(
-                        &sub_id,
+                        sub_id,
                         format!(
                             "stream error: {e}; retrying {retries}/{max_retries} in {delay:?}…"
                         ),
@@ -1908,14 +2152,14 @@ async fn run_turn(
 /// "handled" such that it produces a `ResponseInputItem` that needs to be
 /// sent back to the model on the next turn.
 #[derive(Debug)]
-struct ProcessedResponseItem {
-    item: ResponseItem,
+pub(crate) struct ProcessedResponseItem {
+    pub(crate) item: ResponseItem,
     response: Option<ResponseInputItem>,
 }
 
 #[derive(Debug)]
-struct TurnRunResult {
-    processed_items: Vec<ProcessedResponseItem>,
+pub(crate) struct TurnRunResult {
+    pub(crate) processed_items: Vec<ProcessedResponseItem>,
     total_token_usage: Option<TokenUsage>,
 }
 
@@ -1923,6 +2167,7 @@ async fn try_run_turn(
     sess: &Session,
     turn_context: &TurnContext,
     turn_diff_tracker: &mut TurnDiffTracker,
+    turn_limits: &mut TurnLimitTracker,
     sub_id: &str,
     prompt: &Prompt,
 ) -> CodexResult<TurnRunResult> {
@@ -1993,6 +2238,10 @@ async fn try_run_turn(
     let mut stream = turn_context.client.clone().stream(&prompt).await?;
 
     let mut output = Vec::new();
+    // Read-only tool calls are held back here and run concurrently once the
+    // next item that has to be handled in order (or the end of the response)
+    // arrives.
+    let mut parallel_calls = Vec::new();
 
     loop {
         // Poll the next item from the model stream. We must inspect *both* Ok and Err
@@ -2020,14 +2269,34 @@ async fn try_run_turn(
         match event {
             ResponseEvent::Created => {}
             ResponseEvent::OutputItemDone(item) => {
-                let response = handle_response_item(
-                    sess,
-                    turn_context,
-                    turn_diff_tracker,
-                    sub_id,
-                    item.clone(),
-                )
-                .await?;
+                // Tool calls past the limit are answered without running them.
+                let refusal = turn_limits.check_tool_call(&item);
+                if refusal.is_none() && is_parallel_safe_call(sess, &item) {
+                    parallel_calls.push(item);
+                    continue;
+                }
+                output.extend(
+                    handle_parallel_response_items(
+                        sess,
+                        turn_context,
+                        sub_id,
+                        std::mem::take(&mut parallel_calls),
+                    )
+                    .await?,
+                );
+                let response = match refusal {
+                    Some(refusal) => Some(refusal),
+                    None => {
+                        handle_response_item(
+                            sess,
+                            turn_context,
+                            turn_diff_tracker,
+                            sub_id,
+                            item.clone(),
+                        )
+                        .await?
+                    }
+                };
                 output.push(ProcessedResponseItem { item, response });
             }
             ResponseEvent::WebSearchCallBegin { call_id } => {
@@ -2048,17 +2317,21 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
+                output.extend(
+                    handle_parallel_response_items(
+                        sess,
+                        turn_context,
+                        sub_id,
+                        std::mem::take(&mut parallel_calls),
+                    )
+                    .await?,
+                );
                 sess.update_token_usage_info(sub_id, turn_context, token_usage.as_ref())
                     .await;
 
                 let unified_diff = turn_diff_tracker.get_unified_diff();
                 if let Ok(Some(unified_diff)) = unified_diff {
-                    let msg = EventMsg::TurnDiff(TurnDiffEvent { unified_diff });
-                    let event = Event {
-                        id: sub_id.to_string(),
-                        msg,
-                    };
-                    sess.send_event(event).await;
+                    sess.send_turn_diff(sub_id, unified_diff).await;
                 }
 
                 let result = TurnRunResult {
@@ -2382,6 +2655,15 @@ async fn handle_function_call(
                 ))
             })?;
             let abs = turn_context.resolve_path(Some(args.path));
+            if !turn_context
+                .sandbox_policy
+                .is_path_readable(&abs, &turn_context.cwd)
+            {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "reading {} is denied by the sandbox (sandbox_deny_read)",
+                    abs.display()
+                )));
+            }
             sess.inject_input(vec![InputItem::LocalImage { path: abs }])
                 .await
                 .map_err(|_| {
@@ -2405,6 +2687,7 @@ async fn handle_function_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                resource_limits: turn_context.resource_limits,
             };
             handle_container_exec_with_params(
                 exec_params,
@@ -2476,6 +2759,7 @@ async fn handle_custom_tool_call(
                 env: HashMap::new(),
                 with_escalated_permissions: None,
                 justification: None,
+                resource_limits: turn_context.resource_limits,
             };
 
             handle_container_exec_with_params(
@@ -2505,6 +2789,7 @@ fn to_exec_params(params: ShellToolCallParams, turn_context: &TurnContext) -> Ex
         env: create_env(&turn_context.shell_environment_policy),
         with_escalated_permissions: params.with_escalated_permissions,
         justification: params.justification,
+        resource_limits: turn_context.resource_limits,
     }
 }
 
@@ -2564,8 +2849,18 @@ async fn handle_container_exec_with_params(
         )));
     }
 
-    // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+    // check if this was a patch, and apply it if so. Under an overlay policy
+    // the patch applies to the files as the layer has them.
+    let overlay = OverlayLayer::from_policy(&turn_context.sandbox_policy, &turn_context.cwd);
+    let maybe_apply_patch = match &overlay {
+        Some(overlay) => {
+            maybe_parse_apply_patch_verified_with_reader(&params.command, &params.cwd, &|path| {
+                overlay.read_to_string(path)
+            })
+        }
+        None => maybe_parse_apply_patch_verified(&params.command, &params.cwd),
+    };
+    let apply_patch_exec = match maybe_apply_patch {
         MaybeApplyPatchVerified::Body(changes) => {
             match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
                 InternalApplyPatchInvocation::Output(item) => return item,
@@ -2579,7 +2874,7 @@ async fn handle_container_exec_with_params(
             // could not resolve it into a patch that would apply
             // cleanly. Return to model for resample.
             return Err(FunctionCallError::RespondToModel(format!(
-                "error: {parse_error:#?}"
+                "error: {parse_error}"
             )));
         }
         MaybeApplyPatchVerified::ShellParseError(error) => {
@@ -2589,10 +2884,11 @@ async fn handle_container_exec_with_params(
         MaybeApplyPatchVerified::NotApplyPatch => None,
     };
 
-    let (params, safety, command_for_display) = match &apply_patch_exec {
+    let (mut params, safety, mut command_for_display) = match &apply_patch_exec {
         Some(ApplyPatchExec {
             action: ApplyPatchAction { patch, cwd, .. },
             user_explicitly_approved_this_action,
+            ..
         }) => {
             let path_to_codex = std::env::current_exe()
                 .ok()
@@ -2614,6 +2910,7 @@ async fn handle_container_exec_with_params(
                 env: HashMap::new(),
                 with_escalated_permissions: params.with_escalated_permissions,
                 justification: params.justification.clone(),
+                resource_limits: params.resource_limits,
             };
             let safety = if *user_explicitly_approved_this_action {
                 SafetyCheck::AutoApprove {
@@ -2648,8 +2945,11 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    let mut edited_command = None;
     let sandbox_type = match safety {
-        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AutoApprove { sandbox_type } => {
+            sandbox_type.with_linux_backend(sess.services.linux_sandbox_backend)
+        }
         SafetyCheck::AskUser => {
             let decision = sess
                 .request_command_approval(
@@ -2665,7 +2965,15 @@ async fn handle_container_exec_with_params(
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone()).await;
                 }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
+                ReviewDecision::ApprovedWithEdit { command } if apply_patch_exec.is_none() => {
+                    params.command = command.clone();
+                    command_for_display = command.clone();
+                    edited_command = Some(command);
+                }
+                ReviewDecision::Denied
+                | ReviewDecision::Abort
+                | ReviewDecision::ApprovedPartially { .. }
+                | ReviewDecision::ApprovedWithEdit { .. } => {
                     return Err(FunctionCallError::RespondToModel(
                         "exec command rejected by user".to_string(),
                     ));
@@ -2684,6 +2992,13 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    // Tells the model what the user changed about its call.
+    let note = match &edited_command {
+        Some(command) => Some(edited_command_note(command)),
+        None => apply_patch_exec
+            .as_ref()
+            .and_then(|exec| exec.rejected_message.clone()),
+    };
     let exec_command_context = ExecCommandContext {
         sub_id: sub_id.clone(),
         call_id: call_id.clone(),
@@ -2693,6 +3008,7 @@ async fn handle_container_exec_with_params(
             |ApplyPatchExec {
                  action,
                  user_explicitly_approved_this_action,
+                 ..
              }| ApplyPatchCommandContext {
                 user_explicitly_approved_this_action,
                 changes: convert_apply_patch_to_protocol(&action),
@@ -2700,6 +3016,7 @@ async fn handle_container_exec_with_params(
         ),
     };
 
+    let sandbox_type = effective_sandbox_type(turn_context, sandbox_type);
     let params = maybe_translate_shell_command(params, sess, turn_context);
     let output_result = sess
         .run_exec_with_events(
@@ -2727,7 +3044,10 @@ async fn handle_container_exec_with_params(
     match output_result {
         Ok(output) => {
             let ExecToolCallOutput { exit_code, .. } = &output;
-            let content = format_exec_output(&output);
+            let mut content = format_exec_output(&output);
+            if let Some(note) = note {
+                content = format!("{content}\n{note}");
+            }
             if *exit_code == 0 {
                 Ok(content)
             } else {
@@ -2752,6 +3072,19 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Under an overlay policy every command runs in the overlay, so that it sees
+/// and writes the layer rather than the workspace, even one that would run
+/// unsandboxed because it is known to be safe or the user approved it. The
+/// policy's network setting applies to such commands as well.
+fn effective_sandbox_type(turn_context: &TurnContext, sandbox_type: SandboxType) -> SandboxType {
+    match &turn_context.sandbox_policy {
+        SandboxPolicy::Overlay { .. } if sandbox_type == SandboxType::None => {
+            get_platform_sandbox().unwrap_or(SandboxType::None)
+        }
+        _ => sandbox_type,
+    }
+}
+
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
     params: ExecParams,
@@ -2770,10 +3103,47 @@ async fn handle_sandbox_error(
         return Err(FunctionCallError::RespondToModel(content));
     }
 
+    // The limits apply with or without the sandbox, so running the command
+    // again without it would not help.
+    if let SandboxErr::ResourceLimit { limit, output } = &error {
+        sess.notify_background_event(&sub_id, format!("Execution failed: {error}"))
+            .await;
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{}\n\nthe command was stopped because it exceeded its {limit} limit (sandbox_resource_limits); do not run it again unchanged, reduce its parallelism or workload instead",
+            format_exec_output(output)
+        )));
+    }
+
+    // Reads denied by `sandbox_deny_read` are deliberate, so running the
+    // command again without the sandbox is not offered.
+    if let SandboxErr::Denied { output, .. } = &error
+        && let Some(path) = find_denied_read_path(
+            &params.command,
+            output,
+            &turn_context.sandbox_policy,
+            &turn_context.cwd,
+        )
+    {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "{}\n\nreading {} is denied by the sandbox (sandbox_deny_read); do not try to read it another way",
+            format_exec_output(output),
+            path.display()
+        )));
+    }
+
     // Early out if either the user never wants to be asked for approval, or
     // we're letting the model manage escalation requests. Otherwise, continue
     match turn_context.approval_policy {
         AskForApproval::Never | AskForApproval::OnRequest => {
+            if let SandboxErr::Denied { output, denials } = &error
+                && !denials.is_empty()
+            {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "{}\n\nfailed in sandbox {sandbox_type:?}, which denied: {}",
+                    format_exec_output(output),
+                    denials.join(", ")
+                )));
+            }
             return Err(FunctionCallError::RespondToModel(format!(
                 "failed in sandbox {sandbox_type:?} with execution error: {error:?}"
             )));
@@ -2793,76 +3163,119 @@ async fn handle_sandbox_error(
     sess.notify_background_event(&sub_id, format!("Execution failed: {error}"))
         .await;
 
+    let reason = match &error {
+        SandboxErr::Denied { denials, .. } if !denials.is_empty() => format!(
+            "the sandbox denied {}; retry without sandbox?",
+            denials.join(", ")
+        ),
+        _ => "command failed; retry without sandbox?".to_string(),
+    };
     let decision = sess
         .request_command_approval(
             sub_id.clone(),
             call_id.clone(),
             params.command.clone(),
             cwd.clone(),
-            Some("command failed; retry without sandbox?".to_string()),
+            Some(reason),
         )
         .await;
 
-    match decision {
+    let (params, exec_command_context, edited_command) = match decision {
         ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
             // executions skip the sandbox directly.
             // TODO(ragona): Isn't this a bug? It always saves the command in an | fork?
             sess.add_approved_command(params.command.clone()).await;
-            // Inform UI we are retrying without sandbox.
-            sess.notify_background_event(&sub_id, "retrying command without sandbox")
-                .await;
-
-            // This is an escalated retry; the policy will not be
-            // examined and the sandbox has been set to `None`.
-            let retry_output_result = sess
-                .run_exec_with_events(
-                    turn_diff_tracker,
-                    exec_command_context.clone(),
-                    ExecInvokeArgs {
-                        params,
-                        sandbox_type: SandboxType::None,
-                        sandbox_policy: &turn_context.sandbox_policy,
-                        sandbox_cwd: &turn_context.cwd,
-                        codex_linux_sandbox_exe: &sess.services.codex_linux_sandbox_exe,
-                        stdout_stream: if exec_command_context.apply_patch.is_some() {
-                            None
-                        } else {
-                            Some(StdoutStream {
-                                sub_id: sub_id.clone(),
-                                call_id: call_id.clone(),
-                                tx_event: sess.tx_event.clone(),
-                            })
-                        },
-                    },
-                )
-                .await;
-
-            match retry_output_result {
-                Ok(retry_output) => {
-                    let ExecToolCallOutput { exit_code, .. } = &retry_output;
-                    let content = format_exec_output(&retry_output);
-                    if *exit_code == 0 {
-                        Ok(content)
-                    } else {
-                        Err(FunctionCallError::RespondToModel(content))
-                    }
-                }
-                Err(e) => Err(FunctionCallError::RespondToModel(format!(
-                    "retry failed: {e}"
-                ))),
-            }
+            (params, exec_command_context, None)
         }
-        ReviewDecision::Denied | ReviewDecision::Abort => {
+        ReviewDecision::ApprovedWithEdit { command }
+            if exec_command_context.apply_patch.is_none() =>
+        {
+            (
+                ExecParams {
+                    command: command.clone(),
+                    ..params
+                },
+                ExecCommandContext {
+                    command_for_display: command.clone(),
+                    ..exec_command_context
+                },
+                Some(command),
+            )
+        }
+        ReviewDecision::Denied
+        | ReviewDecision::Abort
+        | ReviewDecision::ApprovedPartially { .. }
+        | ReviewDecision::ApprovedWithEdit { .. } => {
             // Fall through to original failure handling.
-            Err(FunctionCallError::RespondToModel(
+            return Err(FunctionCallError::RespondToModel(
                 "exec command rejected by user".to_string(),
-            ))
+            ));
+        }
+    };
+
+    // This is an escalated retry; the policy will not be
+    // examined and the sandbox has been set to `None`, except in
+    // overlay mode.
+    let retry_sandbox_type = effective_sandbox_type(turn_context, SandboxType::None);
+    // Inform UI we are retrying without sandbox.
+    let retry_message = if retry_sandbox_type == SandboxType::None {
+        "retrying command without sandbox"
+    } else {
+        "retrying command in the overlay"
+    };
+    sess.notify_background_event(&sub_id, retry_message).await;
+
+    let retry_output_result = sess
+        .run_exec_with_events(
+            turn_diff_tracker,
+            exec_command_context.clone(),
+            ExecInvokeArgs {
+                params,
+                sandbox_type: retry_sandbox_type,
+                sandbox_policy: &turn_context.sandbox_policy,
+                sandbox_cwd: &turn_context.cwd,
+                codex_linux_sandbox_exe: &sess.services.codex_linux_sandbox_exe,
+                stdout_stream: if exec_command_context.apply_patch.is_some() {
+                    None
+                } else {
+                    Some(StdoutStream {
+                        sub_id: sub_id.clone(),
+                        call_id: call_id.clone(),
+                        tx_event: sess.tx_event.clone(),
+                    })
+                },
+            },
+        )
+        .await;
+
+    match retry_output_result {
+        Ok(retry_output) => {
+            let ExecToolCallOutput { exit_code, .. } = &retry_output;
+            let mut content = format_exec_output(&retry_output);
+            if let Some(command) = &edited_command {
+                content = format!("{content}\n{}", edited_command_note(command));
+            }
+            if *exit_code == 0 {
+                Ok(content)
+            } else {
+                Err(FunctionCallError::RespondToModel(content))
+            }
         }
+        Err(e) => Err(FunctionCallError::RespondToModel(format!(
+            "retry failed: {e}"
+        ))),
     }
 }
 
+/// Tells the model the user edited its command, and what ran instead.
+fn edited_command_note(command: &[String]) -> String {
+    let command =
+        shlex::try_join(command.iter().map(String::as_str)).unwrap_or_else(|_| command.join(" "));
+    format!("The user edited the command before approving it, so this ran instead: {command}")
+}
+
 fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
     let ExecToolCallOutput {
         aggregated_output, ..
@@ -3012,7 +3425,7 @@ fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
     serde_json::to_string(&payload).expect("serialize ExecOutput")
 }
 
-pub(super) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
+pub(crate) fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<String> {
     responses.iter().rev().find_map(|item| {
         if let ResponseItem::Message { role, content, .. } = item {
             if role == "assistant" {
@@ -3132,6 +3545,7 @@ mod tests {
     use crate::protocol::CompactedItem;
     use crate::protocol::InitialHistory;
     use crate::protocol::ResumedHistory;
+    use crate::protocol::RolloutLine;
     use crate::state::TaskKind;
     use crate::tasks::SessionTask;
     use crate::tasks::SessionTaskContext;
@@ -3406,6 +3820,7 @@ mod tests {
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.sandbox_resource_limits,
             tools_config,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -3417,8 +3832,11 @@ mod tests {
             notifier: UserNotifier::default(),
             rollout: Mutex::new(None),
             codex_linux_sandbox_exe: None,
+            linux_sandbox_backend: config.linux_sandbox_backend,
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            turn_limits: config.turn_limits,
+            max_parallel_tool_calls: config.max_parallel_tool_calls,
         };
         let session = Session {
             conversation_id,
@@ -3473,6 +3891,7 @@ mod tests {
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             shell_environment_policy: config.shell_environment_policy.clone(),
+            resource_limits: config.sandbox_resource_limits,
             tools_config,
             is_review_mode: false,
             final_output_json_schema: None,
@@ -3484,8 +3903,11 @@ mod tests {
             notifier: UserNotifier::default(),
             rollout: Mutex::new(None),
             codex_linux_sandbox_exe: None,
+            linux_sandbox_backend: config.linux_sandbox_backend,
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            turn_limits: config.turn_limits,
+            max_parallel_tool_calls: config.max_parallel_tool_calls,
         };
         let session = Arc::new(Session {
             conversation_id,
@@ -3599,6 +4021,81 @@ mod tests {
         );
     }
 
+    /// Sends a turn diff, then runs until aborted.
+    struct DiffThenNeverEndingTask;
+
+    #[async_trait::async_trait]
+    impl SessionTask for DiffThenNeverEndingTask {
+        fn kind(&self) -> TaskKind {
+            TaskKind::Regular
+        }
+
+        async fn run(
+            self: Arc<Self>,
+            session: Arc<SessionTaskContext>,
+            _ctx: Arc<TurnContext>,
+            sub_id: String,
+            _input: Vec<InputItem>,
+        ) -> Option<String> {
+            session
+                .clone_session()
+                .send_turn_diff(&sub_id, "+draft\n".to_string())
+                .await;
+            loop {
+                sleep(Duration::from_secs(60)).await;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn abort_records_the_last_turn_diff() {
+        let (sess, tc, rx) = make_session_and_context_with_rx();
+        let codex_home = tempfile::tempdir().expect("create temp dir");
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect("load default test config");
+        let recorder = RolloutRecorder::new(
+            &config,
+            RolloutRecorderParams::new(sess.conversation_id, None),
+        )
+        .await
+        .expect("create rollout recorder");
+        let rollout_path = recorder.get_rollout_path();
+        *sess.services.rollout.lock().await = Some(recorder);
+
+        sess.spawn_task(
+            Arc::clone(&tc),
+            "sub-diff".to_string(),
+            Vec::new(),
+            DiffThenNeverEndingTask,
+        )
+        .await;
+        let first = rx.recv().await.expect("first event");
+        assert!(matches!(first.msg, EventMsg::TurnDiff(_)));
+
+        sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
+        let recorder = sess.services.rollout.lock().await.take();
+        recorder
+            .expect("rollout recorder")
+            .shutdown()
+            .await
+            .expect("shut down rollout recorder");
+
+        let text = std::fs::read_to_string(rollout_path).expect("read rollout");
+        let diffs: Vec<String> = text
+            .lines()
+            .filter_map(|line| serde_json::from_str::<RolloutLine>(line).ok())
+            .filter_map(|line| match line.item {
+                RolloutItem::EventMsg(EventMsg::TurnDiff(ev)) => Some(ev.unified_diff),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(diffs, vec!["+draft\n".to_string()]);
+    }
+
     fn sample_rollout(
         session: &Session,
         turn_context: &TurnContext,
@@ -3732,6 +4229,7 @@ mod tests {
             env: HashMap::new(),
             with_escalated_permissions: Some(true),
             justification: Some("test".to_string()),
+            resource_limits: turn_context.resource_limits,
         };
 
         let params2 = ExecParams {
@@ -0,0 +1,89 @@
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::models::ShellToolCallParams;
+use futures::prelude::*;
+
+use super::ProcessedResponseItem;
+use super::Session;
+use super::TurnContext;
+use super::handle_response_item;
+use crate::error::Result as CodexResult;
+use crate::is_safe_command::is_known_safe_command;
+use crate::turn_diff_tracker::TurnDiffTracker;
+
+/// Whether `item` is a tool call that cannot change the workspace and may
+/// therefore run concurrently with other such calls from the same response:
+/// shell commands accepted by [`is_known_safe_command`], `view_image`, and MCP
+/// tools that their server marks as read-only.
+pub(crate) fn is_parallel_safe_call(sess: &Session, item: &ResponseItem) -> bool {
+    if sess.services.max_parallel_tool_calls <= 1 {
+        return false;
+    }
+
+    match item {
+        ResponseItem::FunctionCall {
+            name, arguments, ..
+        } => {
+            if sess
+                .services
+                .mcp_connection_manager
+                .parse_tool_name(name)
+                .is_some()
+            {
+                return sess.services.mcp_connection_manager.is_read_only_tool(name);
+            }
+            match name.as_str() {
+                "container.exec" | "shell" => {
+                    serde_json::from_str::<ShellToolCallParams>(arguments).is_ok_and(|params| {
+                        !params.with_escalated_permissions.unwrap_or(false)
+                            && is_known_safe_command(&params.command)
+                    })
+                }
+                "view_image" => true,
+                _ => false,
+            }
+        }
+        ResponseItem::LocalShellCall {
+            call_id,
+            id,
+            action,
+            ..
+        } => {
+            let LocalShellAction::Exec(action) = action;
+            (call_id.is_some() || id.is_some()) && is_known_safe_command(&action.command)
+        }
+        _ => false,
+    }
+}
+
+/// Handle tool calls accepted by [`is_parallel_safe_call`], running at most
+/// `max_parallel_tool_calls` of them at once. The processed items are returned
+/// in the order the model emitted the calls.
+pub(crate) async fn handle_parallel_response_items(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    items: Vec<ResponseItem>,
+) -> CodexResult<Vec<ProcessedResponseItem>> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    stream::iter(items.into_iter().map(|item| async move {
+        // None of these calls edit files, so there is nothing to add to the
+        // turn's diff and each one can use its own tracker.
+        let mut turn_diff_tracker = TurnDiffTracker::new();
+        let response = handle_response_item(
+            sess,
+            turn_context,
+            &mut turn_diff_tracker,
+            sub_id,
+            item.clone(),
+        )
+        .await?;
+        Ok(ProcessedResponseItem { item, response })
+    }))
+    .buffered(sess.services.max_parallel_tool_calls)
+    .try_collect()
+    .await
+}
@@ -1,13 +1,17 @@
+use crate::config_keymap::Keymap;
 use crate::config_profile::ConfigProfile;
 use crate::config_types::History;
+use crate::config_types::LinuxSandboxBackend;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
 use crate::config_types::Notifications;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::ResourceLimits;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::config_types::Tui;
+use crate::config_types::TurnLimits;
 use crate::config_types::UriBasedFileOpener;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
@@ -16,6 +20,7 @@ use crate::model_family::find_family_for_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
 use crate::openai_model_info::get_model_info;
+use crate::overlay;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 use anyhow::Context;
@@ -47,6 +52,10 @@ pub const GPT_5_CODEX_MEDIUM_MODEL: &str = "gpt-5-codex";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
+/// Default number of read-only tool calls from one model response that may
+/// run at the same time. Parallel tool calls stay off unless configured.
+pub(crate) const DEFAULT_MAX_PARALLEL_TOOL_CALLS: usize = 1;
+
 pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
 
 /// Application configuration loaded from disk and merged with overrides.
@@ -69,6 +78,14 @@ pub struct Config {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Caps on tool calls, wall-clock time and follow-up model requests per
+    /// task. When one is hit the task ends with a summary from the model.
+    pub turn_limits: TurnLimits,
+
+    /// Maximum number of read-only tool calls from a single model response
+    /// that run concurrently. `1` runs every call sequentially.
+    pub max_parallel_tool_calls: usize,
+
     /// Key into the model_providers map that specifies which provider to use.
     pub model_provider_id: String,
 
@@ -80,6 +97,11 @@ pub struct Config {
 
     pub sandbox_policy: SandboxPolicy,
 
+    /// Paths sandboxed commands must not read, with `~` expanded. Kept apart
+    /// from `sandbox_policy` so it can be reapplied when the sandbox mode is
+    /// changed during a session.
+    pub sandbox_deny_read: Vec<PathBuf>,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
     /// When `true`, `AgentReasoning` events emitted by the backend will be
@@ -123,6 +145,12 @@ pub struct Config {
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
 
+    /// Key bindings for the TUI, from `[tui.keymap]`.
+    pub tui_keymap: Keymap,
+
+    /// Whether the composer uses vim-style modal editing, from `[tui] vim_mode`.
+    pub tui_vim_mode: bool,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -156,6 +184,12 @@ pub struct Config {
     /// When this program is invoked, arg0 will be set to `codex-linux-sandbox`.
     pub codex_linux_sandbox_exe: Option<PathBuf>,
 
+    /// How sandboxed commands are isolated on Linux.
+    pub linux_sandbox_backend: LinuxSandboxBackend,
+
+    /// Limits on the CPU time, memory and processes of shell tool commands.
+    pub sandbox_resource_limits: ResourceLimits,
+
     /// Value to use for `reasoning.effort` when making a request using the
     /// Responses API.
     pub model_reasoning_effort: Option<ReasoningEffort>,
@@ -633,6 +667,21 @@ pub struct ConfigToml {
     /// Token usage threshold triggering auto-compaction of conversation history.
     pub model_auto_compact_token_limit: Option<i64>,
 
+    /// Maximum number of tool calls the model may make within one task.
+    pub max_tool_calls_per_turn: Option<u32>,
+
+    /// Maximum wall-clock time, in seconds, that one task may run.
+    #[serde(default, with = "crate::config_types::option_duration_secs")]
+    pub max_turn_duration_seconds: Option<std::time::Duration>,
+
+    /// Maximum number of follow-up model requests driven by tool output
+    /// within one task.
+    pub max_autonomous_turns: Option<u32>,
+
+    /// Maximum number of read-only tool calls from a single model response
+    /// that run concurrently. Set to `1` to disable parallel execution.
+    pub max_parallel_tool_calls: Option<usize>,
+
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
@@ -645,6 +694,21 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Paths that sandboxed commands must not read in `read-only` and
+    /// `workspace-write` mode, such as `~/.ssh` or `.env`. A leading `~/` is
+    /// expanded to the home directory; other relative paths are resolved
+    /// against the working directory.
+    #[serde(default)]
+    pub sandbox_deny_read: Vec<PathBuf>,
+
+    /// How sandboxed commands are isolated on Linux: `landlock` (default) or
+    /// `namespaces`.
+    pub linux_sandbox_backend: Option<LinuxSandboxBackend>,
+
+    /// Limits on the CPU time, memory and processes of shell tool commands.
+    #[serde(default)]
+    pub sandbox_resource_limits: ResourceLimits,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
@@ -768,13 +832,30 @@ impl From<ToolsToml> for Tools {
     }
 }
 
+/// Fails for sandbox policies that cannot be enforced on this platform:
+/// only the Linux namespaces backend can mount an overlay.
+fn ensure_sandbox_policy_supported(sandbox_policy: &SandboxPolicy) -> std::io::Result<()> {
+    if sandbox_policy.overlay_layer_dir().is_some() && !cfg!(target_os = "linux") {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "sandbox_mode = \"overlay\" is only supported on Linux",
+        ));
+    }
+    Ok(())
+}
+
 impl ConfigToml {
     /// Derive the effective sandbox policy from the configuration.
-    fn derive_sandbox_policy(&self, sandbox_mode_override: Option<SandboxMode>) -> SandboxPolicy {
+    /// `overlay_layer_dir` is where an overlay policy keeps its layer.
+    fn derive_sandbox_policy(
+        &self,
+        sandbox_mode_override: Option<SandboxMode>,
+        overlay_layer_dir: &Path,
+    ) -> SandboxPolicy {
         let resolved_sandbox_mode = sandbox_mode_override
             .or(self.sandbox_mode)
             .unwrap_or_default();
-        match resolved_sandbox_mode {
+        let sandbox_policy = match resolved_sandbox_mode {
             SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
             SandboxMode::WorkspaceWrite => match self.sandbox_workspace_write.as_ref() {
                 Some(SandboxWorkspaceWrite {
@@ -787,11 +868,23 @@ impl ConfigToml {
                     network_access: *network_access,
                     exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
                     exclude_slash_tmp: *exclude_slash_tmp,
+                    deny_read: Vec::new(),
                 },
                 None => SandboxPolicy::new_workspace_write_policy(),
             },
             SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
-        }
+            SandboxMode::Overlay => {
+                SandboxPolicy::new_overlay_policy(overlay_layer_dir.to_path_buf())
+            }
+        };
+        sandbox_policy.with_deny_read(self.resolve_sandbox_deny_read())
+    }
+
+    fn resolve_sandbox_deny_read(&self) -> Vec<PathBuf> {
+        self.sandbox_deny_read
+            .iter()
+            .map(|path| expand_home_dir(path))
+            .collect()
     }
 
     pub fn is_cwd_trusted(&self, resolved_cwd: &Path) -> bool {
@@ -907,7 +1000,30 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
-        let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
+        let resolved_cwd = {
+            use std::env;
+
+            match cwd {
+                None => {
+                    tracing::info!("cwd not set, using current dir");
+                    env::current_dir()?
+                }
+                Some(p) if p.is_absolute() => p,
+                Some(p) => {
+                    // Resolve relative path against the current working directory.
+                    tracing::info!("cwd is relative, resolving against current dir");
+                    let mut current = env::current_dir()?;
+                    current.push(p);
+                    current
+                }
+            }
+        };
+        // Sessions move the layer into a directory of their own.
+        let sandbox_policy =
+            cfg.derive_sandbox_policy(sandbox_mode, &overlay::overlays_dir(&codex_home));
+        ensure_sandbox_policy_supported(&sandbox_policy)?;
+
+        let sandbox_deny_read = cfg.resolve_sandbox_deny_read();
 
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
@@ -931,25 +1047,6 @@ impl Config {
 
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
-        let resolved_cwd = {
-            use std::env;
-
-            match cwd {
-                None => {
-                    tracing::info!("cwd not set, using current dir");
-                    env::current_dir()?
-                }
-                Some(p) if p.is_absolute() => p,
-                Some(p) => {
-                    // Resolve relative path against the current working directory.
-                    tracing::info!("cwd is relative, resolving against current dir");
-                    let mut current = env::current_dir()?;
-                    current.push(p);
-                    current
-                }
-            }
-        };
-
         let history = cfg.history.unwrap_or_default();
 
         let tools_web_search_request = override_tools_web_search_request
@@ -1013,6 +1110,15 @@ impl Config {
             model_context_window,
             model_max_output_tokens,
             model_auto_compact_token_limit,
+            turn_limits: TurnLimits {
+                max_tool_calls_per_turn: cfg.max_tool_calls_per_turn,
+                max_turn_duration: cfg.max_turn_duration_seconds,
+                max_autonomous_turns: cfg.max_autonomous_turns,
+            },
+            max_parallel_tool_calls: cfg
+                .max_parallel_tool_calls
+                .unwrap_or(DEFAULT_MAX_PARALLEL_TOOL_CALLS)
+                .max(1),
             model_provider_id,
             model_provider,
             cwd: resolved_cwd,
@@ -1021,6 +1127,7 @@ impl Config {
                 .or(cfg.approval_policy)
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
+            sandbox_deny_read,
             shell_environment_policy,
             notify: cfg.notify,
             user_instructions,
@@ -1032,6 +1139,8 @@ impl Config {
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
+            linux_sandbox_backend: cfg.linux_sandbox_backend.unwrap_or_default(),
+            sandbox_resource_limits: cfg.sandbox_resource_limits,
 
             hide_agent_reasoning: cfg.hide_agent_reasoning.unwrap_or(false),
             show_raw_agent_reasoning: cfg
@@ -1068,6 +1177,12 @@ impl Config {
                 .as_ref()
                 .map(|t| t.notifications.clone())
                 .unwrap_or_default(),
+            tui_keymap: cfg
+                .tui
+                .as_ref()
+                .map(|t| t.keymap.clone())
+                .unwrap_or_default(),
+            tui_vim_mode: cfg.tui.as_ref().is_some_and(|t| t.vim_mode),
         };
         Ok(config)
     }
@@ -1167,6 +1282,14 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Expands a leading `~` in `path` to the home directory, if there is one.
+fn expand_home_dir(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
 /// Returns the path to the folder where Codex logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -1244,7 +1367,8 @@ network_access = false  # This should be ignored.
         let sandbox_mode_override = None;
         assert_eq!(
             SandboxPolicy::DangerFullAccess,
-            sandbox_full_access_cfg.derive_sandbox_policy(sandbox_mode_override)
+            sandbox_full_access_cfg
+                .derive_sandbox_policy(sandbox_mode_override, Path::new("/layer"))
         );
 
         let sandbox_read_only = r#"
@@ -1258,8 +1382,8 @@ network_access = true  # This should be ignored.
             .expect("TOML deserialization should succeed");
         let sandbox_mode_override = None;
         assert_eq!(
-            SandboxPolicy::ReadOnly,
-            sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override)
+            SandboxPolicy::new_read_only_policy(),
+            sandbox_read_only_cfg.derive_sandbox_policy(sandbox_mode_override, Path::new("/layer"))
         );
 
         let sandbox_workspace_write = r#"
@@ -1282,8 +1406,92 @@ exclude_slash_tmp = true
                 network_access: false,
                 exclude_tmpdir_env_var: true,
                 exclude_slash_tmp: true,
+                deny_read: Vec::new(),
+            },
+            sandbox_workspace_write_cfg
+                .derive_sandbox_policy(sandbox_mode_override, Path::new("/layer"))
+        );
+
+        let sandbox_deny_read = r#"
+sandbox_mode = "read-only"
+sandbox_deny_read = ["~/.ssh", ".env"]
+"#;
+
+        let sandbox_deny_read_cfg = toml::from_str::<ConfigToml>(sandbox_deny_read)
+            .expect("TOML deserialization should succeed");
+        let home = home_dir().expect("home dir");
+        assert_eq!(
+            SandboxPolicy::new_read_only_policy()
+                .with_deny_read(vec![home.join(".ssh"), PathBuf::from(".env")]),
+            sandbox_deny_read_cfg.derive_sandbox_policy(None, Path::new("/layer"))
+        );
+        assert_eq!(
+            SandboxPolicy::DangerFullAccess,
+            sandbox_deny_read_cfg
+                .derive_sandbox_policy(Some(SandboxMode::DangerFullAccess), Path::new("/layer"))
+        );
+
+        let sandbox_overlay_cfg = toml::from_str::<ConfigToml>(r#"sandbox_mode = "overlay""#)
+            .expect("TOML deserialization should succeed");
+        assert_eq!(
+            SandboxPolicy::new_overlay_policy(PathBuf::from("/layer")),
+            sandbox_overlay_cfg.derive_sandbox_policy(None, Path::new("/layer"))
+        );
+    }
+
+    #[test]
+    fn overlay_sandbox_is_only_supported_on_linux() {
+        let overlay_policy = SandboxPolicy::new_overlay_policy(PathBuf::from("/layer"));
+        assert_eq!(
+            cfg!(target_os = "linux"),
+            ensure_sandbox_policy_supported(&overlay_policy).is_ok()
+        );
+        assert!(ensure_sandbox_policy_supported(&SandboxPolicy::new_read_only_policy()).is_ok());
+    }
+
+    #[test]
+    fn test_sandbox_resource_limits_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[sandbox_resource_limits]
+cpu_time_seconds = 600
+max_processes = 256
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        assert_eq!(
+            ResourceLimits {
+                cpu_time_seconds: Some(600),
+                memory_mb: None,
+                max_processes: Some(256),
             },
-            sandbox_workspace_write_cfg.derive_sandbox_policy(sandbox_mode_override)
+            cfg.sandbox_resource_limits
+        );
+        assert_eq!(
+            ResourceLimits::default(),
+            toml::from_str::<ConfigToml>("")
+                .expect("TOML deserialization should succeed")
+                .sandbox_resource_limits
+        );
+    }
+
+    #[test]
+    fn test_linux_sandbox_backend_parsing() {
+        let cfg = toml::from_str::<ConfigToml>(r#"linux_sandbox_backend = "namespaces""#)
+            .expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(LinuxSandboxBackend::Namespaces),
+            cfg.linux_sandbox_backend
+        );
+        assert_eq!(
+            crate::exec::SandboxType::LinuxNamespaces,
+            crate::exec::SandboxType::LinuxSeccomp
+                .with_linux_backend(LinuxSandboxBackend::Namespaces)
+        );
+        assert_eq!(
+            crate::exec::SandboxType::MacosSeatbelt,
+            crate::exec::SandboxType::MacosSeatbelt
+                .with_linux_backend(LinuxSandboxBackend::Namespaces)
         );
     }
 
@@ -1777,10 +1985,13 @@ model_verbosity = "high"
                 model_context_window: Some(200_000),
                 model_max_output_tokens: Some(100_000),
                 model_auto_compact_token_limit: None,
+                turn_limits: TurnLimits::default(),
+                max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                sandbox_deny_read: Vec::new(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
@@ -1792,6 +2003,8 @@ model_verbosity = "high"
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
+                linux_sandbox_backend: LinuxSandboxBackend::default(),
+                sandbox_resource_limits: ResourceLimits::default(),
                 hide_agent_reasoning: false,
                 show_raw_agent_reasoning: false,
                 model_reasoning_effort: Some(ReasoningEffort::High),
@@ -1809,6 +2022,8 @@ model_verbosity = "high"
                 active_profile: Some("o3".to_string()),
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
+                tui_keymap: Default::default(),
+                tui_vim_mode: false,
             },
             o3_profile_config
         );
@@ -1836,10 +2051,13 @@ model_verbosity = "high"
             model_context_window: Some(16_385),
             model_max_output_tokens: Some(4_096),
             model_auto_compact_token_limit: None,
+            turn_limits: TurnLimits::default(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             model_provider_id: "openai-chat-completions".to_string(),
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_deny_read: Vec::new(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
@@ -1851,6 +2069,8 @@ model_verbosity = "high"
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_resource_limits: ResourceLimits::default(),
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
             model_reasoning_effort: None,
@@ -1868,6 +2088,8 @@ model_verbosity = "high"
             active_profile: Some("gpt3".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_keymap: Default::default(),
+            tui_vim_mode: false,
         };
 
         assert_eq!(expected_gpt3_profile_config, gpt3_profile_config);
@@ -1910,10 +2132,13 @@ model_verbosity = "high"
             model_context_window: Some(200_000),
             model_max_output_tokens: Some(100_000),
             model_auto_compact_token_limit: None,
+            turn_limits: TurnLimits::default(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_deny_read: Vec::new(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
@@ -1925,6 +2150,8 @@ model_verbosity = "high"
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_resource_limits: ResourceLimits::default(),
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
             model_reasoning_effort: None,
@@ -1942,6 +2169,8 @@ model_verbosity = "high"
             active_profile: Some("zdr".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_keymap: Default::default(),
+            tui_vim_mode: false,
         };
 
         assert_eq!(expected_zdr_profile_config, zdr_profile_config);
@@ -1970,10 +2199,13 @@ model_verbosity = "high"
             model_context_window: Some(272_000),
             model_max_output_tokens: Some(128_000),
             model_auto_compact_token_limit: None,
+            turn_limits: TurnLimits::default(),
+            max_parallel_tool_calls: DEFAULT_MAX_PARALLEL_TOOL_CALLS,
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_deny_read: Vec::new(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
@@ -1985,6 +2217,8 @@ model_verbosity = "high"
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
+            linux_sandbox_backend: LinuxSandboxBackend::default(),
+            sandbox_resource_limits: ResourceLimits::default(),
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
             model_reasoning_effort: Some(ReasoningEffort::High),
@@ -2002,6 +2236,8 @@ model_verbosity = "high"
             active_profile: Some("gpt5".to_string()),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
+            tui_keymap: Default::default(),
+            tui_vim_mode: false,
         };
 
         assert_eq!(expected_gpt5_profile_config, gpt5_profile_config);
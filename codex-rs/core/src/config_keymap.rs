@@ -0,0 +1,369 @@
+//! The `[tui.keymap]` table, which rebinds the keys of the TUI.
+//!
+//! ```toml
+//! [tui.keymap]
+//! preset = "emacs"
+//! open-transcript = "ctrl+o"
+//! newline = ["shift+enter", "alt+enter"]
+//! paste-image = []
+//! ```
+//!
+//! Chords are written as `+`-separated modifiers (`ctrl`, `alt`, `shift`)
+//! followed by a key: a single character or one of the names accepted by
+//! [`Key`]'s parser, such as `enter`, `esc`, `pageup` or `f5`. Binding an
+//! action replaces all of the preset's chords for it, and an empty list
+//! unbinds it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Deserializer;
+use serde::de::Error as SerdeError;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Keymap {
+    /// Default bindings that the entries below are applied on top of.
+    #[serde(default)]
+    pub preset: KeymapPreset,
+
+    /// Chords for individual actions.
+    #[serde(flatten)]
+    pub bindings: BTreeMap<KeymapAction, KeyChords>,
+}
+
+/// Sets of default bindings. Each preset keeps the default chords and adds
+/// its own navigation keys where they do not clash with typing.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeymapPreset {
+    #[default]
+    Default,
+    /// `Ctrl+P`/`Ctrl+N` for history and cursor movement, and `Ctrl+V`,
+    /// `Alt+V`, `Alt+<` and `Alt+>` to page through the transcript.
+    Emacs,
+    /// `j`/`k`, `Ctrl+F`/`Ctrl+B` and `g`/`G` to move through the transcript.
+    Vi,
+}
+
+/// Actions that can be bound to keys.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeymapAction {
+    /// Send the composer's message.
+    Submit,
+    /// Insert a line break in the composer.
+    Newline,
+    /// Recall the previous message sent from the composer.
+    HistoryPrev,
+    /// Recall the next message sent from the composer.
+    HistoryNext,
+    /// Open the transcript, or close it when it is open.
+    OpenTranscript,
+    /// Step back to an earlier message to edit it.
+    Backtrack,
+    /// Interrupt the running task, or quit when pressed twice.
+    Interrupt,
+    /// Quit when the composer is empty.
+    Quit,
+    /// Attach the image on the clipboard.
+    PasteImage,
+    /// Move the last queued message back into the composer.
+    EditQueuedMessage,
+    /// Send the queued messages to the running turn without interrupting it.
+    SteerTurn,
+    /// Edit the composer draft in `$VISUAL` or `$EDITOR`.
+    OpenEditor,
+    /// Search the message history of all sessions.
+    SearchHistory,
+    /// Approve the command or patch once.
+    AcceptApproval,
+    /// Approve the command for the rest of the session.
+    AcceptApprovalForSession,
+    /// Refuse the command or patch.
+    DeclineApproval,
+    /// Edit the command awaiting approval before running it.
+    EditApprovalCommand,
+    /// Open the patch awaiting approval in the diff browser.
+    ReviewChanges,
+    /// Choose which files and hunks of a patch to apply.
+    SelectPatchHunks,
+    /// Close the transcript or another pager.
+    ClosePager,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    ScrollToTop,
+    ScrollToBottom,
+    /// Jump to the next hunk in the diff browser.
+    NextHunk,
+    /// Jump to the previous hunk in the diff browser.
+    PrevHunk,
+    /// Jump to the next file in the diff browser.
+    NextFile,
+    /// Jump to the previous file in the diff browser.
+    PrevFile,
+    /// Switch the diff browser between unified and side-by-side layouts.
+    ToggleDiffLayout,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    MoveWordLeft,
+    MoveWordRight,
+    LineStart,
+    LineEnd,
+    DeleteBackward,
+    DeleteForward,
+    DeleteWordBackward,
+    DeleteWordForward,
+    KillToLineStart,
+    KillToLineEnd,
+}
+
+/// The chords bound to one action, written as a single chord or a list.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct KeyChords(pub Vec<KeyChord>);
+
+impl<'de> Deserialize<'de> for KeyChords {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ChordsVisitor;
+
+        impl<'de> Visitor<'de> for ChordsVisitor {
+            type Value = KeyChords;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a key chord such as \"ctrl+t\", or a list of them")
+            }
+
+            fn visit_str<E: SerdeError>(self, value: &str) -> Result<KeyChords, E> {
+                value
+                    .parse()
+                    .map(|chord| KeyChords(vec![chord]))
+                    .map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<KeyChords, A::Error> {
+                let mut chords = Vec::new();
+                while let Some(chord) = seq.next_element::<String>()? {
+                    chords.push(chord.parse().map_err(A::Error::custom)?);
+                }
+                Ok(KeyChords(chords))
+            }
+        }
+
+        deserializer.deserialize_any(ChordsVisitor)
+    }
+}
+
+/// A key together with the modifiers held while pressing it.
+///
+/// Characters carry their case instead of `shift`: `shift+g` is parsed as
+/// `G`, and `ctrl+T` as `ctrl+t`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl KeyChord {
+    pub const fn plain(key: Key) -> Self {
+        Self {
+            key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    pub const fn ctrl(key: Key) -> Self {
+        Self {
+            ctrl: true,
+            ..Self::plain(key)
+        }
+    }
+
+    pub const fn alt(key: Key) -> Self {
+        Self {
+            alt: true,
+            ..Self::plain(key)
+        }
+    }
+
+    pub const fn shift(key: Key) -> Self {
+        Self {
+            shift: true,
+            ..Self::plain(key)
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    F(u8),
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+        let key = match name.to_ascii_lowercase().as_str() {
+            "enter" | "return" => Key::Enter,
+            "esc" | "escape" => Key::Esc,
+            "tab" => Key::Tab,
+            "backspace" => Key::Backspace,
+            "delete" | "del" => Key::Delete,
+            "insert" | "ins" => Key::Insert,
+            "up" => Key::Up,
+            "down" => Key::Down,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "home" => Key::Home,
+            "end" => Key::End,
+            "pageup" | "pgup" => Key::PageUp,
+            "pagedown" | "pgdn" => Key::PageDown,
+            "space" => Key::Char(' '),
+            "plus" => Key::Char('+'),
+            other => match other.strip_prefix('f').map(str::parse::<u8>) {
+                Some(Ok(n)) if (1..=24).contains(&n) => Key::F(n),
+                _ => return Err(format!("unknown key `{name}`")),
+            },
+        };
+        Ok(key)
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(chord: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = match chord.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None if chord == "+" => ("", "+"),
+            None => match chord.rsplit_once('+') {
+                Some((modifiers, key)) => (modifiers, key),
+                None => ("", chord),
+            },
+        };
+        let key: Key = key
+            .parse()
+            .map_err(|err| format!("invalid key chord `{chord}`: {err}"))?;
+        let mut parsed = KeyChord::plain(key);
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => parsed.ctrl = true,
+                "alt" | "meta" | "option" => parsed.alt = true,
+                "shift" => parsed.shift = true,
+                other => {
+                    return Err(format!(
+                        "invalid key chord `{chord}`: unknown modifier `{other}`"
+                    ));
+                }
+            }
+        }
+        if let Key::Char(c) = parsed.key {
+            let c = if parsed.shift {
+                c.to_ascii_uppercase()
+            } else if parsed.ctrl || parsed.alt {
+                c.to_ascii_lowercase()
+            } else {
+                c
+            };
+            parsed.key = Key::Char(c);
+            parsed.shift = false;
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_chords() {
+        assert_eq!("ctrl+t".parse(), Ok(KeyChord::ctrl(Key::Char('t'))));
+        assert_eq!("Ctrl+T".parse(), Ok(KeyChord::ctrl(Key::Char('t'))));
+        assert_eq!("shift+g".parse(), Ok(KeyChord::plain(Key::Char('G'))));
+        assert_eq!("shift+enter".parse(), Ok(KeyChord::shift(Key::Enter)));
+        assert_eq!("alt+PageUp".parse(), Ok(KeyChord::alt(Key::PageUp)));
+        assert_eq!("ctrl++".parse(), Ok(KeyChord::ctrl(Key::Char('+'))));
+        assert_eq!("space".parse(), Ok(KeyChord::plain(Key::Char(' '))));
+        assert_eq!("f5".parse(), Ok(KeyChord::plain(Key::F(5))));
+        assert_eq!(
+            "hyper+x".parse::<KeyChord>(),
+            Err("invalid key chord `hyper+x`: unknown modifier `hyper`".to_string())
+        );
+        assert_eq!(
+            "ctrl+bogus".parse::<KeyChord>(),
+            Err("invalid key chord `ctrl+bogus`: unknown key `bogus`".to_string())
+        );
+    }
+
+    #[test]
+    fn deserializes_keymap_table() {
+        let keymap: Keymap = toml::from_str(
+            r#"
+            preset = "vi"
+            submit = "ctrl+enter"
+            newline = ["enter", "shift+enter"]
+            paste-image = []
+            "#,
+        )
+        .expect("keymap should parse");
+
+        assert_eq!(
+            keymap,
+            Keymap {
+                preset: KeymapPreset::Vi,
+                bindings: BTreeMap::from([
+                    (
+                        KeymapAction::Submit,
+                        KeyChords(vec![KeyChord::ctrl(Key::Enter)]),
+                    ),
+                    (
+                        KeymapAction::Newline,
+                        KeyChords(vec![
+                            KeyChord::plain(Key::Enter),
+                            KeyChord::shift(Key::Enter),
+                        ]),
+                    ),
+                    (KeymapAction::PasteImage, KeyChords(Vec::new())),
+                ]),
+            }
+        );
+
+        let err = toml::from_str::<Keymap>(r#"launch-rockets = "ctrl+r""#)
+            .expect_err("unknown actions are rejected");
+        assert!(err.to_string().contains("unknown variant `launch-rockets`"));
+    }
+}
@@ -3,6 +3,7 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use crate::config_keymap::Keymap;
 use serde::Deserializer;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -141,7 +142,7 @@ pub enum McpServerTransportConfig {
     },
 }
 
-mod option_duration_secs {
+pub(crate) mod option_duration_secs {
     use serde::Deserialize;
     use serde::Deserializer;
     use serde::Serializer;
@@ -198,14 +199,30 @@ impl UriBasedFileOpener {
     }
 }
 
+/// Caps on how long a single task may keep calling tools without returning
+/// control to the user. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TurnLimits {
+    /// Maximum number of tool calls the model may make within one task.
+    pub max_tool_calls_per_turn: Option<u32>,
+
+    /// Maximum wall-clock time a task may run before it is stopped at the
+    /// next tool-call boundary.
+    pub max_turn_duration: Option<Duration>,
+
+    /// Maximum number of follow-up model requests (each one triggered by tool
+    /// output rather than user input) within one task.
+    pub max_autonomous_turns: Option<u32>,
+}
+
 /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct History {
     /// If true, history entries will not be written to disk.
     pub persistence: HistoryPersistence,
 
-    /// If set, the maximum size of the history file in bytes.
-    /// TODO(mbolin): Not currently honored.
+    /// If set, the maximum size of the history file in bytes. Once the file
+    /// grows past it, the oldest entries are dropped.
     pub max_bytes: Option<usize>,
 }
 
@@ -219,6 +236,38 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// How sandboxed commands are isolated on Linux.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LinuxSandboxBackend {
+    /// Landlock file-system rules plus a seccomp filter for the network.
+    #[default]
+    Landlock,
+    /// User, mount, PID and network namespaces, like bubblewrap. Works on
+    /// kernels without Landlock and gives each command a private `/tmp`.
+    Namespaces,
+}
+
+/// Limits on the resources a command run by the shell tool may use. Unset
+/// limits are not enforced.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// CPU time, in seconds, each process of the command may use.
+    #[serde(default)]
+    pub cpu_time_seconds: Option<u64>,
+
+    /// Memory, in MiB, of the command. Bounds the memory of the whole process
+    /// tree when the command can run in a cgroup with the `memory`
+    /// controller, and the virtual address space of each process otherwise.
+    #[serde(default)]
+    pub memory_mb: Option<u64>,
+
+    /// Number of processes the command may run at the same time, counting
+    /// the command itself.
+    #[serde(default)]
+    pub max_processes: Option<u64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
 #[serde(untagged)]
 pub enum Notifications {
@@ -239,6 +288,15 @@ pub struct Tui {
     /// Defaults to `false`.
     #[serde(default)]
     pub notifications: Notifications,
+
+    /// Key bindings, from the `[tui.keymap]` table.
+    #[serde(default)]
+    pub keymap: Keymap,
+
+    /// Edit the composer with vim-style normal, insert and visual modes.
+    /// Defaults to `false`.
+    #[serde(default)]
+    pub vim_mode: bool,
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
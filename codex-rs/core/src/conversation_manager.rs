@@ -15,6 +15,7 @@ use crate::protocol::SessionConfiguredEvent;
 use crate::rollout::RolloutRecorder;
 use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ForkOrigin;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::RolloutItem;
 use std::collections::HashMap;
@@ -168,15 +169,12 @@ fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> Initia
     let items: Vec<RolloutItem> = history.get_rollout_items();
 
     // Find indices of user message inputs in rollout order.
-    let mut user_positions: Vec<usize> = Vec::new();
-    for (idx, item) in items.iter().enumerate() {
-        if let RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. }) = item
-            && role == "user"
-            && content_items_to_text(content).is_some_and(|text| !is_session_prefix_message(&text))
-        {
-            user_positions.push(idx);
-        }
-    }
+    let user_positions: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| user_message_text(item).is_some())
+        .map(|(idx, _)| idx)
+        .collect();
 
     // If fewer than or equal to n user messages exist, treat as empty (out of range).
     if user_positions.len() <= n {
@@ -194,6 +192,36 @@ fn truncate_before_nth_user_message(history: InitialHistory, n: usize) -> Initia
     }
 }
 
+/// Where the conversation forked into `items` branched off: the first
+/// session meta of a rollout is the conversation's own, and the fork kept
+/// every user message before the one it diverges at.
+pub(crate) fn fork_origin(items: &[RolloutItem]) -> Option<ForkOrigin> {
+    let conversation_id = items.iter().find_map(|item| match item {
+        RolloutItem::SessionMeta(meta_line) => Some(meta_line.meta.id),
+        _ => None,
+    })?;
+    Some(ForkOrigin {
+        conversation_id,
+        nth_user_message: items
+            .iter()
+            .filter(|item| user_message_text(item).is_some())
+            .count(),
+    })
+}
+
+/// The text of a message the user sent, leaving out the messages every
+/// session starts with.
+pub(crate) fn user_message_text(item: &RolloutItem) -> Option<String> {
+    match item {
+        RolloutItem::ResponseItem(ResponseItem::Message { role, content, .. })
+            if role == "user" =>
+        {
+            content_items_to_text(content).filter(|text| !is_session_prefix_message(text))
+        }
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,5 @@
 use crate::exec::ExecToolCallOutput;
+use crate::exec::ResourceLimit;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use codex_protocol::mcp_protocol::ConversationId;
@@ -6,6 +7,7 @@ use codex_protocol::protocol::RateLimitSnapshot;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -19,7 +21,13 @@ pub enum SandboxErr {
         "sandbox denied exec error, exit code: {}, stdout: {}, stderr: {}",
         .output.exit_code, .output.stdout.text, .output.stderr.text
     )]
-    Denied { output: Box<ExecToolCallOutput> },
+    Denied {
+        output: Box<ExecToolCallOutput>,
+        /// The operations that the sandbox reported denying, e.g.
+        /// `network access (connect)`. Empty when the sandbox
+        /// cannot report them.
+        denials: Vec<String>,
+    },
 
     /// Error from linux seccomp filter setup
     #[cfg(target_os = "linux")]
@@ -39,9 +47,20 @@ pub enum SandboxErr {
     #[error("command was killed by a signal")]
     Signal(i32),
 
+    /// Command was stopped by one of the configured resource limits
+    #[error("command exceeded its {limit} limit")]
+    ResourceLimit {
+        limit: ResourceLimit,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
+
+    /// A `sandbox_deny_read` path that the sandbox cannot deny
+    #[error("cannot deny reading {}: {reason}", .path.display())]
+    DenyRead { path: PathBuf, reason: String },
 }
 
 #[derive(Error, Debug)]
@@ -254,12 +273,22 @@ impl CodexErr {
 
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
-        CodexErr::Sandbox(SandboxErr::Denied { output }) => output.stderr.text.clone(),
+        CodexErr::Sandbox(SandboxErr::Denied { output, denials }) if !denials.is_empty() => {
+            format!(
+                "{}\nsandbox denied: {}",
+                output.stderr.text,
+                denials.join(", ")
+            )
+        }
+        CodexErr::Sandbox(SandboxErr::Denied { output, .. }) => output.stderr.text.clone(),
         // Timeouts are not sandbox errors from a UX perspective; present them plainly
         CodexErr::Sandbox(SandboxErr::Timeout { output }) => format!(
             "error: command timed out after {} ms",
             output.duration.as_millis()
         ),
+        CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, .. }) => {
+            format!("error: command exceeded its {limit} limit (sandbox_resource_limits)")
+        }
         _ => e.to_string(),
     }
 }
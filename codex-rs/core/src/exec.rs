@@ -15,10 +15,13 @@ use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
 
+use crate::config_types::LinuxSandboxBackend;
+use crate::config_types::ResourceLimits;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
-use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::landlock::spawn_command_under_linux_namespaces;
+use crate::landlock::spawn_command_under_linux_sandbox_reporting_denials;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
@@ -26,6 +29,7 @@ use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
+use crate::spawn::release_exec_cgroup;
 use crate::spawn::spawn_child_async;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
@@ -33,6 +37,7 @@ const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 // Hardcode these since it does not seem worth including the libc crate just
 // for these.
 const SIGKILL_CODE: i32 = 9;
+const SIGXCPU_CODE: i32 = 24;
 const TIMEOUT_CODE: i32 = 64;
 const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 const EXEC_TIMEOUT_EXIT_CODE: i32 = 124; // conventional timeout exit code
@@ -53,6 +58,7 @@ pub struct ExecParams {
     pub env: HashMap<String, String>,
     pub with_escalated_permissions: Option<bool>,
     pub justification: Option<String>,
+    pub resource_limits: ResourceLimits,
 }
 
 impl ExecParams {
@@ -70,6 +76,22 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Only available on Linux.
+    LinuxNamespaces,
+}
+
+impl SandboxType {
+    /// The sandbox to use in place of this one when Linux sandboxing is
+    /// configured to use `backend`.
+    pub fn with_linux_backend(self, backend: LinuxSandboxBackend) -> Self {
+        match (self, backend) {
+            (SandboxType::LinuxSeccomp, LinuxSandboxBackend::Namespaces) => {
+                SandboxType::LinuxNamespaces
+            }
+            (sandbox_type, _) => sandbox_type,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -90,6 +112,7 @@ pub async fn process_exec_tool_call(
     let start = Instant::now();
 
     let timeout_duration = params.timeout_duration();
+    let resource_limits = params.resource_limits;
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -108,6 +131,7 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                resource_limits,
             )
             .await?;
             consume_truncated_output(child, timeout_duration, stdout_stream.clone()).await
@@ -123,7 +147,8 @@ pub async fn process_exec_tool_call(
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
-            let child = spawn_command_under_linux_sandbox(
+            let (denials_reader, denials_writer) = io::pipe()?;
+            let child = spawn_command_under_linux_sandbox_reporting_denials(
                 codex_linux_sandbox_exe,
                 command,
                 command_cwd,
@@ -131,6 +156,36 @@ pub async fn process_exec_tool_call(
                 sandbox_cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                resource_limits,
+                denials_writer,
+            )
+            .await?;
+
+            let mut raw_output =
+                consume_truncated_output(child, timeout_duration, stdout_stream).await?;
+            raw_output.sandbox_denials = read_sandbox_denials(denials_reader);
+            Ok(raw_output)
+        }
+        SandboxType::LinuxNamespaces => {
+            let ExecParams {
+                command,
+                cwd: command_cwd,
+                env,
+                ..
+            } = params;
+
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            let child = spawn_command_under_linux_namespaces(
+                codex_linux_sandbox_exe,
+                command,
+                command_cwd,
+                sandbox_policy,
+                sandbox_cwd,
+                StdioPolicy::RedirectForShellTool,
+                env,
+                resource_limits,
             )
             .await?;
 
@@ -140,6 +195,8 @@ pub async fn process_exec_tool_call(
     let duration = start.elapsed();
     match raw_output_result {
         Ok(raw_output) => {
+            let exceeded_limit = exceeded_resource_limit(&resource_limits, &raw_output);
+
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
 
@@ -148,7 +205,7 @@ pub async fn process_exec_tool_call(
                 if let Some(signal) = raw_output.exit_status.signal() {
                     if signal == TIMEOUT_CODE {
                         timed_out = true;
-                    } else {
+                    } else if exceeded_limit.is_none() {
                         return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                     }
                 }
@@ -162,6 +219,7 @@ pub async fn process_exec_tool_call(
             let stdout = raw_output.stdout.from_utf8_lossy();
             let stderr = raw_output.stderr.from_utf8_lossy();
             let aggregated_output = raw_output.aggregated_output.from_utf8_lossy();
+            let denials = raw_output.sandbox_denials;
             let exec_output = ExecToolCallOutput {
                 exit_code,
                 stdout,
@@ -171,15 +229,28 @@ pub async fn process_exec_tool_call(
                 timed_out,
             };
 
+            // A command that hit a limit often runs on until it times out,
+            // e.g. a shell that keeps retrying `fork`, so the limit is the
+            // more useful explanation.
+            if let Some(limit) = exceeded_limit {
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimit {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
             if timed_out {
                 return Err(CodexErr::Sandbox(SandboxErr::Timeout {
                     output: Box::new(exec_output),
                 }));
             }
 
-            if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
+            if exit_code != 0
+                && (!denials.is_empty() || is_likely_sandbox_denied(sandbox_type, exit_code))
+            {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied {
                     output: Box::new(exec_output),
+                    denials,
                 }));
             }
 
@@ -212,6 +283,108 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
     true
 }
 
+/// Reads the operations that codex-linux-sandbox reported denying, one per
+/// line, e.g. `write to /home/user/.cargo/registry` or
+/// `network access (connect)`. Nothing but the helper can write to
+/// the pipe, and it writes them just before it exits, so whatever is not
+/// there yet (e.g. after a timeout) is not waited for.
+fn read_sandbox_denials(mut reader: io::PipeReader) -> Vec<String> {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsRawFd;
+        // SAFETY: plain fcntl call on a descriptor that `reader` owns.
+        unsafe { libc::fcntl(reader.as_raw_fd(), libc::F_SETFL, libc::O_NONBLOCK) };
+    }
+    let mut denials = Vec::new();
+    if let Err(err) = io::Read::read_to_end(&mut reader, &mut denials)
+        && err.kind() != io::ErrorKind::WouldBlock
+    {
+        tracing::warn!("failed to read the sandbox denials: {err}");
+    }
+    String::from_utf8_lossy(&denials)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// One of the limits in [`ResourceLimits`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceLimit {
+    CpuTime,
+    Memory,
+    Processes,
+}
+
+impl std::fmt::Display for ResourceLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResourceLimit::CpuTime => f.write_str("CPU time"),
+            ResourceLimit::Memory => f.write_str("memory"),
+            ResourceLimit::Processes => f.write_str("process count"),
+        }
+    }
+}
+
+/// The configured limit that stopped the command: the process or memory
+/// limit when the event counters of the command's cgroup show it was reached,
+/// or the CPU time limit when the kernel killed the command with `SIGXCPU`,
+/// or the command exited with the status a shell reports for a child killed
+/// that way. Limits enforced with `setrlimit` alone only make calls fail, so
+/// they are not reported.
+fn exceeded_resource_limit(
+    limits: &ResourceLimits,
+    raw_output: &RawExecToolCallOutput,
+) -> Option<ResourceLimit> {
+    if let Some(limit) = raw_output.cgroup_limit_reached {
+        return Some(limit);
+    }
+
+    #[cfg(target_family = "unix")]
+    let signal = raw_output.exit_status.signal();
+    #[cfg(not(target_family = "unix"))]
+    let signal: Option<i32> = None;
+    if limits.cpu_time_seconds.is_some()
+        && (signal == Some(SIGXCPU_CODE)
+            || raw_output.exit_status.code() == Some(EXIT_CODE_SIGNAL_BASE + SIGXCPU_CODE))
+    {
+        return Some(ResourceLimit::CpuTime);
+    }
+    None
+}
+
+/// The path that a command denied by the sandbox was most likely prevented
+/// from reading by `sandbox_deny_read`: a denied path named in its error
+/// output, or one of its arguments that resolves to a denied path.
+pub(crate) fn find_denied_read_path(
+    command: &[String],
+    output: &ExecToolCallOutput,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Option<PathBuf> {
+    let denied_paths = sandbox_policy.get_denied_read_paths_with_cwd(cwd);
+    if let Some(path) = denied_paths
+        .iter()
+        .find(|path| output.stderr.text.contains(&*path.to_string_lossy()))
+    {
+        return Some(path.clone());
+    }
+    if denied_paths.is_empty() {
+        return None;
+    }
+
+    // Arguments may be whole shell scripts, so look at every word in them.
+    command
+        .iter()
+        .flat_map(|arg| arg.split(|c: char| c.is_whitespace() || "<>|;&'\"()=".contains(c)))
+        .filter(|word| !word.is_empty() && !word.starts_with('-'))
+        .map(|word| match (word.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => PathBuf::from(word),
+        })
+        .find(|path| !sandbox_policy.is_path_readable(path, cwd))
+        .map(|path| cwd.join(path))
+}
+
 #[derive(Debug)]
 pub struct StreamOutput<T> {
     pub text: T,
@@ -224,6 +397,10 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    /// The limit that the counters of the command's cgroup show it reached.
+    pub cgroup_limit_reached: Option<ResourceLimit>,
+    /// The operations that the sandbox reported denying the command.
+    pub sandbox_denials: Vec<String>,
 }
 
 impl StreamOutput<String> {
@@ -266,7 +443,11 @@ async fn exec(
 ) -> Result<RawExecToolCallOutput> {
     let timeout = params.timeout_duration();
     let ExecParams {
-        command, cwd, env, ..
+        command,
+        cwd,
+        env,
+        resource_limits,
+        ..
     } = params;
 
     let (program, args) = command.split_first().ok_or_else(|| {
@@ -284,6 +465,8 @@ async fn exec(
         sandbox_policy,
         StdioPolicy::RedirectForShellTool,
         env,
+        resource_limits,
+        None,
     )
     .await?;
     consume_truncated_output(child, timeout, stdout_stream).await
@@ -310,6 +493,7 @@ async fn consume_truncated_output(
             "stderr pipe was unexpectedly not available",
         ))
     })?;
+    let pid = child.id();
 
     let (agg_tx, agg_rx) = async_channel::unbounded::<Vec<u8>>();
 
@@ -349,6 +533,7 @@ async fn consume_truncated_output(
 
     let stdout = stdout_handle.await??;
     let stderr = stderr_handle.await??;
+    let cgroup_limit_reached = pid.and_then(release_exec_cgroup);
 
     drop(agg_tx);
 
@@ -367,6 +552,8 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        cgroup_limit_reached,
+        sandbox_denials: Vec::new(),
     })
 }
 
@@ -436,3 +623,188 @@ fn synthetic_exit_status(code: i32) -> ExitStatus {
     #[expect(clippy::unwrap_used)]
     std::process::ExitStatus::from_raw(code.try_into().unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn output_with_stderr(stderr: &str) -> ExecToolCallOutput {
+        ExecToolCallOutput {
+            exit_code: 1,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(stderr.to_string()),
+            aggregated_output: StreamOutput::new(stderr.to_string()),
+            duration: Duration::from_millis(1),
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn denied_read_path_is_found_in_arguments_and_stderr() {
+        let cwd = Path::new("/work");
+        let policy = SandboxPolicy::new_read_only_policy()
+            .with_deny_read(vec![PathBuf::from(".env"), PathBuf::from("/keys")]);
+        let command =
+            |script: &str| vec!["bash".to_string(), "-lc".to_string(), script.to_string()];
+
+        assert_eq!(
+            Some(PathBuf::from("/work/.env")),
+            find_denied_read_path(
+                &command("grep TOKEN .env"),
+                &output_with_stderr(""),
+                &policy,
+                cwd
+            )
+        );
+        assert_eq!(
+            Some(PathBuf::from("/keys")),
+            find_denied_read_path(
+                &command("./collect.sh"),
+                &output_with_stderr("cat: /keys/id: Permission denied"),
+                &policy,
+                cwd
+            )
+        );
+        assert_eq!(
+            None,
+            find_denied_read_path(
+                &command("cat README.md"),
+                &output_with_stderr("cat: README.md: Permission denied"),
+                &policy,
+                cwd
+            )
+        );
+    }
+
+    #[cfg(unix)]
+    fn exited_with(status: i32, stderr: &str) -> RawExecToolCallOutput {
+        RawExecToolCallOutput {
+            exit_status: ExitStatus::from_raw(status),
+            stdout: StreamOutput {
+                text: Vec::new(),
+                truncated_after_lines: None,
+            },
+            stderr: StreamOutput {
+                text: stderr.as_bytes().to_vec(),
+                truncated_after_lines: None,
+            },
+            aggregated_output: StreamOutput {
+                text: Vec::new(),
+                truncated_after_lines: None,
+            },
+            timed_out: false,
+            cgroup_limit_reached: None,
+            sandbox_denials: Vec::new(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sandbox_denials_are_read_from_the_pipe() {
+        use std::io::Write;
+
+        let (reader, mut writer) = io::pipe().unwrap();
+        writer
+            .write_all(b"network access (connect)\nnetwork access (socket)\n")
+            .unwrap();
+        // The helper may still hold its end open, e.g. after a timeout.
+        assert_eq!(
+            read_sandbox_denials(reader),
+            vec![
+                "network access (connect)".to_string(),
+                "network access (socket)".to_string()
+            ]
+        );
+        drop(writer);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exceeded_resource_limit_comes_from_the_kernel() {
+        let limits = ResourceLimits {
+            cpu_time_seconds: Some(1),
+            memory_mb: Some(64),
+            max_processes: Some(16),
+        };
+        let exit_code = |code: i32| code << 8;
+
+        assert_eq!(
+            Some(ResourceLimit::CpuTime),
+            exceeded_resource_limit(&limits, &exited_with(SIGXCPU_CODE, ""))
+        );
+        let mut output = exited_with(exit_code(1), "");
+        output.cgroup_limit_reached = Some(ResourceLimit::Processes);
+        assert_eq!(
+            Some(ResourceLimit::Processes),
+            exceeded_resource_limit(&limits, &output)
+        );
+        // What the command prints or exits with is not evidence.
+        assert_eq!(
+            None,
+            exceeded_resource_limit(
+                &limits,
+                &exited_with(exit_code(1), "memory allocation of 1048576 bytes failed")
+            )
+        );
+        assert_eq!(
+            None,
+            exceeded_resource_limit(
+                &limits,
+                &exited_with(
+                    exit_code(254),
+                    "bash: fork: Resource temporarily unavailable"
+                )
+            )
+        );
+        // A shell reports a child killed by `SIGXCPU` with this status.
+        assert_eq!(
+            Some(ResourceLimit::CpuTime),
+            exceeded_resource_limit(
+                &limits,
+                &exited_with(exit_code(EXIT_CODE_SIGNAL_BASE + SIGXCPU_CODE), "")
+            )
+        );
+        assert_eq!(
+            None,
+            exceeded_resource_limit(&ResourceLimits::default(), &exited_with(SIGXCPU_CODE, ""))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn cpu_time_limit_stops_busy_loop() {
+        let cwd = std::env::temp_dir();
+        let params = ExecParams {
+            command: vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "while :; do :; done".to_string(),
+            ],
+            cwd: cwd.clone(),
+            timeout_ms: Some(10_000),
+            env: std::env::vars().collect(),
+            with_escalated_permissions: None,
+            justification: None,
+            resource_limits: ResourceLimits {
+                cpu_time_seconds: Some(1),
+                ..ResourceLimits::default()
+            },
+        };
+        let result = process_exec_tool_call(
+            params,
+            SandboxType::None,
+            &SandboxPolicy::DangerFullAccess,
+            &cwd,
+            &None,
+            None,
+        )
+        .await;
+
+        let Err(CodexErr::Sandbox(SandboxErr::ResourceLimit { limit, output })) = result else {
+            panic!("expected the CPU time limit to stop the command: {result:?}");
+        };
+        assert_eq!(ResourceLimit::CpuTime, limit);
+        assert!(!output.timed_out);
+    }
+}
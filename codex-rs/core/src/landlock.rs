@@ -1,7 +1,11 @@
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use std::collections::HashMap;
+use std::io::PipeWriter;
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
 use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
@@ -13,6 +17,7 @@ use tokio::process::Child;
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
 /// the equivalent CLI options.
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_command_under_linux_sandbox<P>(
     codex_linux_sandbox_exe: P,
     command: Vec<String>,
@@ -21,11 +26,116 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     sandbox_policy_cwd: &Path,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
     let args = create_linux_sandbox_command_args(command, sandbox_policy, sandbox_policy_cwd);
+    spawn_linux_sandbox_helper(
+        codex_linux_sandbox_exe,
+        args,
+        command_cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        resource_limits,
+        None,
+    )
+    .await
+}
+
+/// Like [`spawn_command_under_linux_sandbox`], but the helper also writes the
+/// operations that the sandbox denied the command to `denials_pipe`, one per
+/// line, once the command exits. The command itself cannot write to it.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn spawn_command_under_linux_sandbox_reporting_denials<P>(
+    codex_linux_sandbox_exe: P,
+    command: Vec<String>,
+    command_cwd: PathBuf,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+    denials_pipe: PipeWriter,
+) -> std::io::Result<Child>
+where
+    P: AsRef<Path>,
+{
+    let mut args = Vec::new();
+    #[cfg(unix)]
+    args.extend([
+        "--denials-fd".to_string(),
+        denials_pipe.as_raw_fd().to_string(),
+    ]);
+    args.extend(create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+    ));
+    spawn_linux_sandbox_helper(
+        codex_linux_sandbox_exe,
+        args,
+        command_cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        resource_limits,
+        Some(denials_pipe),
+    )
+    .await
+}
+
+/// Spawn a shell tool command in new user, mount, PID and network namespaces
+/// set up by the codex-linux-sandbox helper, as an alternative to Landlock.
+#[allow(clippy::too_many_arguments)]
+pub async fn spawn_command_under_linux_namespaces<P>(
+    codex_linux_sandbox_exe: P,
+    command: Vec<String>,
+    command_cwd: PathBuf,
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+) -> std::io::Result<Child>
+where
+    P: AsRef<Path>,
+{
+    let mut args = vec!["--namespaces".to_string()];
+    args.extend(create_linux_sandbox_command_args(
+        command,
+        sandbox_policy,
+        sandbox_policy_cwd,
+    ));
+    spawn_linux_sandbox_helper(
+        codex_linux_sandbox_exe,
+        args,
+        command_cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        resource_limits,
+        None,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn spawn_linux_sandbox_helper<P>(
+    codex_linux_sandbox_exe: P,
+    args: Vec<String>,
+    command_cwd: PathBuf,
+    sandbox_policy: &SandboxPolicy,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
+    denials_pipe: Option<PipeWriter>,
+) -> std::io::Result<Child>
+where
+    P: AsRef<Path>,
+{
     let arg0 = Some("codex-linux-sandbox");
     spawn_child_async(
         codex_linux_sandbox_exe.as_ref().to_path_buf(),
@@ -35,6 +145,8 @@ where
         sandbox_policy,
         stdio_policy,
         env,
+        resource_limits,
+        denials_pipe,
     )
     .await
 }
@@ -5,7 +5,7 @@
 //! JSON-Lines tooling. Each record has the following schema:
 //!
 //! ````text
-//! {"conversation_id":"<uuid>","ts":<unix_seconds>,"text":"<message>"}
+//! {"session_id":"<uuid>","ts":<unix_seconds>,"text":"<message>","cwd":"<dir>"}
 //! ````
 //!
 //! To minimise the chance of interleaved writes when multiple processes are
@@ -13,11 +13,20 @@
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
 //! the file descriptor is opened with the `O_APPEND` flag. POSIX guarantees
 //! that writes up to `PIPE_BUF` bytes are atomic in that case.
+//!
+//! When `history.max_bytes` is set, the file is rotated once it grows past
+//! the limit: the oldest entries are dropped and the rest are written to a
+//! new file that replaces the old one.
 
+use std::collections::HashSet;
 use std::fs::File;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::io::Result;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 
 use serde::Deserialize;
@@ -42,11 +51,28 @@ const HISTORY_FILENAME: &str = "history.jsonl";
 const MAX_RETRIES: usize = 10;
 const RETRY_SLEEP: Duration = Duration::from_millis(100);
 
+/// Share of `history.max_bytes` that is kept when the file is rotated, so
+/// that rotation happens once in a while rather than on every append.
+const ROTATE_KEEP_PERCENT: usize = 75;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct HistoryEntry {
     pub session_id: String,
     pub ts: u64,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+}
+
+impl From<HistoryEntry> for codex_protocol::message_history::HistoryEntry {
+    fn from(entry: HistoryEntry) -> Self {
+        Self {
+            conversation_id: entry.session_id,
+            ts: entry.ts,
+            text: entry.text,
+            cwd: entry.cwd,
+        }
+    }
 }
 
 fn history_filepath(config: &Config) -> PathBuf {
@@ -92,6 +118,7 @@ pub(crate) async fn append_entry(
         session_id: conversation_id.to_string(),
         ts,
         text: text.to_string(),
+        cwd: Some(config.cwd.clone()),
     };
     let mut line = serde_json::to_string(&entry)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
@@ -111,14 +138,25 @@ pub(crate) async fn append_entry(
     ensure_owner_only_permissions(&history_file).await?;
 
     // Perform a blocking write under an advisory write lock using std::fs.
+    let max_bytes = config.history.max_bytes;
     tokio::task::spawn_blocking(move || -> Result<()> {
         // Retry a few times to avoid indefinite blocking when contended.
         for _ in 0..MAX_RETRIES {
             match history_file.try_lock() {
                 Ok(()) => {
+                    // Another session may have rotated the file while we
+                    // waited for the lock, replacing it with a new one. Lines
+                    // written to the old one would be lost.
+                    if !is_current_file(&path, &history_file)? {
+                        history_file = options.open(&path)?;
+                        continue;
+                    }
                     // While holding the exclusive lock, write the full line.
                     history_file.write_all(line.as_bytes())?;
                     history_file.flush()?;
+                    if let Some(max_bytes) = max_bytes {
+                        rotate_if_needed(&path, &history_file, max_bytes)?;
+                    }
                     return Ok(());
                 }
                 Err(std::fs::TryLockError::WouldBlock) => {
@@ -138,6 +176,74 @@ pub(crate) async fn append_entry(
     Ok(())
 }
 
+/// Whether `path` still names `file`, rather than a file that replaced it.
+#[cfg(unix)]
+fn is_current_file(path: &Path, file: &File) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let opened = file.metadata()?;
+    match std::fs::metadata(path) {
+        Ok(current) => Ok(current.dev() == opened.dev() && current.ino() == opened.ino()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Files that are open cannot be replaced on this platform.
+#[cfg(not(unix))]
+fn is_current_file(_path: &Path, _file: &File) -> Result<bool> {
+    Ok(true)
+}
+
+/// Rewrites the history file with only the newest entries once it has grown
+/// past `max_bytes`. The rewritten file replaces the old one by rename, so it
+/// gets a new `log_id`: sessions still browsing the old file stop finding
+/// entries instead of finding the wrong ones. `file` must be locked
+/// exclusively.
+fn rotate_if_needed(path: &Path, file: &File, max_bytes: usize) -> Result<()> {
+    if file.metadata()?.len() <= max_bytes as u64 {
+        return Ok(());
+    }
+
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(0))?;
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents)?;
+    let keep_from = rotation_keep_from(&contents, max_bytes * ROTATE_KEEP_PERCENT / 100);
+
+    let tmp_path = path.with_file_name(format!("{HISTORY_FILENAME}.tmp"));
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        options.mode(0o600);
+    }
+    let mut rotated = options.open(&tmp_path)?;
+    rotated.write_all(&contents[keep_from..])?;
+    rotated.flush()?;
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Offset of the first whole line to keep so that at most `budget` bytes of
+/// the newest lines remain.
+fn rotation_keep_from(contents: &[u8], budget: usize) -> usize {
+    if contents.len() <= budget {
+        return 0;
+    }
+    let mut keep_from = contents.len();
+    for (idx, _) in contents
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(_, byte)| **byte == b'\n')
+    {
+        if contents.len() - (idx + 1) > budget {
+            break;
+        }
+        keep_from = idx + 1;
+    }
+    keep_from
+}
+
 /// Asynchronously fetch the history file's *identifier* (inode on Unix) and
 /// the current number of entries by counting newline characters.
 pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
@@ -263,6 +369,71 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     None
 }
 
+/// Return up to `limit` of the newest entries sent from `cwd` and in
+/// `conversation_id`, when those are given, newest first and without repeating
+/// a text. Entries written before the working directory was recorded never
+/// match a `cwd` filter. Any I/O errors are logged and result in no entries.
+///
+/// Note this function is not async because it uses a sync advisory file
+/// locking API.
+pub(crate) fn search(
+    config: &Config,
+    cwd: Option<&Path>,
+    conversation_id: Option<&ConversationId>,
+    limit: usize,
+) -> Vec<HistoryEntry> {
+    let path = history_filepath(config);
+    let mut file = match File::open(&path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to open history file");
+            return Vec::new();
+        }
+    };
+
+    for _ in 0..MAX_RETRIES {
+        match file.try_lock_shared() {
+            Ok(()) => {
+                let mut contents = String::new();
+                if let Err(e) = file.read_to_string(&mut contents) {
+                    tracing::warn!(error = %e, "failed to read history file");
+                    return Vec::new();
+                }
+                let conversation_id = conversation_id.map(ToString::to_string);
+                return search_lines(&contents, cwd, conversation_id.as_deref(), limit);
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {
+                std::thread::sleep(RETRY_SLEEP);
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "failed to acquire shared lock on history file");
+                return Vec::new();
+            }
+        }
+    }
+
+    Vec::new()
+}
+
+fn search_lines(
+    contents: &str,
+    cwd: Option<&Path>,
+    session_id: Option<&str>,
+    limit: usize,
+) -> Vec<HistoryEntry> {
+    let mut seen = HashSet::new();
+    contents
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<HistoryEntry>(line).ok())
+        .filter(|entry| cwd.is_none_or(|cwd| entry.cwd.as_deref() == Some(cwd)))
+        .filter(|entry| session_id.is_none_or(|id| entry.session_id == id))
+        .filter(|entry| seen.insert(entry.text.clone()))
+        .take(limit)
+        .collect()
+}
+
 /// On Unix systems ensure the file permissions are `0o600` (rw-------). If the
 /// permissions cannot be changed the error is propagated to the caller.
 #[cfg(unix)]
@@ -284,3 +455,98 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn line(session_id: &str, text: &str, cwd: Option<&str>) -> String {
+        let entry = HistoryEntry {
+            session_id: session_id.to_string(),
+            ts: 0,
+            text: text.to_string(),
+            cwd: cwd.map(PathBuf::from),
+        };
+        format!("{}\n", serde_json::to_string(&entry).unwrap())
+    }
+
+    #[test]
+    fn search_filters_and_dedups_newest_first() {
+        let contents = [
+            line("a", "fix the build", Some("/repo")),
+            line("a", "old entry without cwd", None),
+            line("b", "write tests", Some("/other")),
+            line("b", "fix the build", Some("/repo")),
+            line("b", "explain this", Some("/repo")),
+        ]
+        .concat();
+        let texts = |entries: Vec<HistoryEntry>| -> Vec<String> {
+            entries.into_iter().map(|entry| entry.text).collect()
+        };
+
+        assert_eq!(
+            texts(search_lines(&contents, None, None, 10)),
+            vec![
+                "explain this",
+                "fix the build",
+                "write tests",
+                "old entry without cwd"
+            ]
+        );
+        assert_eq!(
+            texts(search_lines(&contents, Some(Path::new("/repo")), None, 10)),
+            vec!["explain this", "fix the build"]
+        );
+        assert_eq!(
+            texts(search_lines(&contents, None, Some("a"), 10)),
+            vec!["old entry without cwd", "fix the build"]
+        );
+        assert_eq!(
+            texts(search_lines(&contents, None, None, 1)),
+            vec!["explain this"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rotated_file_is_no_longer_current() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILENAME);
+        let lines: Vec<String> = (0..4).map(|i| line("s", &format!("m{i}"), None)).collect();
+        std::fs::write(&path, lines.concat()).unwrap();
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&path)
+            .unwrap();
+        assert!(is_current_file(&path, &file).unwrap());
+
+        rotate_if_needed(&path, &file, lines[0].len() * 2).unwrap();
+        assert!(!is_current_file(&path, &file).unwrap());
+    }
+
+    #[test]
+    fn rotation_keeps_newest_whole_lines_and_replaces_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILENAME);
+        let lines: Vec<String> = (0..10)
+            .map(|i| line("s", &format!("message {i}"), None))
+            .collect();
+        std::fs::write(&path, lines.concat()).unwrap();
+        let line_len = lines[0].len();
+
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&path)
+            .unwrap();
+        // Under the limit nothing changes.
+        rotate_if_needed(&path, &file, line_len * 10).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), lines.concat());
+
+        // Over the limit, the newest lines that fit in 75% of it remain.
+        rotate_if_needed(&path, &file, line_len * 4).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), lines[7..].concat());
+    }
+}
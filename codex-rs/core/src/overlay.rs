@@ -0,0 +1,469 @@
+//! The copy-on-write layer of [`SandboxPolicy::Overlay`].
+//!
+//! codex-linux-sandbox mounts an overlay file system over the workspace with
+//! the layer's `upper` directory on top, so a sandboxed command's writes to
+//! the workspace land in `upper`, in the workspace's layout. A deleted path is
+//! recorded as a whiteout, a character device with device number 0:0, and a
+//! directory created in place of a deleted one is marked opaque, hiding what
+//! the workspace has beneath it.
+//!
+//! [`OverlayLayer`] reads the workspace as sandboxed commands see it, and
+//! commits the layer to the workspace or discards it.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_protocol::mcp_protocol::ConversationId;
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+
+use crate::protocol::SandboxPolicy;
+
+/// Directory under `CODEX_HOME` that holds the layers of overlay policies.
+const OVERLAYS_SUBDIR: &str = "overlays";
+
+/// The directory under `codex_home` that holds the layers of overlay
+/// policies.
+pub fn overlays_dir(codex_home: &Path) -> PathBuf {
+    codex_home.join(OVERLAYS_SUBDIR)
+}
+
+/// The directory of the layer of the session `conversation_id`, under
+/// `codex_home`. Each session has its own layer, which outlives the session
+/// until it is committed or discarded and is picked up again when the session
+/// is resumed.
+pub fn session_layer_dir(codex_home: &Path, conversation_id: ConversationId) -> PathBuf {
+    overlays_dir(codex_home).join(conversation_id.to_string())
+}
+
+/// `policy`, with its layer in the directory of the session `conversation_id`
+/// if it is an overlay policy.
+pub fn with_session_layer(
+    mut policy: SandboxPolicy,
+    codex_home: &Path,
+    conversation_id: ConversationId,
+) -> SandboxPolicy {
+    if let SandboxPolicy::Overlay { layer_dir, .. } = &mut policy {
+        *layer_dir = session_layer_dir(codex_home, conversation_id);
+    }
+    policy
+}
+
+/// The overlay's upper directory within `layer_dir`.
+pub fn upper_dir(layer_dir: &Path) -> PathBuf {
+    layer_dir.join("upper")
+}
+
+/// The overlay's work directory within `layer_dir`, which must be on the
+/// same file system as the upper directory.
+pub fn work_dir(layer_dir: &Path) -> PathBuf {
+    layer_dir.join("work")
+}
+
+/// Where the current contents of a workspace path are.
+#[derive(Debug, PartialEq, Eq)]
+enum Lookup {
+    /// Unchanged, in the workspace.
+    Workspace,
+    /// Written to the layer, at this path.
+    Layer(PathBuf),
+    /// Deleted in the layer.
+    Deleted,
+}
+
+#[derive(Debug, Clone)]
+pub struct OverlayLayer {
+    workspace: PathBuf,
+    layer_dir: PathBuf,
+}
+
+impl OverlayLayer {
+    pub fn new(workspace: PathBuf, layer_dir: PathBuf) -> Self {
+        Self {
+            workspace,
+            layer_dir,
+        }
+    }
+
+    /// The layer of `policy` over the workspace `cwd`, if it is an overlay
+    /// policy.
+    pub fn from_policy(policy: &SandboxPolicy, cwd: &Path) -> Option<Self> {
+        policy
+            .overlay_layer_dir()
+            .map(|layer_dir| Self::new(cwd.to_path_buf(), layer_dir.to_path_buf()))
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    fn upper_dir(&self) -> PathBuf {
+        upper_dir(&self.layer_dir)
+    }
+
+    /// The file holding the current contents of `path`, or `None` if the
+    /// layer deleted it. Paths outside the workspace are returned unchanged.
+    pub fn resolve(&self, path: &Path) -> Option<PathBuf> {
+        match self.lookup(path) {
+            Lookup::Workspace => Some(path.to_path_buf()),
+            Lookup::Layer(path) => Some(path),
+            Lookup::Deleted => None,
+        }
+    }
+
+    /// Reads `path` as sandboxed commands see it.
+    pub fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        match self.resolve(path) {
+            Some(path) => fs::read_to_string(path),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} was deleted", path.display()),
+            )),
+        }
+    }
+
+    fn lookup(&self, path: &Path) -> Lookup {
+        let Ok(relative) = path.strip_prefix(&self.workspace) else {
+            return Lookup::Workspace;
+        };
+        let mut layer_path = self.upper_dir();
+        let mut under_opaque_dir = false;
+        let mut components = relative.components().peekable();
+        while let Some(component) = components.next() {
+            layer_path.push(component);
+            let Ok(metadata) = fs::symlink_metadata(&layer_path) else {
+                // Nothing below this point is in the layer.
+                return if under_opaque_dir {
+                    Lookup::Deleted
+                } else {
+                    Lookup::Workspace
+                };
+            };
+            if is_whiteout(&metadata) {
+                return Lookup::Deleted;
+            }
+            if components.peek().is_none() {
+                return Lookup::Layer(layer_path);
+            }
+            if !metadata.is_dir() {
+                // A file replaced the directory that `path` was in.
+                return Lookup::Deleted;
+            }
+            under_opaque_dir |= is_opaque(&layer_path);
+        }
+        Lookup::Workspace
+    }
+
+    /// The workspace files, symlinks included, that the layer creates,
+    /// modifies or deletes, in sorted order.
+    pub fn changed_paths(&self) -> io::Result<Vec<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        let upper_dir = self.upper_dir();
+        if upper_dir.is_dir() {
+            collect_changes(&upper_dir, &self.workspace, &mut changed)?;
+        }
+        Ok(changed.into_iter().collect())
+    }
+
+    /// [`Self::changed_paths`], without the paths ignored by the workspace's
+    /// `.gitignore` files, as sandboxed commands see them, or by
+    /// `.git/info/exclude`, such as build output under `target/`.
+    pub fn changed_paths_not_ignored(&self) -> io::Result<Vec<PathBuf>> {
+        let mut matchers = HashMap::new();
+        Ok(self
+            .changed_paths()?
+            .into_iter()
+            .filter(|path| !self.is_ignored(path, &mut matchers))
+            .collect())
+    }
+
+    /// Whether `path` is ignored, with `matchers` caching the ignore rules of
+    /// each directory.
+    fn is_ignored(&self, path: &Path, matchers: &mut HashMap<PathBuf, Gitignore>) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.workspace) else {
+            return false;
+        };
+        // The rules of deeper directories take precedence.
+        for dir in relative.ancestors().skip(1) {
+            let dir = if dir.as_os_str().is_empty() {
+                self.workspace.clone()
+            } else {
+                self.workspace.join(dir)
+            };
+            let matcher = matchers
+                .entry(dir)
+                .or_insert_with_key(|dir| self.ignore_rules(dir));
+            match matcher.matched_path_or_any_parents(path, false) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// The ignore rules of the workspace directory `dir`. Missing or
+    /// malformed ignore files ignore nothing.
+    fn ignore_rules(&self, dir: &Path) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(dir);
+        if dir == self.workspace {
+            builder.add(dir.join(".git").join("info").join("exclude"));
+        }
+        if let Some(gitignore) = self.resolve(&dir.join(".gitignore")) {
+            builder.add(gitignore);
+        }
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    }
+
+    /// Applies the layer's changes to the workspace and empties the layer.
+    /// Returns the number of changed files.
+    pub fn commit(&self) -> io::Result<usize> {
+        let changed = self.changed_paths()?.len();
+        let upper_dir = self.upper_dir();
+        if upper_dir.is_dir() {
+            commit_dir(&upper_dir, &self.workspace)?;
+        }
+        self.discard()?;
+        Ok(changed)
+    }
+
+    /// Throws the layer's changes away. Returns the number of changed files.
+    pub fn discard(&self) -> io::Result<usize> {
+        let changed = self.changed_paths()?.len();
+        if self.layer_dir.exists() {
+            make_removable(&self.layer_dir)?;
+            fs::remove_dir_all(&self.layer_dir)?;
+        }
+        Ok(changed)
+    }
+}
+
+fn collect_changes(
+    layer_dir: &Path,
+    workspace_dir: &Path,
+    changed: &mut BTreeSet<PathBuf>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(layer_dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let workspace_path = workspace_dir.join(entry.file_name());
+        let replaced_dir = fs::symlink_metadata(&workspace_path).is_ok_and(|m| m.is_dir());
+        if metadata.is_dir() {
+            if replaced_dir && is_opaque(&entry.path()) {
+                collect_files(&workspace_path, changed)?;
+            } else if !replaced_dir && workspace_path.exists() {
+                // A directory replaced a file.
+                changed.insert(workspace_path.clone());
+            }
+            collect_changes(&entry.path(), &workspace_path, changed)?;
+        } else {
+            if replaced_dir {
+                collect_files(&workspace_path, changed)?;
+            }
+            if !is_whiteout(&metadata) || !replaced_dir {
+                changed.insert(workspace_path);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Adds every file and symlink under `dir` to `files`.
+fn collect_files(dir: &Path, files: &mut BTreeSet<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.insert(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Applies the entries of the layer directory `layer_dir` to the workspace
+/// directory `workspace_dir`.
+fn commit_dir(layer_dir: &Path, workspace_dir: &Path) -> io::Result<()> {
+    for entry in fs::read_dir(layer_dir)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        let layer_path = entry.path();
+        let workspace_path = workspace_dir.join(entry.file_name());
+        let existing = fs::symlink_metadata(&workspace_path).ok();
+
+        if metadata.is_dir() {
+            match existing {
+                Some(existing) if existing.is_dir() => {
+                    if is_opaque(&layer_path) {
+                        fs::remove_dir_all(&workspace_path)?;
+                        fs::create_dir(&workspace_path)?;
+                    }
+                }
+                Some(_) => {
+                    fs::remove_file(&workspace_path)?;
+                    fs::create_dir(&workspace_path)?;
+                }
+                None => fs::create_dir(&workspace_path)?,
+            }
+            fs::set_permissions(&workspace_path, metadata.permissions())?;
+            commit_dir(&layer_path, &workspace_path)?;
+            continue;
+        }
+
+        match existing {
+            Some(existing) if existing.is_dir() => fs::remove_dir_all(&workspace_path)?,
+            Some(_) => fs::remove_file(&workspace_path)?,
+            None => {}
+        }
+        if is_whiteout(&metadata) {
+            continue;
+        }
+        if metadata.file_type().is_symlink() {
+            create_symlink(&fs::read_link(&layer_path)?, &workspace_path)?;
+        } else if metadata.is_file() {
+            fs::copy(&layer_path, &workspace_path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Grants the owner full access to `dir` and the directories below it. The
+/// overlay leaves directories without any permissions in its work directory.
+fn make_removable(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(dir, fs::Permissions::from_mode(0o700))?;
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            make_removable(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn create_symlink(_target: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "overlay layers are only supported on Linux",
+    ))
+}
+
+#[cfg(unix)]
+fn is_whiteout(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+    use std::os::unix::fs::MetadataExt;
+    metadata.file_type().is_char_device() && metadata.rdev() == 0
+}
+
+#[cfg(not(unix))]
+fn is_whiteout(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// Whether the layer directory `path` hides the workspace directory beneath
+/// it. codex-linux-sandbox mounts the overlay with `userxattr`, so the marker
+/// is a `user.` extended attribute.
+#[cfg(target_os = "linux")]
+fn is_opaque(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+    let Ok(path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut value = [0u8; 1];
+    // SAFETY: both strings are NUL-terminated and `value` has room for the
+    // length passed.
+    let len = unsafe {
+        libc::lgetxattr(
+            path.as_ptr(),
+            c"user.overlay.opaque".as_ptr(),
+            value.as_mut_ptr().cast(),
+            value.len(),
+        )
+    };
+    len == 1 && value[0] == b'y'
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_opaque(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    /// A workspace and a layer with `upper` laid out by hand, as the overlay
+    /// file system would.
+    fn layer() -> (TempDir, TempDir, OverlayLayer) {
+        let workspace = TempDir::new().expect("workspace");
+        let layer_dir = TempDir::new().expect("layer");
+        let layer = OverlayLayer::new(workspace.path().to_path_buf(), layer_dir.path().into());
+        (workspace, layer_dir, layer)
+    }
+
+    #[test]
+    fn reads_through_the_layer() {
+        let (workspace, layer_dir, layer) = layer();
+        fs::write(workspace.path().join("unchanged.txt"), "old").expect("write");
+        fs::write(workspace.path().join("changed.txt"), "old").expect("write");
+        fs::create_dir_all(upper_dir(layer_dir.path())).expect("mkdir");
+        fs::write(upper_dir(layer_dir.path()).join("changed.txt"), "new").expect("write");
+
+        let read = |name: &str| layer.read_to_string(&workspace.path().join(name)).ok();
+        assert_eq!(read("unchanged.txt"), Some("old".to_string()));
+        assert_eq!(read("changed.txt"), Some("new".to_string()));
+        assert_eq!(read("missing.txt"), None);
+    }
+
+    #[test]
+    fn commits_and_discards_the_layer() {
+        let (workspace, layer_dir, layer) = layer();
+        fs::write(workspace.path().join("changed.txt"), "old").expect("write");
+        let upper = upper_dir(layer_dir.path());
+        fs::create_dir_all(upper.join("new_dir")).expect("mkdir");
+        fs::write(upper.join("changed.txt"), "new").expect("write");
+        fs::write(upper.join("new_dir/added.txt"), "added").expect("write");
+
+        assert_eq!(
+            layer.changed_paths().expect("changed paths"),
+            vec![
+                workspace.path().join("changed.txt"),
+                workspace.path().join("new_dir/added.txt"),
+            ]
+        );
+        assert_eq!(layer.commit().expect("commit"), 2);
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("changed.txt")).expect("read"),
+            "new"
+        );
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("new_dir/added.txt")).expect("read"),
+            "added"
+        );
+        assert!(!layer_dir.path().exists());
+
+        fs::create_dir_all(&upper).expect("mkdir");
+        fs::write(upper.join("changed.txt"), "newer").expect("write");
+        assert_eq!(layer.discard().expect("discard"), 1);
+        assert_eq!(
+            fs::read_to_string(workspace.path().join("changed.txt")).expect("read"),
+            "new"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! What a conversation did after a branch point, for comparing the branches
+//! of a forked conversation.
+
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::TurnDiffEvent;
+
+use crate::conversation_manager::user_message_text;
+
+/// The part of a recorded conversation from one of its user messages on.
+#[derive(Debug, Default, PartialEq)]
+pub struct BranchSummary {
+    /// The user message the branch starts with, if the conversation got that
+    /// far.
+    pub first_message: Option<String>,
+    /// The diff of each turn of the branch that changed files, oldest first.
+    pub turn_diffs: Vec<String>,
+}
+
+/// Summarizes `items` from their `nth_user_message` (0-based) on.
+pub(crate) fn summarize_branch(items: &[RolloutItem], nth_user_message: usize) -> BranchSummary {
+    let mut summary = BranchSummary::default();
+    let mut user_messages = 0;
+    // Every diff of a turn covers the whole turn so far, so only its last
+    // one is kept.
+    let mut turn_diff: Option<String> = None;
+    for item in items {
+        if let Some(text) = user_message_text(item) {
+            if user_messages == nth_user_message {
+                summary.first_message = Some(text);
+            }
+            user_messages += 1;
+            summary.turn_diffs.extend(turn_diff.take());
+        } else if user_messages > nth_user_message
+            && let RolloutItem::EventMsg(EventMsg::TurnDiff(TurnDiffEvent { unified_diff })) = item
+        {
+            turn_diff = Some(unified_diff.clone());
+        }
+    }
+    summary.turn_diffs.extend(turn_diff);
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::ContentItem;
+    use codex_protocol::models::ResponseItem;
+    use pretty_assertions::assert_eq;
+
+    fn user_msg(text: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        })
+    }
+
+    fn turn_diff(diff: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::TurnDiff(TurnDiffEvent {
+            unified_diff: diff.to_string(),
+        }))
+    }
+
+    #[test]
+    fn keeps_the_last_diff_of_each_turn_after_the_branch_point() {
+        let items = vec![
+            user_msg("set up"),
+            turn_diff("setup"),
+            user_msg("try the first approach"),
+            turn_diff("first, part 1"),
+            turn_diff("first, parts 1 and 2"),
+            user_msg("explain it"),
+            user_msg("polish it"),
+            turn_diff("polish"),
+        ];
+
+        assert_eq!(
+            summarize_branch(&items, 1),
+            BranchSummary {
+                first_message: Some("try the first approach".to_string()),
+                turn_diffs: vec!["first, parts 1 and 2".to_string(), "polish".to_string()],
+            }
+        );
+        assert_eq!(summarize_branch(&items, 4), BranchSummary::default());
+    }
+}
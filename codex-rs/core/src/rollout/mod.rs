@@ -3,6 +3,7 @@
 pub const SESSIONS_SUBDIR: &str = "sessions";
 pub const ARCHIVED_SESSIONS_SUBDIR: &str = "archived_sessions";
 
+pub mod branch;
 pub mod list;
 pub(crate) mod policy;
 pub mod recorder;
@@ -19,6 +19,8 @@ use tracing::info;
 use tracing::warn;
 
 use super::SESSIONS_SUBDIR;
+use super::branch::BranchSummary;
+use super::branch::summarize_branch;
 use super::list::ConversationsPage;
 use super::list::Cursor;
 use super::list::get_conversations;
@@ -26,6 +28,7 @@ use super::policy::is_persisted_response_item;
 use crate::config::Config;
 use crate::default_client::ORIGINATOR;
 use crate::git_info::collect_git_info;
+use codex_protocol::protocol::ForkOrigin;
 use codex_protocol::protocol::InitialHistory;
 use codex_protocol::protocol::ResumedHistory;
 use codex_protocol::protocol::RolloutItem;
@@ -53,6 +56,7 @@ pub enum RolloutRecorderParams {
     Create {
         conversation_id: ConversationId,
         instructions: Option<String>,
+        forked_from: Option<ForkOrigin>,
     },
     Resume {
         path: PathBuf,
@@ -75,6 +79,21 @@ impl RolloutRecorderParams {
         Self::Create {
             conversation_id,
             instructions,
+            forked_from: None,
+        }
+    }
+
+    /// Like [`RolloutRecorderParams::new`], for a conversation forked from
+    /// another one.
+    pub fn fork(
+        conversation_id: ConversationId,
+        instructions: Option<String>,
+        forked_from: ForkOrigin,
+    ) -> Self {
+        Self::Create {
+            conversation_id,
+            instructions,
+            forked_from: Some(forked_from),
         }
     }
 
@@ -93,6 +112,19 @@ impl RolloutRecorder {
         get_conversations(codex_home, page_size, cursor).await
     }
 
+    /// What the conversation recorded at `path` did from its
+    /// `nth_user_message` (0-based) on.
+    pub async fn summarize_branch(
+        path: &Path,
+        nth_user_message: usize,
+    ) -> std::io::Result<BranchSummary> {
+        let history = Self::get_rollout_history(path).await?;
+        Ok(summarize_branch(
+            &history.get_rollout_items(),
+            nth_user_message,
+        ))
+    }
+
     /// Attempt to create a new [`RolloutRecorder`]. If the sessions directory
     /// cannot be created or the rollout file cannot be opened we return the
     /// error so the caller can decide whether to disable persistence.
@@ -101,6 +133,7 @@ impl RolloutRecorder {
             RolloutRecorderParams::Create {
                 conversation_id,
                 instructions,
+                forked_from,
             } => {
                 let LogFileInfo {
                     file,
@@ -127,6 +160,7 @@ impl RolloutRecorder {
                         originator: ORIGINATOR.value.clone(),
                         cli_version: env!("CARGO_PKG_VERSION").to_string(),
                         instructions,
+                        forked_from,
                     }),
                 )
             }
@@ -12,6 +12,7 @@ use crate::command_safety::is_dangerous_command::command_might_be_dangerous;
 use crate::command_safety::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
+use crate::protocol::WritableRoot;
 
 #[derive(Debug, PartialEq)]
 pub enum SafetyCheck {
@@ -32,6 +33,27 @@ pub fn assess_patch_safety(
         };
     }
 
+    // Updating or deleting a file reads it, and the patch may echo the
+    // contents of any file it touches, so denied paths are off limits
+    // entirely.
+    let denied_path = action.changes().iter().find_map(|(path, change)| {
+        let move_path = match change {
+            ApplyPatchFileChange::Update { move_path, .. } => move_path.as_ref(),
+            _ => None,
+        };
+        std::iter::once(path)
+            .chain(move_path)
+            .find(|path| !sandbox_policy.is_path_readable(path, cwd))
+    });
+    if let Some(path) = denied_path {
+        return SafetyCheck::Reject {
+            reason: format!(
+                "patch touches {}, which is denied by sandbox_deny_read",
+                path.display()
+            ),
+        };
+    }
+
     match policy {
         AskForApproval::OnFailure | AskForApproval::Never | AskForApproval::OnRequest => {
             // Continue to see if this can be auto-approved.
@@ -144,7 +166,9 @@ pub(crate) fn assess_safety_for_untrusted_command(
         | (OnRequest, DangerFullAccess) => SafetyCheck::AutoApprove {
             sandbox_type: SandboxType::None,
         },
-        (OnRequest, ReadOnly) | (OnRequest, WorkspaceWrite { .. }) => {
+        (OnRequest, ReadOnly { .. })
+        | (OnRequest, WorkspaceWrite { .. })
+        | (OnRequest, Overlay { .. }) => {
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
@@ -156,10 +180,12 @@ pub(crate) fn assess_safety_for_untrusted_command(
                 }
             }
         }
-        (Never, ReadOnly)
+        (Never, ReadOnly { .. })
         | (Never, WorkspaceWrite { .. })
-        | (OnFailure, ReadOnly)
-        | (OnFailure, WorkspaceWrite { .. }) => {
+        | (Never, Overlay { .. })
+        | (OnFailure, ReadOnly { .. })
+        | (OnFailure, WorkspaceWrite { .. })
+        | (OnFailure, Overlay { .. }) => {
             match get_platform_sandbox() {
                 Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
                 None => {
@@ -200,13 +226,18 @@ fn is_write_patch_constrained_to_writable_paths(
 ) -> bool {
     // Early‑exit if there are no declared writable roots.
     let writable_roots = match sandbox_policy {
-        SandboxPolicy::ReadOnly => {
+        SandboxPolicy::ReadOnly { .. } => {
             return false;
         }
         SandboxPolicy::DangerFullAccess => {
             return true;
         }
         SandboxPolicy::WorkspaceWrite { .. } => sandbox_policy.get_writable_roots_with_cwd(cwd),
+        // Writes to the workspace, except to `.git`, land in the layer.
+        SandboxPolicy::Overlay { .. } => vec![WritableRoot {
+            root: cwd.to_path_buf(),
+            read_only_subpaths: vec![cwd.join(".git")],
+        }],
     };
 
     // Normalize a path by removing `.` and resolving `..` without touching the
@@ -293,6 +324,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: Vec::new(),
         };
 
         assert!(is_write_patch_constrained_to_writable_paths(
@@ -314,6 +346,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: Vec::new(),
         };
         assert!(is_write_patch_constrained_to_writable_paths(
             &add_outside,
@@ -322,12 +355,34 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_patch_touching_denied_path_is_rejected() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path().to_path_buf();
+        let policy = SandboxPolicy::new_workspace_write_policy()
+            .with_deny_read(vec![PathBuf::from("secrets")]);
+
+        let denied = ApplyPatchAction::new_add_for_test(&cwd.join("secrets/key"), "".to_string());
+        let SafetyCheck::Reject { reason } =
+            assess_patch_safety(&denied, AskForApproval::OnRequest, &policy, &cwd)
+        else {
+            panic!("expected the patch to be rejected");
+        };
+        assert!(reason.contains("sandbox_deny_read"), "{reason}");
+
+        let allowed = ApplyPatchAction::new_add_for_test(&cwd.join("src/main.rs"), "".to_string());
+        assert!(!matches!(
+            assess_patch_safety(&allowed, AskForApproval::OnRequest, &policy, &cwd),
+            SafetyCheck::Reject { .. }
+        ));
+    }
+
     #[test]
     fn test_request_escalated_privileges() {
         // Should not be a trusted command
         let command = vec!["git commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = true;
 
@@ -346,7 +401,7 @@ mod tests {
     fn dangerous_command_allowed_if_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let mut approved: HashSet<Vec<String>> = HashSet::new();
         approved.insert(command.clone());
         let request_escalated_privileges = false;
@@ -371,7 +426,7 @@ mod tests {
     fn dangerous_command_not_allowed_if_not_explicitly_approved() {
         let command = vec!["git".to_string(), "reset".to_string(), "--hard".to_string()];
         let approval_policy = AskForApproval::Never;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
@@ -396,7 +451,7 @@ mod tests {
     fn test_request_escalated_privileges_no_sandbox_fallback() {
         let command = vec!["git".to_string(), "commit".to_string()];
         let approval_policy = AskForApproval::OnRequest;
-        let sandbox_policy = SandboxPolicy::ReadOnly;
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
         let approved: HashSet<Vec<String>> = HashSet::new();
         let request_escalated_privileges = false;
 
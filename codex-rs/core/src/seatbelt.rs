@@ -3,6 +3,7 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::StdioPolicy;
@@ -23,6 +24,7 @@ pub async fn spawn_command_under_seatbelt(
     sandbox_policy_cwd: &Path,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
+    resource_limits: ResourceLimits,
 ) -> std::io::Result<Child> {
     let args = create_seatbelt_command_args(command, sandbox_policy, sandbox_policy_cwd);
     let arg0 = None;
@@ -35,6 +37,8 @@ pub async fn spawn_command_under_seatbelt(
         sandbox_policy,
         stdio_policy,
         env,
+        resource_limits,
+        None,
     )
     .await
 }
@@ -97,11 +101,25 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let file_read_policy = if sandbox_policy.has_full_disk_read_access() {
-        "; allow read-only file operations\n(allow file-read*)"
-    } else {
-        ""
-    };
+    // Later rules take precedence, so the denied paths are carved out of the
+    // blanket read access.
+    let mut file_read_policy = "; allow read-only file operations\n(allow file-read*)".to_string();
+    let mut deny_read_cli_args: Vec<String> = Vec::new();
+    for (index, path) in sandbox_policy
+        .get_denied_read_paths_with_cwd(sandbox_policy_cwd)
+        .iter()
+        .enumerate()
+    {
+        let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let deny_param = format!("DENY_READ_{index}");
+        deny_read_cli_args.push(format!(
+            "-D{deny_param}={}",
+            canonical_path.to_string_lossy()
+        ));
+        file_read_policy.push_str(&format!(
+            "\n(deny file-read* (subpath (param \"{deny_param}\")))"
+        ));
+    }
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
@@ -116,6 +134,7 @@ fn create_seatbelt_command_args(
 
     let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
     seatbelt_args.extend(extra_cli_args);
+    seatbelt_args.extend(deny_read_cli_args);
     seatbelt_args.push("--".to_string());
     seatbelt_args.extend(command);
     seatbelt_args
@@ -158,6 +177,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: true,
             exclude_slash_tmp: true,
+            deny_read: Vec::new(),
         };
 
         let args = create_seatbelt_command_args(
@@ -233,6 +253,7 @@ mod tests {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            deny_read: Vec::new(),
         };
 
         let args = create_seatbelt_command_args(
@@ -301,6 +322,48 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn create_seatbelt_args_with_denied_read_paths() {
+        if cfg!(target_os = "windows") {
+            // /tmp does not exist on Windows, so skip this test.
+            return;
+        }
+
+        let tmp = TempDir::new().expect("tempdir");
+        let cwd = tmp.path().canonicalize().expect("canonicalize tempdir");
+        let secrets = cwd.join("secrets");
+        fs::create_dir(&secrets).expect("create secrets");
+        let policy = SandboxPolicy::new_read_only_policy()
+            .with_deny_read(vec![secrets.clone(), PathBuf::from(".env")]);
+
+        let args = create_seatbelt_command_args(
+            vec!["/bin/echo".to_string(), "hello".to_string()],
+            &policy,
+            &cwd,
+        );
+
+        let expected_policy = format!(
+            r#"{MACOS_SEATBELT_BASE_POLICY}
+; allow read-only file operations
+(allow file-read*)
+(deny file-read* (subpath (param "DENY_READ_0")))
+(deny file-read* (subpath (param "DENY_READ_1")))
+
+"#,
+        );
+        let expected_args = vec![
+            "-p".to_string(),
+            expected_policy,
+            format!("-DDENY_READ_0={}", secrets.to_string_lossy()),
+            format!("-DDENY_READ_1={}", cwd.join(".env").to_string_lossy()),
+            "--".to_string(),
+            "/bin/echo".to_string(),
+            "hello".to_string(),
+        ];
+
+        assert_eq!(expected_args, args);
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
@@ -1,10 +1,14 @@
 use std::collections::HashMap;
+use std::io::PipeWriter;
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
 use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::process::Child;
 use tokio::process::Command;
 use tracing::trace;
 
+use crate::config_types::ResourceLimits;
 use crate::protocol::SandboxPolicy;
 
 /// Experimental environment variable that will be set to some non-empty value
@@ -35,6 +39,11 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
+///
+/// `resource_limits` are applied to the child before it execs, so they also
+/// hold for every process it starts. On Unix, the child inherits
+/// `inherited_pipe` under the same descriptor number.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn spawn_child_async(
     program: PathBuf,
     args: Vec<String>,
@@ -43,9 +52,11 @@ pub(crate) async fn spawn_child_async(
     sandbox_policy: &SandboxPolicy,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    #[cfg_attr(not(unix), allow(unused_variables))] resource_limits: ResourceLimits,
+    #[cfg_attr(not(unix), allow(unused_variables))] inherited_pipe: Option<PipeWriter>,
 ) -> std::io::Result<Child> {
     trace!(
-        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
+        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?} {resource_limits:?}"
     );
 
     let mut cmd = Command::new(&program);
@@ -85,6 +96,43 @@ pub(crate) async fn spawn_child_async(
         });
     }
 
+    // Only this child may inherit the pipe, so its descriptor is not made
+    // inheritable until after the fork.
+    #[cfg(unix)]
+    if let Some(fd) = inherited_pipe.as_ref().map(AsRawFd::as_raw_fd) {
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+
+    #[cfg(unix)]
+    if resource_limits != ResourceLimits::default() {
+        #[cfg(target_os = "linux")]
+        let cgroup = exec_cgroup_parent().and_then(|parent| {
+            let limits = parent.cgroup_limits(&resource_limits);
+            (limits != CgroupLimits::default()).then_some((parent, limits))
+        });
+        unsafe {
+            cmd.pre_exec(move || {
+                #[cfg(target_os = "linux")]
+                let memory_in_cgroup = match cgroup {
+                    Some((parent, limits)) => {
+                        parent.join_new_cgroup(&limits) && limits.max_memory_bytes.is_some()
+                    }
+                    None => false,
+                };
+                #[cfg(not(target_os = "linux"))]
+                let memory_in_cgroup = false;
+                set_resource_limits(&resource_limits, !memory_in_cgroup)
+            });
+        }
+    }
+
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
             // Do not create a file descriptor for stdin because otherwise some
@@ -103,5 +151,296 @@ pub(crate) async fn spawn_child_async(
         }
     }
 
+    // The parent's end of `inherited_pipe` is closed once the child has it.
     cmd.kill_on_drop(true).spawn()
 }
+
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(all(unix, not(all(target_os = "linux", target_env = "gnu"))))]
+type RlimitResource = libc::c_int;
+
+/// Applies the CPU time and, when `limit_address_space` is true (i.e. when
+/// the process could not be placed in a cgroup that bounds its memory), the
+/// address space limits of `limits` to the calling process with
+/// `setrlimit(2)`. The process limit is left to the cgroup: `RLIMIT_NPROC`
+/// counts every process of the user, not just those of the command.
+///
+/// This runs between fork and exec, so it must only make async-signal-safe
+/// calls.
+#[cfg(unix)]
+fn set_resource_limits(limits: &ResourceLimits, limit_address_space: bool) -> std::io::Result<()> {
+    if let Some(seconds) = limits.cpu_time_seconds {
+        // SIGXCPU at the soft limit, then SIGKILL a second later in case the
+        // process ignores it.
+        set_rlimit(libc::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+    }
+    if limit_address_space && let Some(megabytes) = limits.memory_mb {
+        let bytes = megabytes.saturating_mul(1024 * 1024);
+        set_rlimit(libc::RLIMIT_AS, bytes, bytes)?;
+    }
+    Ok(())
+}
+
+/// Lowers the soft and hard limits of `resource`, keeping the current hard
+/// limit when it is already lower, as only privileged processes may raise it.
+#[cfg(unix)]
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> std::io::Result<()> {
+    let mut current = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(resource, &mut current) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let hard = current.rlim_max.min(hard as libc::rlim_t);
+    let limit = libc::rlimit {
+        rlim_cur: hard.min(soft as libc::rlim_t),
+        rlim_max: hard,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } == -1 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+use cgroup::CgroupLimits;
+#[cfg(target_os = "linux")]
+use cgroup::exec_cgroup_parent;
+#[cfg(target_os = "linux")]
+pub use cgroup::process_limit_supported;
+#[cfg(target_os = "linux")]
+pub use cgroup::release_exec_cgroup;
+
+/// Without cgroups, the process limit cannot be enforced.
+#[cfg(not(target_os = "linux"))]
+pub fn process_limit_supported() -> bool {
+    false
+}
+
+/// Without cgroups, there is nothing to release.
+#[cfg(not(target_os = "linux"))]
+pub fn release_exec_cgroup(_pid: u32) -> Option<crate::exec::ResourceLimit> {
+    None
+}
+
+/// cgroup v2 support for `ResourceLimits`. Each command with a process or
+/// memory limit runs in a cgroup of its own, named after its process ID,
+/// whose `pids.max` and `memory.max` bound the whole process tree, and whose
+/// `pids.events` and `memory.events` tell which limit stopped it.
+#[cfg(target_os = "linux")]
+mod cgroup {
+    use std::fs;
+    use std::os::fd::AsRawFd;
+    use std::os::fd::OwnedFd;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::sync::OnceLock;
+
+    use tracing::debug;
+
+    use crate::config_types::ResourceLimits;
+    use crate::exec::ResourceLimit;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    const CGROUP_NAME_PREFIX: &str = "codex-exec-";
+
+    /// The cgroup under which command cgroups are created.
+    pub(crate) struct ExecCgroupParent {
+        path: PathBuf,
+        dir: OwnedFd,
+        /// Whether the `pids` controller is enabled for command cgroups.
+        pids: bool,
+        /// Whether the `memory` controller is enabled for command cgroups.
+        memory: bool,
+    }
+
+    /// The limits that a command cgroup enforces.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub(crate) struct CgroupLimits {
+        pub(crate) max_processes: Option<u64>,
+        pub(crate) max_memory_bytes: Option<u64>,
+    }
+
+    static EXEC_CGROUP_PARENT: OnceLock<Option<ExecCgroupParent>> = OnceLock::new();
+
+    /// The cgroup to create command cgroups in, if cgroup v2 is mounted and
+    /// Codex may create cgroups with the `pids` or `memory` controller
+    /// enabled.
+    pub(crate) fn exec_cgroup_parent() -> Option<&'static ExecCgroupParent> {
+        EXEC_CGROUP_PARENT
+            .get_or_init(find_exec_cgroup_parent)
+            .as_ref()
+    }
+
+    /// Whether `ResourceLimits::max_processes` can be enforced on this
+    /// machine.
+    pub fn process_limit_supported() -> bool {
+        exec_cgroup_parent().is_some_and(|parent| parent.pids)
+    }
+
+    fn find_exec_cgroup_parent() -> Option<ExecCgroupParent> {
+        // On a cgroup v2 only system, the single entry reads `0::<path>`.
+        let own = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let own = own.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let own = Path::new(CGROUP_ROOT).join(own.trim_start_matches('/'));
+        // A cgroup that hands controllers down to its children may not hold
+        // processes itself, so Codex's own cgroup cannot be used unless it is
+        // the root. Command cgroups are created next to it instead.
+        let path = if own == Path::new(CGROUP_ROOT) {
+            own
+        } else {
+            own.parent()?.to_path_buf()
+        };
+        let controllers = fs::read_to_string(path.join("cgroup.subtree_control")).ok()?;
+        let pids = controllers.split_whitespace().any(|c| c == "pids");
+        let memory = controllers.split_whitespace().any(|c| c == "memory");
+        if !pids && !memory {
+            debug!(
+                "neither the pids nor the memory controller is enabled in {}",
+                path.display()
+            );
+            return None;
+        }
+        let dir = fs::File::open(&path).ok()?.into();
+        Some(ExecCgroupParent {
+            path,
+            dir,
+            pids,
+            memory,
+        })
+    }
+
+    impl ExecCgroupParent {
+        /// The part of `limits` that a command cgroup can enforce.
+        pub(crate) fn cgroup_limits(&self, limits: &ResourceLimits) -> CgroupLimits {
+            CgroupLimits {
+                max_processes: limits.max_processes.filter(|_| self.pids),
+                max_memory_bytes: limits
+                    .memory_mb
+                    .filter(|_| self.memory)
+                    .map(|megabytes| megabytes.saturating_mul(1024 * 1024)),
+            }
+        }
+
+        /// Moves the calling process into a new cgroup that enforces
+        /// `limits`. Returns false if that was not possible.
+        ///
+        /// This runs between fork and exec, so it must only make
+        /// async-signal-safe calls and must not allocate.
+        pub(crate) fn join_new_cgroup(&self, limits: &CgroupLimits) -> bool {
+            let mut name = [0u8; 32];
+            let prefix_len = CGROUP_NAME_PREFIX.len();
+            name[..prefix_len].copy_from_slice(CGROUP_NAME_PREFIX.as_bytes());
+            let mut digits = [0u8; 20];
+            let pid = format_decimal(u64::from(std::process::id()), &mut digits);
+            name[prefix_len..prefix_len + pid.len()].copy_from_slice(pid);
+            let name = name.as_ptr().cast::<libc::c_char>();
+
+            unsafe {
+                let parent = self.dir.as_raw_fd();
+                let created = libc::mkdirat(parent, name, 0o755) == 0
+                    || std::io::Error::last_os_error().raw_os_error() == Some(libc::EEXIST);
+                if !created {
+                    return false;
+                }
+                let dir = libc::openat(
+                    parent,
+                    name,
+                    libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+                );
+                let mut joined = dir != -1;
+                if joined && let Some(max_processes) = limits.max_processes {
+                    let mut digits = [0u8; 20];
+                    joined =
+                        write_file(dir, c"pids.max", format_decimal(max_processes, &mut digits));
+                }
+                if joined && let Some(max_memory_bytes) = limits.max_memory_bytes {
+                    let mut digits = [0u8; 20];
+                    joined = write_file(
+                        dir,
+                        c"memory.max",
+                        format_decimal(max_memory_bytes, &mut digits),
+                    );
+                    // Without this, the command would swap instead of being
+                    // stopped. Not every kernel has swap accounting.
+                    write_file(dir, c"memory.swap.max", b"0");
+                }
+                joined = joined && write_file(dir, c"cgroup.procs", b"0");
+                if dir != -1 {
+                    libc::close(dir);
+                }
+                if !joined {
+                    libc::unlinkat(parent, name, libc::AT_REMOVEDIR);
+                }
+                joined
+            }
+        }
+    }
+
+    /// Removes the cgroup that the command with process ID `pid` ran in, if it
+    /// had one, and returns the limit that its event counters show the command
+    /// reached. The cgroup is left in place while processes that the command
+    /// started in the background are still running.
+    pub fn release_exec_cgroup(pid: u32) -> Option<ResourceLimit> {
+        let Some(Some(parent)) = EXEC_CGROUP_PARENT.get() else {
+            return None;
+        };
+        let path = parent.path.join(format!("{CGROUP_NAME_PREFIX}{pid}"));
+        if !path.is_dir() {
+            return None;
+        }
+        // A controller's event file exists whenever the controller is
+        // enabled, so a missing one simply means no event.
+        let memory_events = fs::read_to_string(path.join("memory.events")).unwrap_or_default();
+        let pids_events = fs::read_to_string(path.join("pids.events")).unwrap_or_default();
+        if let Err(err) = fs::remove_dir(&path) {
+            debug!("failed to remove {}: {err}", path.display());
+        }
+        if event_count(&memory_events, "oom_kill") > 0 {
+            Some(ResourceLimit::Memory)
+        } else if event_count(&pids_events, "max") > 0 {
+            Some(ResourceLimit::Processes)
+        } else {
+            None
+        }
+    }
+
+    /// The value of the counter `key` in a cgroup `*.events` file.
+    fn event_count(events: &str, key: &str) -> u64 {
+        events
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .find(|(name, _)| *name == key)
+            .and_then(|(_, count)| count.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// Writes `contents` to the file `name` in the directory `dir`.
+    unsafe fn write_file(dir: libc::c_int, name: &std::ffi::CStr, contents: &[u8]) -> bool {
+        unsafe {
+            let fd = libc::openat(dir, name.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+            if fd == -1 {
+                return false;
+            }
+            let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+            libc::close(fd);
+            written == contents.len() as isize
+        }
+    }
+
+    /// Formats `value` in `buf` without allocating.
+    fn format_decimal(mut value: u64, buf: &mut [u8; 20]) -> &[u8] {
+        let mut start = buf.len();
+        loop {
+            start -= 1;
+            buf[start] = b'0' + (value % 10) as u8;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        &buf[start..]
+    }
+}
@@ -1,4 +1,6 @@
 use crate::RolloutRecorder;
+use crate::config_types::LinuxSandboxBackend;
+use crate::config_types::TurnLimits;
 use crate::exec_command::ExecSessionManager;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::unified_exec::UnifiedExecSessionManager;
@@ -13,6 +15,9 @@ pub(crate) struct SessionServices {
     pub(crate) notifier: UserNotifier,
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
+    pub(crate) linux_sandbox_backend: LinuxSandboxBackend,
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
+    pub(crate) turn_limits: TurnLimits,
+    pub(crate) max_parallel_tool_calls: usize,
 }
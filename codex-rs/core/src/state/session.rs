@@ -16,6 +16,8 @@ pub(crate) struct SessionState {
     pub(crate) history: ConversationHistory,
     pub(crate) token_info: Option<TokenUsageInfo>,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    /// The last diff sent for the running turn, not yet in the rollout.
+    pub(crate) last_turn_diff: Option<String>,
 }
 
 impl SessionState {
@@ -9,6 +9,7 @@ use tokio::task::AbortHandle;
 use codex_protocol::models::ResponseInputItem;
 use tokio::sync::oneshot;
 
+use crate::protocol::InputItem;
 use crate::protocol::ReviewDecision;
 use crate::tasks::SessionTask;
 
@@ -61,6 +62,8 @@ impl ActiveTurn {
 pub(crate) struct TurnState {
     pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
     pending_input: Vec<ResponseInputItem>,
+    /// The steers that `pending_input` holds, as the user sent them.
+    pending_steer: Vec<InputItem>,
 }
 
 impl TurnState {
@@ -82,13 +85,29 @@ impl TurnState {
     pub(crate) fn clear_pending(&mut self) {
         self.pending_approvals.clear();
         self.pending_input.clear();
+        self.pending_steer.clear();
     }
 
     pub(crate) fn push_pending_input(&mut self, input: ResponseInputItem) {
         self.pending_input.push(input);
     }
 
+    pub(crate) fn push_pending_steer(&mut self, input: Vec<InputItem>) {
+        self.pending_input.push(input.clone().into());
+        self.pending_steer.extend(input);
+    }
+
+    pub(crate) fn has_pending_steer(&self) -> bool {
+        !self.pending_steer.is_empty()
+    }
+
+    /// Takes the steers that the model has not seen yet.
+    pub(crate) fn take_pending_steer(&mut self) -> Vec<InputItem> {
+        std::mem::take(&mut self.pending_steer)
+    }
+
     pub(crate) fn take_pending_input(&mut self) -> Vec<ResponseInputItem> {
+        self.pending_steer.clear();
         if self.pending_input.is_empty() {
             Vec::with_capacity(0)
         } else {
@@ -1,7 +1,10 @@
 mod compact;
 mod regular;
 mod review;
+mod turn_limits;
 
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -22,6 +25,8 @@ use crate::state::TaskKind;
 pub(crate) use compact::CompactTask;
 pub(crate) use regular::RegularTask;
 pub(crate) use review::ReviewTask;
+pub(crate) use turn_limits::TurnLimitTracker;
+pub(crate) use turn_limits::finish_task_at_limit;
 
 /// Thin wrapper that exposes the parts of [`Session`] task runners need.
 #[derive(Clone)]
@@ -76,11 +81,17 @@ impl Session {
             let sub_clone = sub_id.clone();
             tokio::spawn(async move {
                 let last_agent_message = task_for_run
-                    .run(Arc::clone(&session_ctx), ctx, sub_clone.clone(), input)
+                    .run(
+                        Arc::clone(&session_ctx),
+                        Arc::clone(&ctx),
+                        sub_clone.clone(),
+                        input,
+                    )
                     .await;
                 // Emit completion uniformly from spawn site so all tasks share the same lifecycle.
                 let sess = session_ctx.clone_session();
-                sess.on_task_finished(sub_clone, last_agent_message).await;
+                sess.on_task_finished(ctx, sub_clone, last_agent_message)
+                    .await;
             })
             .abort_handle()
         };
@@ -101,21 +112,41 @@ impl Session {
 
     pub async fn on_task_finished(
         self: &Arc<Self>,
+        turn_context: Arc<TurnContext>,
         sub_id: String,
         last_agent_message: Option<String>,
     ) {
         let mut active = self.active_turn.lock().await;
+        let mut steer = Vec::new();
         if let Some(at) = active.as_mut()
             && at.remove_task(&sub_id)
         {
+            steer = at.turn_state.lock().await.take_pending_steer();
             *active = None;
         }
         drop(active);
         let event = Event {
-            id: sub_id,
+            id: sub_id.clone(),
             msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
         };
         self.send_event(event).await;
+
+        // A steer that arrived after the model's last response would
+        // otherwise be dropped with the turn; answer it in a turn of its own.
+        if !steer.is_empty() {
+            self.spawn_steer_turn(turn_context, sub_id, steer).await;
+        }
+    }
+
+    /// Starts a regular turn for `steer`. Boxed, since the task it spawns
+    /// finishes in [`Session::on_task_finished`], which calls this.
+    fn spawn_steer_turn(
+        self: &Arc<Self>,
+        turn_context: Arc<TurnContext>,
+        sub_id: String,
+        steer: Vec<InputItem>,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(self.spawn_task(turn_context, sub_id, steer, RegularTask))
     }
 
     async fn register_new_active_task(&self, sub_id: String, task: RunningTask) {
@@ -153,6 +184,7 @@ impl Session {
         handle.abort();
         let session_ctx = Arc::new(SessionTaskContext::new(Arc::clone(self)));
         session_task.abort(session_ctx, &sub_id).await;
+        self.record_final_turn_diff().await;
 
         let event = Event {
             id: sub_id.clone(),
@@ -9,6 +9,7 @@ use crate::state::TaskKind;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::TurnLimitTracker;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct RegularTask;
@@ -27,6 +28,7 @@ impl SessionTask for RegularTask {
         input: Vec<InputItem>,
     ) -> Option<String> {
         let sess = session.clone_session();
-        run_task(sess, ctx, sub_id, input).await
+        let mut turn_limits = TurnLimitTracker::new(sess.turn_limits());
+        run_task(sess, ctx, sub_id, input, &mut turn_limits).await
     }
 }
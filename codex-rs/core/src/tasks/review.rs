@@ -10,6 +10,7 @@ use crate::state::TaskKind;
 
 use super::SessionTask;
 use super::SessionTaskContext;
+use super::TurnLimitTracker;
 
 #[derive(Clone, Copy, Default)]
 pub(crate) struct ReviewTask;
@@ -28,7 +29,8 @@ impl SessionTask for ReviewTask {
         input: Vec<InputItem>,
     ) -> Option<String> {
         let sess = session.clone_session();
-        run_task(sess, ctx, sub_id, input).await
+        let mut turn_limits = TurnLimitTracker::new(sess.turn_limits());
+        run_task(sess, ctx, sub_id, input, &mut turn_limits).await
     }
 
     async fn abort(&self, session: Arc<SessionTaskContext>, sub_id: &str) {
@@ -0,0 +1,376 @@
+use std::fmt;
+use std::time::Duration;
+use std::time::Instant;
+
+use askama::Template;
+use codex_protocol::models::FunctionCallOutputPayload;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+use tracing::info;
+
+use crate::Prompt;
+use crate::codex::ProcessedResponseItem;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex::get_last_assistant_message_from_turn;
+use crate::codex::run_prompt;
+use crate::config_types::TurnLimits;
+use crate::protocol::ErrorEvent;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
+use crate::protocol::TurnAbortReason;
+use crate::protocol::TurnAbortedEvent;
+use crate::turn_diff_tracker::TurnDiffTracker;
+
+#[derive(Template)]
+#[template(path = "turn_limits/summary_prompt.md", escape = "none")]
+struct LimitSummaryTemplate<'a> {
+    limit: &'a str,
+}
+
+/// Which of the configured [`TurnLimits`] a task ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TurnLimitExceeded {
+    ToolCalls(u32),
+    Duration(Duration),
+    AutonomousTurns(u32),
+}
+
+impl fmt::Display for TurnLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ToolCalls(max) => write!(f, "the tool call limit ({max}) was reached"),
+            Self::Duration(max) => write!(
+                f,
+                "the time limit ({}) was reached",
+                format_limit_duration(*max)
+            ),
+            Self::AutonomousTurns(max) => write!(
+                f,
+                "the limit on model requests without user input ({max}) was reached"
+            ),
+        }
+    }
+}
+
+fn format_limit_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 && secs.is_multiple_of(60) {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Counts tool calls and follow-up model requests for one task and reports
+/// when a configured limit has been reached. Task runners start one when
+/// their task starts, so the time limit covers the whole task.
+pub(crate) struct TurnLimitTracker {
+    limits: TurnLimits,
+    started_at: Instant,
+    tool_calls: u32,
+    autonomous_turns: u32,
+    /// Set once the task has stopped at a limit; every later tool call is
+    /// refused.
+    stopped: Option<TurnLimitExceeded>,
+}
+
+impl TurnLimitTracker {
+    pub(crate) fn new(limits: TurnLimits) -> Self {
+        Self {
+            limits,
+            started_at: Instant::now(),
+            tool_calls: 0,
+            autonomous_turns: 0,
+            stopped: None,
+        }
+    }
+
+    /// Count `item` before it is dispatched if it is a tool call. Returns the
+    /// output to send back instead of running the call when the task has
+    /// already used up its tool calls.
+    pub(crate) fn check_tool_call(&mut self, item: &ResponseItem) -> Option<ResponseInputItem> {
+        if !matches!(
+            item,
+            ResponseItem::FunctionCall { .. }
+                | ResponseItem::LocalShellCall { .. }
+                | ResponseItem::CustomToolCall { .. }
+        ) {
+            return None;
+        }
+        let exceeded = match (self.stopped, self.limits.max_tool_calls_per_turn) {
+            (Some(exceeded), _) => exceeded,
+            (None, Some(max)) if self.tool_calls >= max => TurnLimitExceeded::ToolCalls(max),
+            (None, _) => {
+                self.tool_calls = self.tool_calls.saturating_add(1);
+                return None;
+            }
+        };
+
+        let content = format!("Tool call not run: {exceeded}.");
+        let function_call_output = |call_id: String| ResponseInputItem::FunctionCallOutput {
+            call_id,
+            output: FunctionCallOutputPayload {
+                content: content.clone(),
+                success: Some(false),
+            },
+        };
+        match item {
+            ResponseItem::FunctionCall { call_id, .. } => {
+                Some(function_call_output(call_id.clone()))
+            }
+            ResponseItem::LocalShellCall { call_id, id, .. } => Some(function_call_output(
+                call_id.clone().or_else(|| id.clone()).unwrap_or_default(),
+            )),
+            ResponseItem::CustomToolCall { call_id, .. } => {
+                Some(ResponseInputItem::CustomToolCallOutput {
+                    call_id: call_id.clone(),
+                    output: content.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Record a model response whose tool call outputs are about to be sent
+    /// back to the model, and return the first limit that the next request
+    /// would exceed.
+    pub(crate) fn record_tool_turn(&mut self) -> Option<TurnLimitExceeded> {
+        self.autonomous_turns = self.autonomous_turns.saturating_add(1);
+        self.check(self.started_at.elapsed())
+    }
+
+    fn check(&self, elapsed: Duration) -> Option<TurnLimitExceeded> {
+        let TurnLimits {
+            max_tool_calls_per_turn,
+            max_turn_duration,
+            max_autonomous_turns,
+        } = self.limits;
+
+        if let Some(max) = max_tool_calls_per_turn
+            && self.tool_calls >= max
+        {
+            return Some(TurnLimitExceeded::ToolCalls(max));
+        }
+        if let Some(max) = max_turn_duration
+            && elapsed >= max
+        {
+            return Some(TurnLimitExceeded::Duration(max));
+        }
+        if let Some(max) = max_autonomous_turns
+            && self.autonomous_turns >= max
+        {
+            return Some(TurnLimitExceeded::AutonomousTurns(max));
+        }
+        None
+    }
+}
+
+/// Build the user message asking the model to wrap up after `exceeded`.
+fn limit_summary_request(exceeded: TurnLimitExceeded) -> ResponseItem {
+    let limit = exceeded.to_string();
+    let text = LimitSummaryTemplate { limit: &limit }
+        .render()
+        .unwrap_or_else(|_| format!("This task has been stopped because {limit}."));
+    ResponseInputItem::from(vec![InputItem::Text { text }]).into()
+}
+
+/// End a task that ran into `exceeded`: sample the model once more with no
+/// tools available, refusing any tool call it makes anyway, so that it can
+/// only answer with a summary of where it stopped, then emit `TurnAborted`
+/// with [`TurnAbortReason::LimitReached`].
+///
+/// `review_thread_history` is the isolated history of a review thread, or
+/// `None` for regular tasks that record into the session history. Returns the
+/// summary so it can be reported as the task's last agent message.
+pub(crate) async fn finish_task_at_limit(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    turn_limits: &mut TurnLimitTracker,
+    sub_id: &str,
+    exceeded: TurnLimitExceeded,
+    review_thread_history: Option<&mut Vec<ResponseItem>>,
+) -> Option<String> {
+    sess.notify_background_event(sub_id, format!("Stopping turn: {exceeded}."))
+        .await;
+    turn_limits.stopped = Some(exceeded);
+
+    let summary_request = limit_summary_request(exceeded);
+    let input = match &review_thread_history {
+        Some(history) => {
+            let mut input = history.to_vec();
+            input.push(summary_request.clone());
+            input
+        }
+        None => {
+            sess.record_conversation_items(std::slice::from_ref(&summary_request))
+                .await;
+            sess.turn_input_with_history(Vec::new()).await
+        }
+    };
+
+    let prompt = Prompt {
+        input,
+        tools: Vec::new(),
+        base_instructions_override: turn_context.base_instructions.clone(),
+        output_schema: None,
+    };
+    let last_agent_message = match run_prompt(
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        turn_limits,
+        sub_id,
+        &prompt,
+    )
+    .await
+    {
+        Ok(output) => {
+            let summary: Vec<ResponseItem> = output
+                .processed_items
+                .into_iter()
+                .filter_map(|ProcessedResponseItem { item, .. }| match &item {
+                    ResponseItem::Message { role, .. } if role == "assistant" => Some(item),
+                    _ => None,
+                })
+                .collect();
+            let last_agent_message = get_last_assistant_message_from_turn(&summary);
+            match review_thread_history {
+                Some(history) => {
+                    history.push(summary_request);
+                    history.extend(summary);
+                }
+                None => sess.record_conversation_items(&summary).await,
+            }
+            last_agent_message
+        }
+        Err(e) => {
+            info!("Turn limit summary error: {e:#}");
+            sess.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: e.to_string(),
+                }),
+            })
+            .await;
+            None
+        }
+    };
+
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            reason: TurnAbortReason::LimitReached,
+        }),
+    })
+    .await;
+
+    last_agent_message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn limits(tool_calls: Option<u32>, duration: Option<u64>, turns: Option<u32>) -> TurnLimits {
+        TurnLimits {
+            max_tool_calls_per_turn: tool_calls,
+            max_turn_duration: duration.map(Duration::from_secs),
+            max_autonomous_turns: turns,
+        }
+    }
+
+    fn function_call(call_id: &str) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            id: None,
+            name: "shell".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut tracker = TurnLimitTracker::new(TurnLimits::default());
+        for _ in 0..100 {
+            for _ in 0..10 {
+                assert_eq!(None, tracker.check_tool_call(&function_call("call")));
+            }
+            assert_eq!(None, tracker.record_tool_turn());
+        }
+    }
+
+    #[test]
+    fn refuses_tool_calls_past_the_limit_within_one_response() {
+        let mut tracker = TurnLimitTracker::new(limits(Some(2), None, None));
+        assert_eq!(None, tracker.check_tool_call(&function_call("call-1")));
+        assert_eq!(None, tracker.check_tool_call(&function_call("call-2")));
+        assert_eq!(
+            Some(ResponseInputItem::FunctionCallOutput {
+                call_id: "call-3".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "Tool call not run: the tool call limit (2) was reached.".to_string(),
+                    success: Some(false),
+                },
+            }),
+            tracker.check_tool_call(&function_call("call-3"))
+        );
+        assert_eq!(
+            Some(TurnLimitExceeded::ToolCalls(2)),
+            tracker.record_tool_turn()
+        );
+    }
+
+    #[test]
+    fn refuses_every_tool_call_once_stopped() {
+        let mut tracker = TurnLimitTracker::new(TurnLimits::default());
+        tracker.stopped = Some(TurnLimitExceeded::AutonomousTurns(3));
+        let Some(ResponseInputItem::FunctionCallOutput { output, .. }) =
+            tracker.check_tool_call(&function_call("call"))
+        else {
+            panic!("expected the call to be refused");
+        };
+        assert_eq!(
+            "Tool call not run: the limit on model requests without user input (3) was reached.",
+            output.content
+        );
+    }
+
+    #[test]
+    fn stops_after_max_autonomous_turns() {
+        let mut tracker = TurnLimitTracker::new(limits(None, None, Some(2)));
+        assert_eq!(None, tracker.record_tool_turn());
+        assert_eq!(
+            Some(TurnLimitExceeded::AutonomousTurns(2)),
+            tracker.record_tool_turn()
+        );
+    }
+
+    #[test]
+    fn stops_after_max_duration() {
+        let tracker = TurnLimitTracker::new(limits(None, Some(60), None));
+        assert_eq!(None, tracker.check(Duration::from_secs(59)));
+        assert_eq!(
+            Some(TurnLimitExceeded::Duration(Duration::from_secs(60))),
+            tracker.check(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn summary_request_names_the_limit() {
+        let item = limit_summary_request(TurnLimitExceeded::ToolCalls(7));
+        let ResponseItem::Message { role, content, .. } = item else {
+            panic!("expected a message");
+        };
+        assert_eq!("user", role);
+        let text = match &content[..] {
+            [codex_protocol::models::ContentItem::InputText { text }] => text.clone(),
+            other => panic!("unexpected content: {other:?}"),
+        };
+        assert!(text.contains("the tool call limit (7) was reached"));
+        assert!(text.contains("Do not call any more tools."));
+    }
+}
@@ -10,6 +10,7 @@ use anyhow::anyhow;
 use sha1::digest::Output;
 use uuid::Uuid;
 
+use crate::overlay::OverlayLayer;
 use crate::protocol::FileChange;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
@@ -40,6 +41,12 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// Under an overlay policy, the layer holding the current contents of
+    /// the workspace. Baselines are then read from the workspace itself, so
+    /// the diff covers everything the layer has accumulated.
+    overlay: Option<OverlayLayer>,
+    /// The diff last returned by [`Self::get_unified_diff`].
+    last_unified_diff: Option<String>,
 }
 
 impl TurnDiffTracker {
@@ -47,51 +54,27 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    /// A tracker that reads current contents through `overlay` and also
+    /// reports the layer's changes made by commands other than apply_patch.
+    pub fn with_overlay(overlay: OverlayLayer) -> Self {
+        Self {
+            overlay: Some(overlay),
+            ..Self::default()
+        }
+    }
+
+    /// Whether commands other than apply_patch can change the diff.
+    pub fn has_overlay(&self) -> bool {
+        self.overlay.is_some()
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
     /// - Also updates internal mappings for move/rename events.
     pub fn on_patch_begin(&mut self, changes: &HashMap<PathBuf, FileChange>) {
         for (path, change) in changes.iter() {
-            // Ensure a stable internal filename exists for this external path.
-            if !self.external_to_temp_name.contains_key(path) {
-                let internal = Uuid::new_v4().to_string();
-                self.external_to_temp_name
-                    .insert(path.clone(), internal.clone());
-                self.temp_name_to_current_path
-                    .insert(internal.clone(), path.clone());
-
-                // If the file exists on disk now, snapshot as baseline; else leave missing to represent /dev/null.
-                let baseline_file_info = if path.exists() {
-                    let mode = file_mode_for_path(path);
-                    let mode_val = mode.unwrap_or(FileMode::Regular);
-                    let content = blob_bytes(path, mode_val).unwrap_or_default();
-                    let oid = if mode == Some(FileMode::Symlink) {
-                        format!("{:x}", git_blob_sha1_hex_bytes(&content))
-                    } else {
-                        self.git_blob_oid_for_path(path)
-                            .unwrap_or_else(|| format!("{:x}", git_blob_sha1_hex_bytes(&content)))
-                    };
-                    Some(BaselineFileInfo {
-                        path: path.clone(),
-                        content,
-                        mode: mode_val,
-                        oid,
-                    })
-                } else {
-                    Some(BaselineFileInfo {
-                        path: path.clone(),
-                        content: vec![],
-                        mode: FileMode::Regular,
-                        oid: ZERO_OID.to_string(),
-                    })
-                };
-
-                if let Some(baseline_file_info) = baseline_file_info {
-                    self.baseline_file_info
-                        .insert(internal.clone(), baseline_file_info);
-                }
-            }
+            self.track_path(path);
 
             // Track rename/move in current mapping if provided in an Update.
             if let FileChange::Update {
@@ -127,6 +110,46 @@ impl TurnDiffTracker {
         }
     }
 
+    /// Ensure a stable internal filename exists for this external path, with
+    /// a baseline snapshot of the file as it is on disk now.
+    fn track_path(&mut self, path: &Path) {
+        if self.external_to_temp_name.contains_key(path) {
+            return;
+        }
+        let internal = Uuid::new_v4().to_string();
+        self.external_to_temp_name
+            .insert(path.to_path_buf(), internal.clone());
+        self.temp_name_to_current_path
+            .insert(internal.clone(), path.to_path_buf());
+
+        // If the file exists on disk now, snapshot as baseline; else leave missing to represent /dev/null.
+        let baseline_file_info = if path.exists() {
+            let mode = file_mode_for_path(path);
+            let mode_val = mode.unwrap_or(FileMode::Regular);
+            let content = blob_bytes(path, mode_val).unwrap_or_default();
+            let oid = if mode == Some(FileMode::Symlink) {
+                format!("{:x}", git_blob_sha1_hex_bytes(&content))
+            } else {
+                self.git_blob_oid_for_path(path)
+                    .unwrap_or_else(|| format!("{:x}", git_blob_sha1_hex_bytes(&content)))
+            };
+            BaselineFileInfo {
+                path: path.to_path_buf(),
+                content,
+                mode: mode_val,
+                oid,
+            }
+        } else {
+            BaselineFileInfo {
+                path: path.to_path_buf(),
+                content: vec![],
+                mode: FileMode::Regular,
+                oid: ZERO_OID.to_string(),
+            }
+        };
+        self.baseline_file_info.insert(internal, baseline_file_info);
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -225,6 +248,24 @@ impl TurnDiffTracker {
     pub fn get_unified_diff(&mut self) -> Result<Option<String>> {
         let mut aggregated = String::new();
 
+        // Commands write to the layer without going through apply_patch, so
+        // pick up every path the layer has changed, except for ignored ones
+        // like build output.
+        if let Some(overlay) = &self.overlay {
+            let changed_paths = overlay
+                .changed_paths_not_ignored()
+                .context("failed to read the overlay layer")?;
+            for path in changed_paths {
+                let is_baseline = self
+                    .baseline_file_info
+                    .values()
+                    .any(|info| info.path == path);
+                if !is_baseline {
+                    self.track_path(&path);
+                }
+            }
+        }
+
         // Compute diffs per tracked internal file in a stable order by external path.
         let mut baseline_file_names: Vec<String> =
             self.baseline_file_info.keys().cloned().collect();
@@ -242,10 +283,25 @@ impl TurnDiffTracker {
             }
         }
 
-        if aggregated.trim().is_empty() {
+        let unified_diff = if aggregated.trim().is_empty() {
+            None
+        } else {
+            Some(aggregated)
+        };
+        self.last_unified_diff = unified_diff.clone();
+        Ok(unified_diff)
+    }
+
+    /// [`Self::get_unified_diff`], if the diff changed since it was last
+    /// computed. Under an overlay policy any command may change the diff, and
+    /// most do not.
+    pub fn get_changed_unified_diff(&mut self) -> Result<Option<String>> {
+        let last_unified_diff = self.last_unified_diff.take();
+        let unified_diff = self.get_unified_diff()?;
+        if unified_diff == last_unified_diff {
             Ok(None)
         } else {
-            Ok(Some(aggregated))
+            Ok(unified_diff)
         }
     }
 
@@ -265,8 +321,17 @@ impl TurnDiffTracker {
             None => return aggregated,
         };
 
-        let current_mode = file_mode_for_path(&current_external_path).unwrap_or(FileMode::Regular);
-        let right_bytes = blob_bytes(&current_external_path, current_mode);
+        let current_file = match &self.overlay {
+            Some(overlay) => overlay.resolve(&current_external_path),
+            None => Some(current_external_path.clone()),
+        };
+        let current_mode = current_file
+            .as_deref()
+            .and_then(file_mode_for_path)
+            .unwrap_or(FileMode::Regular);
+        let right_bytes = current_file
+            .as_deref()
+            .and_then(|file| blob_bytes(file, current_mode));
 
         // Compute displays with &mut self before borrowing any baseline content.
         let left_display = self.relative_to_git_root_str(&baseline_external_path);
@@ -274,7 +339,8 @@ impl TurnDiffTracker {
 
         // Compute right oid before borrowing baseline content.
         let right_oid = if let Some(b) = right_bytes.as_ref() {
-            if current_mode == FileMode::Symlink {
+            // Files in the layer are outside the repository.
+            if current_mode == FileMode::Symlink || self.overlay.is_some() {
                 format!("{:x}", git_blob_sha1_hex_bytes(b))
             } else {
                 self.git_blob_oid_for_path(&current_external_path)
@@ -893,4 +959,62 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[test]
+    fn overlay_diff_includes_layer_changes() {
+        let workspace = tempdir().unwrap();
+        let layer = tempdir().unwrap();
+        let file = workspace.path().join("a.txt");
+        fs::write(&file, "old\n").unwrap();
+        let upper = crate::overlay::upper_dir(layer.path());
+        fs::create_dir_all(&upper).unwrap();
+        fs::write(upper.join("a.txt"), "new\n").unwrap();
+
+        let mut acc = TurnDiffTracker::with_overlay(OverlayLayer::new(
+            workspace.path().to_path_buf(),
+            layer.path().to_path_buf(),
+        ));
+        let diff = acc.get_unified_diff().unwrap().unwrap();
+        let diff = normalize_diff_for_test(&diff, workspace.path());
+        let expected = {
+            let left_oid = git_blob_sha1_hex("old\n");
+            let right_oid = git_blob_sha1_hex("new\n");
+            format!(
+                r#"diff --git a/<TMP>/a.txt b/<TMP>/a.txt
+index {left_oid}..{right_oid}
+--- a/<TMP>/a.txt
++++ b/<TMP>/a.txt
+@@ -1 +1 @@
+-old
++new
+"#,
+            )
+        };
+        assert_eq!(diff, expected);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "old\n");
+    }
+
+    #[test]
+    fn overlay_diff_skips_ignored_paths_and_reports_changes_once() {
+        let workspace = tempdir().unwrap();
+        let layer = tempdir().unwrap();
+        fs::write(workspace.path().join(".gitignore"), "target/\n").unwrap();
+        let upper = crate::overlay::upper_dir(layer.path());
+        fs::create_dir_all(upper.join("target").join("debug")).unwrap();
+        fs::write(upper.join("target").join("debug").join("out"), "binary\n").unwrap();
+        fs::write(upper.join("b.txt"), "new\n").unwrap();
+
+        let mut acc = TurnDiffTracker::with_overlay(OverlayLayer::new(
+            workspace.path().to_path_buf(),
+            layer.path().to_path_buf(),
+        ));
+        let diff = acc.get_changed_unified_diff().unwrap().unwrap();
+        let diff = normalize_diff_for_test(&diff, workspace.path());
+        assert!(diff.contains("b/<TMP>/b.txt"), "{diff}");
+        assert!(!diff.contains("target"), "{diff}");
+        assert_eq!(acc.get_changed_unified_diff().unwrap(), None);
+
+        fs::write(upper.join("b.txt"), "newer\n").unwrap();
+        assert!(acc.get_changed_unified_diff().unwrap().is_some());
+    }
 }
@@ -9,6 +9,7 @@ use codex_core::content_items_to_text;
 use codex_core::is_session_prefix_message;
 use codex_core::protocol::ConversationPathResponseEvent;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ForkOrigin;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::RolloutItem;
@@ -60,6 +61,7 @@ async fn fork_conversation_twice_drops_to_first_message() {
 
     let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
     let NewConversation {
+        conversation_id: base_id,
         conversation: codex,
         ..
     } = conversation_manager
@@ -142,6 +144,7 @@ async fn fork_conversation_twice_drops_to_first_message() {
 
     // Fork once with n=1 → drops the last user input and everything after.
     let NewConversation {
+        conversation_id: fork1_id,
         conversation: codex_fork1,
         ..
     } = conversation_manager
@@ -197,4 +200,32 @@ async fn fork_conversation_twice_drops_to_first_message() {
         serde_json::to_value(&fork2_items).unwrap(),
         serde_json::to_value(&expected_after_second).unwrap()
     );
+
+    // Each fork records where it branched off in its own session meta.
+    let forked_from = |p: &std::path::Path| -> Option<ForkOrigin> {
+        let text = std::fs::read_to_string(p).expect("read rollout file");
+        let first = text.lines().next().expect("session meta line");
+        match serde_json::from_str::<RolloutLine>(first)
+            .expect("rollout line")
+            .item
+        {
+            RolloutItem::SessionMeta(meta_line) => meta_line.meta.forked_from,
+            _ => panic!("expected the session meta first"),
+        }
+    };
+    pretty_assertions::assert_eq!(forked_from(&base_path), None);
+    pretty_assertions::assert_eq!(
+        forked_from(&fork1_path),
+        Some(ForkOrigin {
+            conversation_id: base_id,
+            nth_user_message: 1,
+        })
+    );
+    pretty_assertions::assert_eq!(
+        forked_from(&fork2_path),
+        Some(ForkOrigin {
+            conversation_id: fork1_id,
+            nth_user_message: 0,
+        })
+    );
 }
@@ -12,11 +12,14 @@ mod fork_conversation;
 mod json_result;
 mod live_cli;
 mod model_overrides;
+mod parallel_tool_calls;
 mod prompt_caching;
 mod review;
 mod rmcp_client;
 mod rollout_list_find;
 mod seatbelt;
+mod steer;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod turn_limits;
 mod user_notification;
@@ -0,0 +1,191 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+
+fn echo_call(call_id: &str, word: &str) -> Value {
+    let args = json!({
+        "command": ["echo", word],
+        "timeout_ms": 5_000,
+    })
+    .to_string();
+    ev_function_call(call_id, "shell", &args)
+}
+
+/// A read-only call that blocks until its timeout kills it.
+fn blocking_call(call_id: &str) -> Value {
+    let args = json!({
+        "command": ["tail", "-f", "/dev/null"],
+        "timeout_ms": 2_000,
+    })
+    .to_string();
+    ev_function_call(call_id, "shell", &args)
+}
+
+/// Read-only shell calls from one response start before the earlier ones
+/// finish, and their outputs go back to the model in the order of the calls.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn read_only_calls_run_concurrently_in_order() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                blocking_call("call-1"),
+                echo_call("call-2", "second"),
+                echo_call("call-3", "third"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.max_parallel_tool_calls = 4;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    let codex = &test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "echo three words".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut begun = 0;
+    while begun < 3 {
+        match wait_for_event(codex, |ev| {
+            matches!(
+                ev,
+                EventMsg::ExecCommandBegin(_) | EventMsg::ExecCommandEnd(_)
+            )
+        })
+        .await
+        {
+            EventMsg::ExecCommandBegin(_) => begun += 1,
+            EventMsg::ExecCommandEnd(end) => assert_ne!(
+                "call-1", end.call_id,
+                "all calls should start before the first one ends"
+            ),
+            _ => unreachable!(),
+        }
+    }
+
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(2, requests.len());
+    let first_request = requests[0].body_json::<Value>().unwrap();
+    assert_eq!(json!(true), first_request["parallel_tool_calls"]);
+
+    let follow_up = requests[1].body_json::<Value>().unwrap();
+    let outputs: Vec<(String, String)> = follow_up["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["type"] == "function_call_output")
+        .map(|item| {
+            (
+                item["call_id"].as_str().unwrap().to_string(),
+                item["output"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    let call_ids: Vec<&str> = outputs.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(vec!["call-1", "call-2", "call-3"], call_ids);
+    for ((_, output), word) in outputs[1..].iter().zip(["second", "third"]) {
+        assert!(output.contains(word), "unexpected output: {output}");
+    }
+}
+
+/// With `max_parallel_tool_calls = 1` calls run one after another and the
+/// model is not invited to emit parallel calls.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn concurrency_limit_of_one_runs_calls_sequentially() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                echo_call("call-1", "first"),
+                echo_call("call-2", "second"),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.max_parallel_tool_calls = 1;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    let codex = &test.codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "echo two words".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let mut order = Vec::new();
+    while order.len() < 4 {
+        match wait_for_event(codex, |ev| {
+            matches!(
+                ev,
+                EventMsg::ExecCommandBegin(_) | EventMsg::ExecCommandEnd(_)
+            )
+        })
+        .await
+        {
+            EventMsg::ExecCommandBegin(ev) => order.push(format!("begin {}", ev.call_id)),
+            EventMsg::ExecCommandEnd(ev) => order.push(format!("end {}", ev.call_id)),
+            _ => unreachable!(),
+        }
+    }
+    assert_eq!(
+        vec!["begin call-1", "end call-1", "begin call-2", "end call-2"],
+        order
+    );
+
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    let first_request = requests[0].body_json::<Value>().unwrap();
+    assert_eq!(json!(false), first_request["parallel_tool_calls"]);
+}
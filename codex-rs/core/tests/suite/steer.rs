@@ -0,0 +1,157 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::json;
+
+/// Input steered into a running turn reaches the model together with the
+/// output of the tool call in flight, without interrupting the turn.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn steer_reaches_the_model_at_the_next_tool_call_boundary() {
+    skip_if_no_network!();
+
+    let args = json!({
+        "command": ["sleep", "1"],
+        "timeout_ms": 5_000,
+    })
+    .to_string();
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "using staging"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let codex = test_codex().build(&server).await.unwrap().codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "migrate the database".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+    codex
+        .submit(Op::Steer {
+            items: vec![InputItem::Text {
+                text: "use the staging database".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let complete = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!();
+    };
+    assert_eq!(
+        Some("using staging".to_string()),
+        complete.last_agent_message
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(2, requests.len());
+    let second = String::from_utf8_lossy(&requests[1].body).to_string();
+    assert!(second.contains("function_call_output"));
+    assert!(second.contains("use the staging database"));
+}
+
+/// A steer that is still pending when the turn ends, here because the tool
+/// call limit stopped it, is answered in a turn of its own instead of being
+/// dropped.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn steer_pending_when_the_turn_ends_starts_a_new_turn() {
+    skip_if_no_network!();
+
+    let args = json!({
+        "command": ["sleep", "1"],
+        "timeout_ms": 5_000,
+    })
+    .to_string();
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "stopped after sleep"),
+                ev_completed("resp-2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "using staging"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    let codex = test_codex()
+        .with_config(|config| {
+            config.turn_limits.max_tool_calls_per_turn = Some(1);
+        })
+        .build(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "migrate the database".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ExecCommandBegin(_))).await;
+    codex
+        .submit(Op::Steer {
+            items: vec![InputItem::Text {
+                text: "use the staging database".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let complete = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!();
+    };
+    assert_eq!(
+        Some("using staging".to_string()),
+        complete.last_agent_message
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(3, requests.len());
+    let summary = String::from_utf8_lossy(&requests[1].body).to_string();
+    assert!(!summary.contains("use the staging database"));
+    let steered = String::from_utf8_lossy(&requests[2].body).to_string();
+    assert!(steered.contains("use the staging database"));
+}
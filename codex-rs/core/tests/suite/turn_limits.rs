@@ -0,0 +1,118 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use serde_json::json;
+
+/// When `max_tool_calls_per_turn` is reached, the task stops calling tools,
+/// even within one response, asks the model for a summary without offering
+/// any tools, and reports
+/// `TurnAborted` with `LimitReached` before completing with that summary.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn tool_call_limit_ends_turn_with_summary() {
+    skip_if_no_network!();
+
+    let args = json!({
+        "command": ["echo", "hello"],
+        "timeout_ms": 5_000,
+    })
+    .to_string();
+
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_function_call("call-2", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "stopped after echo"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let codex = test_codex()
+        .with_config(|config| {
+            config.turn_limits.max_tool_calls_per_turn = Some(1);
+        })
+        .build(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "say hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let aborted = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+    let EventMsg::TurnAborted(aborted) = aborted else {
+        unreachable!();
+    };
+    assert_eq!(TurnAbortReason::LimitReached, aborted.reason);
+
+    let complete = wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    let EventMsg::TaskComplete(complete) = complete else {
+        unreachable!();
+    };
+    assert_eq!(
+        Some("stopped after echo".to_string()),
+        complete.last_agent_message
+    );
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(2, requests.len());
+    let summary_request = requests[1].body_json::<serde_json::Value>().unwrap();
+    assert_eq!(json!([]), summary_request["tools"]);
+    let outputs: Vec<(&str, &str)> = summary_request["input"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["type"] == "function_call_output")
+        .map(|item| {
+            (
+                item["call_id"].as_str().unwrap_or_default(),
+                item["output"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect();
+    assert_eq!(2, outputs.len(), "unexpected outputs: {outputs:?}");
+    assert_eq!("call-1", outputs[0].0);
+    assert!(!outputs[0].1.contains("Tool call not run"));
+    assert_eq!(
+        (
+            "call-2",
+            "Tool call not run: the tool call limit (1) was reached."
+        ),
+        outputs[1]
+    );
+    let summary_prompt = summary_request["input"]
+        .as_array()
+        .and_then(|items| items.last())
+        .and_then(|item| item["content"][0]["text"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    assert!(
+        summary_prompt.contains("the tool call limit (1) was reached"),
+        "unexpected summary prompt: {summary_prompt}"
+    );
+}
@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+
+use anyhow::Context;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use serde::Deserialize;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tracing::warn;
+
+/// Forwards approval requests to something outside the process so that
+/// `codex exec` can run with an approval policy other than `never`.
+///
+/// Each `ExecApprovalRequest` / `ApplyPatchApprovalRequest` event is
+/// serialized as a single line of JSON (the same shape as `--json` output)
+/// and handed to the bridge. The bridge answers with a single JSON object of
+/// the form `{"decision": "approved"}`, where `decision` is any
+/// [`ReviewDecision`] value. Anything else is treated as a denial.
+pub(crate) enum ApprovalBridge {
+    /// Run the program once per request, writing the request to its stdin
+    /// and reading the reply from its stdout.
+    Command(Vec<String>),
+    /// Connect to a Unix domain socket once per request, write the request as
+    /// one line and read a one line reply.
+    Socket(PathBuf),
+}
+
+#[derive(Debug, Deserialize)]
+struct ApprovalReply {
+    decision: ReviewDecision,
+}
+
+impl ApprovalBridge {
+    pub(crate) fn from_cli(
+        command: Option<String>,
+        socket: Option<PathBuf>,
+    ) -> anyhow::Result<Option<Self>> {
+        match (command, socket) {
+            (Some(command), _) => {
+                let argv = shlex::split(&command)
+                    .filter(|argv| !argv.is_empty())
+                    .with_context(|| format!("invalid --approval-command: {command}"))?;
+                Ok(Some(Self::Command(argv)))
+            }
+            (None, Some(path)) => {
+                if cfg!(not(unix)) {
+                    anyhow::bail!("--approval-socket is only supported on Unix platforms");
+                }
+                Ok(Some(Self::Socket(path)))
+            }
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// Returns the `Op` answering `event` if it is an approval request, or
+    /// `None` for every other event.
+    pub(crate) async fn resolve(&self, event: &Event) -> Option<Op> {
+        let is_exec = match &event.msg {
+            EventMsg::ExecApprovalRequest(_) => true,
+            EventMsg::ApplyPatchApprovalRequest(_) => false,
+            _ => return None,
+        };
+
+        let decision = match self.ask(event).await {
+            Ok(decision) => decision,
+            Err(err) => {
+                warn!(?err, id = %event.id, "approval bridge failed; denying request");
+                eprintln!("Approval bridge failed, denying request: {err:#}");
+                ReviewDecision::Denied
+            }
+        };
+
+        let id = event.id.clone();
+        Some(if is_exec {
+            Op::ExecApproval { id, decision }
+        } else {
+            Op::PatchApproval { id, decision }
+        })
+    }
+
+    async fn ask(&self, event: &Event) -> anyhow::Result<ReviewDecision> {
+        let mut request = serde_json::to_string(event)?;
+        request.push('\n');
+
+        let reply = match self {
+            Self::Command(argv) => run_command(argv, &request).await?,
+            Self::Socket(path) => query_socket(path, &request).await?,
+        };
+
+        let reply: ApprovalReply = serde_json::from_str(reply.trim())
+            .with_context(|| format!("invalid approval reply: {}", reply.trim()))?;
+        Ok(reply.decision)
+    }
+}
+
+async fn run_command(argv: &[String], request: &str) -> anyhow::Result<String> {
+    let (program, args) = argv
+        .split_first()
+        .context("approval command must not be empty")?;
+    let mut child = tokio::process::Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to spawn approval command {program}"))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(request.as_bytes()).await?;
+        // Dropping stdin closes the pipe so the program sees EOF.
+    }
+
+    let mut stdout = String::new();
+    if let Some(mut out) = child.stdout.take() {
+        out.read_to_string(&mut stdout).await?;
+    }
+
+    let status = child.wait().await?;
+    if !status.success() {
+        anyhow::bail!("approval command exited with {status}");
+    }
+    Ok(stdout)
+}
+
+#[cfg(unix)]
+async fn query_socket(path: &std::path::Path, request: &str) -> anyhow::Result<String> {
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::BufReader;
+
+    let stream = tokio::net::UnixStream::connect(path)
+        .await
+        .with_context(|| format!("failed to connect to approval socket {}", path.display()))?;
+    let (read_half, mut write_half) = stream.into_split();
+    write_half.write_all(request.as_bytes()).await?;
+    write_half.flush().await?;
+
+    let mut reply = String::new();
+    BufReader::new(read_half).read_line(&mut reply).await?;
+    if reply.trim().is_empty() {
+        anyhow::bail!("approval socket closed without a reply");
+    }
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+async fn query_socket(path: &std::path::Path, _request: &str) -> anyhow::Result<String> {
+    anyhow::bail!(
+        "approval socket {} is not supported on this platform",
+        path.display()
+    )
+}
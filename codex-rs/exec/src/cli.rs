@@ -44,6 +44,25 @@ pub struct Cli {
     )]
     pub dangerously_bypass_approvals_and_sandbox: bool,
 
+    /// Forward approval requests as JSON to CMD and read the decision from its
+    /// stdout. Enables the configured approval policy instead of `never`.
+    #[arg(
+        long = "approval-command",
+        value_name = "CMD",
+        conflicts_with_all = ["approval_socket", "dangerously_bypass_approvals_and_sandbox"]
+    )]
+    pub approval_command: Option<String>,
+
+    /// Forward approval requests as JSON to the Unix socket at PATH and read
+    /// the decision from the reply. Enables the configured approval policy
+    /// instead of `never`.
+    #[arg(
+        long = "approval-socket",
+        value_name = "PATH",
+        conflicts_with = "dangerously_bypass_approvals_and_sandbox"
+    )]
+    pub approval_socket: Option<PathBuf>,
+
     /// Tell the agent to use the specified directory as its working root.
     #[clap(long = "cd", short = 'C', value_name = "DIR")]
     pub cwd: Option<PathBuf>,
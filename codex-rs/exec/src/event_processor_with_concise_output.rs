@@ -22,6 +22,8 @@ use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenCountEvent;
 use codex_core::protocol::TokenUsageInfo;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use owo_colors::OwoColorize;
 use owo_colors::Style;
 use shlex::try_join;
@@ -360,8 +362,14 @@ impl EventProcessor for EventProcessorWithConciseOutput {
                 self.handle_plan_update(event);
             }
             EventMsg::GetHistoryEntryResponse(_) => {}
+            EventMsg::SearchHistoryResponse(_) => {}
             EventMsg::McpListToolsResponse(_) => {}
             EventMsg::ListCustomPromptsResponse(_) => {}
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::LimitReached,
+            }) => {
+                self.emit_status("Task stopped: turn limit reached", self.error_style);
+            }
             EventMsg::TurnAborted(_) => {
                 self.emit_status("Task aborted", self.error_style);
             }
@@ -370,6 +378,7 @@ impl EventProcessor for EventProcessorWithConciseOutput {
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
+            EventMsg::OverlayResolved(_) => {}
         }
 
         CodexStatus::Running
@@ -571,7 +571,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     }
                 }
             }
-            EventMsg::GetHistoryEntryResponse(_) => {
+            EventMsg::GetHistoryEntryResponse(_) | EventMsg::SearchHistoryResponse(_) => {
                 // Currently ignored in exec output.
             }
             EventMsg::McpListToolsResponse(_) => {
@@ -590,12 +590,16 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_println!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::LimitReached => {
+                    ts_println!(self, "task stopped: turn limit reached");
+                }
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationPath(_) => {}
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
+            EventMsg::OverlayResolved(_) => {}
         }
         CodexStatus::Running
     }
@@ -1,3 +1,4 @@
+mod approval_bridge;
 mod cli;
 mod event_processor;
 mod event_processor_with_concise_output;
@@ -41,6 +42,7 @@ use tracing::info;
 use tracing::warn;
 use tracing_subscriber::EnvFilter;
 
+use crate::approval_bridge::ApprovalBridge;
 use crate::cli::Command as ExecCommand;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
@@ -57,6 +59,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         config_profile,
         full_auto,
         dangerously_bypass_approvals_and_sandbox,
+        approval_command,
+        approval_socket,
         cwd,
         skip_git_repo_check,
         color,
@@ -117,6 +121,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     let output_schema = load_output_schema(output_schema_path);
+    let approval_bridge = ApprovalBridge::from_cli(approval_command, approval_socket)?;
 
     let session_store_dir = match session_store {
         Some(dir) => dir,
@@ -177,8 +182,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         review_model: None,
         config_profile,
         // This CLI is intended to be headless and has no affordances for asking
-        // the user for approval.
-        approval_policy: Some(AskForApproval::Never),
+        // the user for approval, unless requests can be forwarded to a bridge.
+        approval_policy: approval_bridge.is_none().then_some(AskForApproval::Never),
         sandbox_mode,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider,
@@ -413,7 +418,14 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         if matches!(event.msg, EventMsg::Error(_)) {
             error_seen = true;
         }
+        let approval = match &approval_bridge {
+            Some(bridge) => bridge.resolve(&event).await,
+            None => None,
+        };
         let shutdown: CodexStatus = event_processor.process_event(event);
+        if let Some(op) = approval {
+            conversation.submit(op).await?;
+        }
         match shutdown {
             CodexStatus::Running => continue,
             CodexStatus::InitiateShutdown => {
@@ -0,0 +1,101 @@
+#![cfg(unix)]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+
+/// Writes an approval command that records the request next to itself and
+/// replies with `decision`.
+fn write_approval_script(dir: &Path, decision: &str) -> std::path::PathBuf {
+    let script = dir.join("approve.sh");
+    let request = dir.join("request.json");
+    std::fs::write(
+        &script,
+        format!(
+            "#!/bin/sh\ncat > '{}'\necho '{{\"decision\":\"{decision}\"}}'\n",
+            request.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+    script
+}
+
+async fn run_exec_with_decision(decision: &str) -> anyhow::Result<(bool, Value)> {
+    let test = test_codex_exec();
+    let script = write_approval_script(test.home_path(), decision);
+
+    let args = serde_json::json!({
+        "command": ["touch", "bridged.txt"],
+        "timeout_ms": 5_000,
+    })
+    .to_string();
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--sandbox")
+        .arg("danger-full-access")
+        .arg("-c")
+        .arg("approval_policy=\"untrusted\"")
+        .arg("--approval-command")
+        .arg(script.display().to_string())
+        .arg("touch a file")
+        .assert()
+        .success();
+
+    let request = std::fs::read_to_string(test.home_path().join("request.json"))?;
+    let request: Value = serde_json::from_str(&request)?;
+    let created = test.cwd_path().join("bridged.txt").exists();
+    Ok((created, request))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn approval_command_can_approve_exec_request() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (created, request) = run_exec_with_decision("approved").await?;
+
+    assert!(created, "approved command should have run");
+    assert_eq!(request["msg"]["type"], "exec_approval_request");
+    assert_eq!(
+        request["msg"]["command"],
+        serde_json::json!(["touch", "bridged.txt"])
+    );
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn approval_command_can_deny_exec_request() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let (created, request) = run_exec_with_decision("denied").await?;
+
+    assert!(!created, "denied command must not run");
+    assert_eq!(request["msg"]["type"], "exec_approval_request");
+    Ok(())
+}
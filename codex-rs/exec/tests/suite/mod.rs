@@ -1,5 +1,6 @@
 // Aggregates all former standalone integration tests as modules.
 mod apply_patch;
+mod approval_bridge;
 mod output_schema;
 mod resume;
 mod sandbox;
@@ -0,0 +1,162 @@
+//! Replay commands recorded in Codex rollout files against a policy, to see
+//! how a policy change would affect commands the agent actually ran.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::Error;
+use crate::ExecCall;
+use crate::MatchedScript;
+use crate::Policy;
+
+/// Outcome of checking one recorded command against a policy.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CorpusVerdict {
+    /// Matched a rule and writes no files: safe to run without asking.
+    AutoApproved,
+    /// Matched a rule, but the caller must decide whether the files it writes
+    /// are acceptable.
+    WritesFiles,
+    Forbidden,
+    /// No rule matched.
+    Unmatched,
+    /// A shell script using syntax the policy cannot check.
+    Unparsed,
+}
+
+impl CorpusVerdict {
+    pub fn label(self) -> &'static str {
+        match self {
+            CorpusVerdict::AutoApproved => "auto-approved",
+            CorpusVerdict::WritesFiles => "writes files",
+            CorpusVerdict::Forbidden => "forbidden",
+            CorpusVerdict::Unmatched => "unmatched",
+            CorpusVerdict::Unparsed => "unparsed",
+        }
+    }
+}
+
+/// How many times each distinct command received each verdict.
+#[derive(Debug, Default, Serialize)]
+pub struct CorpusReport {
+    pub total: usize,
+    pub commands: BTreeMap<CorpusVerdict, BTreeMap<String, usize>>,
+}
+
+impl CorpusReport {
+    pub fn count(&self, verdict: CorpusVerdict) -> usize {
+        self.commands
+            .get(&verdict)
+            .map_or(0, |commands| commands.values().sum())
+    }
+}
+
+/// Collect the shell commands recorded in `paths`. Directories are searched
+/// recursively for `.jsonl` rollout files.
+pub fn load_corpus(paths: &[PathBuf]) -> anyhow::Result<Vec<Vec<String>>> {
+    let mut commands = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            for entry in walkdir::WalkDir::new(path).sort_by_file_name() {
+                let entry = entry?;
+                if entry.file_type().is_file()
+                    && entry.path().extension().is_some_and(|ext| ext == "jsonl")
+                {
+                    commands.extend(commands_from_rollout(entry.path())?);
+                }
+            }
+        } else {
+            commands.extend(commands_from_rollout(path)?);
+        }
+    }
+    Ok(commands)
+}
+
+fn commands_from_rollout(path: &Path) -> anyhow::Result<Vec<Vec<String>>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::format_err!("failed to read {}: {e}", path.display()))?;
+    Ok(contents
+        .lines()
+        .filter_map(command_from_rollout_line)
+        .collect())
+}
+
+/// The argv of a shell tool call recorded on one rollout line, if any.
+pub fn command_from_rollout_line(line: &str) -> Option<Vec<String>> {
+    let value: Value = serde_json::from_str(line).ok()?;
+    if value.get("type")?.as_str()? != "response_item" {
+        return None;
+    }
+    let payload = value.get("payload")?;
+    let command = match payload.get("type")?.as_str()? {
+        "function_call" => {
+            let name = payload.get("name")?.as_str()?;
+            if name != "shell" && name != "container.exec" {
+                return None;
+            }
+            let arguments: Value =
+                serde_json::from_str(payload.get("arguments")?.as_str()?).ok()?;
+            arguments.get("command")?.clone()
+        }
+        "local_shell_call" => payload.get("action")?.get("command")?.clone(),
+        _ => return None,
+    };
+    serde_json::from_value(command).ok()
+}
+
+/// The script run by a `bash -lc <script>` style command, if `command` is one.
+pub fn shell_script(command: &[String]) -> Option<&str> {
+    match command {
+        [shell, flag, script]
+            if matches!(shell.as_str(), "bash" | "sh" | "zsh")
+                && matches!(flag.as_str(), "-c" | "-lc") =>
+        {
+            Some(script)
+        }
+        _ => None,
+    }
+}
+
+pub fn classify(policy: &Policy, command: &[String]) -> CorpusVerdict {
+    let result = match (shell_script(command), command.split_first()) {
+        (Some(script), _) => policy.check_script(script),
+        (None, Some((program, args))) => policy
+            .check(&ExecCall {
+                program: program.clone(),
+                args: args.to_vec(),
+            })
+            .map(MatchedScript::from),
+        (None, None) => return CorpusVerdict::Unparsed,
+    };
+    match result {
+        Ok(matched) if matched.might_write_files() => CorpusVerdict::WritesFiles,
+        Ok(MatchedScript::Match { .. }) => CorpusVerdict::AutoApproved,
+        Ok(MatchedScript::Forbidden { .. }) => CorpusVerdict::Forbidden,
+        Err(Error::ShellScriptParseFailed { .. } | Error::UnsupportedShellSyntax { .. }) => {
+            CorpusVerdict::Unparsed
+        }
+        Err(_) => CorpusVerdict::Unmatched,
+    }
+}
+
+pub fn replay_corpus(policy: &Policy, commands: &[Vec<String>]) -> CorpusReport {
+    let mut report = CorpusReport::default();
+    for command in commands {
+        let verdict = classify(policy, command);
+        let display = shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" "));
+        *report
+            .commands
+            .entry(verdict)
+            .or_default()
+            .entry(display)
+            .or_default() += 1;
+        report.total += 1;
+    }
+    report
+}
@@ -29,6 +29,16 @@ pub enum Error {
         program: String,
         option: String,
     },
+    FlagDoesNotTakeValue {
+        program: String,
+        flag: String,
+        value: String,
+    },
+    SubcommandDidNotMatch {
+        program: String,
+        expected: String,
+        actual: Option<String>,
+    },
     UnexpectedArguments {
         program: String,
         args: Vec<PositionalArg>,
@@ -88,6 +98,13 @@ pub enum Error {
     CannotCheckRelativePath {
         file: PathBuf,
     },
+    ShellScriptParseFailed {
+        script: String,
+    },
+    UnsupportedShellSyntax {
+        kind: String,
+        text: String,
+    },
     CannotCanonicalizePath {
         file: String,
         #[serde_as(as = "DisplayFromStr")]
@@ -9,7 +9,9 @@ use crate::Error::CannotCheckRelativePath;
 use crate::Error::ReadablePathNotInReadableFolders;
 use crate::Error::WriteablePathNotInWriteableFolders;
 use crate::ExecCall;
+use crate::MatchedCommand;
 use crate::MatchedExec;
+use crate::MatchedScript;
 use crate::Policy;
 use crate::Result;
 use crate::ValidExec;
@@ -39,6 +41,10 @@ impl ExecvChecker {
         self.execv_policy.check(exec_call)
     }
 
+    pub fn match_script(&self, script: &str) -> Result<MatchedScript> {
+        self.execv_policy.check_script(script)
+    }
+
     /// The caller is responsible for ensuring readable_folders and
     /// writeable_folders are in canonical form.
     pub fn check(
@@ -59,31 +65,7 @@ impl ExecvChecker {
                     .map(|opt| (opt.r#type, opt.value)),
             )
         {
-            match arg_type {
-                ArgType::ReadableFile => {
-                    let readable_file = ensure_absolute_path(&value, cwd)?;
-                    check_file_in_folders!(
-                        readable_file,
-                        readable_folders,
-                        ReadablePathNotInReadableFolders
-                    );
-                }
-                ArgType::WriteableFile => {
-                    let writeable_file = ensure_absolute_path(&value, cwd)?;
-                    check_file_in_folders!(
-                        writeable_file,
-                        writeable_folders,
-                        WriteablePathNotInWriteableFolders
-                    );
-                }
-                ArgType::OpaqueNonFile
-                | ArgType::Unknown
-                | ArgType::PositiveInteger
-                | ArgType::SedCommand
-                | ArgType::Literal(_) => {
-                    continue;
-                }
-            }
+            check_file(&arg_type, &value, cwd, readable_folders, writeable_folders)?;
         }
 
         let mut program = valid_exec.program.to_string();
@@ -96,6 +78,76 @@ impl ExecvChecker {
 
         Ok(program)
     }
+
+    /// Like `check()`, for one command of a script: the files it is
+    /// redirected to or from must be in the allowed folders, too, and
+    /// relative paths must be allowed in every directory the command may run
+    /// in.
+    pub fn check_command(
+        &self,
+        command: MatchedCommand,
+        cwd: &Option<OsString>,
+        readable_folders: &[PathBuf],
+        writeable_folders: &[PathBuf],
+    ) -> Result<String> {
+        let mut program = command.exec.program.clone();
+        for dir in &command.dirs {
+            let dir_cwd = if dir.as_os_str().is_empty() {
+                cwd.clone()
+            } else {
+                Some(ensure_absolute_path(&dir.to_string_lossy(), cwd)?.into_os_string())
+            };
+            for redirect in &command.redirects {
+                check_file(
+                    &redirect.r#type,
+                    &redirect.value,
+                    &dir_cwd,
+                    readable_folders,
+                    writeable_folders,
+                )?;
+            }
+            program = self.check(
+                command.exec.clone(),
+                &dir_cwd,
+                readable_folders,
+                writeable_folders,
+            )?;
+        }
+        Ok(program)
+    }
+}
+
+fn check_file(
+    arg_type: &ArgType,
+    value: &str,
+    cwd: &Option<OsString>,
+    readable_folders: &[PathBuf],
+    writeable_folders: &[PathBuf],
+) -> Result<()> {
+    match arg_type {
+        ArgType::ReadableFile => {
+            let readable_file = ensure_absolute_path(value, cwd)?;
+            check_file_in_folders!(
+                readable_file,
+                readable_folders,
+                ReadablePathNotInReadableFolders
+            );
+        }
+        ArgType::WriteableFile => {
+            let writeable_file = ensure_absolute_path(value, cwd)?;
+            check_file_in_folders!(
+                writeable_file,
+                writeable_folders,
+                WriteablePathNotInWriteableFolders
+            );
+        }
+        ArgType::OpaqueNonFile
+        | ArgType::Unknown
+        | ArgType::PositiveInteger
+        | ArgType::SedCommand
+        | ArgType::Literal(_) => {}
+    }
+    Ok(())
 }
 
 fn ensure_absolute_path(path: &str, cwd: &Option<OsString>) -> Result<PathBuf> {
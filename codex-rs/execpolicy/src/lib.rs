@@ -6,6 +6,7 @@ extern crate starlark;
 mod arg_matcher;
 mod arg_resolver;
 mod arg_type;
+mod corpus;
 mod error;
 mod exec_call;
 mod execv_checker;
@@ -13,12 +14,20 @@ mod opt;
 mod policy;
 mod policy_parser;
 mod program;
+mod script;
 mod sed_command;
 mod valid_exec;
 
 pub use arg_matcher::ArgMatcher;
 pub use arg_resolver::PositionalArg;
 pub use arg_type::ArgType;
+pub use corpus::CorpusReport;
+pub use corpus::CorpusVerdict;
+pub use corpus::classify;
+pub use corpus::command_from_rollout_line;
+pub use corpus::load_corpus;
+pub use corpus::replay_corpus;
+pub use corpus::shell_script;
 pub use error::Error;
 pub use error::Result;
 pub use exec_call::ExecCall;
@@ -31,6 +40,11 @@ pub use program::MatchedExec;
 pub use program::NegativeExamplePassedCheck;
 pub use program::PositiveExampleFailedCheck;
 pub use program::ProgramSpec;
+pub use script::MatchedCommand;
+pub use script::MatchedScript;
+pub use script::Redirect;
+pub use script::ScriptCommand;
+pub use script::parse_script;
 pub use sed_command::parse_sed_command;
 pub use valid_exec::MatchedArg;
 pub use valid_exec::MatchedFlag;
@@ -1,12 +1,16 @@
 use anyhow::Result;
 use clap::Parser;
 use clap::Subcommand;
+use codex_execpolicy::CorpusReport;
+use codex_execpolicy::CorpusVerdict;
 use codex_execpolicy::ExecCall;
 use codex_execpolicy::MatchedExec;
 use codex_execpolicy::Policy;
 use codex_execpolicy::PolicyParser;
 use codex_execpolicy::ValidExec;
 use codex_execpolicy::get_default_policy;
+use codex_execpolicy::load_corpus;
+use codex_execpolicy::replay_corpus;
 use serde::Deserialize;
 use serde::Serialize;
 use serde::de;
@@ -49,6 +53,22 @@ pub enum Command {
         #[serde(deserialize_with = "deserialize_from_json")]
         exec: ExecArg,
     },
+
+    /// Runs the `should_match`/`should_not_match` examples in each policy
+    /// file and, with `--corpus`, replays commands from rollout files.
+    Test {
+        /// Policy files to test. Defaults to the built-in policy.
+        policies: Vec<PathBuf>,
+
+        /// Rollout `.jsonl` files, or directories to search for them (such
+        /// as `~/.codex/sessions`).
+        #[arg(long, value_name = "PATH")]
+        corpus: Vec<PathBuf>,
+
+        /// How many of the most frequent commands to list per outcome.
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -63,6 +83,21 @@ fn main() -> Result<()> {
     env_logger::init();
 
     let args = Args::parse();
+    if let Command::Test {
+        policies,
+        corpus,
+        top,
+    } = args.command
+    {
+        let policies = if policies.is_empty() {
+            args.policy.into_iter().collect()
+        } else {
+            policies
+        };
+        let passed = run_policy_tests(&policies, &corpus, top)?;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+
     let policy = match args.policy {
         Some(policy) => {
             let policy_source = policy.to_string_lossy().to_string();
@@ -86,6 +121,7 @@ fn main() -> Result<()> {
             }
         },
         Command::CheckJson { exec } => exec,
+        Command::Test { .. } => unreachable!("handled above"),
     };
 
     let (output, exit_code) = check_command(&policy, exec, args.require_safe);
@@ -94,6 +130,110 @@ fn main() -> Result<()> {
     std::process::exit(exit_code);
 }
 
+/// Returns whether every example in every policy behaved as declared.
+fn run_policy_tests(policies: &[PathBuf], corpus: &[PathBuf], top: usize) -> Result<bool> {
+    let commands = load_corpus(corpus)?;
+    let mut loaded = Vec::new();
+    if policies.is_empty() {
+        loaded.push((
+            "default.policy".to_string(),
+            get_default_policy().map_err(StarlarkError::into_anyhow),
+        ));
+    }
+    for path in policies {
+        let policy_source = path.to_string_lossy().to_string();
+        let policy = std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|unparsed| {
+                PolicyParser::new(&policy_source, &unparsed)
+                    .parse()
+                    .map_err(StarlarkError::into_anyhow)
+            });
+        loaded.push((policy_source, policy));
+    }
+
+    let mut passed = true;
+    for (name, policy) in loaded {
+        println!("== {name}");
+        let policy = match policy {
+            Ok(policy) => policy,
+            Err(err) => {
+                println!("FAIL could not load policy: {err:#}");
+                passed = false;
+                continue;
+            }
+        };
+
+        let good_failures = policy.check_each_good_list_individually();
+        let bad_failures = policy.check_each_bad_list_individually();
+        for failure in &good_failures {
+            let error = serde_json::to_string(&failure.error)?;
+            println!(
+                "FAIL {}: `{}` should match: {error}",
+                failure.source_location.as_deref().unwrap_or(&name),
+                display_command(&failure.program, &failure.args),
+            );
+        }
+        for failure in &bad_failures {
+            println!(
+                "FAIL {}: `{}` should not match",
+                failure.source_location.as_deref().unwrap_or(&name),
+                display_command(&failure.program, &failure.args),
+            );
+        }
+        if good_failures.is_empty() && bad_failures.is_empty() {
+            println!("ok: all examples behave as declared");
+        } else {
+            passed = false;
+        }
+
+        if !corpus.is_empty() {
+            print_corpus_report(&replay_corpus(&policy, &commands), top);
+        }
+        println!();
+    }
+    Ok(passed)
+}
+
+fn display_command(program: &str, args: &[String]) -> String {
+    ExecCall {
+        program: program.to_string(),
+        args: args.to_vec(),
+    }
+    .to_string()
+}
+
+fn print_corpus_report(report: &CorpusReport, top: usize) {
+    println!("corpus: {} commands", report.total);
+    let verdicts = [
+        CorpusVerdict::AutoApproved,
+        CorpusVerdict::WritesFiles,
+        CorpusVerdict::Forbidden,
+        CorpusVerdict::Unmatched,
+        CorpusVerdict::Unparsed,
+    ];
+    for verdict in verdicts {
+        let count = report.count(verdict);
+        let percent = if report.total == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / report.total as f64
+        };
+        println!("  {:<14} {count:>7} ({percent:.1}%)", verdict.label());
+    }
+    for verdict in verdicts {
+        let Some(commands) = report.commands.get(&verdict) else {
+            continue;
+        };
+        let mut commands: Vec<(&String, &usize)> = commands.iter().collect();
+        commands.sort_by(|(a_cmd, a_n), (b_cmd, b_n)| b_n.cmp(a_n).then(a_cmd.cmp(b_cmd)));
+        println!("most frequent {} commands:", verdict.label());
+        for (command, count) in commands.into_iter().take(top) {
+            println!("  {count:>7}  {command}");
+        }
+    }
+}
+
 fn check_command(
     policy: &Policy,
     ExecArg { program, args }: ExecArg,
@@ -1,16 +1,29 @@
+use std::path::PathBuf;
+
 use multimap::MultiMap;
 use regex_lite::Error as RegexError;
 use regex_lite::Regex;
 
+use crate::ArgType;
 use crate::ExecCall;
 use crate::Forbidden;
+use crate::MatchedArg;
+use crate::MatchedCommand;
 use crate::MatchedExec;
+use crate::MatchedScript;
 use crate::NegativeExamplePassedCheck;
 use crate::ProgramSpec;
+use crate::ValidExec;
 use crate::error::Error;
 use crate::error::Result;
 use crate::policy_parser::ForbiddenProgramRegex;
 use crate::program::PositiveExampleFailedCheck;
+use crate::script::ScriptCommand;
+use crate::script::parse_script;
+
+/// Most directories a command of a script may run in; each `cd` can double
+/// them.
+const MAX_SCRIPT_DIRS: usize = 8;
 
 pub struct Policy {
     programs: MultiMap<String, ProgramSpec>,
@@ -77,7 +90,17 @@ impl Policy {
                 match spec.check(exec_call) {
                     Ok(matched_exec) => return Ok(matched_exec),
                     Err(err) => {
-                        last_err = Err(err);
+                        // A spec for a different subcommand says little about
+                        // why this call was rejected, so prefer other errors.
+                        let keep_previous = matches!(err, Error::SubcommandDidNotMatch { .. })
+                            && !matches!(
+                                last_err,
+                                Err(Error::NoSpecForProgram { .. })
+                                    | Err(Error::SubcommandDidNotMatch { .. })
+                            );
+                        if !keep_previous {
+                            last_err = Err(err);
+                        }
                     }
                 }
             }
@@ -85,6 +108,90 @@ impl Policy {
         last_err
     }
 
+    /// Checks each command of a shell script, such as the argument to
+    /// `bash -lc`. The script matches only if every command does, and is
+    /// forbidden if any command is. `cd <dir>` is always accepted and
+    /// recorded in the directories of the commands after it.
+    pub fn check_script(&self, script: &str) -> Result<MatchedScript> {
+        let mut commands = Vec::new();
+        let mut first_err = None;
+        let mut dirs = vec![PathBuf::new()];
+        for ScriptCommand {
+            exec_call,
+            redirects,
+        } in parse_script(script)?
+        {
+            if let Some(regex) = &self.forbidden_substrings_pattern
+                && let Some(redirect) = redirects.iter().find(|r| regex.is_match(&r.value))
+            {
+                return Ok(MatchedScript::Forbidden {
+                    reason: format!(
+                        "redirect `{}{}` contains forbidden substring",
+                        redirect.operator, redirect.value
+                    ),
+                    cause: Forbidden::Arg {
+                        arg: redirect.value.clone(),
+                        exec_call,
+                    },
+                });
+            }
+            if exec_call.program == "cd" {
+                let dir = cd_target(&exec_call)?;
+                if let Some(regex) = &self.forbidden_substrings_pattern
+                    && regex.is_match(dir)
+                {
+                    return Ok(MatchedScript::Forbidden {
+                        reason: format!("arg `{dir}` contains forbidden substring"),
+                        cause: Forbidden::Arg {
+                            arg: dir.to_string(),
+                            exec_call,
+                        },
+                    });
+                }
+                let exec = ValidExec::new(
+                    "cd",
+                    vec![MatchedArg::new(0, ArgType::ReadableFile, dir)?],
+                    &[],
+                );
+                let mut next_dirs = dirs.clone();
+                for joined in dirs.iter().map(|base| base.join(dir)) {
+                    if !next_dirs.contains(&joined) {
+                        next_dirs.push(joined);
+                    }
+                }
+                if next_dirs.len() > MAX_SCRIPT_DIRS {
+                    return Err(Error::UnsupportedShellSyntax {
+                        kind: "cd".to_string(),
+                        text: "too many directory changes".to_string(),
+                    });
+                }
+                commands.push(MatchedCommand {
+                    exec,
+                    redirects,
+                    dirs: std::mem::replace(&mut dirs, next_dirs),
+                });
+                continue;
+            }
+            match self.check(&exec_call) {
+                Ok(MatchedExec::Match { exec }) => commands.push(MatchedCommand {
+                    exec,
+                    redirects,
+                    dirs: dirs.clone(),
+                }),
+                Ok(MatchedExec::Forbidden { cause, reason }) => {
+                    return Ok(MatchedScript::Forbidden { cause, reason });
+                }
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(MatchedScript::Match { commands }),
+        }
+    }
+
     pub fn check_each_good_list_individually(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for (_program, spec) in self.programs.flat_iter() {
@@ -101,3 +208,15 @@ impl Policy {
         violations
     }
 }
+
+/// The directory `cd` changes to. Only `cd <dir>` is supported: without an
+/// argument or with `-` the new directory depends on the environment.
+fn cd_target(exec_call: &ExecCall) -> Result<&str> {
+    match exec_call.args.as_slice() {
+        [dir] if !dir.starts_with('-') => Ok(dir),
+        _ => Err(Error::UnsupportedShellSyntax {
+            kind: "cd".to_string(),
+            text: exec_call.to_string(),
+        }),
+    }
+}
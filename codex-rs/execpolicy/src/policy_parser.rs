@@ -118,6 +118,63 @@ impl PolicyBuilder {
     }
 }
 
+fn build_program_spec(
+    program: String,
+    subcommand: Option<String>,
+    system_path: Option<UnpackList<String>>,
+    option_bundling: Option<bool>,
+    combined_format: Option<bool>,
+    options: Option<UnpackList<Opt>>,
+    args: Option<UnpackList<ArgMatcher>>,
+    forbidden: Option<String>,
+    should_match: Option<UnpackList<UnpackList<String>>>,
+    should_not_match: Option<UnpackList<UnpackList<String>>>,
+) -> anyhow::Result<ProgramSpec> {
+    let option_bundling = option_bundling.unwrap_or(false);
+    let system_path = system_path.map_or_else(Vec::new, |v| v.items.to_vec());
+    let combined_format = combined_format.unwrap_or(false);
+    let options = options.map_or_else(Vec::new, |v| v.items.to_vec());
+    let args = args.map_or_else(Vec::new, |v| v.items.to_vec());
+
+    let mut allowed_options = HashMap::<String, Opt>::new();
+    for opt in options {
+        let name = opt.name().to_string();
+        if allowed_options
+            .insert(opt.name().to_string(), opt)
+            .is_some()
+        {
+            return Err(anyhow::format_err!("duplicate flag: {name}"));
+        }
+    }
+
+    Ok(ProgramSpec::new(
+        program,
+        subcommand,
+        system_path,
+        option_bundling,
+        combined_format,
+        allowed_options,
+        args,
+        forbidden,
+        should_match
+            .map_or_else(Vec::new, |v| v.items.to_vec())
+            .into_iter()
+            .map(|v| v.items.to_vec())
+            .collect(),
+        should_not_match
+            .map_or_else(Vec::new, |v| v.items.to_vec())
+            .into_iter()
+            .map(|v| v.items.to_vec())
+            .collect(),
+    ))
+}
+
+/// `file:line` of the rule being evaluated.
+fn source_location(eval: &Evaluator) -> Option<String> {
+    eval.call_stack_top_location()
+        .map(|span| format!("{}:{}", span.filename(), span.resolve_span().begin.line + 1))
+}
+
 #[starlark_module]
 fn policy_builtins(builder: &mut GlobalsBuilder) {
     fn define_program<'v>(
@@ -132,42 +189,57 @@ fn policy_builtins(builder: &mut GlobalsBuilder) {
         should_not_match: Option<UnpackList<UnpackList<String>>>,
         eval: &mut Evaluator,
     ) -> anyhow::Result<NoneType> {
-        let option_bundling = option_bundling.unwrap_or(false);
-        let system_path = system_path.map_or_else(Vec::new, |v| v.items.to_vec());
-        let combined_format = combined_format.unwrap_or(false);
-        let options = options.map_or_else(Vec::new, |v| v.items.to_vec());
-        let args = args.map_or_else(Vec::new, |v| v.items.to_vec());
-
-        let mut allowed_options = HashMap::<String, Opt>::new();
-        for opt in options {
-            let name = opt.name().to_string();
-            if allowed_options
-                .insert(opt.name().to_string(), opt)
-                .is_some()
-            {
-                return Err(anyhow::format_err!("duplicate flag: {name}"));
-            }
-        }
+        let program_spec = build_program_spec(
+            program,
+            None,
+            system_path,
+            option_bundling,
+            combined_format,
+            options,
+            args,
+            forbidden,
+            should_match,
+            should_not_match,
+        )?
+        .with_source_location(source_location(eval));
 
-        let program_spec = ProgramSpec::new(
+        #[expect(clippy::unwrap_used)]
+        let policy_builder = eval
+            .extra
+            .as_ref()
+            .unwrap()
+            .downcast_ref::<PolicyBuilder>()
+            .unwrap();
+        policy_builder.add_program_spec(program_spec);
+        Ok(NoneType)
+    }
+
+    fn define_subcommand<'v>(
+        program: String,
+        subcommand: String,
+        system_path: Option<UnpackList<String>>,
+        option_bundling: Option<bool>,
+        combined_format: Option<bool>,
+        options: Option<UnpackList<Opt>>,
+        args: Option<UnpackList<ArgMatcher>>,
+        forbidden: Option<String>,
+        should_match: Option<UnpackList<UnpackList<String>>>,
+        should_not_match: Option<UnpackList<UnpackList<String>>>,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        let program_spec = build_program_spec(
             program,
+            Some(subcommand),
             system_path,
             option_bundling,
             combined_format,
-            allowed_options,
+            options,
             args,
             forbidden,
-            should_match
-                .map_or_else(Vec::new, |v| v.items.to_vec())
-                .into_iter()
-                .map(|v| v.items.to_vec())
-                .collect(),
-            should_not_match
-                .map_or_else(Vec::new, |v| v.items.to_vec())
-                .into_iter()
-                .map(|v| v.items.to_vec())
-                .collect(),
-        );
+            should_match,
+            should_not_match,
+        )?
+        .with_source_location(source_location(eval));
 
         #[expect(clippy::unwrap_used)]
         let policy_builder = eval
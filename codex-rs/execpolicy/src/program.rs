@@ -11,6 +11,7 @@ use crate::error::Error;
 use crate::error::Result;
 use crate::opt::Opt;
 use crate::opt::OptMeta;
+use crate::valid_exec::MatchedArg;
 use crate::valid_exec::MatchedFlag;
 use crate::valid_exec::MatchedOpt;
 use crate::valid_exec::ValidExec;
@@ -18,6 +19,9 @@ use crate::valid_exec::ValidExec;
 #[derive(Debug)]
 pub struct ProgramSpec {
     pub program: String,
+    /// If set, the first argument must be this subcommand (e.g. `status` for
+    /// `git status`), and the options and args below apply to what follows it.
+    pub subcommand: Option<String>,
     pub system_path: Vec<String>,
     pub option_bundling: bool,
     pub combined_format: bool,
@@ -27,11 +31,14 @@ pub struct ProgramSpec {
     required_options: HashSet<String>,
     should_match: Vec<Vec<String>>,
     should_not_match: Vec<Vec<String>>,
+    /// Where the spec was defined, as `file:line`, if known.
+    pub source_location: Option<String>,
 }
 
 impl ProgramSpec {
     pub fn new(
         program: String,
+        subcommand: Option<String>,
         system_path: Vec<String>,
         option_bundling: bool,
         combined_format: bool,
@@ -53,6 +60,7 @@ impl ProgramSpec {
             .collect();
         Self {
             program,
+            subcommand,
             system_path,
             option_bundling,
             combined_format,
@@ -62,8 +70,14 @@ impl ProgramSpec {
             required_options,
             should_match,
             should_not_match,
+            source_location: None,
         }
     }
+
+    pub fn with_source_location(mut self, source_location: Option<String>) -> Self {
+        self.source_location = source_location;
+        self
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Serialize)]
@@ -97,7 +111,30 @@ impl ProgramSpec {
         let mut matched_flags = Vec::<MatchedFlag>::new();
         let mut matched_opts = Vec::<MatchedOpt>::new();
 
-        for (index, arg) in exec_call.args.iter().enumerate() {
+        // The subcommand is reported as a literal arg, just as if it had been
+        // declared with `args=["subcommand", ...]`.
+        let mut subcommand_arg = None;
+        if let Some(subcommand) = &self.subcommand {
+            match exec_call.args.first() {
+                Some(arg) if arg == subcommand => {
+                    subcommand_arg = Some(MatchedArg::new(
+                        0,
+                        ArgType::Literal(subcommand.clone()),
+                        subcommand,
+                    )?);
+                }
+                actual => {
+                    return Err(Error::SubcommandDidNotMatch {
+                        program: self.program.clone(),
+                        expected: subcommand.clone(),
+                        actual: actual.cloned(),
+                    });
+                }
+            }
+        }
+        let first_index = usize::from(subcommand_arg.is_some());
+
+        for (index, arg) in exec_call.args.iter().enumerate().skip(first_index) {
             if let Some(expected) = expecting_option_value {
                 // If we are expecting an option value, then the next argument
                 // should be the value for the option.
@@ -118,25 +155,48 @@ impl ProgramSpec {
                     program: self.program.clone(),
                 });
             } else if arg.starts_with("-") {
-                match self.allowed_options.get(arg) {
-                    Some(opt) => {
-                        match &opt.meta {
-                            OptMeta::Flag => {
-                                matched_flags.push(MatchedFlag { name: arg.clone() });
-                                // A flag does not expect an argument: continue.
-                                continue;
-                            }
-                            OptMeta::Value(arg_type) => {
-                                expecting_option_value = Some((arg.clone(), arg_type.clone()));
-                                continue;
-                            }
+                if let Some(opt) = self.allowed_options.get(arg) {
+                    match &opt.meta {
+                        OptMeta::Flag => {
+                            matched_flags.push(MatchedFlag { name: arg.clone() });
+                            // A flag does not expect an argument: continue.
+                            continue;
+                        }
+                        OptMeta::Value(arg_type) => {
+                            expecting_option_value = Some((arg.clone(), arg_type.clone()));
+                            continue;
                         }
                     }
-                    None => {
-                        // It could be an --option=value style flag...
+                }
+
+                // It could be an --option=value style flag...
+                if self.combined_format
+                    && arg.starts_with("--")
+                    && let Some((name, value)) = arg.split_once('=')
+                    && let Some(opt) = self.allowed_options.get(name)
+                {
+                    match &opt.meta {
+                        OptMeta::Flag => {
+                            return Err(Error::FlagDoesNotTakeValue {
+                                program: self.program.clone(),
+                                flag: name.to_string(),
+                                value: value.to_string(),
+                            });
+                        }
+                        OptMeta::Value(arg_type) => {
+                            matched_opts.push(MatchedOpt::new(name, value, arg_type.clone())?);
+                            continue;
+                        }
                     }
                 }
 
+                // ...or a bundle of short options such as `-al`.
+                if self.option_bundling && !arg.starts_with("--") && arg.len() > 2 {
+                    expecting_option_value =
+                        self.match_bundled_options(arg, &mut matched_flags, &mut matched_opts)?;
+                    continue;
+                }
+
                 return Err(Error::UnknownOption {
                     program: self.program.clone(),
                     option: arg.clone(),
@@ -157,8 +217,11 @@ impl ProgramSpec {
             });
         }
 
-        let matched_args =
+        let mut matched_args =
             resolve_observed_args_with_patterns(&self.program, args, &self.arg_patterns)?;
+        if let Some(subcommand_arg) = subcommand_arg {
+            matched_args.insert(0, subcommand_arg);
+        }
 
         // Verify all required options are present.
         let matched_opt_names: HashSet<String> = matched_opts
@@ -194,20 +257,67 @@ impl ProgramSpec {
         }
     }
 
+    /// Match each character of a bundle such as `-al` as its own short
+    /// option. A short option that takes a value consumes the rest of the
+    /// bundle (`-n5`) or, if nothing is left, the next argument, in which case
+    /// the option is returned so the caller can expect its value.
+    fn match_bundled_options(
+        &self,
+        bundle: &str,
+        matched_flags: &mut Vec<MatchedFlag>,
+        matched_opts: &mut Vec<MatchedOpt>,
+    ) -> Result<Option<(String, ArgType)>> {
+        let letters = &bundle[1..];
+        for (offset, letter) in letters.char_indices() {
+            let name = format!("-{letter}");
+            let Some(opt) = self.allowed_options.get(&name) else {
+                return Err(Error::UnknownOption {
+                    program: self.program.clone(),
+                    option: name,
+                });
+            };
+            match &opt.meta {
+                OptMeta::Flag => matched_flags.push(MatchedFlag { name }),
+                OptMeta::Value(arg_type) => {
+                    let value = &letters[offset + letter.len_utf8()..];
+                    if value.is_empty() {
+                        return Ok(Some((name, arg_type.clone())));
+                    }
+                    matched_opts.push(MatchedOpt::new(&name, value, arg_type.clone())?);
+                    return Ok(None);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// The full argument list for a `should_match`/`should_not_match`
+    /// example, which omits the subcommand.
+    fn example_exec_call(&self, example: &[String]) -> ExecCall {
+        let args = self
+            .subcommand
+            .iter()
+            .cloned()
+            .chain(example.iter().cloned())
+            .collect();
+        ExecCall {
+            program: self.program.clone(),
+            args,
+        }
+    }
+
     pub fn verify_should_match_list(&self) -> Vec<PositiveExampleFailedCheck> {
         let mut violations = Vec::new();
         for good in &self.should_match {
-            let exec_call = ExecCall {
-                program: self.program.clone(),
-                args: good.clone(),
-            };
+            let exec_call = self.example_exec_call(good);
             match self.check(&exec_call) {
                 Ok(_) => {}
                 Err(error) => {
                     violations.push(PositiveExampleFailedCheck {
                         program: self.program.clone(),
-                        args: good.clone(),
+                        args: exec_call.args,
                         error,
+                        source_location: self.source_location.clone(),
                     });
                 }
             }
@@ -218,14 +328,12 @@ impl ProgramSpec {
     pub fn verify_should_not_match_list(&self) -> Vec<NegativeExamplePassedCheck> {
         let mut violations = Vec::new();
         for bad in &self.should_not_match {
-            let exec_call = ExecCall {
-                program: self.program.clone(),
-                args: bad.clone(),
-            };
+            let exec_call = self.example_exec_call(bad);
             if self.check(&exec_call).is_ok() {
                 violations.push(NegativeExamplePassedCheck {
                     program: self.program.clone(),
-                    args: bad.clone(),
+                    args: exec_call.args,
+                    source_location: self.source_location.clone(),
                 });
             }
         }
@@ -238,10 +346,14 @@ pub struct PositiveExampleFailedCheck {
     pub program: String,
     pub args: Vec<String>,
     pub error: Error,
+    /// Location of the rule whose `should_match` example failed.
+    pub source_location: Option<String>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct NegativeExamplePassedCheck {
     pub program: String,
     pub args: Vec<String>,
+    /// Location of the rule whose `should_not_match` example passed.
+    pub source_location: Option<String>,
 }
@@ -0,0 +1,147 @@
+//! Shell scripts, such as the argument to `bash -lc`, checked one command at a
+//! time.
+//!
+//! A script is accepted when it is a list of simple commands joined by `&&`,
+//! `||`, `;` and `|`, each made only of plain words and optionally redirected
+//! to or from files. Anything else (expansions, substitutions, subshells,
+//! control flow, here-documents, ...) is rejected, as the policy cannot tell
+//! what it would run.
+//!
+//! `cd <dir>` needs no spec in the policy. Commands after it may run in
+//! `<dir>` or, if the `cd` failed or ran in a pipeline, where they would have
+//! run without it, so their paths are checked against both.
+
+use std::path::PathBuf;
+
+use codex_bash_parser::FileRedirect;
+use codex_bash_parser::ScriptError;
+use codex_bash_parser::SimpleCommand;
+use serde::Serialize;
+
+use crate::ArgType;
+use crate::ExecCall;
+use crate::Forbidden;
+use crate::MatchedExec;
+use crate::ValidExec;
+use crate::error::Error;
+use crate::error::Result;
+
+/// One simple command of a script, with the redirections applied to it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct ScriptCommand {
+    pub exec_call: ExecCall,
+    pub redirects: Vec<Redirect>,
+}
+
+/// A redirection of a command's input or output.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Redirect {
+    /// Operator as written, including any file descriptor, e.g. `2>`.
+    pub operator: String,
+    /// `ReadableFile` for input, `WriteableFile` for output, and
+    /// `OpaqueNonFile` for targets that are not files, such as `&1` or
+    /// `/dev/null`.
+    pub r#type: ArgType,
+    pub value: String,
+}
+
+/// A command of a script that has been accepted by a `Policy`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct MatchedCommand {
+    pub exec: ValidExec,
+    pub redirects: Vec<Redirect>,
+    /// Directories the command may run in, relative to the script's working
+    /// directory (the empty path is the working directory itself).
+    pub dirs: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub enum MatchedScript {
+    /// Every command of the script matched, in script order.
+    Match { commands: Vec<MatchedCommand> },
+    /// The first forbidden command of the script.
+    Forbidden { cause: Forbidden, reason: String },
+}
+
+impl MatchedScript {
+    /// Whether a possible side effect of running this script includes writing
+    /// a file, either by one of its commands or through a redirection.
+    pub fn might_write_files(&self) -> bool {
+        match self {
+            MatchedScript::Match { commands } => commands.iter().any(|command| {
+                command.exec.might_write_files()
+                    || command
+                        .redirects
+                        .iter()
+                        .any(|redirect| redirect.r#type.might_write_file())
+            }),
+            MatchedScript::Forbidden { .. } => false,
+        }
+    }
+}
+
+impl From<MatchedExec> for MatchedScript {
+    fn from(matched_exec: MatchedExec) -> Self {
+        match matched_exec {
+            MatchedExec::Match { exec } => MatchedScript::Match {
+                commands: vec![MatchedCommand {
+                    exec,
+                    redirects: Vec::new(),
+                    dirs: vec![PathBuf::new()],
+                }],
+            },
+            MatchedExec::Forbidden { cause, reason } => MatchedScript::Forbidden { cause, reason },
+        }
+    }
+}
+
+/// Split `script` into its simple commands, in the order they appear.
+pub fn parse_script(script: &str) -> Result<Vec<ScriptCommand>> {
+    let commands = codex_bash_parser::parse_commands(script).map_err(|err| match err {
+        ScriptError::Parse => Error::ShellScriptParseFailed {
+            script: script.to_string(),
+        },
+        ScriptError::Unsupported { kind, text } => Error::UnsupportedShellSyntax { kind, text },
+    })?;
+    commands.into_iter().map(script_command).collect()
+}
+
+fn script_command(command: SimpleCommand) -> Result<ScriptCommand> {
+    let SimpleCommand {
+        program,
+        args,
+        redirects,
+    } = command;
+    Ok(ScriptCommand {
+        exec_call: ExecCall { program, args },
+        redirects: redirects
+            .into_iter()
+            .map(redirect)
+            .collect::<Result<Vec<_>>>()?,
+    })
+}
+
+/// Classify what a redirection reads or writes.
+fn redirect(redirect: FileRedirect) -> Result<Redirect> {
+    let FileRedirect { operator, target } = redirect;
+    let symbol = operator.trim_start_matches(|c: char| c.is_ascii_digit());
+    let duplicates_descriptor = target == "-" || target.chars().all(|c| c.is_ascii_digit());
+    let r#type = match symbol {
+        ">&" | "<&" if duplicates_descriptor => ArgType::OpaqueNonFile,
+        "<" => ArgType::ReadableFile,
+        ">" | ">>" | ">|" | "&>" | "&>>" | ">&" if target == "/dev/null" => ArgType::OpaqueNonFile,
+        ">" | ">>" | ">|" | "&>" | "&>>" | ">&" => ArgType::WriteableFile,
+        _ => {
+            return Err(Error::UnsupportedShellSyntax {
+                kind: "file_redirect".to_string(),
+                text: format!("{operator}{target}"),
+            });
+        }
+    };
+    r#type.validate(&target)?;
+    Ok(Redirect {
+        operator,
+        r#type,
+        value: target,
+    })
+}
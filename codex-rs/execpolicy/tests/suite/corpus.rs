@@ -0,0 +1,148 @@
+use std::io::Write;
+
+use codex_execpolicy::CorpusVerdict;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::classify;
+use codex_execpolicy::command_from_rollout_line;
+use codex_execpolicy::get_default_policy;
+use codex_execpolicy::load_corpus;
+use codex_execpolicy::replay_corpus;
+use codex_execpolicy::shell_script;
+
+extern crate codex_execpolicy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn test_command_from_shell_function_call() {
+    let line = r#"{"timestamp":"t","type":"response_item","payload":{"type":"function_call","name":"shell","arguments":"{\"command\":[\"ls\",\"-l\"]}","call_id":"c"}}"#;
+    assert_eq!(Some(argv(&["ls", "-l"])), command_from_rollout_line(line));
+}
+
+#[test]
+fn test_command_from_local_shell_call() {
+    let line = r#"{"timestamp":"t","type":"response_item","payload":{"type":"local_shell_call","status":"completed","action":{"type":"exec","command":["pwd"]}}}"#;
+    assert_eq!(Some(argv(&["pwd"])), command_from_rollout_line(line));
+}
+
+#[test]
+fn test_other_rollout_lines_are_ignored() {
+    let message = r#"{"timestamp":"t","type":"response_item","payload":{"type":"message","role":"user","content":[]}}"#;
+    let other_tool = r#"{"timestamp":"t","type":"response_item","payload":{"type":"function_call","name":"update_plan","arguments":"{}","call_id":"c"}}"#;
+    assert_eq!(None, command_from_rollout_line(message));
+    assert_eq!(None, command_from_rollout_line(other_tool));
+    assert_eq!(None, command_from_rollout_line("not json"));
+}
+
+#[test]
+fn test_shell_script() {
+    assert_eq!(
+        Some("ls | wc -l"),
+        shell_script(&argv(&["bash", "-lc", "ls | wc -l"]))
+    );
+    assert_eq!(None, shell_script(&argv(&["ls", "-l", "src"])));
+}
+
+#[test]
+fn test_classify() {
+    let policy = setup();
+    assert_eq!(
+        CorpusVerdict::AutoApproved,
+        classify(&policy, &argv(&["bash", "-lc", "ls -al"]))
+    );
+    assert_eq!(
+        CorpusVerdict::WritesFiles,
+        classify(&policy, &argv(&["cp", "a", "b"]))
+    );
+    assert_eq!(
+        CorpusVerdict::Unmatched,
+        classify(&policy, &argv(&["rm", "-rf", "/"]))
+    );
+    assert_eq!(
+        CorpusVerdict::WritesFiles,
+        classify(&policy, &argv(&["bash", "-lc", "ls -l > out.txt"]))
+    );
+    assert_eq!(
+        CorpusVerdict::Unmatched,
+        classify(&policy, &argv(&["bash", "-lc", "ls && rm -rf /"]))
+    );
+    assert_eq!(
+        CorpusVerdict::Unparsed,
+        classify(&policy, &argv(&["bash", "-lc", "cat $(which ls)"]))
+    );
+}
+
+#[test]
+fn test_replay_corpus_counts_distinct_commands() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let mut rollout =
+        std::fs::File::create(dir.path().join("rollout.jsonl")).expect("create rollout");
+    for command in [r#"[\"ls\"]"#, r#"[\"ls\"]"#, r#"[\"rm\",\"x\"]"#] {
+        writeln!(
+            rollout,
+            r#"{{"timestamp":"t","type":"response_item","payload":{{"type":"function_call","name":"shell","arguments":"{{\"command\":{command}}}","call_id":"c"}}}}"#
+        )
+        .expect("write rollout");
+    }
+    // Files other than `.jsonl` rollouts are skipped.
+    std::fs::write(dir.path().join("notes.txt"), "ls").expect("write notes");
+
+    let commands = load_corpus(&[dir.path().to_path_buf()]).expect("load corpus");
+    let report = replay_corpus(&setup(), &commands);
+
+    assert_eq!(3, report.total);
+    assert_eq!(2, report.count(CorpusVerdict::AutoApproved));
+    assert_eq!(1, report.count(CorpusVerdict::Unmatched));
+    assert_eq!(
+        Some(&1),
+        report
+            .commands
+            .get(&CorpusVerdict::Unmatched)
+            .and_then(|commands| commands.get("rm x"))
+    );
+}
+
+#[test]
+fn test_failed_examples_report_source_location() {
+    let unparsed_policy = r#"
+define_program(
+    program="ls",
+    options=[flag("-l")],
+    should_match=[["-l"]],
+    should_not_match=[["-z"]],
+)
+
+define_program(
+    program="pwd",
+    should_match=[["-P"]],
+    should_not_match=[[]],
+)
+"#;
+    let policy = PolicyParser::new("test.policy", unparsed_policy)
+        .parse()
+        .expect("parse policy");
+
+    let good_failures = policy.check_each_good_list_individually();
+    assert_eq!(1, good_failures.len());
+    assert_eq!("pwd", good_failures[0].program);
+    assert_eq!(
+        Some("test.policy:9".to_string()),
+        good_failures[0].source_location
+    );
+
+    let bad_failures = policy.check_each_bad_list_individually();
+    assert_eq!(1, bad_failures.len());
+    assert_eq!("pwd", bad_failures[0].program);
+    assert_eq!(
+        Some("test.policy:9".to_string()),
+        bad_failures[0].source_location
+    );
+}
@@ -0,0 +1,108 @@
+use codex_execpolicy::ArgType;
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::MatchedArg;
+use codex_execpolicy::MatchedExec;
+use codex_execpolicy::MatchedFlag;
+use codex_execpolicy::MatchedOpt;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PositionalArg;
+use codex_execpolicy::Result;
+use codex_execpolicy::ValidExec;
+use codex_execpolicy::get_default_policy;
+
+extern crate codex_execpolicy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+#[test]
+fn test_git_status_short() -> Result<()> {
+    let policy = setup();
+    let git_status = ExecCall::new("git", &["status", "--short", "src"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "git".into(),
+                flags: vec![MatchedFlag::new("--short")],
+                args: vec![
+                    MatchedArg::new(0, ArgType::Literal("status".into()), "status")?,
+                    MatchedArg::new(2, ArgType::ReadableFile, "src")?,
+                ],
+                system_path: vec!["/usr/bin/git".into()],
+                ..Default::default()
+            }
+        }),
+        policy.check(&git_status)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_git_log_combined_and_bundled_options() -> Result<()> {
+    let policy = setup();
+    let git_log = ExecCall::new("git", &["log", "--max-count=5", "-n3", "--oneline"]);
+    assert_eq!(
+        Ok(MatchedExec::Match {
+            exec: ValidExec {
+                program: "git".into(),
+                flags: vec![MatchedFlag::new("--oneline")],
+                opts: vec![
+                    MatchedOpt::new("--max-count", "5", ArgType::PositiveInteger)?,
+                    MatchedOpt::new("-n", "3", ArgType::PositiveInteger)?,
+                ],
+                args: vec![MatchedArg::new(0, ArgType::Literal("log".into()), "log")?],
+                system_path: vec!["/usr/bin/git".into()],
+            }
+        }),
+        policy.check(&git_log)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_git_option_from_another_subcommand() {
+    let policy = setup();
+
+    // `--cached` is only allowed for `git diff`.
+    let git_log_cached = ExecCall::new("git", &["log", "--cached"]);
+    assert_eq!(
+        Err(Error::UnknownOption {
+            program: "git".into(),
+            option: "--cached".into()
+        }),
+        policy.check(&git_log_cached)
+    );
+}
+
+#[test]
+fn test_git_unknown_subcommand() {
+    let policy = setup();
+    let git_push = ExecCall::new("git", &["push"]);
+    assert_eq!(
+        Err(Error::UnexpectedArguments {
+            program: "git".into(),
+            args: vec![PositionalArg {
+                index: 0,
+                value: "push".into()
+            }],
+        }),
+        policy.check(&git_push)
+    );
+}
+
+#[test]
+fn test_rg_flag_with_value() {
+    let policy = setup();
+    let rg = ExecCall::new("rg", &["--files=src"]);
+    assert_eq!(
+        Err(Error::FlagDoesNotTakeValue {
+            program: "rg".into(),
+            flag: "--files".into(),
+            value: "src".into()
+        }),
+        policy.check(&rg)
+    );
+}
@@ -1,4 +1,3 @@
-use codex_execpolicy::ArgMatcher;
 use codex_execpolicy::ArgType;
 use codex_execpolicy::Error;
 use codex_execpolicy::ExecCall;
@@ -21,18 +20,11 @@ fn setup() -> Policy {
 fn test_head_no_args() {
     let policy = setup();
     let head = ExecCall::new("head", &[]);
-    // It is actually valid to call `head` without arguments: it will read from
-    // stdin instead of from a file. Though recall that a command rejected by
-    // the policy is not "unsafe:" it just means that this library cannot
-    // *guarantee* that the command is safe.
-    //
-    // If we start verifying individual components of a shell command, such as:
-    // `find . -name | head -n 10`, then it might be important to allow the
-    // no-arg case.
+    // Without files, `head` reads from stdin, which is how it is used at the
+    // end of a pipeline such as `find . -name '*.rs' | head -n 10`.
     assert_eq!(
-        Err(Error::VarargMatcherDidNotMatchAnything {
-            program: "head".to_string(),
-            matcher: ArgMatcher::ReadableFiles,
+        Ok(MatchedExec::Match {
+            exec: ValidExec::new("head", vec![], &["/bin/head", "/usr/bin/head"])
         }),
         policy.check(&head)
     )
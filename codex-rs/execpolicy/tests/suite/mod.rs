@@ -1,10 +1,13 @@
 // Aggregates all former standalone integration tests as modules.
 mod bad;
+mod corpus;
 mod cp;
+mod git;
 mod good;
 mod head;
 mod literal;
 mod ls;
 mod parse_sed_command;
 mod pwd;
+mod script;
 mod sed;
@@ -0,0 +1,298 @@
+use codex_execpolicy::ArgType;
+use codex_execpolicy::Error;
+use codex_execpolicy::ExecCall;
+use codex_execpolicy::ExecvChecker;
+use codex_execpolicy::Forbidden;
+use codex_execpolicy::MatchedScript;
+use codex_execpolicy::Policy;
+use codex_execpolicy::PolicyParser;
+use codex_execpolicy::Redirect;
+use codex_execpolicy::Result;
+use codex_execpolicy::get_default_policy;
+use codex_execpolicy::parse_script;
+use std::path::PathBuf;
+
+extern crate codex_execpolicy;
+
+#[expect(clippy::expect_used)]
+fn setup() -> Policy {
+    get_default_policy().expect("failed to load default policy")
+}
+
+fn redirect(operator: &str, r#type: ArgType, value: &str) -> Redirect {
+    Redirect {
+        operator: operator.to_string(),
+        r#type,
+        value: value.to_string(),
+    }
+}
+
+#[test]
+fn test_commands_are_split_in_order() -> Result<()> {
+    let commands = parse_script("cd src && ls -l | head -n 5; pwd")?;
+    let exec_calls: Vec<ExecCall> = commands
+        .into_iter()
+        .map(|command| command.exec_call)
+        .collect();
+    assert_eq!(
+        vec![
+            ExecCall::new("cd", &["src"]),
+            ExecCall::new("ls", &["-l"]),
+            ExecCall::new("head", &["-n", "5"]),
+            ExecCall::new("pwd", &[]),
+        ],
+        exec_calls
+    );
+    Ok(())
+}
+
+#[test]
+fn test_cd_changes_where_later_paths_are_checked() {
+    let policy = setup();
+    let Ok(MatchedScript::Match { commands }) = policy.check_script("cd src && ls") else {
+        panic!("expected `cd src && ls` to match");
+    };
+    let commands: Vec<(&str, Vec<PathBuf>)> = commands
+        .iter()
+        .map(|command| (command.exec.program.as_str(), command.dirs.clone()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("cd", vec![PathBuf::new()]),
+            ("ls", vec![PathBuf::new(), PathBuf::from("src")]),
+        ],
+        commands
+    );
+
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let root = dir.path().join("workspace");
+    let cwd = Some(root.clone().into_os_string());
+    let checker = ExecvChecker::new(setup());
+    let check = |script: &str, readable_folders: &[PathBuf]| {
+        let Ok(MatchedScript::Match { commands }) = checker.match_script(script) else {
+            panic!("expected {script:?} to match");
+        };
+        commands.into_iter().try_for_each(|command| {
+            checker
+                .check_command(command, &cwd, readable_folders, std::slice::from_ref(&root))
+                .map(drop)
+        })
+    };
+    let workspace = std::slice::from_ref(&root);
+    assert_eq!(
+        Ok(()),
+        check("cd src && head -n 1 lib.rs > out.txt", workspace)
+    );
+    assert_eq!(
+        Err(Error::ReadablePathNotInReadableFolders {
+            file: dir.path().to_path_buf(),
+            folders: vec![root.clone()],
+        }),
+        check("cd .. && head -n 1 secret", workspace)
+    );
+    assert_eq!(
+        Err(Error::ReadablePathNotInReadableFolders {
+            file: dir.path().join("secret"),
+            folders: vec![root.clone()],
+        }),
+        check("cd src && head -n 1 ../secret", workspace)
+    );
+    // Once `cd ..` succeeded, `out.txt` is outside the writable workspace.
+    assert_eq!(
+        Err(Error::WriteablePathNotInWriteableFolders {
+            file: dir.path().join("out.txt"),
+            folders: vec![root.clone()],
+        }),
+        check("cd .. && ls > out.txt", &[dir.path().to_path_buf()])
+    );
+}
+
+#[test]
+fn test_cd_without_a_directory_is_rejected() {
+    let policy = setup();
+    for script in ["cd && ls", "cd - && ls", "cd -P src && ls"] {
+        assert!(
+            matches!(
+                policy.check_script(script),
+                Err(Error::UnsupportedShellSyntax { .. })
+            ),
+            "expected {script:?} to be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_words_bash_would_expand_are_rejected() {
+    let policy = setup();
+    for script in [
+        "echo x > ~/.bashrc",
+        "cat ~/.ssh/id_rsa",
+        "cat $HOME/.ssh/id_rsa",
+        "head -n 1 *.rs",
+    ] {
+        assert!(
+            matches!(
+                policy.check_script(script),
+                Err(Error::UnsupportedShellSyntax { .. })
+            ),
+            "expected {script:?} to be rejected"
+        );
+    }
+}
+
+#[test]
+fn test_redirects_are_typed() -> Result<()> {
+    let commands = parse_script("rg 'a b' < patterns > out.txt 2>&1 | head 2>/dev/null")?;
+    assert_eq!(2, commands.len());
+    assert_eq!(ExecCall::new("rg", &["a b"]), commands[0].exec_call);
+    assert_eq!(
+        vec![
+            redirect("<", ArgType::ReadableFile, "patterns"),
+            redirect(">", ArgType::WriteableFile, "out.txt"),
+            redirect("2>&", ArgType::OpaqueNonFile, "1"),
+        ],
+        commands[0].redirects
+    );
+    assert_eq!(
+        vec![redirect("2>", ArgType::OpaqueNonFile, "/dev/null")],
+        commands[1].redirects
+    );
+    Ok(())
+}
+
+#[test]
+fn test_trailing_redirect_applies_to_last_command() -> Result<()> {
+    let commands = parse_script("ls && ls src >> out.txt")?;
+    assert_eq!(Vec::<Redirect>::new(), commands[0].redirects);
+    assert_eq!(
+        vec![redirect(">>", ArgType::WriteableFile, "out.txt")],
+        commands[1].redirects
+    );
+    Ok(())
+}
+
+#[test]
+fn test_unsupported_syntax_is_rejected() {
+    for script in [
+        "cat $(which ls)",
+        "echo $HOME",
+        "(ls)",
+        "FOO=bar ls",
+        "ls &",
+        "{ ls; } > out.txt",
+        "cat <<EOF\nhi\nEOF",
+        "if true; then ls; fi",
+    ] {
+        assert!(
+            matches!(
+                parse_script(script),
+                Err(Error::UnsupportedShellSyntax { .. })
+            ),
+            "expected {script:?} to be rejected"
+        );
+    }
+    assert_eq!(
+        Err(Error::ShellScriptParseFailed {
+            script: "ls &&".to_string()
+        }),
+        parse_script("ls &&")
+    );
+}
+
+#[test]
+fn test_pipeline_matches_when_every_command_matches() {
+    let policy = setup();
+    let Ok(matched) = policy.check_script("rg -n foo src | head -n 5") else {
+        panic!("expected a match");
+    };
+    let MatchedScript::Match { commands } = &matched else {
+        panic!("expected a match, got {matched:?}");
+    };
+    let programs: Vec<&str> = commands
+        .iter()
+        .map(|command| command.exec.program.as_str())
+        .collect();
+    assert_eq!(vec!["rg", "head"], programs);
+    assert!(!matched.might_write_files());
+}
+
+#[test]
+fn test_redirected_output_writes_files() {
+    let policy = setup();
+    let Ok(matched) = policy.check_script("ls -l > listing.txt") else {
+        panic!("expected a match");
+    };
+    assert!(matched.might_write_files());
+}
+
+#[test]
+fn test_one_unmatched_command_fails_the_script() {
+    let policy = setup();
+    assert_eq!(
+        Err(Error::NoSpecForProgram {
+            program: "rm".to_string()
+        }),
+        policy.check_script("ls && rm -rf build")
+    );
+}
+
+#[test]
+fn test_one_forbidden_command_forbids_the_script() {
+    let unparsed_policy = r#"
+define_program(program="ls", args=[ARG_RFILES_OR_CWD])
+forbid_program_regex("^rm$", "rm is not allowed")
+forbid_substrings(["secret"])
+"#;
+    let policy = PolicyParser::new("test.policy", unparsed_policy)
+        .parse()
+        .expect("parse policy");
+
+    assert_eq!(
+        Ok(MatchedScript::Forbidden {
+            cause: Forbidden::Program {
+                program: "rm".to_string(),
+                exec_call: ExecCall::new("rm", &["x"]),
+            },
+            reason: "rm is not allowed".to_string(),
+        }),
+        policy.check_script("ls | frobnicate; rm x")
+    );
+    assert!(matches!(
+        policy.check_script("ls > secret.txt"),
+        Ok(MatchedScript::Forbidden {
+            cause: Forbidden::Arg { .. },
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_execv_checker_checks_redirect_targets() {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let root = dir.path().to_path_buf();
+    let cwd = Some(root.clone().into_os_string());
+    let checker = ExecvChecker::new(setup());
+
+    let Ok(MatchedScript::Match { mut commands }) = checker.match_script("ls > out.txt") else {
+        panic!("expected a match");
+    };
+    let command = commands.remove(0);
+
+    assert!(
+        checker
+            .check_command(
+                command.clone(),
+                &cwd,
+                std::slice::from_ref(&root),
+                std::slice::from_ref(&root)
+            )
+            .is_ok()
+    );
+    assert_eq!(
+        Err(Error::WriteablePathNotInWriteableFolders {
+            file: root.join("out.txt"),
+            folders: vec![],
+        }),
+        checker.check_command(command, &cwd, &[root], &[])
+    );
+}
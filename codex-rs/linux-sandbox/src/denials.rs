@@ -0,0 +1,657 @@
+//! Reports the operations that the Landlock and seccomp sandbox denies a
+//! command, so that Codex can tell the model and the user exactly what was
+//! blocked.
+//!
+//! The command runs in a child process under a seccomp filter that returns
+//! `SECCOMP_RET_USER_NOTIF` for the network syscalls that the plain filter
+//! denies and for syscalls that modify the file system. This process receives
+//! the notifications:
+//!
+//! - network syscalls fail with `EPERM`, as they do under the plain network
+//!   filter, and are recorded;
+//! - file-system syscalls are resumed, so Landlock still decides whether they
+//!   succeed, and are recorded when their path is outside the writable roots,
+//!   where Landlock denies them.
+//!
+//! Once the command exits, each denied operation is written on a line of its
+//! own to the descriptor that Codex passed with `--denials-fd`, which the
+//! command does not inherit. A detached process then keeps answering the
+//! notifications of processes that the command left running in the
+//! background, whose denials go unreported.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::ffi::CString;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::SandboxPolicy;
+use seccompiler::BpfProgram;
+use seccompiler::SeccompAction;
+use seccompiler::SeccompCmpArgLen;
+use seccompiler::SeccompCmpOp;
+use seccompiler::SeccompCondition;
+use seccompiler::SeccompFilter;
+use seccompiler::SeccompRule;
+
+use crate::landlock::NETWORK_SYSCALLS;
+use crate::landlock::apply_filesystem_policy_to_current_thread;
+use crate::landlock::network_seccomp_rules;
+use crate::landlock::seccomp_target_arch;
+use crate::linux_run_main::execvp_command;
+
+// From <linux/seccomp.h>; not exported by the libc crate.
+const SECCOMP_IOCTL_NOTIF_RECV: u64 = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: u64 = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: u64 = 0x4008_2102;
+
+/// `BPF_RET | BPF_K`: return a constant.
+const BPF_RET_K: u16 = 0x06;
+
+/// Denials beyond this many are counted but not listed.
+const MAX_REPORTED_DENIALS: usize = 20;
+
+/// The blocked network syscalls that are reported. The rest, such as
+/// `getpeername`, are commonly used to probe descriptors that are not
+/// sockets, and reporting them would only be noise.
+const REPORTED_NETWORK_SYSCALLS: &[&str] = &[
+    "socket",
+    "socketpair",
+    "connect",
+    "bind",
+    "listen",
+    "accept",
+    "accept4",
+];
+
+/// Paths that shells probe on startup, whose denial is not worth reporting.
+const UNREPORTED_PATHS: &[&str] = &["/dev/tty"];
+
+/// A syscall that modifies the file system.
+struct WriteSyscall {
+    nr: i64,
+    /// What the syscall does, as shown in the report.
+    operation: &'static str,
+    /// The `(dirfd, path)` argument pairs naming the paths it modifies.
+    /// `dirfd` is `None` for paths relative to the working directory.
+    paths: &'static [(Option<usize>, usize)],
+    /// The argument holding `open(2)` flags, for syscalls that only modify
+    /// the file system when opening a file for writing.
+    open_flags: Option<usize>,
+    /// Whether the syscall creates its last path, so that it fails with
+    /// `EEXIST` before Landlock is consulted if the path exists.
+    creates: bool,
+}
+
+const fn write_syscall(
+    nr: i64,
+    operation: &'static str,
+    paths: &'static [(Option<usize>, usize)],
+) -> WriteSyscall {
+    WriteSyscall {
+        nr,
+        operation,
+        paths,
+        open_flags: None,
+        creates: false,
+    }
+}
+
+const fn create_syscall(
+    nr: i64,
+    operation: &'static str,
+    paths: &'static [(Option<usize>, usize)],
+) -> WriteSyscall {
+    WriteSyscall {
+        creates: true,
+        ..write_syscall(nr, operation, paths)
+    }
+}
+
+const WRITE_SYSCALLS: &[WriteSyscall] = &[
+    WriteSyscall {
+        nr: libc::SYS_openat,
+        operation: "write to",
+        paths: &[(Some(0), 1)],
+        open_flags: Some(2),
+        creates: false,
+    },
+    create_syscall(libc::SYS_mkdirat, "create directory", &[(Some(0), 1)]),
+    create_syscall(libc::SYS_mknodat, "create", &[(Some(0), 1)]),
+    write_syscall(libc::SYS_unlinkat, "remove", &[(Some(0), 1)]),
+    write_syscall(libc::SYS_renameat2, "rename", &[(Some(0), 1), (Some(2), 3)]),
+    create_syscall(libc::SYS_linkat, "create link", &[(Some(2), 3)]),
+    create_syscall(libc::SYS_symlinkat, "create link", &[(Some(1), 2)]),
+    write_syscall(libc::SYS_truncate, "truncate", &[(None, 0)]),
+];
+
+/// Syscalls that only exist on x86_64; aarch64 has just the `*at` variants.
+#[cfg(target_arch = "x86_64")]
+const LEGACY_WRITE_SYSCALLS: &[WriteSyscall] = &[
+    WriteSyscall {
+        nr: libc::SYS_open,
+        operation: "write to",
+        paths: &[(None, 0)],
+        open_flags: Some(1),
+        creates: false,
+    },
+    write_syscall(libc::SYS_creat, "write to", &[(None, 0)]),
+    create_syscall(libc::SYS_mkdir, "create directory", &[(None, 0)]),
+    create_syscall(libc::SYS_mknod, "create", &[(None, 0)]),
+    write_syscall(libc::SYS_rmdir, "remove", &[(None, 0)]),
+    write_syscall(libc::SYS_unlink, "remove", &[(None, 0)]),
+    write_syscall(libc::SYS_rename, "rename", &[(None, 0), (None, 1)]),
+    write_syscall(libc::SYS_renameat, "rename", &[(Some(0), 1), (Some(2), 3)]),
+    create_syscall(libc::SYS_link, "create link", &[(None, 1)]),
+    create_syscall(libc::SYS_symlink, "create link", &[(None, 1)]),
+];
+#[cfg(not(target_arch = "x86_64"))]
+const LEGACY_WRITE_SYSCALLS: &[WriteSyscall] = &[];
+
+fn write_syscalls() -> impl Iterator<Item = &'static WriteSyscall> {
+    WRITE_SYSCALLS.iter().chain(LEGACY_WRITE_SYSCALLS)
+}
+
+/// Runs `command` under the Landlock and seccomp sandbox for `policy` and
+/// writes the operations it was denied to `denials`. Returns the exit code to
+/// exit with.
+///
+/// Returns `None` without running the command when there is nothing to
+/// report or when the kernel lacks seccomp user notifications (Linux 5.6 or
+/// later is needed); the caller then runs it under the plain sandbox.
+pub(crate) fn run_command_reporting_denials(
+    policy: &SandboxPolicy,
+    policy_cwd: &Path,
+    command: &[String],
+    denials: File,
+) -> io::Result<Option<i32>> {
+    if policy.has_full_network_access() && policy.has_full_disk_write_access() {
+        return Ok(None);
+    }
+
+    let (mut listener_fd_reader, listener_fd_writer) = io::pipe()?;
+    let (ack_reader, mut ack_writer) = io::pipe()?;
+    // SAFETY: this process is single-threaded, so the child may run Rust
+    // code freely before it execs.
+    let pid = check(unsafe { libc::fork() })?;
+    if pid == 0 {
+        drop(listener_fd_reader);
+        drop(ack_writer);
+        drop(denials);
+        run_child(policy, policy_cwd, command, listener_fd_writer, ack_reader);
+    }
+    drop(listener_fd_writer);
+    drop(ack_reader);
+
+    // The child sends the number of its listener descriptor, or -1 if it
+    // could not install the filter.
+    let mut listener_fd = [0u8; 4];
+    let listener = match listener_fd_reader.read_exact(&mut listener_fd) {
+        Ok(()) => take_listener(pid, i32::from_ne_bytes(listener_fd)),
+        Err(_) => None,
+    };
+    let Some(listener) = listener else {
+        // Either way, the child exits without running the command.
+        let _ = ack_writer.write_all(&[0]);
+        wait_for_exit(pid)?;
+        return Ok(None);
+    };
+    ack_writer.write_all(&[1])?;
+    drop(ack_writer);
+
+    let mut supervisor = Supervisor::new(policy, policy_cwd);
+    let status = supervisor.supervise(&listener, pid)?;
+    supervisor.report(denials);
+
+    // Processes that the command left running in the background are still
+    // under the filter, and their notified syscalls would fail with ENOSYS
+    // once nobody listens. A detached process keeps answering until the
+    // kernel reports that none remain. It is forked after the report so that
+    // it does not hold the descriptor open.
+    // SAFETY: this process is still single-threaded.
+    if unsafe { libc::fork() } == 0 {
+        supervisor.serve_background(&listener);
+        // SAFETY: exiting without running destructors or atexit handlers
+        // that belong to the parent.
+        unsafe { libc::_exit(0) };
+    }
+    drop(listener);
+
+    Ok(Some(exit_code(status)))
+}
+
+/// Sets up the sandbox in the forked child and execs `command`. Never
+/// returns: on failure the child exits and the parent falls back to the plain
+/// sandbox.
+fn run_child(
+    policy: &SandboxPolicy,
+    policy_cwd: &Path,
+    command: &[String],
+    mut listener_fd_writer: io::PipeWriter,
+    mut ack_reader: io::PipeReader,
+) -> ! {
+    // SAFETY: plain prctl call. Codex kills this process on timeout, and the
+    // command must be killed with it.
+    unsafe {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+    }
+
+    let listener = match install_filters(policy, policy_cwd) {
+        Ok(listener) => listener,
+        Err(_) => {
+            let _ = listener_fd_writer.write_all(&(-1i32).to_ne_bytes());
+            // SAFETY: see above.
+            unsafe { libc::_exit(1) };
+        }
+    };
+    let mut ack = [0u8; 1];
+    let acknowledged = listener_fd_writer
+        .write_all(&listener.as_raw_fd().to_ne_bytes())
+        .and_then(|()| ack_reader.read_exact(&mut ack))
+        .is_ok_and(|()| ack[0] == 1);
+    if !acknowledged {
+        // SAFETY: see above.
+        unsafe { libc::_exit(1) };
+    }
+    drop(listener);
+    drop(listener_fd_writer);
+    drop(ack_reader);
+
+    execvp_command(command);
+}
+
+/// Applies Landlock and installs the notifying seccomp filter on the current
+/// thread, returning the filter's listener.
+fn install_filters(policy: &SandboxPolicy, policy_cwd: &Path) -> io::Result<OwnedFd> {
+    apply_filesystem_policy_to_current_thread(policy, policy_cwd).map_err(io::Error::other)?;
+
+    let mut rules = BTreeMap::new();
+    if !policy.has_full_network_access() {
+        rules.extend(network_seccomp_rules().map_err(io::Error::other)?);
+    }
+    if !policy.has_full_disk_write_access() {
+        rules.extend(write_seccomp_rules().map_err(io::Error::other)?);
+    }
+    // seccompiler has no action for user notifications, so the filter is
+    // built with `Trace` and its return values are rewritten.
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Trace(0),
+        seccomp_target_arch(),
+    )
+    .map_err(io::Error::other)?;
+    let mut program: BpfProgram = filter.try_into().map_err(io::Error::other)?;
+    for instruction in &mut program {
+        if instruction.code == BPF_RET_K && instruction.k == libc::SECCOMP_RET_TRACE {
+            instruction.k = libc::SECCOMP_RET_USER_NOTIF;
+        }
+    }
+
+    let program = libc::sock_fprog {
+        len: u16::try_from(program.len()).map_err(io::Error::other)?,
+        filter: program.as_mut_ptr().cast(),
+    };
+    // SAFETY: `program` points to a valid BPF program for the duration of the
+    // call, and the kernel copies it.
+    unsafe {
+        check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+        let listener = check(libc::syscall(
+            libc::SYS_seccomp,
+            libc::SECCOMP_SET_MODE_FILTER,
+            libc::SECCOMP_FILTER_FLAG_NEW_LISTENER,
+            &program,
+        ))?;
+        Ok(OwnedFd::from_raw_fd(listener as libc::c_int))
+    }
+}
+
+fn write_seccomp_rules() -> Result<BTreeMap<i64, Vec<SeccompRule>>, seccompiler::BackendError> {
+    let mut rules = BTreeMap::new();
+    for syscall in write_syscalls() {
+        let syscall_rules = match syscall.open_flags {
+            Some(arg) => [libc::O_WRONLY, libc::O_RDWR]
+                .into_iter()
+                .map(|mode| {
+                    SeccompRule::new(vec![SeccompCondition::new(
+                        arg as u8,
+                        SeccompCmpArgLen::Dword,
+                        SeccompCmpOp::MaskedEq(libc::O_ACCMODE as u64),
+                        mode as u64,
+                    )?])
+                })
+                .collect::<Result<_, _>>()?,
+            None => vec![],
+        };
+        rules.insert(syscall.nr, syscall_rules);
+    }
+    Ok(rules)
+}
+
+/// Duplicates the child's listener descriptor `fd` into this process.
+fn take_listener(pid: libc::pid_t, fd: libc::c_int) -> Option<OwnedFd> {
+    if fd < 0 {
+        return None;
+    }
+    // SAFETY: plain syscalls; the returned descriptors are owned here.
+    unsafe {
+        let pidfd = check(libc::syscall(libc::SYS_pidfd_open, pid, 0)).ok()?;
+        let pidfd = OwnedFd::from_raw_fd(pidfd as libc::c_int);
+        let listener = check(libc::syscall(
+            libc::SYS_pidfd_getfd,
+            pidfd.as_raw_fd(),
+            fd,
+            0,
+        ))
+        .ok()?;
+        Some(OwnedFd::from_raw_fd(listener as libc::c_int))
+    }
+}
+
+struct Supervisor {
+    /// Canonical roots the command may write beneath.
+    writable_roots: Vec<PathBuf>,
+    denials: BTreeSet<String>,
+    omitted_denials: usize,
+}
+
+impl Supervisor {
+    fn new(policy: &SandboxPolicy, policy_cwd: &Path) -> Self {
+        let mut writable_roots: Vec<PathBuf> = policy
+            .get_writable_roots_with_cwd(policy_cwd)
+            .into_iter()
+            .filter_map(|writable_root| writable_root.root.canonicalize().ok())
+            .collect();
+        writable_roots.push(PathBuf::from("/dev/null"));
+        Self {
+            writable_roots,
+            denials: BTreeSet::new(),
+            omitted_denials: 0,
+        }
+    }
+
+    /// Answers notifications until the child `pid` exits, and returns its
+    /// wait status.
+    fn supervise(&mut self, listener: &OwnedFd, pid: libc::pid_t) -> io::Result<libc::c_int> {
+        // SAFETY: plain syscall; the returned descriptor is owned here.
+        let pidfd = unsafe {
+            OwnedFd::from_raw_fd(check(libc::syscall(libc::SYS_pidfd_open, pid, 0))? as libc::c_int)
+        };
+        loop {
+            let mut fds = [
+                libc::pollfd {
+                    fd: listener.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: pidfd.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            if let Err(err) = check(unsafe { libc::poll(fds.as_mut_ptr(), 2, -1) }) {
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            if fds[0].revents & libc::POLLIN != 0 {
+                self.answer_notification(listener);
+            }
+            if fds[1].revents & libc::POLLIN != 0 {
+                return wait_for_exit(pid);
+            }
+        }
+    }
+
+    /// Answers notifications from processes left running in the background
+    /// until none remain.
+    fn serve_background(&mut self, listener: &OwnedFd) {
+        // Do not hold the command's output pipes open.
+        if let Ok(dev_null) = File::options().read(true).write(true).open("/dev/null") {
+            for fd in 0..=2 {
+                // SAFETY: replaces the standard descriptors of this process.
+                unsafe { libc::dup2(dev_null.as_raw_fd(), fd) };
+            }
+        }
+        loop {
+            let mut fds = [libc::pollfd {
+                fd: listener.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            }];
+            // SAFETY: `fds` is a valid array of one pollfd.
+            if unsafe { libc::poll(fds.as_mut_ptr(), 1, -1) } == -1 {
+                continue;
+            }
+            if fds[0].revents & libc::POLLIN != 0 {
+                self.answer_notification(listener);
+            } else if fds[0].revents & (libc::POLLHUP | libc::POLLERR) != 0 {
+                return;
+            }
+        }
+    }
+
+    fn answer_notification(&mut self, listener: &OwnedFd) {
+        // SAFETY: the kernel requires a zeroed buffer, which is a valid value
+        // of this plain C struct.
+        let mut request: libc::seccomp_notif = unsafe { std::mem::zeroed() };
+        // SAFETY: `request` is a valid seccomp_notif buffer.
+        if unsafe {
+            libc::ioctl(
+                listener.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_RECV as _,
+                &mut request,
+            )
+        } == -1
+        {
+            // The process was killed before its notification was received.
+            return;
+        }
+
+        let nr = i64::from(request.data.nr);
+        let mut response = libc::seccomp_notif_resp {
+            id: request.id,
+            val: 0,
+            error: 0,
+            flags: 0,
+        };
+        if let Some((_, name)) = NETWORK_SYSCALLS
+            .iter()
+            .find(|(network_nr, _)| *network_nr == nr)
+        {
+            if REPORTED_NETWORK_SYSCALLS.contains(name) {
+                self.record(format!("network access ({name})"));
+            }
+            response.error = -libc::EPERM;
+        } else {
+            if let Some(syscall) = write_syscalls().find(|syscall| syscall.nr == nr) {
+                self.check_write(listener, &request, syscall);
+            }
+            // Landlock still applies to the resumed syscall.
+            response.flags = libc::SECCOMP_USER_NOTIF_FLAG_CONTINUE as u32;
+        }
+        // SAFETY: `response` is a valid seccomp_notif_resp. Failure means the
+        // process is gone.
+        unsafe {
+            libc::ioctl(
+                listener.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_SEND as _,
+                &response,
+            );
+        }
+    }
+
+    fn check_write(
+        &mut self,
+        listener: &OwnedFd,
+        request: &libc::seccomp_notif,
+        syscall: &WriteSyscall,
+    ) {
+        let args = request.data.args;
+        let paths: Vec<PathBuf> = syscall
+            .paths
+            .iter()
+            .filter_map(|&(dirfd, path)| {
+                let dirfd = dirfd.map_or(libc::AT_FDCWD, |arg| args[arg] as libc::c_int);
+                resolve_path(request.pid, dirfd, args[path])
+            })
+            .collect();
+        // The memory that was read may have been another process's if the
+        // caller died in the meantime.
+        // SAFETY: `request.id` is a valid u64 to pass by pointer.
+        if unsafe {
+            libc::ioctl(
+                listener.as_raw_fd(),
+                SECCOMP_IOCTL_NOTIF_ID_VALID as _,
+                &request.id,
+            )
+        } == -1
+        {
+            return;
+        }
+        let last = paths.len().saturating_sub(1);
+        for (i, path) in paths.into_iter().enumerate() {
+            if syscall.creates && i == last && path.symlink_metadata().is_ok() {
+                continue;
+            }
+            if !self.is_writable(&path) && !UNREPORTED_PATHS.iter().any(|p| path == Path::new(p)) {
+                self.record(format!("{} {}", syscall.operation, path.display()));
+            }
+        }
+    }
+
+    fn is_writable(&self, path: &Path) -> bool {
+        self.writable_roots
+            .iter()
+            .any(|root| path.starts_with(root))
+    }
+
+    fn record(&mut self, denial: String) {
+        if self.denials.len() < MAX_REPORTED_DENIALS {
+            self.denials.insert(denial);
+        } else if !self.denials.contains(&denial) {
+            self.omitted_denials += 1;
+        }
+    }
+
+    /// Writes the recorded denials to `denials`, one per line.
+    fn report(&self, mut denials: File) {
+        let mut report = String::new();
+        for denial in &self.denials {
+            report.push_str(denial);
+            report.push('\n');
+        }
+        if self.omitted_denials > 0 {
+            report.push_str(&format!("{} more operations\n", self.omitted_denials));
+        }
+        let _ = denials.write_all(report.as_bytes());
+    }
+}
+
+/// Resolves the path at address `address` in the memory of process `pid`,
+/// relative to its descriptor `dirfd`, to an absolute path whose existing
+/// ancestors are canonical.
+fn resolve_path(pid: u32, dirfd: libc::c_int, address: u64) -> Option<PathBuf> {
+    let path = read_c_string(pid, address)?;
+    let path = Path::new(std::ffi::OsStr::from_bytes(path.as_bytes()));
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else if dirfd == libc::AT_FDCWD {
+        std::fs::read_link(format!("/proc/{pid}/cwd"))
+            .ok()?
+            .join(path)
+    } else {
+        std::fs::read_link(format!("/proc/{pid}/fd/{dirfd}"))
+            .ok()?
+            .join(path)
+    };
+
+    // The last component is kept as is: an operation on a symlink applies to
+    // the link itself.
+    let mut existing = path.parent()?;
+    let mut rest = vec![path.file_name()?];
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Some(
+                rest.iter()
+                    .rev()
+                    .fold(canonical, |path, name| path.join(name)),
+            );
+        }
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// Reads a NUL-terminated string of at most `PATH_MAX` bytes at `address` in
+/// the memory of process `pid`.
+fn read_c_string(pid: u32, address: u64) -> Option<CString> {
+    let memory = File::open(format!("/proc/{pid}/mem")).ok()?;
+    let mut bytes = Vec::new();
+    let mut chunk = [0u8; 256];
+    while bytes.len() < libc::PATH_MAX as usize {
+        // Reads stop at the end of the mapping, so read in small chunks.
+        let read = memory
+            .read_at(&mut chunk, address + bytes.len() as u64)
+            .ok()
+            .filter(|&read| read > 0)?;
+        if let Some(end) = chunk[..read].iter().position(|&byte| byte == 0) {
+            bytes.extend_from_slice(&chunk[..end]);
+            return CString::new(bytes).ok();
+        }
+        bytes.extend_from_slice(&chunk[..read]);
+    }
+    None
+}
+
+/// Waits for `pid` to exit and returns its wait status.
+fn wait_for_exit(pid: libc::pid_t) -> io::Result<libc::c_int> {
+    loop {
+        let mut status = 0;
+        // SAFETY: `status` is a valid out pointer.
+        if unsafe { libc::waitpid(pid, &mut status, 0) } != -1 {
+            return Ok(status);
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// The exit code for wait status `status`. A command killed by a signal is
+/// mirrored by killing this process with the same signal, so Codex sees the
+/// signal as if it had run the command directly.
+fn exit_code(status: libc::c_int) -> i32 {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        // SAFETY: plain signal calls; core dumps of this process are not
+        // wanted.
+        unsafe {
+            libc::prctl(libc::PR_SET_DUMPABLE, 0);
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        return 128 + signal;
+    }
+    libc::WEXITSTATUS(status)
+}
+
+fn check<T: Default + PartialOrd>(result: T) -> io::Result<T> {
+    if result < T::default() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
@@ -10,6 +10,7 @@ use codex_core::protocol::SandboxPolicy;
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
+use landlock::BitFlags;
 use landlock::CompatLevel;
 use landlock::Compatible;
 use landlock::Ruleset;
@@ -35,42 +36,127 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
         install_network_seccomp_filter_on_current_thread()?;
     }
 
-    if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy
+    apply_filesystem_policy_to_current_thread(sandbox_policy, cwd)
+}
+
+/// Applies the file-system part of `sandbox_policy` to the current thread
+/// with Landlock.
+pub(crate) fn apply_filesystem_policy_to_current_thread(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Result<()> {
+    if !sandbox_policy.has_full_disk_write_access() || !sandbox_policy.has_full_disk_read_access() {
+        let writable_roots: Vec<PathBuf> = sandbox_policy
             .get_writable_roots_with_cwd(cwd)
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
-        install_filesystem_landlock_rules_on_current_thread(writable_roots)?;
+        let denied_read_paths = resolve_denied_read_paths(sandbox_policy, cwd)?;
+        // Policies that Landlock cannot enforce run in the namespaces
+        // backend instead (see `requires_namespaces`); refuse them if they
+        // get here anyway.
+        if let Some(denied) = denied_read_path_in_writable_root(&writable_roots, &denied_read_paths)
+        {
+            return Err(CodexErr::Sandbox(SandboxErr::DenyRead {
+                path: denied.clone(),
+                reason: "it is inside a writable root, which Landlock cannot enforce".to_string(),
+            }));
+        }
+        install_filesystem_landlock_rules_on_current_thread(writable_roots, &denied_read_paths)?;
     }
 
-    // TODO(ragona): Add appropriate restrictions if
-    // `sandbox_policy.has_full_disk_read_access()` is `false`.
-
     Ok(())
 }
 
+/// Canonicalizes the paths that `sandbox_policy` denies reading.
+///
+/// Denied paths that do not exist are skipped, as there is nothing to hide
+/// yet; any other failure to resolve one is an error rather than silently
+/// leaving it readable.
+pub(crate) fn resolve_denied_read_paths(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> std::result::Result<Vec<PathBuf>, SandboxErr> {
+    sandbox_policy
+        .get_denied_read_paths_with_cwd(cwd)
+        .into_iter()
+        .filter_map(|path| match path.canonicalize() {
+            Ok(path) => Some(Ok(path)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => Some(Err(SandboxErr::DenyRead {
+                reason: format!("it cannot be resolved: {err}"),
+                path,
+            })),
+        })
+        .collect()
+}
+
+/// Whether `sandbox_policy` can only be enforced by the namespaces backend:
+/// Landlock can only grant access, so it cannot keep a denied path inside a
+/// writable root unreadable without also making files created next to it
+/// later unusable.
+pub(crate) fn requires_namespaces(sandbox_policy: &SandboxPolicy, cwd: &Path) -> bool {
+    if sandbox_policy.has_full_disk_write_access() {
+        return false;
+    }
+    let writable_roots: Vec<PathBuf> = sandbox_policy
+        .get_writable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(|writable_root| writable_root.root)
+        .collect();
+    resolve_denied_read_paths(sandbox_policy, cwd).is_ok_and(|denied_read_paths| {
+        denied_read_path_in_writable_root(&writable_roots, &denied_read_paths).is_some()
+    })
+}
+
+fn denied_read_path_in_writable_root<'a>(
+    writable_roots: &[PathBuf],
+    denied_read_paths: &'a [PathBuf],
+) -> Option<&'a PathBuf> {
+    denied_read_paths.iter().find(|denied| {
+        writable_roots.iter().any(|root| {
+            denied.starts_with(root.canonicalize().unwrap_or_else(|_| root.to_path_buf()))
+        })
+    })
+}
+
 /// Installs Landlock file-system rules on the current thread allowing read
-/// access to the entire file-system while restricting write access to
-/// `/dev/null` and the provided list of `writable_roots`.
+/// access to the entire file-system except `denied_read_paths`, while
+/// restricting write access to `/dev/null` and the provided list of
+/// `writable_roots`.
 ///
 /// # Errors
 /// Returns [`CodexErr::Sandbox`] variants when the ruleset fails to apply.
-fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathBuf>) -> Result<()> {
+fn install_filesystem_landlock_rules_on_current_thread(
+    writable_roots: Vec<PathBuf>,
+    denied_read_paths: &[PathBuf],
+) -> Result<()> {
     let abi = ABI::V5;
     let access_rw = AccessFs::from_all(abi);
     let access_ro = AccessFs::from_read(abi);
+    let access_dir = access_rw & !AccessFs::from_file(abi);
+
+    let mut rules = Vec::new();
+    collect_rules_excluding(
+        Path::new("/"),
+        access_ro,
+        access_dir,
+        denied_read_paths,
+        &mut rules,
+    );
+    for root in &writable_roots {
+        collect_rules_excluding(root, access_rw, access_dir, denied_read_paths, &mut rules);
+    }
 
     let mut ruleset = Ruleset::default()
         .set_compatibility(CompatLevel::BestEffort)
         .handle_access(access_rw)?
         .create()?
-        .add_rules(landlock::path_beneath_rules(&["/"], access_ro))?
         .add_rules(landlock::path_beneath_rules(&["/dev/null"], access_rw))?
         .set_no_new_privs(true);
 
-    if !writable_roots.is_empty() {
-        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&writable_roots, access_rw))?;
+    for (path, access) in rules {
+        ruleset = ruleset.add_rules(landlock::path_beneath_rules(&[path], access))?;
     }
 
     let status = ruleset.restrict_self()?;
@@ -82,37 +168,76 @@ fn install_filesystem_landlock_rules_on_current_thread(writable_roots: Vec<PathB
     Ok(())
 }
 
-/// Installs a seccomp filter that blocks outbound network access except for
-/// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
-    // Build rule map.
-    let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
+/// Collects the rules that grant `access` beneath `root` without granting
+/// anything beneath `denied_paths`, which must be canonical.
+///
+/// Landlock can only grant access, and a rule covers everything beneath its
+/// path. A directory that contains a denied path is therefore only granted
+/// `access` restricted to `dir_access` (listing, creating and removing
+/// entries), and each of its entries is considered in turn. Files created
+/// later directly inside such a directory could not be read or written, which
+/// is why denied paths inside writable roots need the namespaces backend.
+fn collect_rules_excluding(
+    root: &Path,
+    access: BitFlags<AccessFs>,
+    dir_access: BitFlags<AccessFs>,
+    denied_paths: &[PathBuf],
+    rules: &mut Vec<(PathBuf, BitFlags<AccessFs>)>,
+) {
+    // Symlinks are resolved so a link pointing into a denied path is not
+    // granted either.
+    let real_path = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    if denied_paths
+        .iter()
+        .any(|denied| real_path.starts_with(denied))
+    {
+        return;
+    }
+    if !denied_paths
+        .iter()
+        .any(|denied| denied.starts_with(&real_path))
+    {
+        rules.push((root.to_path_buf(), access));
+        return;
+    }
 
-    // Helper – insert unconditional deny rule for syscall number.
-    let mut deny_syscall = |nr: i64| {
-        rules.insert(nr, vec![]); // empty rule vec = unconditional match
+    rules.push((root.to_path_buf(), access & dir_access));
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return;
     };
+    for entry in entries.flatten() {
+        collect_rules_excluding(&entry.path(), access, dir_access, denied_paths, rules);
+    }
+}
 
-    deny_syscall(libc::SYS_connect);
-    deny_syscall(libc::SYS_accept);
-    deny_syscall(libc::SYS_accept4);
-    deny_syscall(libc::SYS_bind);
-    deny_syscall(libc::SYS_listen);
-    deny_syscall(libc::SYS_getpeername);
-    deny_syscall(libc::SYS_getsockname);
-    deny_syscall(libc::SYS_shutdown);
-    deny_syscall(libc::SYS_sendto);
-    deny_syscall(libc::SYS_sendmsg);
-    deny_syscall(libc::SYS_sendmmsg);
+/// Network related syscalls denied to sandboxed commands, with their names.
+/// `socket` and `socketpair` are only denied for domains other than AF_UNIX.
+pub(crate) const NETWORK_SYSCALLS: &[(i64, &str)] = &[
+    (libc::SYS_connect, "connect"),
+    (libc::SYS_accept, "accept"),
+    (libc::SYS_accept4, "accept4"),
+    (libc::SYS_bind, "bind"),
+    (libc::SYS_listen, "listen"),
+    (libc::SYS_getpeername, "getpeername"),
+    (libc::SYS_getsockname, "getsockname"),
+    (libc::SYS_shutdown, "shutdown"),
+    (libc::SYS_sendto, "sendto"),
+    (libc::SYS_sendmsg, "sendmsg"),
+    (libc::SYS_sendmmsg, "sendmmsg"),
     // NOTE: allowing recvfrom allows some tools like: `cargo clippy` to run
     // with their socketpair + child processes for sub-proc management
-    // deny_syscall(libc::SYS_recvfrom);
-    deny_syscall(libc::SYS_recvmsg);
-    deny_syscall(libc::SYS_recvmmsg);
-    deny_syscall(libc::SYS_getsockopt);
-    deny_syscall(libc::SYS_setsockopt);
-    deny_syscall(libc::SYS_ptrace);
+    (libc::SYS_recvmsg, "recvmsg"),
+    (libc::SYS_recvmmsg, "recvmmsg"),
+    (libc::SYS_getsockopt, "getsockopt"),
+    (libc::SYS_setsockopt, "setsockopt"),
+    (libc::SYS_ptrace, "ptrace"),
+    (libc::SYS_socket, "socket"),
+    (libc::SYS_socketpair, "socketpair"),
+];
 
+/// Seccomp rules matching the calls in [`NETWORK_SYSCALLS`].
+pub(crate) fn network_seccomp_rules()
+-> std::result::Result<BTreeMap<i64, Vec<SeccompRule>>, SandboxErr> {
     // For `socket` we allow AF_UNIX (arg0 == AF_UNIX) and deny everything else.
     let unix_only_rule = SeccompRule::new(vec![SeccompCondition::new(
         0, // first argument (domain)
@@ -121,20 +246,36 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         libc::AF_UNIX as u64,
     )?])?;
 
-    rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
-    rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
+    Ok(NETWORK_SYSCALLS
+        .iter()
+        .map(|&(nr, _)| {
+            if nr == libc::SYS_socket || nr == libc::SYS_socketpair {
+                (nr, vec![unix_only_rule.clone()])
+            } else {
+                (nr, vec![]) // empty rule vec = unconditional match
+            }
+        })
+        .collect())
+}
+
+pub(crate) fn seccomp_target_arch() -> TargetArch {
+    if cfg!(target_arch = "x86_64") {
+        TargetArch::x86_64
+    } else if cfg!(target_arch = "aarch64") {
+        TargetArch::aarch64
+    } else {
+        unimplemented!("unsupported architecture for seccomp filter");
+    }
+}
 
+/// Installs a seccomp filter that blocks outbound network access except for
+/// AF_UNIX domain sockets.
+fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
     let filter = SeccompFilter::new(
-        rules,
+        network_seccomp_rules()?,
         SeccompAction::Allow,                     // default – allow
         SeccompAction::Errno(libc::EPERM as u32), // when rule matches – return EPERM
-        if cfg!(target_arch = "x86_64") {
-            TargetArch::x86_64
-        } else if cfg!(target_arch = "aarch64") {
-            TargetArch::aarch64
-        } else {
-            unimplemented!("unsupported architecture for seccomp filter");
-        },
+        seccomp_target_arch(),
     )?;
 
     let prog: BpfProgram = filter.try_into()?;
@@ -1,7 +1,11 @@
 #[cfg(target_os = "linux")]
+mod denials;
+#[cfg(target_os = "linux")]
 mod landlock;
 #[cfg(target_os = "linux")]
 mod linux_run_main;
+#[cfg(target_os = "linux")]
+mod namespaces;
 
 #[cfg(target_os = "linux")]
 pub fn run_main() -> ! {
@@ -1,11 +1,27 @@
 use clap::Parser;
 use std::ffi::CString;
+use std::fs::File;
+use std::os::fd::FromRawFd;
+use std::os::fd::RawFd;
 use std::path::PathBuf;
 
+use crate::denials::run_command_reporting_denials;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::landlock::requires_namespaces;
+use crate::namespaces::run_command_in_namespaces;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Isolate the command with user, mount, PID and network namespaces
+    /// instead of Landlock and seccomp.
+    #[arg(long)]
+    pub namespaces: bool,
+
+    /// Descriptor to write the operations that the sandbox denied the
+    /// command to, one per line, once it exits.
+    #[arg(long)]
+    pub denials_fd: Option<RawFd>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,19 +35,58 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        namespaces,
+        denials_fd,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
-    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
-        panic!("error running landlock: {e:?}");
+    // The command must not inherit the descriptor, or it could report
+    // denials of its own making.
+    let denials = denials_fd.map(|fd| {
+        // SAFETY: Codex passes a descriptor that it opened for this process
+        // alone, and nothing else here uses it.
+        unsafe {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            File::from_raw_fd(fd)
+        }
+    });
+
+    // Only the namespaces backend can mount the overlay or hide denied paths
+    // inside writable roots.
+    if namespaces
+        || sandbox_policy.overlay_layer_dir().is_some()
+        || requires_namespaces(&sandbox_policy, &sandbox_policy_cwd)
+    {
+        match run_command_in_namespaces(&sandbox_policy, &sandbox_policy_cwd, &command) {
+            Ok(exit_code) => std::process::exit(exit_code),
+            Err(e) => panic!("error running namespaces sandbox: {e:?}"),
+        }
     }
 
     if command.is_empty() {
         panic!("No command specified to execute.");
     }
 
+    if let Some(denials) = denials {
+        match run_command_reporting_denials(&sandbox_policy, &sandbox_policy_cwd, &command, denials)
+        {
+            Ok(Some(exit_code)) => std::process::exit(exit_code),
+            Ok(None) => {}
+            Err(e) => panic!("error running sandbox: {e:?}"),
+        }
+    }
+
+    if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
+        panic!("error running landlock: {e:?}");
+    }
+
+    execvp_command(&command);
+}
+
+/// Replaces the current process with `command`.
+pub(crate) fn execvp_command(command: &[String]) -> ! {
     #[expect(clippy::expect_used)]
     let c_command =
         CString::new(command[0].as_str()).expect("Failed to convert command to CString");
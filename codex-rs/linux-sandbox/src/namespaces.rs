@@ -0,0 +1,580 @@
+//! Alternative to Landlock that isolates the command with Linux namespaces,
+//! in the spirit of bubblewrap.
+//!
+//! The helper unshares user, mount, PID, IPC and (unless the policy grants
+//! network access) network namespaces, then forks an init process that
+//! becomes PID 1 of the new PID namespace. Init builds a new root file system
+//! and forks the command:
+//!
+//! - the host file system is bind-mounted read-only,
+//! - writable roots are bind-mounted read-write on top, except for their
+//!   read-only subpaths,
+//! - `/tmp` and `/dev/shm` are fresh, empty tmpfs mounts,
+//! - `/proc` only shows the processes of the sandbox,
+//! - denied read paths are hidden behind an empty tmpfs or `/dev/null`,
+//! - under [`SandboxPolicy::Overlay`], the workspace is an overlay mount
+//!   whose writable upper directory is the policy's layer, with `.git`
+//!   bind-mounted read-only from the real workspace.
+//!
+//! When the command exits, init exits with its status, and the kernel kills
+//! everything else left in the PID namespace. Init also dies with the helper,
+//! so killing the helper on timeout kills the whole process tree.
+
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::overlay;
+use codex_core::protocol::SandboxPolicy;
+
+use crate::landlock::resolve_denied_read_paths;
+
+/// Where init mounts the new root before pivoting into it.
+const NEW_ROOT: &str = "/newroot";
+
+/// Where the host root is visible while the new root is being built.
+const OLD_ROOT: &str = "/oldroot";
+
+/// What the sandbox needs to know about the host, resolved before entering
+/// the namespaces.
+struct MountPlan {
+    writable_roots: Vec<(PathBuf, Vec<PathBuf>)>,
+    denied_read_paths: Vec<PathBuf>,
+    overlay: Option<OverlayMount>,
+    cwd: PathBuf,
+}
+
+/// The workspace and the directories of the layer mounted over it.
+struct OverlayMount {
+    workspace: PathBuf,
+    upper_dir: PathBuf,
+    work_dir: PathBuf,
+}
+
+/// Runs `command` in new namespaces according to `sandbox_policy` and exits
+/// with its status.
+pub(crate) fn run_command_in_namespaces(
+    sandbox_policy: &SandboxPolicy,
+    sandbox_policy_cwd: &Path,
+    command: &[String],
+) -> io::Result<i32> {
+    let canonicalize = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let plan = MountPlan {
+        writable_roots: sandbox_policy
+            .get_writable_roots_with_cwd(sandbox_policy_cwd)
+            .into_iter()
+            .map(|writable_root| {
+                (
+                    canonicalize(&writable_root.root),
+                    writable_root
+                        .read_only_subpaths
+                        .iter()
+                        .map(|path| canonicalize(path))
+                        .collect(),
+                )
+            })
+            .collect(),
+        denied_read_paths: resolve_denied_read_paths(sandbox_policy, sandbox_policy_cwd)
+            .map_err(io::Error::other)?,
+        overlay: match sandbox_policy.overlay_layer_dir() {
+            Some(layer_dir) => {
+                let upper_dir = overlay::upper_dir(layer_dir);
+                let work_dir = overlay::work_dir(layer_dir);
+                std::fs::create_dir_all(&upper_dir)?;
+                std::fs::create_dir_all(&work_dir)?;
+                Some(OverlayMount {
+                    workspace: sandbox_policy_cwd.canonicalize()?,
+                    upper_dir: upper_dir.canonicalize()?,
+                    work_dir: work_dir.canonicalize()?,
+                })
+            }
+            None => None,
+        },
+        cwd: std::env::current_dir()?,
+    };
+    let c_command = command
+        .iter()
+        .map(|arg| CString::new(arg.as_str()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+    // SAFETY: getuid and getgid cannot fail.
+    let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+    let mut flags =
+        libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID | libc::CLONE_NEWIPC;
+    if !sandbox_policy.has_full_network_access() {
+        flags |= libc::CLONE_NEWNET;
+    }
+    // SAFETY: plain syscall; the helper is single-threaded at this point.
+    check(unsafe { libc::unshare(flags) })?;
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))?;
+
+    // Init watches the read end to notice if the helper died before init
+    // could ask to be killed along with it.
+    let mut pipe_fds = [0; 2];
+    // SAFETY: `pipe_fds` has room for the two descriptors.
+    check(unsafe { libc::pipe2(pipe_fds.as_mut_ptr(), libc::O_CLOEXEC) })?;
+    let [read_fd, write_fd] = pipe_fds;
+
+    // SAFETY: the helper is single-threaded, so the child may keep running
+    // arbitrary code after fork.
+    let init_pid = check(unsafe { libc::fork() })?;
+    if init_pid == 0 {
+        // SAFETY: closing a descriptor owned by this process.
+        unsafe { libc::close(write_fd) };
+        let status = run_init(read_fd, &plan, &c_command).unwrap_or_else(|err| {
+            eprintln!("codex-linux-sandbox: failed to set up namespaces: {err}");
+            1
+        });
+        std::process::exit(status);
+    }
+
+    // SAFETY: closing a descriptor owned by this process.
+    unsafe { libc::close(read_fd) };
+    wait_for_exit_code(init_pid)
+}
+
+/// Body of PID 1: builds the new root, runs the command and returns its
+/// exit code.
+fn run_init(parent_fd: libc::c_int, plan: &MountPlan, command: &[CString]) -> io::Result<i32> {
+    // SAFETY: plain prctl and poll calls on values owned by this process.
+    unsafe {
+        check(libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL, 0, 0, 0))?;
+        let mut poll_fd = libc::pollfd {
+            fd: parent_fd,
+            events: 0,
+            revents: 0,
+        };
+        if libc::poll(&mut poll_fd, 1, 0) > 0 && poll_fd.revents & libc::POLLHUP != 0 {
+            return Err(io::Error::other("codex-linux-sandbox exited"));
+        }
+    }
+
+    set_up_new_root(plan)?;
+
+    // SAFETY: init is single-threaded.
+    let command_pid = check(unsafe { libc::fork() })?;
+    if command_pid == 0 {
+        let err = exec_command(&plan.cwd, command);
+        eprintln!("codex-linux-sandbox: failed to run {command:?}: {err}");
+        std::process::exit(127);
+    }
+
+    wait_for_exit_code(command_pid)
+}
+
+/// Builds the sandbox's view of the file system and makes it the root.
+fn set_up_new_root(plan: &MountPlan) -> io::Result<()> {
+    let new_root = Path::new(NEW_ROOT);
+    let under_new_root = |path: &Path| new_root.join(path.strip_prefix("/").unwrap_or(path));
+    let under_old_root =
+        |path: &Path| Path::new(OLD_ROOT).join(path.strip_prefix("/").unwrap_or(path));
+
+    // Keep the mounts below from propagating back to the host.
+    mount(
+        None,
+        Path::new("/"),
+        None,
+        libc::MS_REC | libc::MS_PRIVATE,
+        None,
+    )?;
+
+    // Build the new root on a scratch tmpfs with the host root moved aside,
+    // so every host path stays reachable under `OLD_ROOT`, including the
+    // host's `/tmp`.
+    mount(
+        Some(Path::new("tmpfs")),
+        Path::new("/tmp"),
+        Some("tmpfs"),
+        libc::MS_NOSUID | libc::MS_NODEV,
+        Some("mode=0700"),
+    )?;
+    std::env::set_current_dir("/tmp")?;
+    std::fs::create_dir("newroot")?;
+    std::fs::create_dir("oldroot")?;
+    pivot_root(Path::new("."), Path::new("oldroot"))?;
+    std::env::set_current_dir("/")?;
+
+    mount(
+        Some(Path::new(OLD_ROOT)),
+        new_root,
+        None,
+        libc::MS_BIND | libc::MS_REC,
+        None,
+    )?;
+    remount_read_only(new_root)?;
+
+    for (target, mode) in [("tmp", "mode=1777"), ("dev/shm", "mode=1777")] {
+        let target = new_root.join(target);
+        if target.is_dir() {
+            mount(
+                Some(Path::new("tmpfs")),
+                &target,
+                Some("tmpfs"),
+                libc::MS_NOSUID | libc::MS_NODEV,
+                Some(mode),
+            )?;
+        }
+    }
+    mount(
+        Some(Path::new("proc")),
+        &new_root.join("proc"),
+        Some("proc"),
+        libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+        None,
+    )?;
+
+    for (root, read_only_subpaths) in &plan.writable_roots {
+        // `/tmp` itself is already a writable tmpfs.
+        if root == Path::new("/tmp") {
+            continue;
+        }
+        let source = under_old_root(root);
+        if !source.is_dir() {
+            continue;
+        }
+        let target = under_new_root(root);
+        std::fs::create_dir_all(&target)?;
+        mount(
+            Some(&source),
+            &target,
+            None,
+            libc::MS_BIND | libc::MS_REC,
+            None,
+        )?;
+        for subpath in read_only_subpaths {
+            let target = under_new_root(subpath);
+            if target.exists() {
+                mount(
+                    Some(&target),
+                    &target,
+                    None,
+                    libc::MS_BIND | libc::MS_REC,
+                    None,
+                )?;
+                remount_read_only(&target)?;
+            }
+        }
+    }
+
+    if let Some(overlay) = &plan.overlay {
+        let target = under_new_root(&overlay.workspace);
+        // A workspace in the host's `/tmp` has no mount point yet.
+        if !target.exists() {
+            std::fs::create_dir_all(&target)?;
+        }
+        let options = format!(
+            "lowerdir={},upperdir={},workdir={},userxattr",
+            escape_overlay_option(&under_old_root(&overlay.workspace)),
+            escape_overlay_option(&under_old_root(&overlay.upper_dir)),
+            escape_overlay_option(&under_old_root(&overlay.work_dir)),
+        );
+        mount(
+            Some(Path::new("overlay")),
+            &target,
+            Some("overlay"),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            Some(&options),
+        )?;
+        let git_dir = overlay.workspace.join(".git");
+        if under_old_root(&git_dir).exists() {
+            let target = under_new_root(&git_dir);
+            mount(
+                Some(&under_old_root(&git_dir)),
+                &target,
+                None,
+                libc::MS_BIND | libc::MS_REC,
+                None,
+            )?;
+            remount_read_only(&target)?;
+        }
+    }
+
+    // The working directory must exist even when it lives in the host's
+    // `/tmp`, which the sandbox does not otherwise see.
+    let cwd = under_new_root(&plan.cwd);
+    if !cwd.exists() && under_old_root(&plan.cwd).is_dir() {
+        std::fs::create_dir_all(&cwd)?;
+        mount(
+            Some(&under_old_root(&plan.cwd)),
+            &cwd,
+            None,
+            libc::MS_BIND | libc::MS_REC,
+            None,
+        )?;
+        remount_read_only(&cwd)?;
+    }
+
+    for denied in &plan.denied_read_paths {
+        let target = under_new_root(denied);
+        if target.is_dir() {
+            mount(
+                Some(Path::new("tmpfs")),
+                &target,
+                Some("tmpfs"),
+                libc::MS_RDONLY | libc::MS_NOSUID | libc::MS_NODEV,
+                Some("mode=0"),
+            )?;
+        } else if target.exists() {
+            mount(
+                Some(&new_root.join("dev/null")),
+                &target,
+                None,
+                libc::MS_BIND,
+                None,
+            )?;
+        }
+    }
+
+    // Make the new root `/` and drop the host root, including the scratch
+    // tmpfs it hangs off.
+    std::env::set_current_dir(new_root)?;
+    pivot_root(Path::new("."), Path::new("."))?;
+    umount_detach(Path::new("."))?;
+    std::env::set_current_dir("/")?;
+    Ok(())
+}
+
+/// Drops every capability, then runs `command` in `cwd`. Only returns on
+/// failure.
+fn exec_command(cwd: &Path, command: &[CString]) -> io::Error {
+    if let Err(err) = drop_capabilities() {
+        return err;
+    }
+    if let Err(err) = std::env::set_current_dir(cwd) {
+        return err;
+    }
+    let Some(program) = command.first() else {
+        return io::Error::new(io::ErrorKind::InvalidInput, "no command specified");
+    };
+    let mut argv: Vec<*const libc::c_char> = command.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(std::ptr::null());
+    // SAFETY: `argv` is a null-terminated array of pointers into `command`,
+    // which outlives the call.
+    unsafe { libc::execvp(program.as_ptr(), argv.as_ptr()) };
+    io::Error::last_os_error()
+}
+
+/// The helper may run as root, which is mapped to root inside the user
+/// namespace. Clearing the bounding set keeps the command from regaining
+/// capabilities on exec, so it cannot undo the mounts that confine it.
+fn drop_capabilities() -> io::Result<()> {
+    #[repr(C)]
+    struct CapUserHeader {
+        version: u32,
+        pid: libc::c_int,
+    }
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct CapUserData {
+        effective: u32,
+        permitted: u32,
+        inheritable: u32,
+    }
+    const LINUX_CAPABILITY_VERSION_3: u32 = 0x2008_0522;
+
+    // SAFETY: plain prctl and capset calls; `header` and `data` match the
+    // kernel's layout for capability version 3.
+    unsafe {
+        // Capabilities the kernel does not know about fail with EINVAL.
+        for capability in 0..64 {
+            libc::prctl(libc::PR_CAPBSET_DROP, capability, 0, 0, 0);
+        }
+        check(libc::prctl(
+            libc::PR_CAP_AMBIENT,
+            libc::PR_CAP_AMBIENT_CLEAR_ALL,
+            0,
+            0,
+            0,
+        ))?;
+        let header = CapUserHeader {
+            version: LINUX_CAPABILITY_VERSION_3,
+            pid: 0,
+        };
+        let data = [CapUserData::default(); 2];
+        check(libc::syscall(libc::SYS_capset, &header, data.as_ptr()) as libc::c_int)?;
+        check(libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0))?;
+    }
+    Ok(())
+}
+
+/// Makes the mount at `target` and every mount beneath it read-only.
+fn remount_read_only(target: &Path) -> io::Result<()> {
+    // Mounts are only remounted while the host's `/proc` is still visible
+    // under `OLD_ROOT`.
+    let mountinfo = std::fs::read_to_string(Path::new(OLD_ROOT).join("proc/self/mountinfo"))?;
+    let mut mounts: Vec<(PathBuf, libc::c_ulong)> = mountinfo
+        .lines()
+        .filter_map(|line| {
+            // Fields: id, parent id, major:minor, root, mount point, options.
+            let mut fields = line.split(' ');
+            let mount_point = PathBuf::from(unescape_mountinfo(fields.nth(4)?));
+            let options = fields.next()?;
+            Some((mount_point, locked_mount_flags(options)))
+        })
+        .filter(|(mount_point, _)| mount_point.starts_with(target))
+        .collect();
+    mounts.sort();
+    mounts.dedup_by(|a, b| a.0 == b.0);
+
+    for (mount_point, flags) in mounts {
+        let result = mount(
+            None,
+            &mount_point,
+            None,
+            libc::MS_REMOUNT | libc::MS_BIND | libc::MS_RDONLY | flags,
+            None,
+        );
+        match result {
+            Ok(()) => {}
+            // Kernel file systems such as sysfs and binfmt_misc may refuse to
+            // be remounted. They are replaced (`/proc`) or owned by the real
+            // root user, so the command cannot write to them anyway.
+            Err(_) if is_kernel_mount(target, &mount_point) => {}
+            Err(err) => {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to remount {} read-only: {err}",
+                        mount_point.display()
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_kernel_mount(target: &Path, mount_point: &Path) -> bool {
+    ["proc", "sys", "dev"]
+        .iter()
+        .any(|dir| mount_point.starts_with(target.join(dir)))
+}
+
+/// Flags of a mount that an unprivileged user namespace is not allowed to
+/// clear, and so must be repeated when remounting it.
+fn locked_mount_flags(options: &str) -> libc::c_ulong {
+    options
+        .split(',')
+        .map(|option| match option {
+            "nosuid" => libc::MS_NOSUID,
+            "nodev" => libc::MS_NODEV,
+            "noexec" => libc::MS_NOEXEC,
+            "noatime" => libc::MS_NOATIME,
+            "nodiratime" => libc::MS_NODIRATIME,
+            "relatime" => libc::MS_RELATIME,
+            _ => 0,
+        })
+        .fold(0, |flags, flag| flags | flag)
+}
+
+/// Mount points in mountinfo escape spaces, tabs, newlines and backslashes
+/// as octal.
+fn unescape_mountinfo(field: &str) -> String {
+    field
+        .replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+/// Escapes the characters that separate overlay mount options and lower
+/// directories.
+fn escape_overlay_option(path: &Path) -> String {
+    let mut escaped = String::new();
+    for ch in path.to_string_lossy().chars() {
+        if matches!(ch, '\\' | ',' | ':') {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+fn mount(
+    source: Option<&Path>,
+    target: &Path,
+    fstype: Option<&str>,
+    flags: libc::c_ulong,
+    data: Option<&str>,
+) -> io::Result<()> {
+    let source = source.map(c_path).transpose()?;
+    let target = c_path(target)?;
+    let fstype = fstype.map(CString::new).transpose()?;
+    let data = data.map(CString::new).transpose()?;
+    let as_ptr = |s: &Option<CString>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+    // SAFETY: every pointer is either null or a valid C string that outlives
+    // the call.
+    check(unsafe {
+        libc::mount(
+            as_ptr(&source),
+            target.as_ptr(),
+            as_ptr(&fstype),
+            flags,
+            as_ptr(&data).cast(),
+        )
+    })?;
+    Ok(())
+}
+
+fn pivot_root(new_root: &Path, put_old: &Path) -> io::Result<()> {
+    let new_root = c_path(new_root)?;
+    let put_old = c_path(put_old)?;
+    // SAFETY: both pointers are valid C strings that outlive the call.
+    check(
+        unsafe { libc::syscall(libc::SYS_pivot_root, new_root.as_ptr(), put_old.as_ptr()) }
+            as libc::c_int,
+    )?;
+    Ok(())
+}
+
+fn umount_detach(target: &Path) -> io::Result<()> {
+    let target = c_path(target)?;
+    // SAFETY: `target` is a valid C string that outlives the call.
+    check(unsafe { libc::umount2(target.as_ptr(), libc::MNT_DETACH) })?;
+    Ok(())
+}
+
+fn c_path(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+/// Waits for `pid` to exit and returns its exit code, or 128 plus the
+/// signal that killed it, as shells do.
+fn wait_for_exit_code(pid: libc::pid_t) -> io::Result<i32> {
+    loop {
+        let mut status = 0;
+        // SAFETY: `status` is a valid out pointer.
+        let waited = unsafe { libc::waitpid(-1, &mut status, 0) };
+        if waited == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err);
+        }
+        // As PID 1, init also reaps orphans of the command.
+        if waited != pid {
+            continue;
+        }
+        if libc::WIFEXITED(status) {
+            return Ok(libc::WEXITSTATUS(status));
+        }
+        if libc::WIFSIGNALED(status) {
+            return Ok(128 + libc::WTERMSIG(status));
+        }
+    }
+}
+
+fn check<T: Default + PartialOrd>(result: T) -> io::Result<T> {
+    if result < T::default() {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(result)
+    }
+}
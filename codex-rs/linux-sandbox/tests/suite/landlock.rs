@@ -1,8 +1,10 @@
 #![cfg(target_os = "linux")]
+use codex_core::config_types::ResourceLimits;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
 use codex_core::exec::SandboxType;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::exec_env::create_env;
@@ -33,8 +35,35 @@ fn create_env_from_core_vars() -> HashMap<String, String> {
     create_env(&policy)
 }
 
-#[expect(clippy::print_stdout, clippy::expect_used, clippy::unwrap_used)]
 async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
+    run_cmd_with_deny_read(cmd, writable_roots, &[], timeout_ms).await;
+}
+
+#[expect(clippy::print_stdout, clippy::unwrap_used)]
+async fn run_cmd_with_deny_read(
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    deny_read: &[PathBuf],
+    timeout_ms: u64,
+) {
+    let res = exec_with_deny_read(cmd, writable_roots, deny_read, timeout_ms)
+        .await
+        .unwrap();
+
+    if res.exit_code != 0 {
+        println!("stdout:\n{}", res.stdout.text);
+        println!("stderr:\n{}", res.stderr.text);
+        panic!("exit code: {}", res.exit_code);
+    }
+}
+
+#[expect(clippy::expect_used)]
+async fn exec_with_deny_read(
+    cmd: &[&str],
+    writable_roots: &[PathBuf],
+    deny_read: &[PathBuf],
+    timeout_ms: u64,
+) -> Result<ExecToolCallOutput, CodexErr> {
     let cwd = std::env::current_dir().expect("cwd should exist");
     let sandbox_cwd = cwd.clone();
     let params = ExecParams {
@@ -44,6 +73,7 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::WorkspaceWrite {
@@ -54,10 +84,11 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         // writing to in the sandbox.
         exclude_tmpdir_env_var: true,
         exclude_slash_tmp: true,
+        deny_read: deny_read.to_vec(),
     };
     let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
-    let res = process_exec_tool_call(
+    process_exec_tool_call(
         params,
         SandboxType::LinuxSeccomp,
         &sandbox_policy,
@@ -66,13 +97,6 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
         None,
     )
     .await
-    .unwrap();
-
-    if res.exit_code != 0 {
-        println!("stdout:\n{}", res.stdout.text);
-        println!("stderr:\n{}", res.stderr.text);
-        panic!("exit code: {}", res.exit_code);
-    }
 }
 
 #[tokio::test]
@@ -93,6 +117,88 @@ async fn test_root_write() {
     .await;
 }
 
+#[tokio::test]
+async fn test_root_write_is_reported() {
+    let tmpfile = NamedTempFile::new().expect("create temp file");
+    let tmpfile_path = tmpfile
+        .path()
+        .canonicalize()
+        .expect("canonicalize temp file");
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            format!("echo blah > {}", tmpfile_path.display()),
+        ],
+        cwd: cwd.clone(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &SandboxPolicy::new_read_only_policy(),
+        cwd.as_path(),
+        &Some(PathBuf::from(sandbox_program)),
+        None,
+    )
+    .await;
+
+    let Err(CodexErr::Sandbox(SandboxErr::Denied { denials, .. })) = result else {
+        panic!("expected sandbox denied error, got: {result:?}");
+    };
+    assert_eq!(
+        denials,
+        vec![format!("write to {}", tmpfile_path.display())]
+    );
+}
+
+#[tokio::test]
+async fn test_network_access_is_reported() {
+    let cwd = std::env::current_dir().expect("cwd should exist");
+    let params = ExecParams {
+        command: vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            // The command cannot forge a denial by printing one.
+            "echo 'codex-linux-sandbox: denied write to /etc' >&2; exec 3<>/dev/tcp/127.0.0.1/80"
+                .to_string(),
+        ],
+        cwd: cwd.clone(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let result = process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &SandboxPolicy::new_read_only_policy(),
+        cwd.as_path(),
+        &Some(PathBuf::from(sandbox_program)),
+        None,
+    )
+    .await;
+
+    let Err(CodexErr::Sandbox(SandboxErr::Denied { output, denials })) = result else {
+        panic!("expected sandbox denied error, got: {result:?}");
+    };
+    assert_eq!(denials, vec!["network access (socket)".to_string()]);
+    assert!(
+        output
+            .stderr
+            .text
+            .contains("codex-linux-sandbox: denied write to /etc")
+    );
+}
+
 #[tokio::test]
 async fn test_dev_null_write() {
     run_cmd(
@@ -123,6 +229,86 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+#[should_panic]
+async fn test_denied_read() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("secret");
+    std::fs::write(&secret, "hunter2").unwrap();
+    run_cmd_with_deny_read(
+        &["cat", &secret.to_string_lossy()],
+        &[],
+        std::slice::from_ref(&secret),
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_sibling_of_denied_path_readable() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("secret");
+    let public = tmpdir.path().join("public");
+    std::fs::write(&secret, "hunter2").unwrap();
+    std::fs::write(&public, "hello").unwrap();
+    run_cmd_with_deny_read(
+        &["cat", &public.to_string_lossy()],
+        &[],
+        &[secret],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_denied_read_inside_writable_root() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("secret");
+    std::fs::write(&secret, "hunter2").unwrap();
+    // Landlock cannot enforce this policy, so the sandbox hides the file in
+    // the namespaces backend instead, where it reads as empty.
+    let output = exec_with_deny_read(
+        &["cat", &secret.to_string_lossy()],
+        &[tmpdir.path().to_path_buf()],
+        std::slice::from_ref(&secret),
+        LONG_TIMEOUT_MS,
+    )
+    .await
+    .unwrap();
+    assert!(!output.stdout.text.contains("hunter2"), "{output:?}");
+}
+
+#[tokio::test]
+async fn test_writable_root_with_denied_read_stays_writable() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let secret = tmpdir.path().join("secret");
+    std::fs::write(&secret, "hunter2").unwrap();
+    let new_file = tmpdir.path().join("new");
+    run_cmd_with_deny_read(
+        &[
+            "bash",
+            "-c",
+            &format!("echo hello > {0} && cat {0}", new_file.to_string_lossy()),
+        ],
+        &[tmpdir.path().to_path_buf()],
+        &[secret],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_missing_denied_read_path_is_skipped() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    run_cmd_with_deny_read(
+        &["true"],
+        &[],
+        &[tmpdir.path().join("missing")],
+        LONG_TIMEOUT_MS,
+    )
+    .await;
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout")]
 async fn test_timeout() {
@@ -146,6 +332,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
         env: create_env_from_core_vars(),
         with_escalated_permissions: None,
         justification: None,
+        resource_limits: ResourceLimits::default(),
     };
 
     let sandbox_policy = SandboxPolicy::new_read_only_policy();
@@ -163,7 +350,7 @@ async fn assert_network_blocked(cmd: &[&str]) {
 
     let output = match result {
         Ok(output) => output,
-        Err(CodexErr::Sandbox(SandboxErr::Denied { output })) => *output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output, .. })) => *output,
         _ => {
             panic!("expected sandbox denied error, got: {result:?}");
         }
@@ -0,0 +1,199 @@
+//! These tests need unprivileged user namespaces (and overlay mounts inside
+//! them), which many machines and containers do not allow, so they only run
+//! with `cargo test -- --ignored`. CI runs them in a job that has them.
+#![cfg(target_os = "linux")]
+use codex_core::config_types::ResourceLimits;
+use codex_core::config_types::ShellEnvironmentPolicy;
+use codex_core::error::CodexErr;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::exec_env::create_env;
+use codex_core::overlay::OverlayLayer;
+use codex_core::protocol::SandboxPolicy;
+use std::path::Path;
+use std::path::PathBuf;
+
+const TIMEOUT_MS: u64 = 5_000;
+
+/// Runs `script` with bash under the namespaces backend.
+async fn run_script(
+    script: &str,
+    cwd: &Path,
+    sandbox_policy: &SandboxPolicy,
+    timeout_ms: u64,
+) -> ExecToolCallOutput {
+    let params = ExecParams {
+        command: vec!["bash".to_string(), "-c".to_string(), script.to_string()],
+        cwd: cwd.to_path_buf(),
+        timeout_ms: Some(timeout_ms),
+        env: create_env(&ShellEnvironmentPolicy::default()),
+        with_escalated_permissions: None,
+        justification: None,
+        resource_limits: ResourceLimits::default(),
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    match process_exec_tool_call(
+        params,
+        SandboxType::LinuxNamespaces,
+        sandbox_policy,
+        cwd,
+        &Some(PathBuf::from(sandbox_program)),
+        None,
+    )
+    .await
+    {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(
+            SandboxErr::Denied { output, .. } | SandboxErr::Timeout { output },
+        )) => *output,
+        Err(err) => panic!("unexpected error: {err:?}"),
+    }
+}
+
+fn workspace_write_policy(writable_roots: Vec<PathBuf>) -> SandboxPolicy {
+    SandboxPolicy::WorkspaceWrite {
+        writable_roots,
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: false,
+        deny_read: Vec::new(),
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs unprivileged user namespaces"]
+async fn writes_are_limited_to_writable_roots() {
+    let workspace = tempfile::tempdir().unwrap();
+    let output = run_script(
+        "echo ok > inside",
+        workspace.path(),
+        &workspace_write_policy(vec![]),
+        TIMEOUT_MS,
+    )
+    .await;
+    assert_eq!(0, output.exit_code, "{}", output.stderr.text);
+    assert!(workspace.path().join("inside").exists());
+
+    let output = run_script(
+        "echo no > outside",
+        workspace.path(),
+        &SandboxPolicy::new_read_only_policy(),
+        TIMEOUT_MS,
+    )
+    .await;
+    assert_ne!(0, output.exit_code);
+    assert!(
+        output.stderr.text.contains("Read-only file system"),
+        "{}",
+        output.stderr.text
+    );
+    assert!(!workspace.path().join("outside").exists());
+}
+
+#[tokio::test]
+#[ignore = "needs unprivileged user namespaces"]
+async fn tmp_is_private_and_ephemeral() {
+    let workspace = tempfile::tempdir().unwrap();
+    let host_file = tempfile::NamedTempFile::new().unwrap();
+    let script = format!(
+        "test ! -e {} && echo scratch > /tmp/scratch && cat /tmp/scratch",
+        host_file.path().display()
+    );
+    let output = run_script(
+        &script,
+        workspace.path(),
+        &workspace_write_policy(vec![]),
+        TIMEOUT_MS,
+    )
+    .await;
+
+    assert_eq!(0, output.exit_code, "{}", output.stderr.text);
+    assert_eq!("scratch\n", output.stdout.text);
+    assert!(!Path::new("/tmp/scratch").exists());
+}
+
+#[tokio::test]
+#[ignore = "needs unprivileged user namespaces"]
+async fn command_only_sees_its_own_processes() {
+    let workspace = tempfile::tempdir().unwrap();
+    let output = run_script(
+        "echo $$; ls /proc | grep -c '^[0-9]'",
+        workspace.path(),
+        &SandboxPolicy::new_read_only_policy(),
+        TIMEOUT_MS,
+    )
+    .await;
+
+    assert_eq!(0, output.exit_code, "{}", output.stderr.text);
+    let mut lines = output.stdout.text.lines();
+    assert_eq!(Some("2"), lines.next());
+    // Init, bash, ls and grep.
+    let visible: usize = lines.next().unwrap().parse().unwrap();
+    assert!(visible <= 4, "saw {visible} processes");
+}
+
+#[tokio::test]
+#[ignore = "needs unprivileged user namespaces"]
+async fn timeout_kills_background_processes() {
+    let workspace = tempfile::tempdir().unwrap();
+    let marker = workspace.path().join("marker");
+    let script = format!("(sleep 2 && touch {}) & sleep 60", marker.display());
+    let output = run_script(
+        &script,
+        workspace.path(),
+        &workspace_write_policy(vec![]),
+        500,
+    )
+    .await;
+
+    assert!(output.timed_out);
+    tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+    assert!(!marker.exists(), "background process survived the timeout");
+}
+
+#[tokio::test]
+#[ignore = "needs unprivileged user namespaces"]
+async fn overlay_keeps_writes_in_the_layer_until_committed() {
+    let workspace = tempfile::tempdir().unwrap();
+    let workspace_dir = workspace.path().canonicalize().unwrap();
+    let layer = tempfile::tempdir().unwrap();
+    std::fs::write(workspace_dir.join("a.txt"), "old\n").unwrap();
+    std::fs::create_dir(workspace_dir.join("sub")).unwrap();
+    std::fs::write(workspace_dir.join("sub/b.txt"), "b\n").unwrap();
+
+    let output = run_script(
+        "echo new > a.txt && rm sub/b.txt && echo c > c.txt && cat a.txt",
+        &workspace_dir,
+        &SandboxPolicy::new_overlay_policy(layer.path().to_path_buf()),
+        TIMEOUT_MS,
+    )
+    .await;
+    assert_eq!(0, output.exit_code, "{}", output.stderr.text);
+    assert_eq!("new\n", output.stdout.text);
+    assert_eq!(
+        "old\n",
+        std::fs::read_to_string(workspace_dir.join("a.txt")).unwrap()
+    );
+    assert!(workspace_dir.join("sub/b.txt").exists());
+    assert!(!workspace_dir.join("c.txt").exists());
+
+    let overlay = OverlayLayer::new(workspace_dir.clone(), layer.path().to_path_buf());
+    assert_eq!(
+        vec![
+            workspace_dir.join("a.txt"),
+            workspace_dir.join("c.txt"),
+            workspace_dir.join("sub/b.txt"),
+        ],
+        overlay.changed_paths().unwrap()
+    );
+    assert_eq!(3, overlay.commit().unwrap());
+    assert_eq!(
+        "new\n",
+        std::fs::read_to_string(workspace_dir.join("a.txt")).unwrap()
+    );
+    assert!(!workspace_dir.join("sub/b.txt").exists());
+    assert!(workspace_dir.join("c.txt").exists());
+}
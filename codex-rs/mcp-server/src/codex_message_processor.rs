@@ -1,3 +1,4 @@
+use crate::conversation_events::ConversationEventHub;
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::json_to_toml::json_to_toml;
@@ -76,6 +77,8 @@ use codex_protocol::mcp_protocol::SendUserTurnResponse;
 use codex_protocol::mcp_protocol::ServerNotification;
 use codex_protocol::mcp_protocol::SetDefaultModelParams;
 use codex_protocol::mcp_protocol::SetDefaultModelResponse;
+use codex_protocol::mcp_protocol::SteerConversationParams;
+use codex_protocol::mcp_protocol::SteerConversationResponse;
 use codex_protocol::mcp_protocol::UserInfoResponse;
 use codex_protocol::mcp_protocol::UserSavedConfig;
 use codex_protocol::models::ContentItem;
@@ -91,6 +94,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
 use tokio::sync::oneshot;
 use tracing::error;
 use tracing::info;
@@ -115,6 +119,7 @@ impl ActiveLogin {
 pub(crate) struct CodexMessageProcessor {
     auth_manager: Arc<AuthManager>,
     conversation_manager: Arc<ConversationManager>,
+    event_hub: Arc<ConversationEventHub>,
     outgoing: Arc<OutgoingMessageSender>,
     codex_linux_sandbox_exe: Option<PathBuf>,
     config: Arc<Config>,
@@ -128,6 +133,7 @@ impl CodexMessageProcessor {
     pub fn new(
         auth_manager: Arc<AuthManager>,
         conversation_manager: Arc<ConversationManager>,
+        event_hub: Arc<ConversationEventHub>,
         outgoing: Arc<OutgoingMessageSender>,
         codex_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
@@ -135,6 +141,7 @@ impl CodexMessageProcessor {
         Self {
             auth_manager,
             conversation_manager,
+            event_hub,
             outgoing,
             codex_linux_sandbox_exe,
             config,
@@ -170,6 +177,9 @@ impl CodexMessageProcessor {
             ClientRequest::InterruptConversation { request_id, params } => {
                 self.interrupt_conversation(request_id, params).await;
             }
+            ClientRequest::SteerConversation { request_id, params } => {
+                self.steer_conversation(request_id, params).await;
+            }
             ClientRequest::AddConversationListener { request_id, params } => {
                 self.add_conversation_listener(request_id, params).await;
             }
@@ -573,6 +583,7 @@ impl CodexMessageProcessor {
             env,
             with_escalated_permissions: None,
             justification: None,
+            resource_limits: self.config.sandbox_resource_limits,
         };
 
         let effective_policy = params
@@ -583,7 +594,9 @@ impl CodexMessageProcessor {
             codex_core::protocol::SandboxPolicy::DangerFullAccess => {
                 codex_core::exec::SandboxType::None
             }
-            _ => get_platform_sandbox().unwrap_or(codex_core::exec::SandboxType::None),
+            _ => get_platform_sandbox()
+                .unwrap_or(codex_core::exec::SandboxType::None)
+                .with_linux_backend(self.config.linux_sandbox_backend),
         };
         tracing::debug!("Sandbox type: {sandbox_type:?}");
         let codex_linux_sandbox_exe = self.config.codex_linux_sandbox_exe.clone();
@@ -943,19 +956,10 @@ impl CodexMessageProcessor {
             return;
         };
 
-        let mapped_items: Vec<CoreInputItem> = items
-            .into_iter()
-            .map(|item| match item {
-                WireInputItem::Text { text } => CoreInputItem::Text { text },
-                WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
-                WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
-            })
-            .collect();
-
         // Submit user input to the conversation.
         let _ = conversation
             .submit(Op::UserInput {
-                items: mapped_items,
+                items: map_input_items(items),
             })
             .await;
 
@@ -965,6 +969,36 @@ impl CodexMessageProcessor {
             .await;
     }
 
+    async fn steer_conversation(&self, request_id: RequestId, params: SteerConversationParams) {
+        let SteerConversationParams {
+            conversation_id,
+            items,
+        } = params;
+        let Ok(conversation) = self
+            .conversation_manager
+            .get_conversation(conversation_id)
+            .await
+        else {
+            let error = JSONRPCErrorError {
+                code: INVALID_REQUEST_ERROR_CODE,
+                message: format!("conversation not found: {conversation_id}"),
+                data: None,
+            };
+            self.outgoing.send_error(request_id, error).await;
+            return;
+        };
+
+        let _ = conversation
+            .submit(Op::Steer {
+                items: map_input_items(items),
+            })
+            .await;
+
+        self.outgoing
+            .send_response(request_id, SteerConversationResponse {})
+            .await;
+    }
+
     async fn send_user_turn(&self, request_id: RequestId, params: SendUserTurnParams) {
         let SendUserTurnParams {
             conversation_id,
@@ -991,18 +1025,9 @@ impl CodexMessageProcessor {
             return;
         };
 
-        let mapped_items: Vec<CoreInputItem> = items
-            .into_iter()
-            .map(|item| match item {
-                WireInputItem::Text { text } => CoreInputItem::Text { text },
-                WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
-                WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
-            })
-            .collect();
-
         let _ = conversation
             .submit(Op::UserTurn {
-                items: mapped_items,
+                items: map_input_items(items),
                 cwd,
                 approval_policy,
                 sandbox_policy,
@@ -1068,6 +1093,10 @@ impl CodexMessageProcessor {
             return;
         };
 
+        let mut events = self
+            .event_hub
+            .subscribe(conversation_id, conversation.clone())
+            .await;
         let subscription_id = Uuid::new_v4();
         let (cancel_tx, mut cancel_rx) = oneshot::channel();
         self.conversation_listeners
@@ -1081,13 +1110,14 @@ impl CodexMessageProcessor {
                         // User has unsubscribed, so exit this task.
                         break;
                     }
-                    event = conversation.next_event() => {
+                    event = events.recv() => {
                         let event = match event {
                             Ok(event) => event,
-                            Err(err) => {
-                                tracing::warn!("conversation.next_event() failed with: {err}");
-                                break;
+                            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                                tracing::warn!("conversation listener fell behind; skipped {skipped} events");
+                                continue;
                             }
+                            Err(broadcast::error::RecvError::Closed) => break,
                         };
 
                         // For now, we send a notification for every event,
@@ -1243,6 +1273,17 @@ async fn apply_bespoke_event_handling(
     }
 }
 
+fn map_input_items(items: Vec<WireInputItem>) -> Vec<CoreInputItem> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            WireInputItem::Text { text } => CoreInputItem::Text { text },
+            WireInputItem::Image { image_url } => CoreInputItem::Image { image_url },
+            WireInputItem::LocalImage { path } => CoreInputItem::LocalImage { path },
+        })
+        .collect()
+}
+
 fn derive_config_from_params(
     params: NewConversationParams,
     codex_linux_sandbox_exe: Option<PathBuf>,
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::Event;
+use codex_protocol::mcp_protocol::ConversationId;
+use tokio::sync::Mutex;
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Number of events buffered per conversation for listeners that fall behind,
+/// and for the next listener while nobody is subscribed.
+const EVENT_BUFFER_CAPACITY: usize = 1024;
+
+struct ConversationChannel {
+    sender: broadcast::Sender<Event>,
+    /// Events that arrived while the conversation had no subscribers. The next
+    /// subscriber receives them before any live event.
+    pending: VecDeque<Event>,
+}
+
+/// Fans the events of each conversation out to every listener.
+///
+/// A [`CodexConversation`] hands each event to a single reader, so when
+/// several clients listen to the same conversation one task drains it and
+/// rebroadcasts every event to all subscribers.
+#[derive(Default)]
+pub(crate) struct ConversationEventHub {
+    channels: Arc<Mutex<HashMap<ConversationId, ConversationChannel>>>,
+}
+
+/// One listener's view of a conversation's events.
+pub(crate) struct ConversationEvents {
+    pending: VecDeque<Event>,
+    receiver: broadcast::Receiver<Event>,
+}
+
+impl ConversationEvents {
+    /// The next event: first any that were buffered while nobody listened,
+    /// then live ones. Cancel-safe, so it can be used in `tokio::select!`.
+    pub(crate) async fn recv(&mut self) -> Result<Event, broadcast::error::RecvError> {
+        match self.pending.pop_front() {
+            Some(event) => Ok(event),
+            None => self.receiver.recv().await,
+        }
+    }
+}
+
+impl ConversationEventHub {
+    /// Subscribe to the events of `conversation`, starting to drain it if this
+    /// is its first subscriber.
+    pub(crate) async fn subscribe(
+        &self,
+        conversation_id: ConversationId,
+        conversation: Arc<CodexConversation>,
+    ) -> ConversationEvents {
+        let mut channels = self.channels.lock().await;
+        if let Some(channel) = channels.get_mut(&conversation_id) {
+            return ConversationEvents {
+                pending: std::mem::take(&mut channel.pending),
+                receiver: channel.sender.subscribe(),
+            };
+        }
+
+        let (sender, receiver) = broadcast::channel(EVENT_BUFFER_CAPACITY);
+        channels.insert(
+            conversation_id,
+            ConversationChannel {
+                sender,
+                pending: VecDeque::new(),
+            },
+        );
+
+        let channels = self.channels.clone();
+        tokio::spawn(async move {
+            loop {
+                match conversation.next_event().await {
+                    Ok(event) => {
+                        let mut channels = channels.lock().await;
+                        if let Some(channel) = channels.get_mut(&conversation_id) {
+                            channel.publish(event);
+                        }
+                    }
+                    Err(err) => {
+                        warn!("conversation.next_event() failed with: {err}");
+                        break;
+                    }
+                }
+            }
+            // Dropping the sender ends every subscription.
+            channels.lock().await.remove(&conversation_id);
+        });
+
+        ConversationEvents {
+            pending: VecDeque::new(),
+            receiver,
+        }
+    }
+}
+
+impl ConversationChannel {
+    fn publish(&mut self, event: Event) {
+        if self.sender.receiver_count() > 0 {
+            let _ = self.sender.send(event);
+            return;
+        }
+        if self.pending.len() == EVENT_BUFFER_CAPACITY {
+            warn!("no listener for conversation events; dropping the oldest buffered event");
+            self.pending.pop_front();
+        }
+        self.pending.push_back(event);
+    }
+}
@@ -23,12 +23,14 @@ use tracing_subscriber::EnvFilter;
 mod codex_message_processor;
 mod codex_tool_config;
 mod codex_tool_runner;
+mod conversation_events;
 mod error_code;
 mod exec_approval;
 mod json_to_toml;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
+mod serve;
 
 use crate::message_processor::MessageProcessor;
 use crate::outgoing_message::OutgoingMessage;
@@ -40,6 +42,8 @@ pub use crate::exec_approval::ExecApprovalElicitRequestParams;
 pub use crate::exec_approval::ExecApprovalResponse;
 pub use crate::patch_approval::PatchApprovalElicitRequestParams;
 pub use crate::patch_approval::PatchApprovalResponse;
+pub use crate::serve::ServeArgs;
+pub use crate::serve::run_serve_main;
 
 /// Size of the bounded channels used to communicate between tasks. The value
 /// is a balance between throughput and memory usage – 128 messages should be
@@ -0,0 +1,804 @@
+//! `codex serve`: the JSON-RPC protocol from `mcp_protocol.rs` over WebSocket,
+//! plus plain HTTP endpoints for one-off requests and Server-Sent Events.
+//!
+//! Every connection shares one [`ConversationManager`], so several clients
+//! (a web dashboard, an editor, a CLI) can drive and observe the same
+//! long-lived conversations.
+//!
+//! Endpoints (all require the server token, either as `Authorization: Bearer
+//! <token>` or, for browsers that cannot set headers on WebSocket and
+//! EventSource connections, as a `token` query parameter):
+//!
+//! - `GET /ws`: WebSocket; each text message is one JSON-RPC message, exactly
+//!   as `codex mcp` reads and writes them on stdio.
+//! - `POST /rpc`: one JSON-RPC request in the body, its response in the reply.
+//! - `GET /conversations/<id>/events`: the conversation's events as SSE.
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::DefaultBodyLimit;
+use axum::extract::Path;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::extract::ws::Message;
+use axum::extract::ws::WebSocket;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::http::header;
+use axum::middleware;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::response::sse;
+use axum::response::sse::KeepAlive;
+use axum::response::sse::Sse;
+use axum::routing::get;
+use axum::routing::post;
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::AuthManager;
+use codex_core::ConversationManager;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::protocol::Event;
+use codex_protocol::mcp_protocol::ClientRequest;
+use codex_protocol::mcp_protocol::ConversationId;
+use futures::SinkExt;
+use futures::StreamExt;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use mcp_types::JSONRPCRequest;
+use mcp_types::JSONRPCResponse;
+use mcp_types::RequestId;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::info;
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
+use url::form_urlencoded;
+use uuid::Uuid;
+
+use crate::codex_message_processor::CodexMessageProcessor;
+use crate::conversation_events::ConversationEventHub;
+use crate::error_code::INVALID_REQUEST_ERROR_CODE;
+use crate::outgoing_message::OutgoingError;
+use crate::outgoing_message::OutgoingMessage;
+use crate::outgoing_message::OutgoingMessageSender;
+use crate::outgoing_message::OutgoingResponse;
+
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const PARSE_ERROR_CODE: i64 = -32700;
+
+/// How long `POST /rpc` waits for the response to its request.
+const RPC_RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interval between SSE comments that keep idle event streams open and
+/// detect clients that went away.
+const SSE_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Upper bound on the size of a `POST /rpc` body.
+const MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// Upper bound on the size of a single (possibly fragmented) WebSocket
+/// message.
+const MAX_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Serve Codex conversations over WebSocket and HTTP.
+#[derive(Debug, Parser)]
+pub struct ServeArgs {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Address to listen on. Use port 0 to let the OS pick a free port.
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8765")]
+    pub listen: SocketAddr,
+
+    /// Token that clients must present. A random token is generated and
+    /// printed on startup when omitted.
+    #[arg(long, value_name = "TOKEN")]
+    pub token: Option<String>,
+
+    /// Origin (e.g. `http://localhost:3000`) whose pages may connect, in
+    /// addition to pages served from the address the client connected to.
+    /// May be repeated.
+    #[arg(long = "allow-origin", value_name = "ORIGIN")]
+    pub allowed_origins: Vec<String>,
+}
+
+/// State shared by every connection.
+struct ServerState {
+    auth_manager: Arc<AuthManager>,
+    conversation_manager: Arc<ConversationManager>,
+    event_hub: Arc<ConversationEventHub>,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    token: String,
+    allowed_origins: Vec<String>,
+}
+
+impl ServerState {
+    fn new(
+        config: Config,
+        codex_linux_sandbox_exe: Option<PathBuf>,
+        token: String,
+        allowed_origins: Vec<String>,
+    ) -> Self {
+        let auth_manager = AuthManager::shared(config.codex_home.clone());
+        let conversation_manager = Arc::new(ConversationManager::new(auth_manager.clone()));
+        Self {
+            auth_manager,
+            conversation_manager,
+            event_hub: Arc::new(ConversationEventHub::default()),
+            codex_linux_sandbox_exe,
+            config: Arc::new(config),
+            token,
+            allowed_origins,
+        }
+    }
+
+    /// A processor for one client. Its replies, notifications and approval
+    /// requests go to `outgoing` only.
+    fn message_processor(&self, outgoing: Arc<OutgoingMessageSender>) -> CodexMessageProcessor {
+        CodexMessageProcessor::new(
+            self.auth_manager.clone(),
+            self.conversation_manager.clone(),
+            self.event_hub.clone(),
+            outgoing,
+            self.codex_linux_sandbox_exe.clone(),
+            self.config.clone(),
+        )
+    }
+
+    fn is_authorized(&self, headers: &HeaderMap, query: Option<&str>) -> bool {
+        let header_token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(Cow::Borrowed);
+        let query_token = || {
+            form_urlencoded::parse(query.unwrap_or_default().as_bytes())
+                .find_map(|(key, value)| (key == "token").then_some(value))
+        };
+        header_token
+            .or_else(query_token)
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), self.token.as_bytes()))
+    }
+
+    /// Requests from browsers carry an `Origin`; only pages served from the
+    /// address the browser connected to, or from an origin passed to
+    /// `--allow-origin`, may use the server. Other clients send no `Origin`.
+    fn is_allowed_origin(&self, headers: &HeaderMap) -> bool {
+        let Some(origin) = headers.get(header::ORIGIN) else {
+            return true;
+        };
+        let Ok(origin) = origin.to_str() else {
+            return false;
+        };
+        if self.allowed_origins.iter().any(|allowed| allowed == origin) {
+            return true;
+        }
+        let origin_host = origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"));
+        let host = headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok());
+        origin_host.is_some() && origin_host == host
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[allow(clippy::print_stderr)]
+pub async fn run_serve_main(
+    args: ServeArgs,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> IoResult<()> {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let ServeArgs {
+        config_overrides,
+        listen,
+        token,
+        allowed_origins,
+    } = args;
+
+    let cli_kv_overrides = config_overrides.parse_overrides().map_err(|e| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("error parsing -c overrides: {e}"),
+        )
+    })?;
+    let config = Config::load_with_cli_overrides(cli_kv_overrides, ConfigOverrides::default())
+        .map_err(|e| {
+            std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
+        })?;
+
+    let listener = TcpListener::bind(listen).await?;
+    let local_addr = listener.local_addr()?;
+    if !local_addr.ip().is_loopback() {
+        warn!("codex serve is listening on non-loopback address {local_addr}");
+    }
+
+    let token = match token {
+        Some(token) => token,
+        None => {
+            let token = Uuid::new_v4().simple().to_string();
+            eprintln!("codex serve token: {token}");
+            token
+        }
+    };
+    eprintln!("codex serve listening on http://{local_addr} (WebSocket: ws://{local_addr}/ws)");
+
+    let state = Arc::new(ServerState::new(
+        config,
+        codex_linux_sandbox_exe,
+        token,
+        allowed_origins,
+    ));
+    serve(listener, state).await
+}
+
+async fn serve(listener: TcpListener, state: Arc<ServerState>) -> IoResult<()> {
+    axum::serve(listener, router(state)).await
+}
+
+fn router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route("/ws", get(serve_websocket))
+        .route("/rpc", post(serve_rpc))
+        .route("/conversations/{conversation_id}/events", get(serve_events))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+        // The request head is parsed (and size-limited) by hyper before this
+        // runs, so unauthenticated clients and foreign pages never reach a
+        // handler.
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+async fn require_token(
+    State(state): State<Arc<ServerState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if !state.is_allowed_origin(request.headers()) {
+        return (StatusCode::FORBIDDEN, "Forbidden").into_response();
+    }
+    if !state.is_authorized(request.headers(), request.uri().query()) {
+        return (StatusCode::UNAUTHORIZED, "Unauthorized").into_response();
+    }
+    next.run(request).await
+}
+
+/// Route one JSON-RPC message from a client to `processor`.
+async fn dispatch_message(
+    processor: &mut CodexMessageProcessor,
+    outgoing: &OutgoingMessageSender,
+    message: JSONRPCMessage,
+) {
+    match message {
+        JSONRPCMessage::Request(request) => {
+            let request_id = request.id.clone();
+            match client_request(request) {
+                Some(request) => processor.process_request(request).await,
+                None => {
+                    let error = JSONRPCErrorError {
+                        code: METHOD_NOT_FOUND_ERROR_CODE,
+                        message: "unsupported method".to_string(),
+                        data: None,
+                    };
+                    outgoing.send_error(request_id, error).await;
+                }
+            }
+        }
+        // Replies to approval requests that this server sent.
+        JSONRPCMessage::Response(JSONRPCResponse { id, result, .. }) => {
+            outgoing.notify_client_response(id, result).await;
+        }
+        JSONRPCMessage::Notification(notification) => {
+            debug!("ignoring notification: {}", notification.method);
+        }
+        JSONRPCMessage::Error(error) => {
+            warn!("client reported error: {error:?}");
+        }
+    }
+}
+
+fn client_request(request: JSONRPCRequest) -> Option<ClientRequest> {
+    serde_json::to_value(request)
+        .ok()
+        .and_then(|value| serde_json::from_value(value).ok())
+}
+
+async fn serve_websocket(
+    State(state): State<Arc<ServerState>>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    upgrade
+        .max_message_size(MAX_MESSAGE_BYTES)
+        .on_upgrade(move |socket| run_websocket(socket, state))
+}
+
+async fn run_websocket(socket: WebSocket, state: Arc<ServerState>) {
+    let (mut sink, mut stream) = socket.split();
+    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<OutgoingMessage>();
+    let (text_tx, mut text_rx) = mpsc::unbounded_channel::<String>();
+
+    let serializer_handle = tokio::spawn({
+        let text_tx = text_tx.clone();
+        async move {
+            while let Some(outgoing) = outgoing_rx.recv().await {
+                let message: JSONRPCMessage = outgoing.into();
+                match serde_json::to_string(&message) {
+                    Ok(json) => {
+                        if text_tx.send(json).is_err() {
+                            break;
+                        }
+                    }
+                    Err(err) => warn!("failed to serialize JSONRPCMessage: {err}"),
+                }
+            }
+        }
+    });
+    let writer_handle = tokio::spawn(async move {
+        while let Some(text) = text_rx.recv().await {
+            if sink.send(Message::Text(text.into())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let outgoing = Arc::new(OutgoingMessageSender::new(outgoing_tx));
+    let mut processor = state.message_processor(outgoing.clone());
+    // Pings are answered and closes acknowledged by the WebSocket layer.
+    while let Some(message) = stream.next().await {
+        match message {
+            Ok(Message::Text(text)) => match serde_json::from_str::<JSONRPCMessage>(&text) {
+                Ok(message) => dispatch_message(&mut processor, &outgoing, message).await,
+                Err(err) => {
+                    let _ = text_tx.send(parse_error(&err).to_string());
+                }
+            },
+            Ok(Message::Binary(_)) => {
+                warn!("closing WebSocket: binary messages are not supported");
+                break;
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(Message::Ping(_) | Message::Pong(_)) => {}
+            Err(err) => {
+                debug!("WebSocket connection ended with error: {err}");
+                break;
+            }
+        }
+    }
+
+    // Dropping the processor ends this client's conversation listeners.
+    drop(processor);
+    serializer_handle.abort();
+    writer_handle.abort();
+}
+
+async fn serve_rpc(State(state): State<Arc<ServerState>>, body: Bytes) -> Response {
+    let request = match serde_json::from_slice::<JSONRPCRequest>(&body) {
+        Ok(request) => request,
+        Err(err) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                [(header::CONTENT_TYPE, "application/json")],
+                parse_error(&err).to_string(),
+            )
+                .into_response();
+        }
+    };
+    let request_id = request.id.clone();
+    let Some(client_request) = client_request(request) else {
+        let error = jsonrpc_error(
+            request_id,
+            METHOD_NOT_FOUND_ERROR_CODE,
+            "unsupported method".to_string(),
+        );
+        return json_response(StatusCode::NOT_FOUND, &error);
+    };
+
+    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<OutgoingMessage>();
+    let outgoing = Arc::new(OutgoingMessageSender::new(outgoing_tx));
+    let mut processor = state.message_processor(outgoing);
+    processor.process_request(client_request).await;
+
+    let reply = tokio::time::timeout(RPC_RESPONSE_TIMEOUT, async {
+        while let Some(message) = outgoing_rx.recv().await {
+            match message {
+                OutgoingMessage::Response(OutgoingResponse { id, result }) if id == request_id => {
+                    return Some(JSONRPCMessage::Response(JSONRPCResponse {
+                        jsonrpc: JSONRPC_VERSION.into(),
+                        id,
+                        result,
+                    }));
+                }
+                OutgoingMessage::Error(OutgoingError { id, error }) if id == request_id => {
+                    return Some(JSONRPCMessage::Error(JSONRPCError {
+                        jsonrpc: JSONRPC_VERSION.into(),
+                        id,
+                        error,
+                    }));
+                }
+                // Notifications and server requests need a WebSocket client.
+                _ => {}
+            }
+        }
+        None
+    })
+    .await;
+
+    match reply {
+        Ok(Some(message)) => json_response(StatusCode::OK, &message),
+        _ => {
+            let error = jsonrpc_error(
+                request_id,
+                INVALID_REQUEST_ERROR_CODE,
+                "request did not complete; use the WebSocket endpoint for this method".to_string(),
+            );
+            json_response(StatusCode::GATEWAY_TIMEOUT, &error)
+        }
+    }
+}
+
+/// The reply to a message that is not valid JSON-RPC. Its `id` is `null`
+/// because the request's id could not be read.
+fn parse_error(err: &serde_json::Error) -> serde_json::Value {
+    serde_json::json!({
+        "jsonrpc": JSONRPC_VERSION,
+        "id": null,
+        "error": {
+            "code": PARSE_ERROR_CODE,
+            "message": format!("invalid JSON-RPC message: {err}"),
+        },
+    })
+}
+
+fn jsonrpc_error(id: RequestId, code: i64, message: String) -> JSONRPCMessage {
+    JSONRPCMessage::Error(JSONRPCError {
+        jsonrpc: JSONRPC_VERSION.into(),
+        id,
+        error: JSONRPCErrorError {
+            code,
+            message,
+            data: None,
+        },
+    })
+}
+
+fn json_response(status: StatusCode, message: &JSONRPCMessage) -> Response {
+    match serde_json::to_vec(message) {
+        Ok(body) => (status, [(header::CONTENT_TYPE, "application/json")], body).into_response(),
+        Err(err) => {
+            warn!("failed to serialize JSONRPCMessage: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+async fn serve_events(
+    State(state): State<Arc<ServerState>>,
+    Path(conversation_id): Path<String>,
+) -> Response {
+    let conversation = match ConversationId::from_string(&conversation_id) {
+        Ok(id) => state
+            .conversation_manager
+            .get_conversation(id)
+            .await
+            .ok()
+            .map(|conversation| (id, conversation)),
+        Err(_) => None,
+    };
+    let Some((conversation_id, conversation)) = conversation else {
+        return (StatusCode::NOT_FOUND, "Not Found").into_response();
+    };
+
+    let mut events = state
+        .event_hub
+        .subscribe(conversation_id, conversation)
+        .await;
+    info!("streaming events for conversation {conversation_id}");
+
+    let stream = async_stream::stream! {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Some(event) = sse_event(conversation_id, &event) {
+                        yield Ok::<_, Infallible>(event);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("event stream fell behind; skipped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    };
+    Sse::new(stream)
+        .keep_alive(
+            KeepAlive::new()
+                .interval(SSE_KEEP_ALIVE_INTERVAL)
+                .text("keep-alive"),
+        )
+        .into_response()
+}
+
+/// One conversation event as SSE, tagged with its conversation like the
+/// `codex/event/*` notifications.
+fn sse_event(conversation_id: ConversationId, event: &Event) -> Option<sse::Event> {
+    let mut data = match serde_json::to_value(event) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => return None,
+    };
+    data.insert(
+        "conversationId".to_string(),
+        conversation_id.to_string().into(),
+    );
+    let data = serde_json::Value::Object(data);
+    Some(
+        sse::Event::default()
+            .event(event.msg.to_string())
+            .data(data.to_string()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config::ConfigToml;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::net::TcpStream;
+
+    const TOKEN: &str = "secret-token";
+
+    async fn start_server(codex_home: &TempDir) -> SocketAddr {
+        let config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let state = Arc::new(ServerState::new(
+            config,
+            None,
+            TOKEN.to_string(),
+            vec!["http://dashboard.test".to_string()],
+        ));
+        tokio::spawn(serve(listener, state));
+        addr
+    }
+
+    async fn http_exchange(addr: SocketAddr, request: &str) -> String {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    fn masked_text_frame(text: &str) -> Vec<u8> {
+        let mask = [1, 2, 3, 4];
+        let mut frame = vec![0x81];
+        assert!(text.len() < 126);
+        frame.push(0x80 | text.len() as u8);
+        frame.extend_from_slice(&mask);
+        frame.extend(text.bytes().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[tokio::test]
+    async fn rejects_requests_without_token() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"getUserAgent"}"#;
+        let response = http_exchange(
+            addr,
+            &format!(
+                "POST /rpc HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn accepts_token_in_query() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let request = |token: &str| {
+            format!(
+                "GET /conversations/{}/events?token={token} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+                ConversationId::new()
+            )
+        };
+        let response = http_exchange(addr, &request(TOKEN)).await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+        let response = http_exchange(addr, &request("wrong")).await;
+        assert!(response.starts_with("HTTP/1.1 401"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn rejects_foreign_origins() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let request = |origin: &str| {
+            format!(
+                "GET /conversations/{}/events HTTP/1.1\r\nHost: localhost:8765\r\nOrigin: {origin}\r\nConnection: close\r\nAuthorization: Bearer {TOKEN}\r\n\r\n",
+                ConversationId::new()
+            )
+        };
+        let response = http_exchange(addr, &request("http://evil.test")).await;
+        assert!(response.starts_with("HTTP/1.1 403"), "{response}");
+        let response = http_exchange(addr, &request("http://localhost:8765")).await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+        let response = http_exchange(addr, &request("http://dashboard.test")).await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn parse_errors_have_a_null_id() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let body = "{not json";
+        let response = http_exchange(
+            addr,
+            &format!(
+                "POST /rpc HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {TOKEN}\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 400"), "{response}");
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        let error: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::Value::Null, error["id"]);
+        assert_eq!(PARSE_ERROR_CODE, error["error"]["code"]);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_request_head() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /ws HTTP/1.1\r\nX-Filler: ")
+            .await
+            .unwrap();
+        // Keep sending one endless header line; the server must give up long
+        // before the client does.
+        let filler = vec![b'a'; 64 * 1024];
+        let mut rejected = false;
+        for _ in 0..256 {
+            if stream.write_all(&filler).await.is_err() {
+                rejected = true;
+                break;
+            }
+        }
+        if !rejected {
+            let mut response = String::new();
+            let _ = stream.read_to_string(&mut response).await;
+            assert!(response.starts_with("HTTP/1.1 431"), "{response}");
+        }
+    }
+
+    #[tokio::test]
+    async fn rpc_endpoint_returns_response() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let body = r#"{"jsonrpc":"2.0","id":7,"method":"getUserAgent"}"#;
+        let response = http_exchange(
+            addr,
+            &format!(
+                "POST /rpc HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {TOKEN}\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        let (_, json) = response.split_once("\r\n\r\n").unwrap();
+        let message: JSONRPCMessage = serde_json::from_str(json).unwrap();
+        let JSONRPCMessage::Response(response) = message else {
+            panic!("expected a response: {json}");
+        };
+        assert_eq!(RequestId::Integer(7), response.id);
+        assert!(response.result.get("userAgent").is_some());
+    }
+
+    #[tokio::test]
+    async fn unknown_conversation_events_are_not_found() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let response = http_exchange(
+            addr,
+            &format!(
+                "GET /conversations/{}/events HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAuthorization: Bearer {TOKEN}\r\n\r\n",
+                ConversationId::new()
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+    }
+
+    #[tokio::test]
+    async fn websocket_round_trip() {
+        let codex_home = TempDir::new().unwrap();
+        let addr = start_server(&codex_home).await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(
+                format!(
+                    "GET /ws HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {TOKEN}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+                )
+                .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(reader.read_u8().await.unwrap());
+        }
+        let head = String::from_utf8(head).unwrap().to_ascii_lowercase();
+        assert!(head.starts_with("http/1.1 101"), "{head}");
+        assert!(head.contains("sec-websocket-accept: s3pplmbitxaq9kygzzhzrbk+xoo="));
+
+        reader
+            .get_mut()
+            .write_all(&masked_text_frame(
+                r#"{"jsonrpc":"2.0","id":3,"method":"getUserAgent"}"#,
+            ))
+            .await
+            .unwrap();
+
+        let mut header = [0u8; 2];
+        reader.read_exact(&mut header).await.unwrap();
+        assert_eq!(0x81, header[0]);
+        let len = match header[1] {
+            126 => usize::from(reader.read_u16().await.unwrap()),
+            len => usize::from(len),
+        };
+        let mut payload = vec![0u8; len];
+        reader.read_exact(&mut payload).await.unwrap();
+        let message: JSONRPCMessage = serde_json::from_slice(&payload).unwrap();
+        let JSONRPCMessage::Response(response) = message else {
+            panic!("expected a response");
+        };
+        assert_eq!(RequestId::Integer(3), response.id);
+    }
+}
@@ -25,6 +25,7 @@ use codex_protocol::mcp_protocol::ResumeConversationParams;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserTurnParams;
 use codex_protocol::mcp_protocol::SetDefaultModelParams;
+use codex_protocol::mcp_protocol::SteerConversationParams;
 
 use mcp_types::CallToolRequestParams;
 use mcp_types::ClientCapabilities;
@@ -278,6 +279,15 @@ impl McpProcess {
         self.send_request("interruptConversation", params).await
     }
 
+    /// Send a `steerConversation` JSON-RPC request.
+    pub async fn send_steer_conversation_request(
+        &mut self,
+        params: SteerConversationParams,
+    ) -> anyhow::Result<i64> {
+        let params = Some(serde_json::to_value(params)?);
+        self.send_request("steerConversation", params).await
+    }
+
     /// Send a `getAuthStatus` JSON-RPC request.
     pub async fn send_get_auth_status_request(
         &mut self,
@@ -8,6 +8,8 @@ use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserMessageResponse;
+use codex_protocol::mcp_protocol::SteerConversationParams;
+use codex_protocol::mcp_protocol::SteerConversationResponse;
 use mcp_test_support::McpProcess;
 use mcp_test_support::create_final_assistant_message_sse_response;
 use mcp_test_support::create_mock_chat_completions_server;
@@ -158,6 +160,82 @@ async fn test_send_message_session_not_found() {
     assert_eq!(err.id, RequestId::Integer(req_id));
 }
 
+#[tokio::test]
+async fn test_steer_conversation_fails_when_idle() {
+    // Nothing reaches the model.
+    let server = create_mock_chat_completions_server(Vec::new()).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri()).expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timed out")
+        .expect("init failed");
+
+    let new_conv_id = mcp
+        .send_new_conversation_request(NewConversationParams::default())
+        .await
+        .expect("send newConversation");
+    let new_conv_resp: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(new_conv_id)),
+    )
+    .await
+    .expect("newConversation timeout")
+    .expect("newConversation resp");
+    let NewConversationResponse {
+        conversation_id, ..
+    } = to_response::<_>(new_conv_resp).expect("deserialize newConversation response");
+    let add_listener_id = mcp
+        .send_add_conversation_listener_request(AddConversationListenerParams { conversation_id })
+        .await
+        .expect("send addConversationListener");
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(add_listener_id)),
+    )
+    .await
+    .expect("addConversationListener timeout")
+    .expect("addConversationListener resp");
+
+    // With no turn running, there is nothing to steer.
+    let steer_id = mcp
+        .send_steer_conversation_request(SteerConversationParams {
+            conversation_id,
+            items: vec![InputItem::Text {
+                text: "Use the staging database instead".to_string(),
+            }],
+        })
+        .await
+        .expect("send steerConversation");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(steer_id)),
+    )
+    .await
+    .expect("steerConversation response timeout")
+    .expect("steerConversation response error");
+    let SteerConversationResponse {} =
+        to_response::<_>(response).expect("deserialize steerConversation response");
+
+    let error = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event/error"),
+    )
+    .await
+    .expect("error timeout")
+    .expect("error notification");
+    let message = error
+        .params
+        .as_ref()
+        .and_then(|params| params["msg"]["message"].as_str())
+        .unwrap_or_default();
+    assert!(message.contains("no turn is running"), "{message}");
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
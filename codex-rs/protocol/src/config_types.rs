@@ -58,4 +58,7 @@ pub enum SandboxMode {
 
     #[serde(rename = "danger-full-access")]
     DangerFullAccess,
+
+    #[serde(rename = "overlay")]
+    Overlay,
 }
@@ -129,6 +129,13 @@ pub enum ClientRequest {
         request_id: RequestId,
         params: InterruptConversationParams,
     },
+    /// Add user input to the running turn without interrupting it; see
+    /// `Op::Steer`.
+    SteerConversation {
+        #[serde(rename = "id")]
+        request_id: RequestId,
+        params: SteerConversationParams,
+    },
     AddConversationListener {
         #[serde(rename = "id")]
         request_id: RequestId,
@@ -570,6 +577,17 @@ pub struct InterruptConversationResponse {
 #[serde(rename_all = "camelCase")]
 pub struct SendUserMessageResponse {}
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SteerConversationParams {
+    pub conversation_id: ConversationId,
+    pub items: Vec<InputItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct SteerConversationResponse {}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct AddConversationListenerParams {
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use serde::Deserialize;
 use serde::Serialize;
 use ts_rs::TS;
@@ -7,4 +9,8 @@ pub struct HistoryEntry {
     pub conversation_id: String,
     pub ts: u64,
     pub text: String,
+    /// Working directory of the session the message was sent from. Entries
+    /// written before this was recorded have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
@@ -36,6 +36,9 @@ pub const USER_INSTRUCTIONS_CLOSE_TAG: &str = "</user_instructions>";
 pub const ENVIRONMENT_CONTEXT_OPEN_TAG: &str = "<environment_context>";
 pub const ENVIRONMENT_CONTEXT_CLOSE_TAG: &str = "</environment_context>";
 pub const USER_MESSAGE_BEGIN: &str = "## My request for Codex:";
+/// The [`ErrorEvent`] message for an [`Op::Steer`] that found no running turn.
+pub const NO_TURN_TO_STEER_MESSAGE: &str =
+    "no turn is running to steer; send the input as a new message instead";
 
 /// Submission Queue Entry - requests from user
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -62,6 +65,17 @@ pub enum Op {
         items: Vec<InputItem>,
     },
 
+    /// Input from the user that steers the running turn without interrupting
+    /// it. The model sees it at the next tool-call boundary, once the tool
+    /// calls in flight finish, and the turn keeps going until the model has
+    /// responded to it. A steer that the turn ends without seeing starts a
+    /// new turn. Fails with an [`EventMsg::Error`] carrying
+    /// [`NO_TURN_TO_STEER_MESSAGE`] when no turn is running.
+    Steer {
+        /// User input items, see `InputItem`
+        items: Vec<InputItem>,
+    },
+
     /// Similar to [`Op::UserInput`], but contains additional context required
     /// for a turn of a [`crate::codex_conversation::CodexConversation`].
     UserTurn {
@@ -155,6 +169,21 @@ pub enum Op {
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
+    /// Request the most recent distinct entries of the persistent message
+    /// history, newest first, for searching it. Matching the entries against
+    /// what the user types is left to the client.
+    /// Reply is delivered via `EventMsg::SearchHistoryResponse`.
+    SearchHistoryRequest {
+        /// Only return entries sent from this working directory.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<PathBuf>,
+        /// Only return entries sent in this conversation.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        conversation_id: Option<ConversationId>,
+        /// Maximum number of entries to return.
+        limit: usize,
+    },
+
     /// Request the full in-memory conversation transcript for the current session.
     /// Reply is delivered via `EventMsg::ConversationHistory`.
     GetPath,
@@ -174,6 +203,11 @@ pub enum Op {
     /// Request a code review from the agent.
     Review { review_request: ReviewRequest },
 
+    /// Commit the changes in the layer of an `Overlay` sandbox policy to the
+    /// workspace, or discard them.
+    /// Reply is delivered via `EventMsg::OverlayResolved`.
+    ResolveOverlay { decision: OverlayDecision },
+
     /// Request to shut down codex instance.
     Shutdown,
 }
@@ -217,7 +251,12 @@ pub enum SandboxPolicy {
 
     /// Read-only access to the entire file-system.
     #[serde(rename = "read-only")]
-    ReadOnly,
+    ReadOnly {
+        /// Paths that must not be read from within the sandbox, such as
+        /// credential directories. Relative paths are resolved against cwd.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
+    },
 
     /// Same as `ReadOnly` but additionally grants write access to the current
     /// working directory ("workspace").
@@ -243,6 +282,32 @@ pub enum SandboxPolicy {
         /// writable roots on UNIX. Defaults to `false`.
         #[serde(default)]
         exclude_slash_tmp: bool,
+
+        /// Paths that must not be read from within the sandbox, even inside
+        /// writable roots. Relative paths are resolved against cwd.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
+    },
+
+    /// Same as `ReadOnly`, except that writes to the current working
+    /// directory ("workspace") land in a copy-on-write layer on top of it
+    /// instead of the real tree, for the user to commit to the workspace or
+    /// discard. Only supported on Linux.
+    #[serde(rename = "overlay")]
+    Overlay {
+        /// Directory holding the layer, i.e. the changes made on top of the
+        /// workspace.
+        layer_dir: PathBuf,
+
+        /// When set to `true`, outbound network access is allowed. `false` by
+        /// default.
+        #[serde(default)]
+        network_access: bool,
+
+        /// Paths that must not be read from within the sandbox. Relative
+        /// paths are resolved against cwd.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        deny_read: Vec<PathBuf>,
     },
 }
 
@@ -288,7 +353,9 @@ impl FromStr for SandboxPolicy {
 impl SandboxPolicy {
     /// Returns a policy with read-only disk access and no network.
     pub fn new_read_only_policy() -> Self {
-        SandboxPolicy::ReadOnly
+        SandboxPolicy::ReadOnly {
+            deny_read: Vec::new(),
+        }
     }
 
     /// Returns a policy that can read the entire disk, but can only write to
@@ -300,42 +367,113 @@ impl SandboxPolicy {
             network_access: false,
             exclude_tmpdir_env_var: false,
             exclude_slash_tmp: false,
+            deny_read: Vec::new(),
         }
     }
 
-    /// Always returns `true`; restricting read access is not supported.
+    /// Returns a policy whose writes to the workspace land in the layer at
+    /// `layer_dir`. It does not allow network access.
+    pub fn new_overlay_policy(layer_dir: PathBuf) -> Self {
+        SandboxPolicy::Overlay {
+            layer_dir,
+            network_access: false,
+            deny_read: Vec::new(),
+        }
+    }
+
+    /// Returns this policy with `deny_read` replaced. `DangerFullAccess` has
+    /// no restrictions to add it to and is returned unchanged.
+    pub fn with_deny_read(mut self, paths: Vec<PathBuf>) -> Self {
+        match &mut self {
+            SandboxPolicy::DangerFullAccess => {}
+            SandboxPolicy::ReadOnly { deny_read }
+            | SandboxPolicy::WorkspaceWrite { deny_read, .. }
+            | SandboxPolicy::Overlay { deny_read, .. } => {
+                *deny_read = paths;
+            }
+        }
+        self
+    }
+
+    /// Paths as configured in `deny_read`, possibly relative.
+    pub fn deny_read(&self) -> &[PathBuf] {
+        match self {
+            SandboxPolicy::DangerFullAccess => &[],
+            SandboxPolicy::ReadOnly { deny_read }
+            | SandboxPolicy::WorkspaceWrite { deny_read, .. }
+            | SandboxPolicy::Overlay { deny_read, .. } => deny_read,
+        }
+    }
+
+    /// The directory of the copy-on-write layer, for an `Overlay` policy.
+    pub fn overlay_layer_dir(&self) -> Option<&Path> {
+        match self {
+            SandboxPolicy::Overlay { layer_dir, .. } => Some(layer_dir),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` unless some paths are denied for reading.
     pub fn has_full_disk_read_access(&self) -> bool {
-        true
+        self.deny_read().is_empty()
+    }
+
+    /// Absolute paths that must not be read, tailored to the current working
+    /// directory.
+    pub fn get_denied_read_paths_with_cwd(&self, cwd: &Path) -> Vec<PathBuf> {
+        self.deny_read()
+            .iter()
+            .map(|path| normalize_lexically(&cwd.join(path)))
+            .collect()
+    }
+
+    /// Whether `path` (resolved against `cwd` if relative) may be read under
+    /// this policy. Symlinks are followed, so a link to a denied path is
+    /// denied too.
+    pub fn is_path_readable(&self, path: &Path, cwd: &Path) -> bool {
+        let path = normalize_lexically(&cwd.join(path));
+        let real_path = resolve_symlinks(&path);
+        !self
+            .get_denied_read_paths_with_cwd(cwd)
+            .iter()
+            .any(|denied| {
+                path.starts_with(denied) || real_path.starts_with(resolve_symlinks(denied))
+            })
     }
 
     pub fn has_full_disk_write_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
+            SandboxPolicy::ReadOnly { .. } => false,
             SandboxPolicy::WorkspaceWrite { .. } => false,
+            SandboxPolicy::Overlay { .. } => false,
         }
     }
 
     pub fn has_full_network_access(&self) -> bool {
         match self {
             SandboxPolicy::DangerFullAccess => true,
-            SandboxPolicy::ReadOnly => false,
-            SandboxPolicy::WorkspaceWrite { network_access, .. } => *network_access,
+            SandboxPolicy::ReadOnly { .. } => false,
+            SandboxPolicy::WorkspaceWrite { network_access, .. }
+            | SandboxPolicy::Overlay { network_access, .. } => *network_access,
         }
     }
 
     /// Returns the list of writable roots (tailored to the current working
     /// directory) together with subpaths that should remain read‑only under
-    /// each writable root.
+    /// each writable root. The workspace of an `Overlay` policy is not one:
+    /// only its layer is written to.
     pub fn get_writable_roots_with_cwd(&self, cwd: &Path) -> Vec<WritableRoot> {
         match self {
             SandboxPolicy::DangerFullAccess => Vec::new(),
-            SandboxPolicy::ReadOnly => Vec::new(),
+            SandboxPolicy::ReadOnly { .. } => Vec::new(),
+            SandboxPolicy::Overlay { .. } => Vec::new(),
             SandboxPolicy::WorkspaceWrite {
                 writable_roots,
                 exclude_tmpdir_env_var,
                 exclude_slash_tmp,
                 network_access: _,
+                deny_read: _,
             } => {
                 // Start from explicitly configured writable roots.
                 let mut roots: Vec<PathBuf> = writable_roots.clone();
@@ -387,6 +525,36 @@ impl SandboxPolicy {
     }
 }
 
+/// Resolve `.` and `..` components without touching the file-system.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Resolve symlinks in the longest prefix of `path` that exists, keeping the
+/// rest as is, so that a path through a link is compared like the path it
+/// points to even when the file itself does not exist yet.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    for ancestor in path.ancestors() {
+        if let Ok(real_ancestor) = ancestor.canonicalize() {
+            return match path.strip_prefix(ancestor) {
+                Ok(rest) if !rest.as_os_str().is_empty() => real_ancestor.join(rest),
+                _ => real_ancestor,
+            };
+        }
+    }
+    path.to_path_buf()
+}
+
 /// User input
 #[non_exhaustive]
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
@@ -499,6 +667,9 @@ pub enum EventMsg {
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
+    /// Response to SearchHistoryRequest.
+    SearchHistoryResponse(SearchHistoryResponseEvent),
+
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
@@ -519,6 +690,9 @@ pub enum EventMsg {
 
     /// Exited review mode with an optional final result to apply.
     ExitedReviewMode(ExitedReviewModeEvent),
+
+    /// Response to `Op::ResolveOverlay`.
+    OverlayResolved(OverlayResolvedEvent),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -924,6 +1098,18 @@ pub struct SessionMeta {
     pub originator: String,
     pub cli_version: String,
     pub instructions: Option<String>,
+    /// Set when this conversation was forked from another one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forked_from: Option<ForkOrigin>,
+}
+
+/// The conversation a fork was made from and where it branched off.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, TS)]
+pub struct ForkOrigin {
+    pub conversation_id: ConversationId,
+    /// The fork kept the parent's history up to, but not including, this
+    /// user message (0-based).
+    pub nth_user_message: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, TS)]
@@ -1157,6 +1343,25 @@ pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
+/// What to do with the changes in the layer of an `Overlay` sandbox policy.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayDecision {
+    /// Apply the changes to the workspace.
+    Commit,
+
+    /// Throw the changes away.
+    Discard,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct OverlayResolvedEvent {
+    pub decision: OverlayDecision,
+
+    /// Number of files in the workspace that the decision applied to.
+    pub changed_files: usize,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
@@ -1166,6 +1371,18 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
+/// Response payload for `Op::SearchHistoryRequest`. The filters are echoed
+/// so that clients can tell responses to superseded requests apart.
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SearchHistoryResponseEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conversation_id: Option<ConversationId>,
+    /// Matching entries, newest first, without repeated texts.
+    pub entries: Vec<HistoryEntry>,
+}
+
 /// Response payload for `Op::ListMcpTools`.
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct McpListToolsResponseEvent {
@@ -1206,7 +1423,7 @@ pub struct SessionConfiguredEvent {
 }
 
 /// User's decision in response to an ExecApprovalRequest.
-#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, PartialEq, Eq, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     /// User has approved this command and the agent should execute it.
@@ -1225,6 +1442,29 @@ pub enum ReviewDecision {
     /// User has denied this command and the agent should not do anything until
     /// the user's next command.
     Abort,
+
+    /// User has approved a patch except for the `rejected` files and hunks,
+    /// which should not be applied. Only valid in response to an
+    /// ApplyPatchApprovalRequest; commands treat it as `Denied`.
+    ApprovedPartially { rejected: Vec<RejectedPatchHunk> },
+
+    /// User has approved the command after editing it, and the agent should
+    /// execute `command` instead. Only valid in response to an
+    /// ExecApprovalRequest; patches treat it as `Denied`.
+    ApprovedWithEdit { command: Vec<String> },
+}
+
+/// A part of a patch the user rejected, see [`ReviewDecision::ApprovedPartially`].
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, TS)]
+pub struct RejectedPatchHunk {
+    /// The file, as a key of the request's `changes`.
+    pub path: PathBuf,
+
+    /// The 0-based index of the `@@` hunk in the file's `unified_diff`, or
+    /// `None` to reject the whole file. Added and deleted files have a single
+    /// hunk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hunk: Option<usize>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
@@ -1261,6 +1501,10 @@ pub enum TurnAbortReason {
     Interrupted,
     Replaced,
     ReviewEnded,
+    /// A configured turn limit (tool calls, wall-clock time or autonomous
+    /// model requests) was reached. The task still completes with a summary
+    /// from the model, so a `TaskComplete` event follows.
+    LimitReached,
 }
 
 #[cfg(test)]
@@ -1322,4 +1566,57 @@ mod tests {
         assert_eq!(deserialized, event);
         Ok(())
     }
+
+    #[test]
+    fn sandbox_policy_deny_read_serialization() -> Result<()> {
+        let policy = SandboxPolicy::new_read_only_policy();
+        assert_eq!(
+            json!({ "mode": "read-only" }),
+            serde_json::to_value(&policy)?
+        );
+        assert_eq!(policy, serde_json::from_str(r#"{"mode":"read-only"}"#)?);
+
+        let policy = policy.with_deny_read(vec![PathBuf::from(".env")]);
+        let serialized = serde_json::to_string(&policy)?;
+        assert_eq!(r#"{"mode":"read-only","deny_read":[".env"]}"#, serialized);
+        assert_eq!(policy, serde_json::from_str(&serialized)?);
+        Ok(())
+    }
+
+    #[test]
+    fn sandbox_policy_is_path_readable() {
+        let cwd = Path::new("/work/project");
+        let policy = SandboxPolicy::new_workspace_write_policy()
+            .with_deny_read(vec![PathBuf::from("/home/me/.ssh"), PathBuf::from(".env")]);
+
+        assert!(!policy.has_full_disk_read_access());
+        assert!(!policy.is_path_readable(Path::new("/home/me/.ssh/id_rsa"), cwd));
+        assert!(!policy.is_path_readable(Path::new(".env"), cwd));
+        assert!(!policy.is_path_readable(Path::new("src/../.env"), cwd));
+        assert!(policy.is_path_readable(Path::new(".envrc"), cwd));
+        assert!(policy.is_path_readable(Path::new("/home/me/.sshrc"), cwd));
+
+        let full_access = SandboxPolicy::DangerFullAccess.with_deny_read(vec![PathBuf::from("/")]);
+        assert!(full_access.is_path_readable(Path::new("/etc/passwd"), cwd));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sandbox_policy_is_path_readable_follows_symlinks() -> Result<()> {
+        let home = tempfile::tempdir()?;
+        let ssh = home.path().join(".ssh");
+        let cwd = home.path().join("project");
+        std::fs::create_dir(&ssh)?;
+        std::fs::create_dir(&cwd)?;
+        std::fs::write(ssh.join("id_rsa"), "key")?;
+        std::os::unix::fs::symlink(ssh.join("id_rsa"), cwd.join("key"))?;
+        std::os::unix::fs::symlink(&ssh, cwd.join("keys"))?;
+        let policy = SandboxPolicy::new_workspace_write_policy().with_deny_read(vec![ssh]);
+
+        assert!(!policy.is_path_readable(Path::new("key"), &cwd));
+        assert!(!policy.is_path_readable(Path::new("keys/id_rsa"), &cwd));
+        assert!(!policy.is_path_readable(Path::new("keys/not_yet_created"), &cwd));
+        assert!(policy.is_path_readable(Path::new("not_yet_created"), &cwd));
+        Ok(())
+    }
 }
@@ -2,8 +2,12 @@ use crate::app_backtrack::BacktrackState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::ChatWidget;
+use crate::diff_browser::files_from_changes;
+use crate::diff_browser::files_from_unified_diff;
+use crate::external_editor;
 use crate::file_search::FileSearchManager;
 use crate::history_cell::HistoryCell;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
 use crate::resume_picker::ResumeSelection;
 use crate::tui;
@@ -13,6 +17,7 @@ use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::config::Config;
 use codex_core::config::persist_model_selection;
+use codex_core::config_keymap::KeymapAction;
 use codex_core::model_family::find_family_for_model;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
@@ -125,6 +130,29 @@ impl App {
                     resumed.session_configured,
                 )
             }
+            #[cfg(unix)]
+            ResumeSelection::Attach {
+                session_id,
+                read_only,
+            } => {
+                let stream = crate::attach::connect(&config.codex_home, &session_id, read_only)
+                    .await
+                    .wrap_err_with(|| format!("Failed to attach to session {session_id}"))?;
+                let init = crate::chatwidget::ChatWidgetInit {
+                    config: config.clone(),
+                    frame_requester: tui.frame_requester(),
+                    app_event_tx: app_event_tx.clone(),
+                    initial_prompt: None,
+                    initial_images: Vec::new(),
+                    enhanced_keys_supported,
+                    auth_manager: auth_manager.clone(),
+                };
+                ChatWidget::new_attached(init, stream, read_only)
+            }
+            #[cfg(not(unix))]
+            ResumeSelection::Attach { .. } => {
+                color_eyre::eyre::bail!("`codex attach` is only supported on Unix");
+            }
         };
 
         let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
@@ -287,17 +315,31 @@ impl App {
                 self.chat_widget.on_diff_complete();
                 // Enter alternate screen using TUI helper and build pager lines
                 let _ = tui.enter_alt_screen();
-                let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
-                    vec!["No changes detected.".italic().into()]
+                let files = files_from_unified_diff(&text);
+                self.overlay = Some(if !files.is_empty() {
+                    Overlay::new_diff(files, "D I F F".to_string())
                 } else {
-                    text.lines().map(ansi_escape_line).collect()
-                };
-                self.overlay = Some(Overlay::new_static_with_title(
-                    pager_lines,
-                    "D I F F".to_string(),
-                ));
+                    let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
+                        vec!["No changes detected.".italic().into()]
+                    } else {
+                        text.lines().map(ansi_escape_line).collect()
+                    };
+                    Overlay::new_static_with_title(pager_lines, "D I F F".to_string())
+                });
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::OpenExternalEditor => {
+                let draft = self.chat_widget.editor_draft();
+                match tui
+                    .with_restored(|| external_editor::edit_draft(&draft))
+                    .await
+                {
+                    Ok(Ok(text)) => self.chat_widget.apply_editor_draft(&text),
+                    Ok(Err(err)) | Err(err) => self
+                        .chat_widget
+                        .add_error_message(format!("Failed to edit the draft: {err}")),
+                }
+            }
             AppEvent::StartFileSearch(query) => {
                 if !query.is_empty() {
                     self.file_search.on_user_query(query);
@@ -363,6 +405,23 @@ impl App {
             AppEvent::OpenReviewCustomPrompt => {
                 self.chat_widget.show_review_custom_prompt();
             }
+            AppEvent::OpenHistorySearch => {
+                self.chat_widget.open_history_search();
+            }
+            AppEvent::SetComposerText(text) => {
+                self.chat_widget.set_composer_text(text);
+            }
+            AppEvent::ApprovalResolved(id) => {
+                self.chat_widget.dismiss_approval_request(&id);
+            }
+            AppEvent::OpenPatchDiff(changes) => {
+                let _ = tui.enter_alt_screen();
+                self.overlay = Some(Overlay::new_diff(
+                    files_from_changes(&changes, &self.config.cwd),
+                    "P A T C H".to_string(),
+                ));
+                tui.frame_requester().schedule_frame();
+            }
         }
         Ok(true)
     }
@@ -377,13 +436,12 @@ impl App {
     }
 
     async fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        let keymap = keymap();
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('t'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if keymap.matches(KeymapAction::OpenTranscript, &key_event) => {
                 // Enter alternate screen and set viewport to full size.
                 let _ = tui.enter_alt_screen();
                 self.overlay = Some(Overlay::new_transcript(self.transcript_cells.clone()));
@@ -393,10 +451,9 @@ impl App {
             // with an empty composer. In any other state, forward Esc so the
             // active UI (e.g. status indicator, modals, popups) handles it.
             KeyEvent {
-                code: KeyCode::Esc,
                 kind: KeyEventKind::Press | KeyEventKind::Repeat,
                 ..
-            } => {
+            } if keymap.matches(KeymapAction::Backtrack, &key_event) => {
                 if self.chat_widget.is_normal_backtrack_mode()
                     && self.chat_widget.composer_is_empty()
                 {
@@ -424,7 +481,7 @@ impl App {
                 // Any non-Esc key press should cancel a primed backtrack.
                 // This avoids stale "Esc-primed" state after the user starts typing
                 // (even if they later backspace to empty).
-                if key_event.code != KeyCode::Esc && self.backtrack.primed {
+                if !keymap.matches(KeymapAction::Backtrack, &key_event) && self.backtrack.primed {
                     self.reset_backtrack_state();
                 }
                 self.chat_widget.handle_key_event(key_event);
@@ -5,9 +5,11 @@ use std::sync::Arc;
 use crate::app::App;
 use crate::history_cell::CompositeHistoryCell;
 use crate::history_cell::UserHistoryCell;
+use crate::keymap::keymap;
 use crate::pager_overlay::Overlay;
 use crate::tui;
 use crate::tui::TuiEvent;
+use codex_core::config_keymap::KeymapAction;
 use codex_core::protocol::ConversationPathResponseEvent;
 use codex_protocol::mcp_protocol::ConversationId;
 use color_eyre::eyre::Result;
@@ -40,13 +42,18 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
-        if self.backtrack.overlay_preview_active {
-            match event {
-                TuiEvent::Key(KeyEvent {
-                    code: KeyCode::Esc,
+        let is_backtrack_key = |event: &TuiEvent| {
+            matches!(
+                event,
+                TuiEvent::Key(key_event @ KeyEvent {
                     kind: KeyEventKind::Press | KeyEventKind::Repeat,
                     ..
-                }) => {
+                }) if keymap().matches(KeymapAction::Backtrack, key_event)
+            )
+        };
+        if self.backtrack.overlay_preview_active {
+            match event {
+                _ if is_backtrack_key(&event) => {
                     self.overlay_step_backtrack(tui, event)?;
                     Ok(true)
                 }
@@ -64,12 +71,7 @@ impl App {
                     Ok(true)
                 }
             }
-        } else if let TuiEvent::Key(KeyEvent {
-            code: KeyCode::Esc,
-            kind: KeyEventKind::Press | KeyEventKind::Repeat,
-            ..
-        }) = event
-        {
+        } else if is_backtrack_key(&event) {
             // First Esc in transcript overlay: begin backtrack preview at latest user message.
             self.begin_overlay_backtrack_preview(tui);
             Ok(true)
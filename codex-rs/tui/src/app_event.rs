@@ -1,7 +1,9 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use codex_core::protocol::ConversationPathResponseEvent;
 use codex_core::protocol::Event;
+use codex_core::protocol::FileChange;
 use codex_file_search::FileMatch;
 
 use crate::history_cell::HistoryCell;
@@ -76,4 +78,20 @@ pub(crate) enum AppEvent {
 
     /// Open the custom prompt option from the review popup.
     OpenReviewCustomPrompt,
+
+    /// Edit the composer draft in the user's `$VISUAL` or `$EDITOR`.
+    OpenExternalEditor,
+
+    /// Open the Ctrl+R search over the message history.
+    OpenHistorySearch,
+
+    /// Replace the composer's text, e.g. with a message picked from history.
+    SetComposerText(String),
+
+    /// An approval request was answered in another terminal attached to this
+    /// session; close its prompt here.
+    ApprovalResolved(String),
+
+    /// Show the changes of a patch awaiting approval in the diff browser.
+    OpenPatchDiff(HashMap<PathBuf, FileChange>),
 }
@@ -0,0 +1,581 @@
+//! Let a second terminal attach to a live TUI session.
+//!
+//! While a conversation is open, the TUI listens on a Unix socket at
+//! `$CODEX_HOME/attach/<session-id>.sock`. `codex attach <session-id>` connects
+//! to it and renders the same history through its own `ChatWidget`.
+//! `codex attach --read-only` connects to `<session-id>.read-only.sock`
+//! instead, whose clients can only watch.
+//!
+//! The protocol is newline-delimited JSON. The host sends [`HostMessage`]s:
+//! first a bounded backlog of what the session has produced so far, then live
+//! updates. Clients of the read-write socket send `Op`s, which the host
+//! submits to its conversation as if they came from its own composer; the
+//! host drops anything sent on the read-only socket. When any terminal
+//! answers an approval request, the others close their prompt for it.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_protocol::mcp_protocol::ConversationId;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::app_event_sender::AppEventSender;
+
+/// What a hosting TUI sends to attached clients, one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum HostMessage {
+    Event {
+        event: Event,
+    },
+    /// A prompt typed in another terminal. Core does not echo user input as
+    /// a live event, so the host relays it explicitly.
+    UserPrompt {
+        text: String,
+    },
+    /// An approval request was answered in another terminal.
+    ApprovalResolved {
+        id: String,
+    },
+}
+
+/// Most messages kept for clients that attach later. The first message (the
+/// `SessionConfigured` event) is always kept.
+const MAX_BACKLOG_MESSAGES: usize = 2_000;
+
+/// Socket a TUI hosting `session_id` listens on for read-write or read-only
+/// clients.
+pub(crate) fn socket_path(codex_home: &Path, session_id: &str, read_only: bool) -> PathBuf {
+    let name = if read_only {
+        format!("{session_id}.read-only.sock")
+    } else {
+        format!("{session_id}.sock")
+    };
+    codex_home.join("attach").join(name)
+}
+
+/// The text a `UserInput` or `Steer` op shows in the transcript, if any.
+pub(crate) fn user_prompt_text(op: &Op) -> Option<String> {
+    let (Op::UserInput { items } | Op::Steer { items }) = op else {
+        return None;
+    };
+    let text = items
+        .iter()
+        .filter_map(|item| match item {
+            InputItem::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Everything a newly attached client needs to catch up, plus the clients
+/// currently attached.
+#[derive(Default)]
+struct HostState {
+    backlog: Vec<HostMessage>,
+    clients: Vec<(u64, tokio::sync::mpsc::UnboundedSender<HostMessage>)>,
+    next_client_id: u64,
+}
+
+impl HostState {
+    /// Record `message` and send it to every client except `origin`.
+    fn publish(&mut self, message: HostMessage, origin: Option<u64>) {
+        self.broadcast(&message, origin);
+        let turn_ended = matches!(
+            &message,
+            HostMessage::Event {
+                event: Event {
+                    msg: EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_),
+                    ..
+                },
+            }
+        );
+        self.backlog.push(message);
+        if turn_ended {
+            // The final messages of a finished turn carry everything its
+            // deltas did.
+            self.backlog.retain(|message| !is_delta(message));
+        }
+        if self.backlog.len() > MAX_BACKLOG_MESSAGES {
+            let excess = self.backlog.len() - MAX_BACKLOG_MESSAGES;
+            self.backlog.drain(1..=excess);
+        }
+    }
+
+    /// Send `message` to every client except `origin` without recording it.
+    fn broadcast(&mut self, message: &HostMessage, origin: Option<u64>) {
+        self.clients
+            .retain(|(id, client)| Some(*id) == origin || client.send(message.clone()).is_ok());
+    }
+
+    /// Track ops that change what other terminals should see: prompts are
+    /// relayed, and answered approval requests are closed in the other
+    /// terminals and dropped from the backlog so late clients do not show
+    /// stale approval prompts.
+    fn observe_op(&mut self, op: &Op, origin: Option<u64>) {
+        match op {
+            Op::ExecApproval { id, .. } | Op::PatchApproval { id, .. } => {
+                self.broadcast(&HostMessage::ApprovalResolved { id: id.clone() }, origin);
+                let answered = self.backlog.iter().position(|message| {
+                    matches!(
+                        message,
+                        HostMessage::Event {
+                            event: Event {
+                                id: event_id,
+                                msg: EventMsg::ExecApprovalRequest(_)
+                                    | EventMsg::ApplyPatchApprovalRequest(_),
+                            },
+                        } if event_id == id
+                    )
+                });
+                if let Some(index) = answered {
+                    self.backlog.remove(index);
+                }
+            }
+            _ => {
+                if let Some(text) = user_prompt_text(op) {
+                    self.publish(HostMessage::UserPrompt { text }, origin);
+                }
+            }
+        }
+    }
+}
+
+/// Streaming deltas, which late clients can do without once their turn ended.
+fn is_delta(message: &HostMessage) -> bool {
+    matches!(
+        message,
+        HostMessage::Event {
+            event: Event {
+                msg: EventMsg::AgentMessageDelta(_)
+                    | EventMsg::AgentReasoningDelta(_)
+                    | EventMsg::AgentReasoningRawContentDelta(_)
+                    | EventMsg::ExecCommandOutputDelta(_),
+                ..
+            },
+        }
+    )
+}
+
+/// Accepts attached clients for one conversation. Dropping it removes the
+/// socket and disconnects every client.
+pub(crate) struct AttachHost {
+    state: Arc<std::sync::Mutex<HostState>>,
+    #[cfg(unix)]
+    socket_paths: Vec<PathBuf>,
+    #[cfg(unix)]
+    accept_tasks: Vec<tokio::task::JoinHandle<()>>,
+}
+
+impl AttachHost {
+    /// Forward an event the conversation produced to attached clients.
+    pub(crate) fn publish_event(&self, event: &Event) {
+        if let Ok(mut state) = self.state.lock() {
+            state.publish(
+                HostMessage::Event {
+                    event: event.clone(),
+                },
+                None,
+            );
+        }
+    }
+
+    /// Note an op submitted from the hosting TUI itself.
+    pub(crate) fn observe_local_op(&self, op: &Op) {
+        if let Ok(mut state) = self.state.lock() {
+            state.observe_op(op, None);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use super::*;
+
+    use std::os::unix::fs::DirBuilderExt;
+
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::net::UnixListener;
+    use tokio::net::UnixStream;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use crate::app_event::AppEvent;
+    use crate::history_cell;
+
+    impl AttachHost {
+        /// Start listening for clients that want to attach to
+        /// `conversation_id`.
+        pub(crate) fn start(
+            codex_home: &Path,
+            conversation_id: ConversationId,
+            conversation: Arc<CodexConversation>,
+            app_event_tx: AppEventSender,
+        ) -> io::Result<Self> {
+            let mut host = Self {
+                state: Arc::new(std::sync::Mutex::new(HostState::default())),
+                socket_paths: Vec::new(),
+                accept_tasks: Vec::new(),
+            };
+            for read_only in [false, true] {
+                let socket_path = socket_path(codex_home, &conversation_id.to_string(), read_only);
+                if let Some(dir) = socket_path.parent() {
+                    // Only the current user may attach.
+                    std::fs::DirBuilder::new()
+                        .recursive(true)
+                        .mode(0o700)
+                        .create(dir)?;
+                }
+                // A socket left behind by a crashed TUI would make bind fail.
+                match std::fs::remove_file(&socket_path) {
+                    Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                    _ => {}
+                }
+                let listener = UnixListener::bind(&socket_path)?;
+                host.socket_paths.push(socket_path);
+
+                let accept_state = host.state.clone();
+                let conversation = conversation.clone();
+                let app_event_tx = app_event_tx.clone();
+                host.accept_tasks.push(tokio::spawn(async move {
+                    while let Ok((stream, _)) = listener.accept().await {
+                        serve_client(
+                            stream,
+                            read_only,
+                            accept_state.clone(),
+                            conversation.clone(),
+                            app_event_tx.clone(),
+                        );
+                    }
+                }));
+            }
+            Ok(host)
+        }
+    }
+
+    impl Drop for AttachHost {
+        fn drop(&mut self) {
+            for accept_task in &self.accept_tasks {
+                accept_task.abort();
+            }
+            if let Ok(mut state) = self.state.lock() {
+                // Closing the channels ends the writer tasks, which closes
+                // the sockets and lets clients exit.
+                state.clients.clear();
+            }
+            for socket_path in &self.socket_paths {
+                let _ = std::fs::remove_file(socket_path);
+            }
+        }
+    }
+
+    fn serve_client(
+        stream: UnixStream,
+        read_only: bool,
+        state: Arc<std::sync::Mutex<HostState>>,
+        conversation: Arc<CodexConversation>,
+        app_event_tx: AppEventSender,
+    ) {
+        let (read_half, mut write_half) = stream.into_split();
+        let (tx, mut rx) = unbounded_channel::<HostMessage>();
+
+        let client_id = {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+            for message in &state.backlog {
+                let _ = tx.send(message.clone());
+            }
+            let client_id = state.next_client_id;
+            state.next_client_id += 1;
+            state.clients.push((client_id, tx));
+            client_id
+        };
+
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                let Ok(mut line) = serde_json::to_string(&message) else {
+                    continue;
+                };
+                line.push('\n');
+                if write_half.write_all(line.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(read_half).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if read_only {
+                    tracing::warn!("ignoring op from read-only attached client");
+                    continue;
+                }
+                let op = match serde_json::from_str::<Op>(&line) {
+                    Ok(op) => op,
+                    Err(err) => {
+                        tracing::warn!("ignoring invalid op from attached client: {err}");
+                        continue;
+                    }
+                };
+                // An attached client leaving must not end the host's session.
+                if matches!(op, Op::Shutdown) {
+                    continue;
+                }
+                if let Some(text) = user_prompt_text(&op) {
+                    app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                        history_cell::new_user_prompt(text),
+                    )));
+                }
+                if let Op::ExecApproval { id, .. } | Op::PatchApproval { id, .. } = &op {
+                    app_event_tx.send(AppEvent::ApprovalResolved(id.clone()));
+                }
+                if let Ok(mut state) = state.lock() {
+                    state.observe_op(&op, Some(client_id));
+                }
+                if let Err(err) = conversation.submit(op).await {
+                    tracing::error!("failed to submit op from attached client: {err}");
+                }
+            }
+            if let Ok(mut state) = state.lock() {
+                state.clients.retain(|(id, _)| *id != client_id);
+            }
+        });
+    }
+
+    /// Connect to the TUI hosting `session_id`, as a client that can only
+    /// watch when `read_only`.
+    pub(crate) async fn connect(
+        codex_home: &Path,
+        session_id: &str,
+        read_only: bool,
+    ) -> io::Result<UnixStream> {
+        let path = socket_path(codex_home, session_id, read_only);
+        UnixStream::connect(&path)
+            .await
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => io::Error::new(
+                    err.kind(),
+                    format!("no running Codex session {session_id} to attach to"),
+                ),
+                _ => err,
+            })
+    }
+}
+
+#[cfg(unix)]
+pub(crate) use unix::connect;
+
+#[cfg(not(unix))]
+impl AttachHost {
+    pub(crate) fn start(
+        _codex_home: &Path,
+        _conversation_id: ConversationId,
+        _conversation: Arc<CodexConversation>,
+        _app_event_tx: AppEventSender,
+    ) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "attaching to sessions requires Unix sockets",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::AgentMessageDeltaEvent;
+    use codex_core::protocol::AgentMessageEvent;
+    use codex_core::protocol::ExecApprovalRequestEvent;
+    use codex_core::protocol::TaskCompleteEvent;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn event(id: &str, msg: EventMsg) -> Event {
+        Event {
+            id: id.to_string(),
+            msg,
+        }
+    }
+
+    fn approval_request() -> EventMsg {
+        EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call".to_string(),
+            command: vec!["ls".to_string()],
+            cwd: PathBuf::from("/tmp"),
+            reason: None,
+        })
+    }
+
+    #[test]
+    fn prompts_are_relayed_to_other_clients_only() {
+        let mut state = HostState::default();
+        let (first_tx, mut first_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (second_tx, mut second_rx) = tokio::sync::mpsc::unbounded_channel();
+        state.clients.push((0, first_tx));
+        state.clients.push((1, second_tx));
+
+        state.observe_op(
+            &Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: "hello".to_string(),
+                }],
+            },
+            Some(0),
+        );
+
+        assert!(first_rx.try_recv().is_err());
+        let Ok(HostMessage::UserPrompt { text }) = second_rx.try_recv() else {
+            panic!("expected the prompt to be relayed");
+        };
+        assert_eq!("hello", text);
+        assert_eq!(1, state.backlog.len());
+    }
+
+    #[test]
+    fn answered_approvals_leave_the_backlog() {
+        let mut state = HostState::default();
+        state.publish(
+            HostMessage::Event {
+                event: event("1", approval_request()),
+            },
+            None,
+        );
+        state.publish(
+            HostMessage::Event {
+                event: event(
+                    "1",
+                    EventMsg::AgentMessage(AgentMessageEvent {
+                        message: "done".to_string(),
+                    }),
+                ),
+            },
+            None,
+        );
+
+        state.observe_op(
+            &Op::ExecApproval {
+                id: "1".to_string(),
+                decision: codex_core::protocol::ReviewDecision::Approved,
+            },
+            None,
+        );
+
+        assert_eq!(1, state.backlog.len());
+        assert!(matches!(
+            &state.backlog[0],
+            HostMessage::Event {
+                event: Event {
+                    msg: EventMsg::AgentMessage(_),
+                    ..
+                }
+            }
+        ));
+    }
+
+    #[test]
+    fn answered_approvals_close_in_other_terminals() {
+        let mut state = HostState::default();
+        let (first_tx, mut first_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (second_tx, mut second_rx) = tokio::sync::mpsc::unbounded_channel();
+        state.clients.push((0, first_tx));
+        state.clients.push((1, second_tx));
+
+        state.observe_op(
+            &Op::PatchApproval {
+                id: "7".to_string(),
+                decision: codex_core::protocol::ReviewDecision::Denied,
+            },
+            Some(1),
+        );
+
+        let Ok(HostMessage::ApprovalResolved { id }) = first_rx.try_recv() else {
+            panic!("expected the other client to close its prompt");
+        };
+        assert_eq!("7", id);
+        assert!(second_rx.try_recv().is_err());
+        assert!(state.backlog.is_empty());
+    }
+
+    #[test]
+    fn finished_turns_drop_their_deltas_from_the_backlog() {
+        let mut state = HostState::default();
+        for msg in [
+            EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: "do".to_string(),
+            }),
+            EventMsg::AgentMessageDelta(AgentMessageDeltaEvent {
+                delta: "ne".to_string(),
+            }),
+            EventMsg::AgentMessage(AgentMessageEvent {
+                message: "done".to_string(),
+            }),
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: None,
+            }),
+        ] {
+            state.publish(
+                HostMessage::Event {
+                    event: event("1", msg),
+                },
+                None,
+            );
+        }
+
+        assert_eq!(2, state.backlog.len());
+        assert!(!state.backlog.iter().any(is_delta));
+    }
+
+    #[test]
+    fn backlog_is_capped_but_keeps_the_first_message() {
+        let mut state = HostState::default();
+        for n in 0..MAX_BACKLOG_MESSAGES + 10 {
+            state.publish(
+                HostMessage::UserPrompt {
+                    text: n.to_string(),
+                },
+                None,
+            );
+        }
+
+        assert_eq!(MAX_BACKLOG_MESSAGES, state.backlog.len());
+        let texts: Vec<&str> = state
+            .backlog
+            .iter()
+            .take(2)
+            .filter_map(|message| match message {
+                HostMessage::UserPrompt { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(vec!["0", "11"], texts);
+    }
+
+    #[test]
+    fn user_prompt_text_ignores_images() {
+        let op = Op::UserInput {
+            items: vec![
+                InputItem::LocalImage {
+                    path: PathBuf::from("a.png"),
+                },
+                InputItem::Text {
+                    text: "look".to_string(),
+                },
+            ],
+        };
+        assert_eq!(Some("look".to_string()), user_prompt_text(&op));
+        assert_eq!(None, user_prompt_text(&Op::Interrupt));
+    }
+}
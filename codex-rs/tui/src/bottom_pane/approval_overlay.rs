@@ -1,22 +1,29 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPaneView;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::command_editor::CommandEditor;
+use crate::bottom_pane::command_editor::EditorKey;
+use crate::bottom_pane::hunk_selection::HunkSelection;
+use crate::bottom_pane::hunk_selection::SelectionKey;
 use crate::bottom_pane::list_selection_view::HeaderLine;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
 use crate::bottom_pane::list_selection_view::SelectionItem;
 use crate::bottom_pane::list_selection_view::SelectionViewParams;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
+use crate::keymap::keymap;
 use crate::text_formatting::truncate_text;
+use codex_core::config_keymap::KeymapAction;
+use codex_core::protocol::FileChange;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
-use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize;
@@ -34,9 +41,20 @@ pub(crate) enum ApprovalRequest {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        changes: HashMap<PathBuf, FileChange>,
+        /// Directory the patch paths are shown relative to.
+        cwd: PathBuf,
     },
 }
 
+impl ApprovalRequest {
+    fn id(&self) -> &str {
+        match self {
+            ApprovalRequest::Exec { id, .. } | ApprovalRequest::ApplyPatch { id, .. } => id,
+        }
+    }
+}
+
 /// Modal overlay asking the user to approve or deny one or more requests.
 pub(crate) struct ApprovalOverlay {
     current: Option<ApprovalRequestState>,
@@ -44,6 +62,10 @@ pub(crate) struct ApprovalOverlay {
     app_event_tx: AppEventSender,
     list: ListSelectionView,
     options: Vec<ApprovalOption>,
+    /// Set while the user picks the parts of a patch to apply.
+    hunk_selection: Option<HunkSelection>,
+    /// Set while the user edits the command before running it.
+    command_editor: Option<CommandEditor>,
     current_complete: bool,
     done: bool,
 }
@@ -62,6 +84,8 @@ impl ApprovalOverlay {
                 app_event_tx,
             ),
             options: Vec::new(),
+            hunk_selection: None,
+            command_editor: None,
             current_complete: false,
             done: false,
         };
@@ -78,6 +102,8 @@ impl ApprovalOverlay {
     fn set_current(&mut self, request: ApprovalRequest) {
         self.current = Some(ApprovalRequestState::from(request));
         self.current_complete = false;
+        self.hunk_selection = None;
+        self.command_editor = None;
         let (options, params) = self.build_options();
         self.options = options;
         self.list = ListSelectionView::new(params, self.app_event_tx.clone());
@@ -95,7 +121,10 @@ impl ApprovalOverlay {
         };
         let (options, title) = match &state.variant {
             ApprovalVariant::Exec { .. } => (exec_options(), "Allow command?".to_string()),
-            ApprovalVariant::ApplyPatch { .. } => (patch_options(), "Apply changes?".to_string()),
+            ApprovalVariant::ApplyPatch { changes, cwd, .. } => (
+                patch_options(HunkSelection::has_choices(changes, cwd)),
+                "Apply changes?".to_string(),
+            ),
         };
 
         let items = options
@@ -110,9 +139,21 @@ impl ApprovalOverlay {
             })
             .collect();
 
+        let review_key = match &state.variant {
+            ApprovalVariant::ApplyPatch { .. } => keymap()
+                .label(KeymapAction::ReviewChanges)
+                .map(|key| shortcut_label(&key)),
+            ApprovalVariant::Exec { .. } => None,
+        };
+        let footer_hint = match review_key {
+            Some(key) => {
+                format!("Press Enter to confirm, {key} to review the changes or Esc to cancel")
+            }
+            None => "Press Enter to confirm or Esc to cancel".to_string(),
+        };
         let params = SelectionViewParams {
             title,
-            footer_hint: Some("Press Enter to confirm or Esc to cancel".to_string()),
+            footer_hint: Some(footer_hint),
             items,
             header: state.header.clone(),
             ..Default::default()
@@ -128,12 +169,27 @@ impl ApprovalOverlay {
         let Some(option) = self.options.get(actual_idx) else {
             return;
         };
+        let decision = match (&option.choice, self.current.as_ref().map(|s| &s.variant)) {
+            (ApprovalChoice::Decide(decision), _) => decision.clone(),
+            (
+                ApprovalChoice::ChooseChanges,
+                Some(ApprovalVariant::ApplyPatch { changes, cwd, .. }),
+            ) => {
+                self.hunk_selection = Some(HunkSelection::new(changes, cwd));
+                return;
+            }
+            (ApprovalChoice::EditCommand, Some(ApprovalVariant::Exec { command, .. })) => {
+                self.command_editor = Some(CommandEditor::new(command));
+                return;
+            }
+            _ => return,
+        };
         if let Some(state) = self.current.as_ref() {
-            match (&state.variant, option.decision) {
-                (ApprovalVariant::Exec { id, command }, decision) => {
+            match &state.variant {
+                ApprovalVariant::Exec { id, command } => {
                     self.handle_exec_decision(id, command, decision);
                 }
-                (ApprovalVariant::ApplyPatch { id, .. }, decision) => {
+                ApprovalVariant::ApplyPatch { id, .. } => {
                     self.handle_patch_decision(id, decision);
                 }
             }
@@ -144,7 +200,7 @@ impl ApprovalOverlay {
     }
 
     fn handle_exec_decision(&self, id: &str, command: &[String], decision: ReviewDecision) {
-        if let Some(lines) = build_exec_history_lines(command.to_vec(), decision) {
+        if let Some(lines) = build_exec_history_lines(command.to_vec(), &decision) {
             self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
                 history_cell::new_user_approval_decision(lines),
             )));
@@ -170,27 +226,91 @@ impl ApprovalOverlay {
         }
     }
 
+    /// Opens the patch awaiting approval in the diff browser when `key_event`
+    /// asks for it.
+    fn try_open_patch_diff(&self, key_event: &KeyEvent) -> bool {
+        if let Some(ApprovalVariant::ApplyPatch { changes, .. }) =
+            self.current.as_ref().map(|state| &state.variant)
+            && !self.current_complete
+            && keymap().matches(KeymapAction::ReviewChanges, key_event)
+        {
+            self.app_event_tx
+                .send(AppEvent::OpenPatchDiff(changes.clone()));
+            return true;
+        }
+        false
+    }
+
+    fn handle_hunk_selection_key(&mut self, key_event: &KeyEvent) {
+        if key_event.kind != KeyEventKind::Press || self.try_open_patch_diff(key_event) {
+            return;
+        }
+        let Some(selection) = self.hunk_selection.as_mut() else {
+            return;
+        };
+        match selection.handle_key_event(key_event) {
+            SelectionKey::Handled => {}
+            SelectionKey::Back => self.hunk_selection = None,
+            SelectionKey::Confirm => {
+                let decision = selection.decision();
+                self.hunk_selection = None;
+                if let Some(ApprovalVariant::ApplyPatch { id, .. }) =
+                    self.current.as_ref().map(|state| &state.variant)
+                {
+                    self.handle_patch_decision(id, decision);
+                }
+                self.current_complete = true;
+                self.advance_queue();
+            }
+        }
+    }
+
+    fn handle_command_editor_key(&mut self, key_event: KeyEvent) {
+        if key_event.kind == KeyEventKind::Release {
+            return;
+        }
+        let Some(editor) = self.command_editor.as_mut() else {
+            return;
+        };
+        match editor.handle_key_event(key_event) {
+            EditorKey::Handled => {}
+            EditorKey::Back => self.command_editor = None,
+            EditorKey::Confirm(edited) => {
+                self.command_editor = None;
+                if let Some(ApprovalVariant::Exec { id, command }) =
+                    self.current.as_ref().map(|state| &state.variant)
+                {
+                    let decision = if edited == *command {
+                        ReviewDecision::Approved
+                    } else {
+                        ReviewDecision::ApprovedWithEdit { command: edited }
+                    };
+                    self.handle_exec_decision(id, command, decision);
+                }
+                self.current_complete = true;
+                self.advance_queue();
+            }
+        }
+    }
+
     fn try_handle_shortcut(&mut self, key_event: &KeyEvent) -> bool {
         if key_event.kind != KeyEventKind::Press {
             return false;
         }
-        let KeyEvent {
-            code: KeyCode::Char(c),
-            modifiers,
-            ..
-        } = key_event
-        else {
-            return false;
-        };
-        if modifiers.contains(KeyModifiers::CONTROL) || modifiers.contains(KeyModifiers::ALT) {
-            return false;
+        if self.try_open_patch_diff(key_event) {
+            return true;
         }
-        let lower = c.to_ascii_lowercase();
-        if let Some(idx) = self
-            .options
-            .iter()
-            .position(|opt| opt.shortcut.map(|s| s == lower).unwrap_or(false))
-        {
+        // Letter shortcuts work with Caps Lock or Shift held, too.
+        let lowercase = match key_event.code {
+            KeyCode::Char(c) => {
+                KeyEvent::new(KeyCode::Char(c.to_ascii_lowercase()), key_event.modifiers)
+            }
+            _ => *key_event,
+        };
+        let keymap = keymap();
+        if let Some(idx) = self.options.iter().position(|opt| {
+            keymap.matches(opt.shortcut, key_event) || keymap.matches(opt.shortcut, &lowercase)
+        }) {
             self.apply_selection(idx);
             true
         } else {
@@ -201,6 +321,14 @@ impl ApprovalOverlay {
 
 impl BottomPaneView for ApprovalOverlay {
     fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.hunk_selection.is_some() {
+            self.handle_hunk_selection_key(&key_event);
+            return;
+        }
+        if self.command_editor.is_some() {
+            self.handle_command_editor_key(key_event);
+            return;
+        }
         if self.try_handle_shortcut(&key_event) {
             return;
         }
@@ -236,11 +364,29 @@ impl BottomPaneView for ApprovalOverlay {
     }
 
     fn desired_height(&self, width: u16) -> u16 {
-        self.list.desired_height(width)
+        match (&self.hunk_selection, &self.command_editor) {
+            (Some(selection), _) => selection.desired_height(width),
+            (None, Some(editor)) => editor.desired_height(width),
+            (None, None) => self.list.desired_height(width),
+        }
     }
 
     fn render(&self, area: Rect, buf: &mut Buffer) {
-        self.list.render(area, buf);
+        match (&self.hunk_selection, &self.command_editor) {
+            (Some(selection), _) => selection.render(area, buf),
+            (None, Some(editor)) => editor.render(area, buf),
+            (None, None) => self.list.render(area, buf),
+        }
+    }
+
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        match self.command_editor.as_mut() {
+            Some(editor) => {
+                editor.handle_paste(&pasted);
+                true
+            }
+            None => false,
+        }
     }
 
     fn try_consume_approval_request(
@@ -251,7 +397,26 @@ impl BottomPaneView for ApprovalOverlay {
         None
     }
 
+    fn dismiss_approval_request(&mut self, id: &str) {
+        let current_id = self.current.as_ref().map(|state| match &state.variant {
+            ApprovalVariant::Exec { id, .. } | ApprovalVariant::ApplyPatch { id, .. } => {
+                id.as_str()
+            }
+        });
+        if current_id == Some(id) {
+            self.advance_queue();
+        } else {
+            self.queue.retain(|request| request.id() != id);
+        }
+    }
+
     fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        if self.hunk_selection.is_some() {
+            return None;
+        }
+        if let Some(editor) = &self.command_editor {
+            return editor.cursor_pos(area);
+        }
         self.list.cursor_pos(area)
     }
 }
@@ -296,6 +461,8 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 id,
                 reason,
                 grant_root,
+                changes,
+                cwd,
             } => {
                 let mut header = Vec::new();
                 if let Some(reason) = reason
@@ -318,7 +485,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     header.push(HeaderLine::Spacer);
                 }
                 Self {
-                    variant: ApprovalVariant::ApplyPatch { id },
+                    variant: ApprovalVariant::ApplyPatch { id, changes, cwd },
                     header,
                 }
             }
@@ -327,62 +494,115 @@ impl From<ApprovalRequest> for ApprovalRequestState {
 }
 
 enum ApprovalVariant {
-    Exec { id: String, command: Vec<String> },
-    ApplyPatch { id: String },
+    Exec {
+        id: String,
+        command: Vec<String>,
+    },
+    ApplyPatch {
+        id: String,
+        changes: HashMap<PathBuf, FileChange>,
+        cwd: PathBuf,
+    },
+}
+
+/// What picking an approval option does.
+#[derive(Clone)]
+enum ApprovalChoice {
+    Decide(ReviewDecision),
+    /// Let the user choose the parts of the patch to apply.
+    ChooseChanges,
+    /// Let the user edit the command before running it.
+    EditCommand,
 }
 
 #[derive(Clone)]
 struct ApprovalOption {
     label: String,
     description: String,
-    decision: ReviewDecision,
-    shortcut: Option<char>,
+    choice: ApprovalChoice,
+    shortcut: KeymapAction,
+}
+
+impl ApprovalOption {
+    fn new(label: &str, description: &str, choice: ApprovalChoice, shortcut: KeymapAction) -> Self {
+        let description = match keymap().label(shortcut) {
+            Some(key) => format!("({}) {description}", shortcut_label(&key)),
+            None => description.to_string(),
+        };
+        Self {
+            label: label.to_string(),
+            description,
+            choice,
+            shortcut,
+        }
+    }
+}
+
+/// Single-letter shortcuts are shown uppercase, as they work with Shift held.
+fn shortcut_label(key: &str) -> String {
+    if key.chars().count() == 1 {
+        key.to_uppercase()
+    } else {
+        key.to_string()
+    }
 }
 
 fn exec_options() -> Vec<ApprovalOption> {
     vec![
-        ApprovalOption {
-            label: "Approve and run now".to_string(),
-            description: "(Y) Run this command one time".to_string(),
-            decision: ReviewDecision::Approved,
-            shortcut: Some('y'),
-        },
-        ApprovalOption {
-            label: "Always approve this session".to_string(),
-            description: "(A) Automatically approve this command for the rest of the session"
-                .to_string(),
-            decision: ReviewDecision::ApprovedForSession,
-            shortcut: Some('a'),
-        },
-        ApprovalOption {
-            label: "Cancel".to_string(),
-            description: "(N) Do not run the command".to_string(),
-            decision: ReviewDecision::Abort,
-            shortcut: Some('n'),
-        },
+        ApprovalOption::new(
+            "Approve and run now",
+            "Run this command one time",
+            ApprovalChoice::Decide(ReviewDecision::Approved),
+            KeymapAction::AcceptApproval,
+        ),
+        ApprovalOption::new(
+            "Always approve this session",
+            "Automatically approve this command for the rest of the session",
+            ApprovalChoice::Decide(ReviewDecision::ApprovedForSession),
+            KeymapAction::AcceptApprovalForSession,
+        ),
+        ApprovalOption::new(
+            "Edit the command",
+            "Change the command, then run it",
+            ApprovalChoice::EditCommand,
+            KeymapAction::EditApprovalCommand,
+        ),
+        ApprovalOption::new(
+            "Cancel",
+            "Do not run the command",
+            ApprovalChoice::Decide(ReviewDecision::Abort),
+            KeymapAction::DeclineApproval,
+        ),
     ]
 }
 
-fn patch_options() -> Vec<ApprovalOption> {
-    vec![
-        ApprovalOption {
-            label: "Approve".to_string(),
-            description: "(Y) Apply the proposed changes".to_string(),
-            decision: ReviewDecision::Approved,
-            shortcut: Some('y'),
-        },
-        ApprovalOption {
-            label: "Cancel".to_string(),
-            description: "(N) Do not apply the changes".to_string(),
-            decision: ReviewDecision::Abort,
-            shortcut: Some('n'),
-        },
-    ]
+fn patch_options(has_choices: bool) -> Vec<ApprovalOption> {
+    let mut options = vec![ApprovalOption::new(
+        "Approve",
+        "Apply the proposed changes",
+        ApprovalChoice::Decide(ReviewDecision::Approved),
+        KeymapAction::AcceptApproval,
+    )];
+    if has_choices {
+        options.push(ApprovalOption::new(
+            "Choose changes",
+            "Apply only the files and hunks you select",
+            ApprovalChoice::ChooseChanges,
+            KeymapAction::SelectPatchHunks,
+        ));
+    }
+    options.push(ApprovalOption::new(
+        "Cancel",
+        "Do not apply the changes",
+        ApprovalChoice::Decide(ReviewDecision::Abort),
+        KeymapAction::DeclineApproval,
+    ));
+    options
 }
 
 fn build_exec_history_lines(
     command: Vec<String>,
-    decision: ReviewDecision,
+    decision: &ReviewDecision,
 ) -> Option<Vec<Line<'static>>> {
     use ReviewDecision::*;
 
@@ -437,6 +657,20 @@ fn build_exec_history_lines(
                 ],
             )
         }
+        ApprovedWithEdit { command } => {
+            let snippet = Span::from(exec_snippet(command)).dim();
+            (
+                "✔ ".green(),
+                vec![
+                    "You ".into(),
+                    "edited".bold(),
+                    " the command and approved codex to run ".into(),
+                    snippet,
+                ],
+            )
+        }
+        // Only patches are approved in part.
+        ApprovedPartially { .. } => return None,
     };
 
     let mut lines = Vec::new();
@@ -465,6 +699,7 @@ fn exec_snippet(command: &[String]) -> String {
 mod tests {
     use super::*;
     use crate::app_event::AppEvent;
+    use crossterm::event::KeyModifiers;
     use tokio::sync::mpsc::unbounded_channel;
 
     fn make_exec_request() -> ApprovalRequest {
@@ -486,6 +721,25 @@ mod tests {
         assert!(view.is_complete());
     }
 
+    #[test]
+    fn dismissing_the_current_request_shows_the_next() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx);
+        view.enqueue_request(ApprovalRequest::Exec {
+            id: "next".to_string(),
+            command: vec!["ls".to_string()],
+            reason: None,
+        });
+
+        view.dismiss_approval_request("test");
+        assert!(!view.is_complete());
+        view.dismiss_approval_request("next");
+        assert!(view.is_complete());
+        // Requests answered elsewhere are not answered again here.
+        assert!(rx.try_recv().is_err());
+    }
+
     #[test]
     fn shortcut_triggers_selection() {
         let (tx, mut rx) = unbounded_channel::<AppEvent>();
@@ -504,6 +758,113 @@ mod tests {
         assert!(saw_op, "expected approval decision to emit an op");
     }
 
+    #[test]
+    fn review_key_opens_patch_diff_without_deciding() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let changes = HashMap::from([(
+            PathBuf::from("README.md"),
+            FileChange::Add {
+                content: "hello\n".to_string(),
+            },
+        )]);
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::ApplyPatch {
+                id: "test".to_string(),
+                reason: None,
+                grant_root: None,
+                changes: changes.clone(),
+                cwd: PathBuf::from("/repo"),
+            },
+            tx,
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+
+        assert!(!view.is_complete());
+        match rx.try_recv() {
+            Ok(AppEvent::OpenPatchDiff(opened)) => assert_eq!(opened, changes),
+            other => panic!("expected OpenPatchDiff, got {other:?}"),
+        }
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn chosen_hunks_are_approved_partially() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let changes = HashMap::from([
+            (
+                PathBuf::from("/repo/a.txt"),
+                FileChange::Update {
+                    unified_diff: "@@ -1 +1 @@\n-one\n+ONE\n@@ -9 +9 @@\n-nine\n+NINE\n"
+                        .to_string(),
+                    move_path: None,
+                },
+            ),
+            (
+                PathBuf::from("/repo/b.txt"),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+        ]);
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::ApplyPatch {
+                id: "test".to_string(),
+                reason: None,
+                grant_root: None,
+                changes,
+                cwd: PathBuf::from("/repo"),
+            },
+            tx,
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE));
+        assert!(view.hunk_selection.is_some());
+        // Deselect b.txt, the last row, then apply the rest.
+        view.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::NONE));
+        view.handle_key_event(KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE));
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert!(view.is_complete());
+        match rx.try_recv() {
+            Ok(AppEvent::CodexOp(Op::PatchApproval { decision, .. })) => assert_eq!(
+                decision,
+                ReviewDecision::ApprovedPartially {
+                    rejected: vec![codex_core::protocol::RejectedPatchHunk {
+                        path: PathBuf::from("/repo/b.txt"),
+                        hunk: None,
+                    }],
+                }
+            ),
+            other => panic!("expected PatchApproval, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn edited_command_is_approved_with_edit() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx);
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE));
+        assert!(view.command_editor.is_some());
+        assert!(view.handle_paste(" --dry-run".to_string()));
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        assert!(view.is_complete());
+        let mut decision = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision: d, .. }) = ev {
+                decision = Some(d);
+            }
+        }
+        assert_eq!(
+            decision,
+            Some(ReviewDecision::ApprovedWithEdit {
+                command: vec!["echo".into(), "hi".into(), "--dry-run".into()],
+            })
+        );
+    }
+
     #[test]
     fn header_includes_command_snippet() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
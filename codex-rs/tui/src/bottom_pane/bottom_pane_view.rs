@@ -1,4 +1,5 @@
 use crate::bottom_pane::ApprovalRequest;
+use codex_core::protocol::SearchHistoryResponseEvent;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -46,4 +47,11 @@ pub(crate) trait BottomPaneView {
     ) -> Option<ApprovalRequest> {
         Some(request)
     }
+
+    /// Close the approval request `id` without answering it, because it was
+    /// answered elsewhere.
+    fn dismiss_approval_request(&mut self, _id: &str) {}
+
+    /// Receive the result of an `Op::SearchHistoryRequest`.
+    fn on_history_search_response(&mut self, _event: SearchHistoryResponseEvent) {}
 }
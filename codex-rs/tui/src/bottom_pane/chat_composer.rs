@@ -1,3 +1,4 @@
+use codex_core::config_keymap::KeymapAction;
 use codex_core::protocol::TokenUsageInfo;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -23,6 +24,9 @@ use super::footer::FooterProps;
 use super::footer::render_footer;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
+use super::vim::Vim;
+use super::vim::VimMode;
+use super::vim::VimOutcome;
 use crate::bottom_pane::paste_burst::FlushResult;
 use crate::slash_command::SlashCommand;
 use crate::style::user_message_style;
@@ -35,6 +39,7 @@ use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
+use crate::keymap::keymap;
 use crate::ui_consts::LIVE_PREFIX_COLS;
 use codex_file_search::FileMatch;
 use std::cell::RefCell;
@@ -84,6 +89,8 @@ pub(crate) struct ChatComposer {
     // When true, disables paste-burst logic and inserts characters immediately.
     disable_paste_burst: bool,
     custom_prompts: Vec<CustomPrompt>,
+    // Modal editing state, when vim mode is enabled.
+    vim: Option<Vim>,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -125,6 +132,7 @@ impl ChatComposer {
             paste_burst: PasteBurst::default(),
             disable_paste_burst: false,
             custom_prompts: Vec::new(),
+            vim: None,
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
@@ -256,6 +264,16 @@ impl ChatComposer {
         }
     }
 
+    pub(crate) fn set_vim_mode(&mut self, enabled: bool) {
+        self.vim = enabled.then(|| Vim::new(&self.textarea));
+    }
+
+    /// Whether vim mode will use the next Esc itself, such as to leave insert
+    /// mode, rather than let it backtrack or interrupt.
+    pub(crate) fn vim_consumes_esc(&self) -> bool {
+        self.vim.as_ref().is_some_and(Vim::consumes_esc)
+    }
+
     /// Replace the entire composer content with `text` and reset cursor.
     pub(crate) fn set_text_content(&mut self, text: String) {
         // Clear any existing content, placeholders, and attachments first.
@@ -273,6 +291,44 @@ impl ChatComposer {
         self.textarea.text().to_string()
     }
 
+    /// The draft to hand to an external editor: large pastes are expanded and
+    /// image placeholders are kept so they can be matched when it comes back.
+    pub(crate) fn editor_draft(&self) -> String {
+        let mut text = self.textarea.text().to_string();
+        for (placeholder, actual) in &self.pending_pastes {
+            text = text.replace(placeholder, actual);
+        }
+        text
+    }
+
+    /// Replace the draft with `text` from an external editor. Image
+    /// placeholders that are still present keep their attachments.
+    pub(crate) fn apply_editor_draft(&mut self, text: &str) {
+        self.pending_pastes.clear();
+        self.textarea.set_text("");
+        let mut placeholders: Vec<&str> = self
+            .attached_images
+            .iter()
+            .map(|img| img.placeholder.as_str())
+            .collect();
+        placeholders.sort_unstable();
+        placeholders.dedup();
+        let mut rest = text;
+        while let Some((idx, placeholder)) = placeholders
+            .iter()
+            .filter_map(|placeholder| rest.find(placeholder).map(|idx| (idx, *placeholder)))
+            .min_by_key(|(idx, _)| *idx)
+        {
+            self.textarea.insert_str(&rest[..idx]);
+            self.textarea.insert_element(placeholder);
+            rest = &rest[idx + placeholder.len()..];
+        }
+        self.textarea.insert_str(rest);
+        self.retain_attached_images();
+        self.sync_command_popup();
+        self.sync_file_search_popup();
+    }
+
     /// Attempt to start a burst by retro-capturing recent chars before the cursor.
     pub fn attach_image(&mut self, path: PathBuf, width: u32, height: u32, format_label: &str) {
         let placeholder = format!("[image {width}x{height} {format_label}]");
@@ -330,11 +386,28 @@ impl ChatComposer {
 
     /// Handle a key event coming from the main UI.
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
-        let result = match &mut self.active_popup {
-            ActivePopup::Command(_) => self.handle_key_event_with_slash_popup(key_event),
-            ActivePopup::File(_) => self.handle_key_event_with_file_popup(key_event),
-            ActivePopup::None => self.handle_key_event_without_popup(key_event),
+        // Outside insert mode, vim interprets keys before popups and the
+        // composer see them.
+        let popup_active = self.popup_active();
+        let vim_handled = key_event.kind != KeyEventKind::Release
+            && self.vim.as_mut().is_some_and(|vim| {
+                !(popup_active && vim.mode() == VimMode::Insert)
+                    && vim.handle_key(&mut self.textarea, &key_event) == VimOutcome::Handled
+            });
+        let result = if vim_handled {
+            (InputResult::None, true)
+        } else {
+            match &mut self.active_popup {
+                ActivePopup::Command(_) => self.handle_key_event_with_slash_popup(key_event),
+                ActivePopup::File(_) => self.handle_key_event_with_file_popup(key_event),
+                ActivePopup::None => self.handle_key_event_without_popup(key_event),
+            }
         };
+        if let InputResult::Submitted(_) = result.0
+            && let Some(vim) = self.vim.as_mut()
+        {
+            vim.reset(&self.textarea);
+        }
 
         // Update (or hide/show) popup after processing the key.
         self.sync_command_popup();
@@ -729,33 +802,47 @@ impl ChatComposer {
 
     /// Handle key event when no popup is visible.
     fn handle_key_event_without_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        let keymap = keymap();
+        let history_action = keymap.action_for(
+            &[KeymapAction::HistoryPrev, KeymapAction::HistoryNext],
+            &key_event,
+        );
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } if self.is_empty() => {
+            } if self.is_empty() && keymap.matches(KeymapAction::Quit, &key_event) => {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
+            KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            } if keymap.matches(KeymapAction::OpenEditor, &key_event) => {
+                self.app_event_tx.send(AppEvent::OpenExternalEditor);
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            } if keymap.matches(KeymapAction::SearchHistory, &key_event) => {
+                self.app_event_tx.send(AppEvent::OpenHistorySearch);
+                (InputResult::None, true)
+            }
             // -------------------------------------------------------------
             // History navigation (Up / Down) – only when the composer is not
             // empty or when the cursor is at the correct position, to avoid
             // interfering with normal cursor movement.
             // -------------------------------------------------------------
-            KeyEvent {
-                code: KeyCode::Up | KeyCode::Down,
-                ..
-            } => {
+            _ if history_action.is_some() => {
                 if self
                     .history
                     .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
                 {
-                    let replace_text = match key_event.code {
-                        KeyCode::Up => self.history.navigate_up(&self.app_event_tx),
-                        KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
-                        _ => unreachable!(),
+                    let replace_text = if history_action == Some(KeymapAction::HistoryPrev) {
+                        self.history.navigate_up(&self.app_event_tx)
+                    } else {
+                        self.history.navigate_down(&self.app_event_tx)
                     };
                     if let Some(text) = replace_text {
                         self.textarea.set_text(&text);
@@ -765,11 +852,7 @@ impl ChatComposer {
                 }
                 self.handle_input_basic(key_event)
             }
-            KeyEvent {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
+            _ if keymap.matches(KeymapAction::Submit, &key_event) => {
                 // If we're in a paste-like burst capture, treat Enter as part of the burst
                 // and accumulate it rather than submitting or inserting immediately.
                 // Do not treat Enter as paste inside a slash-command context.
@@ -977,6 +1060,14 @@ impl ChatComposer {
         self.pending_pastes
             .retain(|(placeholder, _)| text_after.contains(placeholder));
 
+        self.retain_attached_images();
+
+        (InputResult::None, true)
+    }
+
+    /// Drops attached images whose placeholders are no longer in the text.
+    fn retain_attached_images(&mut self) {
+        let text = self.textarea.text();
         // Keep attached images in proportion to how many matching placeholders exist in the text.
         // This handles duplicate placeholders that share the same visible label.
         if !self.attached_images.is_empty() {
@@ -984,7 +1075,7 @@ impl ChatComposer {
             for img in &self.attached_images {
                 needed
                     .entry(img.placeholder.clone())
-                    .or_insert_with(|| text_after.matches(&img.placeholder).count());
+                    .or_insert_with(|| text.matches(&img.placeholder).count());
             }
 
             let mut used: HashMap<String, usize> = HashMap::new();
@@ -999,8 +1090,6 @@ impl ChatComposer {
             }
             self.attached_images = kept;
         }
-
-        (InputResult::None, true)
     }
 
     /// Attempts to remove an image or paste placeholder if the cursor is at the end of one.
@@ -1264,6 +1353,7 @@ impl WidgetRef for ChatComposer {
                         esc_backtrack_hint: self.esc_backtrack_hint,
                         use_shift_enter_hint: self.use_shift_enter_hint,
                         token_usage_info: self.token_usage_info.as_ref(),
+                        vim_mode: self.vim.as_ref().map(Vim::mode),
                     },
                 );
             }
@@ -2080,6 +2170,36 @@ mod tests {
         assert_eq!(vec![path], imgs);
     }
 
+    #[test]
+    fn editor_draft_round_trip_keeps_surviving_images() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        let kept = PathBuf::from("/tmp/kept.png");
+        composer.attach_image(kept.clone(), 32, 16, "PNG");
+        composer.attach_image(PathBuf::from("/tmp/dropped.png"), 10, 5, "PNG");
+        let large = "x".repeat(LARGE_PASTE_CHAR_THRESHOLD + 1);
+        composer.handle_paste(large.clone());
+
+        let draft = composer.editor_draft();
+        assert_eq!(draft, format!("[image 32x16 PNG][image 10x5 PNG]{large}"));
+
+        composer.apply_editor_draft("see [image 32x16 PNG] please");
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match result {
+            InputResult::Submitted(text) => assert_eq!(text, "see [image 32x16 PNG] please"),
+            _ => panic!("expected Submitted"),
+        }
+        assert_eq!(vec![kept], composer.take_recent_submission_images());
+    }
+
     #[test]
     fn attach_image_without_text_submits_empty_text_and_images() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
@@ -0,0 +1,175 @@
+//! Editing a command awaiting approval, shown by the approval overlay when
+//! the user chooses to change the command before running it.
+
+use std::cell::RefCell;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::StatefulWidgetRef;
+use ratatui::widgets::Widget;
+
+use super::textarea::TextArea;
+use super::textarea::TextAreaState;
+
+const FOOTER_HINT: &str = "Enter to run the edited command or Esc to go back";
+
+/// Rows of the command shown before it scrolls.
+const MAX_INPUT_ROWS: u16 = 8;
+
+/// What a key press did to the editor.
+pub(crate) enum EditorKey {
+    Handled,
+    /// Run this command instead.
+    Confirm(Vec<String>),
+    /// Go back to the approval options.
+    Back,
+}
+
+pub(crate) struct CommandEditor {
+    textarea: TextArea,
+    textarea_state: RefCell<TextAreaState>,
+    /// Why the last attempt to confirm failed.
+    error: Option<String>,
+}
+
+impl CommandEditor {
+    /// Starts editing `command`, joined into a single line with shell quoting.
+    pub(crate) fn new(command: &[String]) -> Self {
+        let text = shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" "));
+        let mut textarea = TextArea::new();
+        textarea.set_text(&text);
+        textarea.set_cursor(text.len());
+        Self {
+            textarea,
+            textarea_state: RefCell::new(TextAreaState::default()),
+            error: None,
+        }
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) -> EditorKey {
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => return EditorKey::Back,
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => match shlex::split(self.textarea.text()) {
+                Some(command) if !command.is_empty() => return EditorKey::Confirm(command),
+                Some(_) => self.error = Some("The command is empty".to_string()),
+                None => self.error = Some("The command has an unclosed quote".to_string()),
+            },
+            other => {
+                self.error = None;
+                self.textarea.input(other);
+            }
+        }
+        EditorKey::Handled
+    }
+
+    pub(crate) fn handle_paste(&mut self, pasted: &str) {
+        self.error = None;
+        self.textarea.insert_str(pasted);
+    }
+
+    fn input_height(&self, width: u16) -> u16 {
+        self.textarea
+            .desired_height(width.saturating_sub(2))
+            .clamp(1, MAX_INPUT_ROWS)
+    }
+
+    pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        // Title and spacer, input, spacer or error, and footer.
+        2 + self.input_height(width) + 2
+    }
+
+    fn input_area(&self, area: Rect) -> Rect {
+        Rect {
+            x: area.x.saturating_add(2),
+            y: area.y.saturating_add(2),
+            width: area.width.saturating_sub(2),
+            height: self.input_height(area.width),
+        }
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height < 4 || area.width <= 2 {
+            return;
+        }
+        let line = |y: u16| Rect {
+            x: area.x,
+            y,
+            width: area.width,
+            height: 1,
+        };
+        Paragraph::new(Line::from(vec![gutter(), "Edit the command".bold()]))
+            .render(line(area.y), buf);
+        Paragraph::new(Line::from(gutter())).render(line(area.y + 1), buf);
+
+        let input_area = self.input_area(area);
+        for row in 0..input_area.height {
+            Paragraph::new(Line::from(gutter())).render(line(input_area.y + row), buf);
+        }
+        let mut state = self.textarea_state.borrow_mut();
+        StatefulWidgetRef::render_ref(&(&self.textarea), input_area, buf, &mut state);
+
+        if let Some(error) = &self.error {
+            Paragraph::new(Line::from(error.clone().red())).render(line(input_area.bottom()), buf);
+        }
+        Paragraph::new(FOOTER_HINT.dim()).render(line(area.bottom() - 1), buf);
+    }
+
+    pub(crate) fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        if area.height < 4 || area.width <= 2 {
+            return None;
+        }
+        let state = *self.textarea_state.borrow();
+        self.textarea
+            .cursor_pos_with_state(self.input_area(area), state)
+    }
+}
+
+fn gutter() -> Span<'static> {
+    "▌ ".dim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn edited_text_is_split_like_a_shell() {
+        let mut editor = CommandEditor::new(&["git".to_string(), "commit".to_string()]);
+        editor.handle_paste(" -m 'two words'");
+        match editor.handle_key_event(key(KeyCode::Enter)) {
+            EditorKey::Confirm(command) => {
+                assert_eq!(command, vec!["git", "commit", "-m", "two words"]);
+            }
+            _ => panic!("expected the edited command"),
+        }
+
+        editor.handle_paste(" 'unclosed");
+        assert!(matches!(
+            editor.handle_key_event(key(KeyCode::Enter)),
+            EditorKey::Handled
+        ));
+        assert_eq!(
+            editor.error.as_deref(),
+            Some("The command has an unclosed quote")
+        );
+    }
+}
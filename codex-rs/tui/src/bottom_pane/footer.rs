@@ -1,7 +1,8 @@
+use codex_core::config_keymap::Key;
+use codex_core::config_keymap::KeyChord;
+use codex_core::config_keymap::KeymapAction;
 use codex_core::protocol::TokenUsageInfo;
 use codex_protocol::num_format::format_si_suffix;
-use crossterm::event::KeyCode;
-use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
@@ -12,7 +13,9 @@ use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::WidgetRef;
 
+use super::vim::VimMode;
 use crate::key_hint;
+use crate::keymap::keymap;
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct FooterProps<'a> {
@@ -21,6 +24,8 @@ pub(crate) struct FooterProps<'a> {
     pub(crate) esc_backtrack_hint: bool,
     pub(crate) use_shift_enter_hint: bool,
     pub(crate) token_usage_info: Option<&'a TokenUsageInfo>,
+    /// The vim mode of the composer, when vim mode is enabled.
+    pub(crate) vim_mode: Option<VimMode>,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -53,6 +58,9 @@ pub(crate) fn render_footer(area: Rect, buf: &mut Buffer, props: FooterProps<'_>
     };
 
     let mut spans = footer_spans(content);
+    if let Some(vim_mode) = props.vim_mode {
+        spans.splice(0..0, [Span::from(vim_mode.label()).bold(), "   ".into()]);
+    }
     if let Some(token_usage_info) = props.token_usage_info {
         append_token_usage_spans(&mut spans, token_usage_info);
     }
@@ -110,7 +118,7 @@ fn shortcuts_spans(state: ShortcutsState) -> Vec<Span<'static>> {
             if !segment.prefix.is_empty() {
                 spans.push(segment.prefix.into());
             }
-            spans.push(segment.binding.span());
+            spans.push(segment.binding);
             spans.push(segment.label.into());
         }
     }
@@ -123,18 +131,16 @@ fn ctrl_c_reminder_spans(state: CtrlCReminderState) -> Vec<Span<'static>> {
     } else {
         " to quit"
     };
-    vec![
-        " ".into(),
-        key_hint::ctrl('C'),
-        " again".into(),
-        followup.into(),
-    ]
+    let Some(interrupt) = keymap().hint(KeymapAction::Interrupt) else {
+        return Vec::new();
+    };
+    vec![" ".into(), interrupt, " again".into(), followup.into()]
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 struct FooterSegment {
     prefix: &'static str,
-    binding: ShortcutBinding,
+    binding: Span<'static>,
     label: &'static str,
 }
 
@@ -147,67 +153,42 @@ enum ShortcutId {
     EditPrevious,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-struct ShortcutBinding {
-    code: KeyCode,
-    modifiers: KeyModifiers,
-    display: ShortcutDisplay,
-    condition: DisplayCondition,
-}
-
-impl ShortcutBinding {
-    fn span(&self) -> Span<'static> {
-        self.display.into_span()
-    }
-}
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
-enum ShortcutDisplay {
-    Plain(&'static str),
-    Ctrl(char),
-    Shift(char),
-}
-
-impl ShortcutDisplay {
-    fn into_span(self) -> Span<'static> {
-        match self {
-            ShortcutDisplay::Plain(text) => key_hint::plain(text),
-            ShortcutDisplay::Ctrl(ch) => key_hint::ctrl(ch),
-            ShortcutDisplay::Shift(ch) => key_hint::shift(ch),
-        }
-    }
-}
-
+/// Which of the chords bound to an action the footer shows.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum DisplayCondition {
     Always,
-    WhenShiftEnterHint,
-    WhenNotShiftEnterHint,
+    /// Shift+Enter when the terminal can report it, and another chord
+    /// otherwise.
+    ShiftEnterWhenSupported,
 }
 
 impl DisplayCondition {
-    fn matches(self, state: ShortcutsState) -> bool {
+    fn pick(self, chords: &[KeyChord], state: ShortcutsState) -> Option<KeyChord> {
+        let shift_enter = KeyChord::shift(Key::Enter);
         match self {
-            DisplayCondition::Always => true,
-            DisplayCondition::WhenShiftEnterHint => state.use_shift_enter_hint,
-            DisplayCondition::WhenNotShiftEnterHint => !state.use_shift_enter_hint,
+            DisplayCondition::Always => chords.first().copied(),
+            DisplayCondition::ShiftEnterWhenSupported => {
+                if state.use_shift_enter_hint && chords.contains(&shift_enter) {
+                    Some(shift_enter)
+                } else {
+                    chords.iter().copied().find(|chord| *chord != shift_enter)
+                }
+            }
         }
     }
 }
 
 struct ShortcutDescriptor {
     id: ShortcutId,
-    bindings: &'static [ShortcutBinding],
+    action: KeymapAction,
+    condition: DisplayCondition,
     footer_label: &'static str,
     footer_prefix: &'static str,
 }
 
 impl ShortcutDescriptor {
-    fn binding_for(&self, state: ShortcutsState) -> Option<ShortcutBinding> {
-        self.bindings
-            .iter()
-            .find(|binding| binding.condition.matches(state))
-            .copied()
+    fn binding_for(&self, state: ShortcutsState) -> Option<KeyChord> {
+        self.condition.pick(&keymap().chords(self.action), state)
     }
 
     fn should_show(&self, state: ShortcutsState) -> bool {
@@ -224,7 +205,7 @@ impl ShortcutDescriptor {
         let binding = self.binding_for(state)?;
         Some(FooterSegment {
             prefix: self.footer_prefix,
-            binding,
+            binding: key_hint::chord(&binding),
             label: self.footer_label,
         })
     }
@@ -233,64 +214,36 @@ impl ShortcutDescriptor {
 const SHORTCUTS: &[ShortcutDescriptor] = &[
     ShortcutDescriptor {
         id: ShortcutId::Send,
-        bindings: &[ShortcutBinding {
-            code: KeyCode::Enter,
-            modifiers: KeyModifiers::NONE,
-            display: ShortcutDisplay::Plain("⏎"),
-            condition: DisplayCondition::Always,
-        }],
+        action: KeymapAction::Submit,
+        condition: DisplayCondition::Always,
         footer_label: " send   ",
         footer_prefix: "",
     },
     ShortcutDescriptor {
         id: ShortcutId::InsertNewline,
-        bindings: &[
-            ShortcutBinding {
-                code: KeyCode::Enter,
-                modifiers: KeyModifiers::SHIFT,
-                display: ShortcutDisplay::Shift('⏎'),
-                condition: DisplayCondition::WhenShiftEnterHint,
-            },
-            ShortcutBinding {
-                code: KeyCode::Char('j'),
-                modifiers: KeyModifiers::CONTROL,
-                display: ShortcutDisplay::Ctrl('J'),
-                condition: DisplayCondition::WhenNotShiftEnterHint,
-            },
-        ],
+        action: KeymapAction::Newline,
+        condition: DisplayCondition::ShiftEnterWhenSupported,
         footer_label: " newline   ",
         footer_prefix: "",
     },
     ShortcutDescriptor {
         id: ShortcutId::ShowTranscript,
-        bindings: &[ShortcutBinding {
-            code: KeyCode::Char('t'),
-            modifiers: KeyModifiers::CONTROL,
-            display: ShortcutDisplay::Ctrl('T'),
-            condition: DisplayCondition::Always,
-        }],
+        action: KeymapAction::OpenTranscript,
+        condition: DisplayCondition::Always,
         footer_label: " transcript   ",
         footer_prefix: "",
     },
     ShortcutDescriptor {
         id: ShortcutId::Quit,
-        bindings: &[ShortcutBinding {
-            code: KeyCode::Char('c'),
-            modifiers: KeyModifiers::CONTROL,
-            display: ShortcutDisplay::Ctrl('C'),
-            condition: DisplayCondition::Always,
-        }],
+        action: KeymapAction::Interrupt,
+        condition: DisplayCondition::Always,
         footer_label: " quit",
         footer_prefix: "",
     },
     ShortcutDescriptor {
         id: ShortcutId::EditPrevious,
-        bindings: &[ShortcutBinding {
-            code: KeyCode::Esc,
-            modifiers: KeyModifiers::NONE,
-            display: ShortcutDisplay::Plain("Esc"),
-            condition: DisplayCondition::Always,
-        }],
+        action: KeymapAction::Backtrack,
+        condition: DisplayCondition::Always,
         footer_label: " edit prev",
         footer_prefix: "   ",
     },
@@ -347,6 +300,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 token_usage_info: None,
+                vim_mode: None,
             },
         );
 
@@ -358,6 +312,7 @@ mod tests {
                 esc_backtrack_hint: true,
                 use_shift_enter_hint: true,
                 token_usage_info: Some(&token_usage(4_200, 900, 8_000)),
+                vim_mode: None,
             },
         );
 
@@ -369,6 +324,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 token_usage_info: None,
+                vim_mode: None,
             },
         );
 
@@ -380,6 +336,19 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 token_usage_info: None,
+                vim_mode: None,
+            },
+        );
+
+        snapshot_footer(
+            "footer_vim_normal_mode",
+            FooterProps {
+                ctrl_c_quit_hint: false,
+                is_task_running: false,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                token_usage_info: None,
+                vim_mode: Some(VimMode::Normal),
             },
         );
     }
@@ -0,0 +1,513 @@
+//! Reverse search (Ctrl+R) over the messages sent from every session.
+//!
+//! The entries come from `history.jsonl` through `Op::SearchHistoryRequest`,
+//! narrowed server-side to the current directory or session when a filter is
+//! selected, and are ranked here with the same fuzzy matcher as the slash
+//! command popup.
+
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_common::fuzzy_match::fuzzy_match;
+use codex_core::config_keymap::KeymapAction;
+use codex_core::protocol::Op;
+use codex_core::protocol::SearchHistoryResponseEvent;
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::message_history::HistoryEntry;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::exec_command::relativize_to_home;
+use crate::keymap::keymap;
+use crate::resume_picker::human_time_ago;
+
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+use super::popup_consts::MAX_POPUP_ROWS;
+use super::scroll_state::ScrollState;
+use super::selection_popup_common::GenericDisplayRow;
+use super::selection_popup_common::render_rows;
+
+/// Number of newest distinct entries requested for each filter.
+const SEARCH_LIMIT: usize = 1000;
+
+/// Lines of the selected message shown below the matches.
+const PREVIEW_LINES: usize = 3;
+
+/// The footer's key hints. Pressing the history search key again moves to an
+/// older match, as Ctrl+R does in shells.
+fn footer_hint() -> String {
+    let older = keymap()
+        .label(KeymapAction::SearchHistory)
+        .unwrap_or_else(|| "↓".to_string());
+    format!("⏎ use  ⇥ filter  {older} older  esc cancel")
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HistoryFilter {
+    All,
+    Directory,
+    Session,
+}
+
+impl HistoryFilter {
+    fn label(self) -> &'static str {
+        match self {
+            HistoryFilter::All => "all sessions",
+            HistoryFilter::Directory => "this directory",
+            HistoryFilter::Session => "this session",
+        }
+    }
+}
+
+pub(crate) struct HistorySearchView {
+    app_event_tx: AppEventSender,
+    cwd: PathBuf,
+    conversation_id: Option<ConversationId>,
+    filter: HistoryFilter,
+    query: String,
+    /// Newest first, as returned for `filter`. `None` while a search is
+    /// outstanding.
+    entries: Option<Vec<HistoryEntry>>,
+    /// Indices into `entries` matching `query`, best match first, with the
+    /// matched character positions.
+    matches: Vec<(usize, Option<Vec<usize>>)>,
+    state: ScrollState,
+    complete: bool,
+}
+
+impl HistorySearchView {
+    pub(crate) fn new(
+        app_event_tx: AppEventSender,
+        cwd: PathBuf,
+        conversation_id: Option<ConversationId>,
+    ) -> Self {
+        let mut view = Self {
+            app_event_tx,
+            cwd,
+            conversation_id,
+            filter: HistoryFilter::All,
+            query: String::new(),
+            entries: None,
+            matches: Vec::new(),
+            state: ScrollState::new(),
+            complete: false,
+        };
+        view.request_entries();
+        view
+    }
+
+    /// The `cwd` and `conversation_id` filters sent for the current filter.
+    fn filters(&self) -> (Option<PathBuf>, Option<ConversationId>) {
+        match self.filter {
+            HistoryFilter::All => (None, None),
+            HistoryFilter::Directory => (Some(self.cwd.clone()), None),
+            HistoryFilter::Session => (None, self.conversation_id),
+        }
+    }
+
+    fn request_entries(&mut self) {
+        let (cwd, conversation_id) = self.filters();
+        self.entries = None;
+        self.update_matches();
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::SearchHistoryRequest {
+                cwd,
+                conversation_id,
+                limit: SEARCH_LIMIT,
+            }));
+    }
+
+    fn cycle_filter(&mut self) {
+        self.filter = match self.filter {
+            HistoryFilter::All => HistoryFilter::Directory,
+            HistoryFilter::Directory if self.conversation_id.is_some() => HistoryFilter::Session,
+            HistoryFilter::Directory | HistoryFilter::Session => HistoryFilter::All,
+        };
+        self.request_entries();
+    }
+
+    fn update_matches(&mut self) {
+        let query = self.query.trim();
+        let entries = self.entries.as_deref().unwrap_or_default();
+        let mut matches: Vec<(usize, Option<Vec<usize>>, i32)> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, entry)| {
+                if query.is_empty() {
+                    return Some((idx, None, 0));
+                }
+                fuzzy_match(&single_line(&entry.text), query)
+                    .map(|(indices, score)| (idx, Some(indices), score))
+            })
+            .collect();
+        // Stable, so equally good matches stay newest first.
+        matches.sort_by_key(|(_, _, score)| *score);
+        self.matches = matches
+            .into_iter()
+            .map(|(idx, indices, _)| (idx, indices))
+            .collect();
+        self.state.reset();
+        self.state.clamp_selection(self.matches.len());
+    }
+
+    fn selected_entry(&self) -> Option<&HistoryEntry> {
+        let (idx, _) = self.matches.get(self.state.selected_idx?)?;
+        self.entries.as_ref()?.get(*idx)
+    }
+
+    fn move_selection(&mut self, older: bool) {
+        let len = self.matches.len();
+        if older {
+            self.state.move_down_wrap(len);
+        } else {
+            self.state.move_up_wrap(len);
+        }
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    fn rows(&self) -> Vec<GenericDisplayRow> {
+        let Some(entries) = self.entries.as_ref() else {
+            return Vec::new();
+        };
+        self.matches
+            .iter()
+            .filter_map(|(idx, indices)| {
+                Some(GenericDisplayRow {
+                    name: single_line(&entries.get(*idx)?.text),
+                    match_indices: indices.clone(),
+                    is_current: false,
+                    description: None,
+                })
+            })
+            .collect()
+    }
+
+    fn rows_height(&self) -> u16 {
+        self.matches.len().clamp(1, MAX_POPUP_ROWS) as u16
+    }
+
+    fn preview_lines(&self, width: u16) -> Vec<Line<'static>> {
+        let Some(entry) = self.selected_entry() else {
+            return Vec::new();
+        };
+        let mut header = vec![human_time_ago(entry_time(entry))];
+        if let Some(cwd) = entry.cwd.as_ref() {
+            let display = relativize_to_home(cwd)
+                .map(|rel| {
+                    if rel.as_os_str().is_empty() {
+                        "~".to_string()
+                    } else {
+                        format!("~/{}", rel.display())
+                    }
+                })
+                .unwrap_or_else(|| cwd.display().to_string());
+            header.push(display);
+        }
+        let mut lines = vec![Line::from(header.join(" · ").dim())];
+
+        let wrap_width = width.saturating_sub(2).max(1) as usize;
+        let wrapped: Vec<String> = entry
+            .text
+            .lines()
+            .flat_map(|line| textwrap::wrap(line, wrap_width))
+            .map(std::borrow::Cow::into_owned)
+            .collect();
+        let truncated = wrapped.len() > PREVIEW_LINES;
+        for (i, text) in wrapped.into_iter().take(PREVIEW_LINES).enumerate() {
+            if truncated && i + 1 == PREVIEW_LINES {
+                lines.push(Line::from(format!("{text} …")));
+            } else {
+                lines.push(Line::from(text));
+            }
+        }
+        lines
+    }
+}
+
+impl BottomPaneView for HistorySearchView {
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => {
+                self.on_ctrl_c();
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                if let Some(entry) = self.selected_entry() {
+                    self.app_event_tx
+                        .send(AppEvent::SetComposerText(entry.text.clone()));
+                }
+                self.complete = true;
+            }
+            KeyEvent {
+                code: KeyCode::Tab, ..
+            } => self.cycle_filter(),
+            KeyEvent {
+                code: KeyCode::Up, ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('p'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => self.move_selection(false),
+            KeyEvent {
+                code: KeyCode::Down,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('n'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => self.move_selection(true),
+            key_event if keymap().matches(KeymapAction::SearchHistory, &key_event) => {
+                self.move_selection(true)
+            }
+            KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            } => {
+                if self.query.pop().is_some() {
+                    self.update_matches();
+                }
+            }
+            KeyEvent {
+                code: KeyCode::Char(c),
+                modifiers,
+                ..
+            } if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            _ => {}
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn on_ctrl_c(&mut self) -> CancellationEvent {
+        self.complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        let pasted = single_line(&pasted);
+        if pasted.is_empty() {
+            return false;
+        }
+        self.query.push_str(&pasted);
+        self.update_matches();
+        true
+    }
+
+    fn on_history_search_response(&mut self, event: SearchHistoryResponseEvent) {
+        // Ignore responses to a filter that is no longer selected.
+        if (event.cwd, event.conversation_id) != self.filters() {
+            return;
+        }
+        self.entries = Some(event.entries);
+        self.update_matches();
+    }
+
+    fn desired_height(&self, _width: u16) -> u16 {
+        // Title, query and spacer; matches; spacer and preview; spacer and
+        // hint.
+        3 + self.rows_height() + 1 + 1 + PREVIEW_LINES as u16 + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 || area.width == 0 {
+            return;
+        }
+        let bottom = area.y + area.height;
+        let mut next_y = area.y;
+        let render_line = |line: Line<'static>, next_y: &mut u16, buf: &mut Buffer| {
+            if *next_y < bottom {
+                let mut spans: Vec<Span<'static>> = vec!["▌ ".dim()];
+                spans.extend(line.spans);
+                Paragraph::new(Line::from(spans)).render(
+                    Rect {
+                        x: area.x,
+                        y: *next_y,
+                        width: area.width,
+                        height: 1,
+                    },
+                    buf,
+                );
+                *next_y += 1;
+            }
+        };
+
+        render_line(
+            Line::from(vec![
+                "Search history".bold(),
+                format!(" · {}", self.filter.label()).dim(),
+            ]),
+            &mut next_y,
+            buf,
+        );
+        let query_line = if self.query.is_empty() {
+            Line::from("type to search".dim())
+        } else {
+            Line::from(self.query.clone())
+        };
+        render_line(query_line, &mut next_y, buf);
+        render_line(Line::default(), &mut next_y, buf);
+
+        let rows_height = self.rows_height().min(bottom.saturating_sub(next_y));
+        if rows_height > 0 {
+            let empty_message = if self.entries.is_some() {
+                "no matches"
+            } else {
+                "searching…"
+            };
+            render_rows(
+                Rect {
+                    x: area.x,
+                    y: next_y,
+                    width: area.width,
+                    height: rows_height,
+                },
+                buf,
+                &self.rows(),
+                &self.state,
+                MAX_POPUP_ROWS,
+                empty_message,
+                true,
+            );
+            next_y += rows_height;
+        }
+
+        render_line(Line::default(), &mut next_y, buf);
+        for line in self.preview_lines(area.width) {
+            render_line(line, &mut next_y, buf);
+        }
+
+        if area.height >= 2 {
+            Paragraph::new(footer_hint().dim()).render(
+                Rect {
+                    x: area.x,
+                    y: bottom - 1,
+                    width: area.width,
+                    height: 1,
+                },
+                buf,
+            );
+        }
+    }
+}
+
+/// `text` on one line, with line breaks shown as `⏎`. Maps character for
+/// character so that match indices stay valid.
+fn single_line(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\n' => '⏎',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+fn entry_time(entry: &HistoryEntry) -> DateTime<Utc> {
+    DateTime::from_timestamp(entry.ts as i64, 0).unwrap_or_else(Utc::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_event::AppEvent;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn entry(text: &str) -> HistoryEntry {
+        HistoryEntry {
+            conversation_id: "c".to_string(),
+            ts: 0,
+            text: text.to_string(),
+            cwd: Some(PathBuf::from("/repo")),
+        }
+    }
+
+    fn type_str(view: &mut HistorySearchView, text: &str) {
+        for c in text.chars() {
+            view.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn ranks_matches_and_filters_by_directory() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut view =
+            HistorySearchView::new(AppEventSender::new(tx_raw), PathBuf::from("/repo"), None);
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::CodexOp(Op::SearchHistoryRequest {
+                cwd: None,
+                ..
+            }))
+        ));
+
+        view.on_history_search_response(SearchHistoryResponseEvent {
+            cwd: None,
+            conversation_id: None,
+            entries: vec![
+                entry("run the tests\nthen commit"),
+                entry("fix the build"),
+                entry("refactor the bus"),
+            ],
+        });
+        type_str(&mut view, "fb");
+        let names: Vec<String> = view.rows().into_iter().map(|row| row.name).collect();
+        assert_eq!(names, vec!["fix the build", "refactor the bus"]);
+
+        // Switching to this directory asks again and drops stale responses.
+        view.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::CodexOp(Op::SearchHistoryRequest {
+                cwd: Some(_),
+                ..
+            }))
+        ));
+        view.on_history_search_response(SearchHistoryResponseEvent {
+            cwd: None,
+            conversation_id: None,
+            entries: vec![entry("fix the build")],
+        });
+        assert!(view.rows().is_empty());
+
+        view.on_history_search_response(SearchHistoryResponseEvent {
+            cwd: Some(PathBuf::from("/repo")),
+            conversation_id: None,
+            entries: vec![entry("run the tests\nthen commit")],
+        });
+        view.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        view.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        let names: Vec<String> = view.rows().into_iter().map(|row| row.name).collect();
+        assert_eq!(names, vec!["run the tests⏎then commit"]);
+
+        view.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert!(view.is_complete());
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(AppEvent::SetComposerText(text)) if text == "run the tests\nthen commit"
+        ));
+    }
+}
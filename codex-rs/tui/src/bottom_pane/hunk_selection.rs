@@ -0,0 +1,354 @@
+//! Picking the files and hunks of a patch to apply, shown by the approval
+//! overlay when the user chooses to approve only part of a patch.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::FileChange;
+use codex_core::protocol::RejectedPatchHunk;
+use codex_core::protocol::ReviewDecision;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+
+use crate::diff_browser::DiffFile;
+use crate::diff_browser::DiffLine;
+use crate::diff_browser::file_from_change;
+use crate::text_formatting::truncate_text;
+
+use super::popup_consts::MAX_POPUP_ROWS;
+use super::scroll_state::ScrollState;
+use super::selection_popup_common::GenericDisplayRow;
+use super::selection_popup_common::measure_rows_height;
+use super::selection_popup_common::render_rows;
+
+const FOOTER_HINT: &str = "Space to toggle, Enter to apply the selected changes or Esc to go back";
+
+/// Characters of a hunk's first changed line shown next to it.
+const PREVIEW_CHARS: usize = 48;
+
+/// What a key press did to the selection.
+pub(crate) enum SelectionKey {
+    Handled,
+    /// Apply the selected changes.
+    Confirm,
+    /// Go back to the approval options.
+    Back,
+}
+
+/// One file of the patch and which of its hunks are selected.
+struct SelectableFile {
+    /// The file, as a key of the request's `changes`.
+    key: PathBuf,
+    diff: DiffFile,
+    /// One entry per hunk, and a single one for files without hunks, such
+    /// as pure renames.
+    selected: Vec<bool>,
+}
+
+pub(crate) struct HunkSelection {
+    files: Vec<SelectableFile>,
+    /// Each row as a file and, for files with several hunks, one of its hunks.
+    rows: Vec<(usize, Option<usize>)>,
+    state: ScrollState,
+}
+
+impl HunkSelection {
+    pub(crate) fn new(changes: &HashMap<PathBuf, FileChange>, cwd: &Path) -> Self {
+        let mut changes: Vec<(&PathBuf, &FileChange)> = changes.iter().collect();
+        changes.sort_by_key(|(path, _)| *path);
+        let files: Vec<SelectableFile> = changes
+            .into_iter()
+            .map(|(path, change)| {
+                let diff = file_from_change(path, change, cwd);
+                SelectableFile {
+                    key: path.clone(),
+                    selected: vec![true; diff.hunks.len().max(1)],
+                    diff,
+                }
+            })
+            .collect();
+        let mut rows = Vec::new();
+        for (idx, file) in files.iter().enumerate() {
+            rows.push((idx, None));
+            if file.selected.len() > 1 {
+                rows.extend((0..file.selected.len()).map(|hunk| (idx, Some(hunk))));
+            }
+        }
+        let mut state = ScrollState::new();
+        state.clamp_selection(rows.len());
+        Self { files, rows, state }
+    }
+
+    /// Whether the patch has more than one part to choose from.
+    pub(crate) fn has_choices(changes: &HashMap<PathBuf, FileChange>, cwd: &Path) -> bool {
+        let selection = Self::new(changes, cwd);
+        selection.files.len() > 1 || selection.rows.len() > 1
+    }
+
+    /// The decision for the current selection: approve or deny everything,
+    /// or approve all but the files and hunks left unselected.
+    pub(crate) fn decision(&self) -> ReviewDecision {
+        let rejected: Vec<RejectedPatchHunk> = self
+            .files
+            .iter()
+            .flat_map(|file| {
+                if file.selected.iter().all(|selected| !selected) {
+                    return vec![RejectedPatchHunk {
+                        path: file.key.clone(),
+                        hunk: None,
+                    }];
+                }
+                file.selected
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, selected)| !**selected)
+                    .map(|(hunk, _)| RejectedPatchHunk {
+                        path: file.key.clone(),
+                        hunk: Some(hunk),
+                    })
+                    .collect()
+            })
+            .collect();
+        if rejected.is_empty() {
+            ReviewDecision::Approved
+        } else if rejected.len() == self.files.len() && rejected.iter().all(|r| r.hunk.is_none()) {
+            ReviewDecision::Denied
+        } else {
+            ReviewDecision::ApprovedPartially { rejected }
+        }
+    }
+
+    pub(crate) fn handle_key_event(&mut self, key_event: &KeyEvent) -> SelectionKey {
+        let len = self.rows.len();
+        match key_event.code {
+            KeyCode::Up => {
+                self.state.move_up_wrap(len);
+                self.state.ensure_visible(len, MAX_POPUP_ROWS.min(len));
+            }
+            KeyCode::Down => {
+                self.state.move_down_wrap(len);
+                self.state.ensure_visible(len, MAX_POPUP_ROWS.min(len));
+            }
+            KeyCode::Char(' ') => self.toggle_current(),
+            KeyCode::Enter => return SelectionKey::Confirm,
+            KeyCode::Esc => return SelectionKey::Back,
+            _ => {}
+        }
+        SelectionKey::Handled
+    }
+
+    /// Toggle the hunk under the cursor, or all hunks of the file under it.
+    fn toggle_current(&mut self) {
+        let Some((file, hunk)) = self
+            .state
+            .selected_idx
+            .and_then(|idx| self.rows.get(idx).copied())
+        else {
+            return;
+        };
+        let Some(file) = self.files.get_mut(file) else {
+            return;
+        };
+        match hunk {
+            Some(hunk) => {
+                if let Some(selected) = file.selected.get_mut(hunk) {
+                    *selected = !*selected;
+                }
+            }
+            None => {
+                let select = !file.selected.iter().all(|selected| *selected);
+                file.selected
+                    .iter_mut()
+                    .for_each(|selected| *selected = select);
+            }
+        }
+    }
+
+    fn display_rows(&self) -> Vec<GenericDisplayRow> {
+        self.rows
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, (file_idx, hunk))| {
+                let file = self.files.get(*file_idx)?;
+                let cursor = if self.state.selected_idx == Some(idx) {
+                    '>'
+                } else {
+                    ' '
+                };
+                let (name, description) = match hunk {
+                    None => {
+                        let mark = if file.selected.iter().all(|selected| *selected) {
+                            "[x]"
+                        } else if file.selected.iter().any(|selected| *selected) {
+                            "[-]"
+                        } else {
+                            "[ ]"
+                        };
+                        let (added, removed) = file.diff.counts();
+                        (
+                            format!("{cursor} {mark} {}", file.diff.path),
+                            format!("+{added} -{removed}"),
+                        )
+                    }
+                    Some(hunk) => {
+                        let mark = if file.selected.get(*hunk).copied().unwrap_or(false) {
+                            "[x]"
+                        } else {
+                            "[ ]"
+                        };
+                        let diff_hunk = file.diff.hunks.get(*hunk)?;
+                        let preview = diff_hunk
+                            .lines
+                            .iter()
+                            .find_map(|line| match line {
+                                DiffLine::Insert(text) => Some(format!("+{}", text.trim())),
+                                DiffLine::Delete(text) => Some(format!("-{}", text.trim())),
+                                DiffLine::Context(_) => None,
+                            })
+                            .unwrap_or_default();
+                        (
+                            format!("{cursor}     {mark} {}", diff_hunk.header()),
+                            truncate_text(&preview, PREVIEW_CHARS),
+                        )
+                    }
+                };
+                Some(GenericDisplayRow {
+                    name,
+                    match_indices: None,
+                    is_current: false,
+                    description: Some(description),
+                })
+            })
+            .collect()
+    }
+
+    pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        let rows_height =
+            measure_rows_height(&self.display_rows(), &self.state, MAX_POPUP_ROWS, width);
+        // Title and spacer, rows, spacer and footer.
+        2 + rows_height + 2
+    }
+
+    pub(crate) fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height < 4 || area.width == 0 {
+            return;
+        }
+        let line = |y: u16| Rect {
+            x: area.x,
+            y,
+            width: area.width,
+            height: 1,
+        };
+        Paragraph::new(Line::from(vec![
+            "▌ ".dim(),
+            "Choose the changes to apply".bold(),
+        ]))
+        .render(line(area.y), buf);
+        Paragraph::new(Line::from("▌ ".dim())).render(line(area.y + 1), buf);
+        render_rows(
+            Rect {
+                x: area.x,
+                y: area.y + 2,
+                width: area.width,
+                height: area.height - 4,
+            },
+            buf,
+            &self.display_rows(),
+            &self.state,
+            MAX_POPUP_ROWS,
+            "no changes",
+            true,
+        );
+        Paragraph::new(FOOTER_HINT.dim()).render(line(area.bottom() - 1), buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn unselected_hunks_and_files_are_rejected() {
+        let cwd = PathBuf::from("/repo");
+        let changes = HashMap::from([
+            (
+                cwd.join("a.txt"),
+                FileChange::Update {
+                    unified_diff: "@@ -1 +1 @@\n-one\n+ONE\n@@ -9 +9 @@\n-nine\n+NINE\n"
+                        .to_string(),
+                    move_path: None,
+                },
+            ),
+            (
+                cwd.join("b.txt"),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+        ]);
+        let mut selection = HunkSelection::new(&changes, &cwd);
+        assert_eq!(selection.decision(), ReviewDecision::Approved);
+
+        // Rows: a.txt, its two hunks, b.txt. Deselect a.txt's second hunk.
+        selection.handle_key_event(&key(KeyCode::Down));
+        selection.handle_key_event(&key(KeyCode::Down));
+        selection.handle_key_event(&key(KeyCode::Char(' ')));
+        assert_eq!(
+            selection.decision(),
+            ReviewDecision::ApprovedPartially {
+                rejected: vec![RejectedPatchHunk {
+                    path: cwd.join("a.txt"),
+                    hunk: Some(1),
+                }],
+            }
+        );
+
+        // Deselecting a file rejects all of it.
+        selection.handle_key_event(&key(KeyCode::Down));
+        selection.handle_key_event(&key(KeyCode::Char(' ')));
+        assert_eq!(
+            selection.decision(),
+            ReviewDecision::ApprovedPartially {
+                rejected: vec![
+                    RejectedPatchHunk {
+                        path: cwd.join("a.txt"),
+                        hunk: Some(1),
+                    },
+                    RejectedPatchHunk {
+                        path: cwd.join("b.txt"),
+                        hunk: None,
+                    },
+                ],
+            }
+        );
+
+        // Toggling a partly selected file selects all of it, and toggling it
+        // again leaves nothing selected.
+        selection.handle_key_event(&key(KeyCode::Down));
+        selection.handle_key_event(&key(KeyCode::Char(' ')));
+        assert_eq!(
+            selection.decision(),
+            ReviewDecision::ApprovedPartially {
+                rejected: vec![RejectedPatchHunk {
+                    path: cwd.join("b.txt"),
+                    hunk: None,
+                }],
+            }
+        );
+        selection.handle_key_event(&key(KeyCode::Char(' ')));
+        assert_eq!(selection.decision(), ReviewDecision::Denied);
+    }
+}
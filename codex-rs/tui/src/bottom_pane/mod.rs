@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use crate::app_event_sender::AppEventSender;
 use crate::tui::FrameRequester;
 use bottom_pane_view::BottomPaneView;
+use codex_core::protocol::SearchHistoryResponseEvent;
 use codex_core::protocol::TokenUsageInfo;
 use codex_file_search::FileMatch;
 use crossterm::event::KeyCode;
@@ -21,10 +22,14 @@ pub(crate) use approval_overlay::ApprovalRequest;
 mod bottom_pane_view;
 mod chat_composer;
 mod chat_composer_history;
+mod command_editor;
 mod command_popup;
 pub mod custom_prompt_view;
 mod file_search_popup;
 mod footer;
+mod history_search_view;
+mod hunk_selection;
+pub(crate) use history_search_view::HistorySearchView;
 mod list_selection_view;
 pub(crate) use list_selection_view::SelectionViewParams;
 mod paste_burst;
@@ -32,6 +37,7 @@ pub mod popup_consts;
 mod scroll_state;
 mod selection_popup_common;
 mod textarea;
+mod vim;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum CancellationEvent {
@@ -77,20 +83,23 @@ pub(crate) struct BottomPaneParams {
     pub(crate) enhanced_keys_supported: bool,
     pub(crate) placeholder_text: String,
     pub(crate) disable_paste_burst: bool,
+    pub(crate) vim_mode: bool,
 }
 
 impl BottomPane {
     const BOTTOM_PAD_LINES: u16 = 1;
     pub fn new(params: BottomPaneParams) -> Self {
         let enhanced_keys_supported = params.enhanced_keys_supported;
+        let mut composer = ChatComposer::new(
+            params.has_input_focus,
+            params.app_event_tx.clone(),
+            enhanced_keys_supported,
+            params.placeholder_text,
+            params.disable_paste_burst,
+        );
+        composer.set_vim_mode(params.vim_mode);
         Self {
-            composer: ChatComposer::new(
-                params.has_input_focus,
-                params.app_event_tx.clone(),
-                enhanced_keys_supported,
-                params.placeholder_text,
-                params.disable_paste_burst,
-            ),
+            composer,
             view_stack: Vec::new(),
             app_event_tx: params.app_event_tx,
             frame_requester: params.frame_requester,
@@ -199,6 +208,7 @@ impl BottomPane {
             // send an interrupt even while the composer has focus.
             if matches!(key_event.code, crossterm::event::KeyCode::Esc)
                 && self.is_task_running
+                && !self.composer.vim_consumes_esc()
                 && let Some(status) = &self.status
             {
                 // Send Op::Interrupt
@@ -268,6 +278,17 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// The composer draft to edit in an external editor.
+    pub(crate) fn editor_draft(&self) -> String {
+        self.composer.editor_draft()
+    }
+
+    /// Replace the composer draft with the text from an external editor.
+    pub(crate) fn apply_editor_draft(&mut self, text: &str) {
+        self.composer.apply_editor_draft(text);
+        self.request_redraw();
+    }
+
     /// Get the current composer text (for tests and programmatic checks).
     pub(crate) fn composer_text(&self) -> String {
         self.composer.current_text()
@@ -371,10 +392,14 @@ impl BottomPane {
     }
 
     /// Return true when the pane is in the regular composer state without any
-    /// overlays or popups and not running a task. This is the safe context to
-    /// use Esc-Esc for backtracking from the main view.
+    /// overlays or popups, not running a task, and not in a vim mode that uses
+    /// Esc itself. This is the safe context to use Esc-Esc for backtracking
+    /// from the main view.
     pub(crate) fn is_normal_backtrack_mode(&self) -> bool {
-        !self.is_task_running && self.view_stack.is_empty() && !self.composer.popup_active()
+        !self.is_task_running
+            && self.view_stack.is_empty()
+            && !self.composer.popup_active()
+            && !self.composer.vim_consumes_esc()
     }
 
     /// Update the *context-window remaining* indicator in the composer. This
@@ -408,6 +433,20 @@ impl BottomPane {
         self.push_view(Box::new(modal));
     }
 
+    /// Close the approval request `id`, which was answered in another
+    /// terminal attached to this session.
+    pub(crate) fn dismiss_approval_request(&mut self, id: &str) {
+        let views = self.view_stack.len();
+        for view in &mut self.view_stack {
+            view.dismiss_approval_request(id);
+        }
+        self.view_stack.retain(|view| !view.is_complete());
+        if self.view_stack.len() != views {
+            self.on_active_view_complete();
+        }
+        self.request_redraw();
+    }
+
     fn on_active_view_complete(&mut self) {
         self.resume_status_timer_after_modal();
     }
@@ -462,6 +501,13 @@ impl BottomPane {
         }
     }
 
+    pub(crate) fn on_history_search_response(&mut self, event: SearchHistoryResponseEvent) {
+        if let Some(view) = self.view_stack.last_mut() {
+            view.on_history_search_response(event);
+            self.request_redraw();
+        }
+    }
+
     pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
         self.composer.on_file_search_result(query, matches);
         self.request_redraw();
@@ -533,6 +579,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
         pane.push_approval_request(exec_request());
         assert_eq!(CancellationEvent::Handled, pane.on_ctrl_c());
@@ -553,6 +600,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
 
         // Create an approval modal (active view).
@@ -584,6 +632,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
 
         // Start a running task so the status indicator is active above the composer.
@@ -652,6 +701,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
 
         // Begin a task: show initial status.
@@ -683,6 +733,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
 
         // Activate spinner (status view replaces composer) with no live ring.
@@ -734,6 +785,7 @@ mod tests {
             enhanced_keys_supported: false,
             placeholder_text: "Ask Codex to do anything".to_string(),
             disable_paste_burst: false,
+            vim_mode: false,
         });
 
         pane.set_task_running(true);
@@ -1,3 +1,5 @@
+use crate::keymap::keymap;
+use codex_core::config_keymap::KeymapAction;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyModifiers;
@@ -14,6 +16,25 @@ use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+/// Actions handled by the textarea, in the order their chords are tried.
+const EDITING_ACTIONS: &[KeymapAction] = &[
+    KeymapAction::Newline,
+    KeymapAction::DeleteWordBackward,
+    KeymapAction::DeleteBackward,
+    KeymapAction::DeleteWordForward,
+    KeymapAction::DeleteForward,
+    KeymapAction::KillToLineStart,
+    KeymapAction::KillToLineEnd,
+    KeymapAction::MoveWordLeft,
+    KeymapAction::MoveWordRight,
+    KeymapAction::MoveLeft,
+    KeymapAction::MoveRight,
+    KeymapAction::MoveUp,
+    KeymapAction::MoveDown,
+    KeymapAction::LineStart,
+    KeymapAction::LineEnd,
+];
+
 #[derive(Debug, Clone)]
 struct TextElement {
     range: Range<usize>,
@@ -218,191 +239,66 @@ impl TextArea {
                 code: KeyCode::Char(c),
                 // Insert plain characters (and Shift-modified). Do NOT insert when ALT is held,
                 // because many terminals map Option/Meta combos to ALT+<char> (e.g. ESC f/ESC b)
-                // for word navigation. Those are handled explicitly below.
+                // for word navigation. Those are handled by the keymap below.
                 modifiers: KeyModifiers::NONE | KeyModifiers::SHIFT,
                 ..
             } => self.insert_str(&c.to_string()),
-            KeyEvent {
-                code: KeyCode::Char('j' | 'm'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Enter,
-                ..
-            } => self.insert_str("\n"),
-            KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers,
-                ..
-            } if modifiers == (KeyModifiers::CONTROL | KeyModifiers::ALT) => {
-                self.delete_backward_word()
+            _ => match Self::action_for(&event) {
+                Some(action) => self.apply_action(action, event.code),
+                // An Enter that reaches the textarea was not taken as submit.
+                None if event.code == KeyCode::Enter => self.insert_str("\n"),
+                None => {
+                    #[cfg(feature = "debug-logs")]
+                    tracing::debug!("Unhandled key event in TextArea: {:?}", event);
+                }
             },
-            KeyEvent {
-                code: KeyCode::Backspace,
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => self.delete_backward_word(),
-            KeyEvent {
-                code: KeyCode::Backspace,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Char('h'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => self.delete_backward(1),
-            KeyEvent {
-                code: KeyCode::Delete,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }  => self.delete_forward_word(),
-            KeyEvent {
-                code: KeyCode::Delete,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Char('d'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => self.delete_forward(1),
-
-            KeyEvent {
-                code: KeyCode::Char('w'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.delete_backward_word();
-            }
-            // Meta-b -> move to beginning of previous word
-            // Meta-f -> move to end of next word
-            // Many terminals map Option (macOS) to Alt. Some send Alt|Shift, so match contains(ALT).
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => {
-                self.set_cursor(self.beginning_of_previous_word());
-            }
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::ALT,
-                ..
-            } => {
-                self.set_cursor(self.end_of_next_word());
-            }
-            KeyEvent {
-                code: KeyCode::Char('u'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.kill_to_beginning_of_line();
-            }
-            KeyEvent {
-                code: KeyCode::Char('k'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.kill_to_end_of_line();
-            }
-
-            // Cursor movement
-            KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
-                self.move_cursor_left();
-            }
-            KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::NONE,
-                ..
-            } => {
-                self.move_cursor_right();
-            }
-            KeyEvent {
-                code: KeyCode::Char('b'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_left();
-            }
-            KeyEvent {
-                code: KeyCode::Char('f'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_right();
-            }
-            // Some terminals send Alt+Arrow for word-wise movement:
-            // Option/Left -> Alt+Left (previous word start)
-            // Option/Right -> Alt+Right (next word end)
-            KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Left,
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.set_cursor(self.beginning_of_previous_word());
-            }
-            KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::ALT,
-                ..
-            }
-            | KeyEvent {
-                code: KeyCode::Right,
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.set_cursor(self.end_of_next_word());
-            }
-            KeyEvent {
-                code: KeyCode::Up, ..
-            } => {
-                self.move_cursor_up();
-            }
-            KeyEvent {
-                code: KeyCode::Down,
-                ..
-            } => {
-                self.move_cursor_down();
-            }
-            KeyEvent {
-                code: KeyCode::Home,
-                ..
-            } => {
-                self.move_cursor_to_beginning_of_line(false);
-            }
-            KeyEvent {
-                code: KeyCode::Char('a'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_to_beginning_of_line(true);
-            }
+        }
+    }
 
-            KeyEvent {
-                code: KeyCode::End, ..
-            } => {
-                self.move_cursor_to_end_of_line(false);
-            }
-            KeyEvent {
-                code: KeyCode::Char('e'),
-                modifiers: KeyModifiers::CONTROL,
-                ..
-            } => {
-                self.move_cursor_to_end_of_line(true);
-            }
-            _o => {
-                #[cfg(feature = "debug-logs")]
-                tracing::debug!("Unhandled key event in TextArea: {:?}", _o);
-            }
+    fn action_for(event: &KeyEvent) -> Option<KeymapAction> {
+        let keymap = keymap();
+        keymap.action_for(EDITING_ACTIONS, event).or_else(|| {
+            // Terminals report stray modifiers on these keys, which keep their
+            // plain meaning unless a chord with the modifiers is bound.
+            matches!(
+                event.code,
+                KeyCode::Backspace
+                    | KeyCode::Delete
+                    | KeyCode::Up
+                    | KeyCode::Down
+                    | KeyCode::Home
+                    | KeyCode::End
+            )
+            .then(|| {
+                keymap.action_for(
+                    EDITING_ACTIONS,
+                    &KeyEvent::new(event.code, KeyModifiers::NONE),
+                )
+            })
+            .flatten()
+        })
+    }
+
+    fn apply_action(&mut self, action: KeymapAction, code: KeyCode) {
+        match action {
+            KeymapAction::Newline => self.insert_str("\n"),
+            KeymapAction::DeleteWordBackward => self.delete_backward_word(),
+            KeymapAction::DeleteBackward => self.delete_backward(1),
+            KeymapAction::DeleteWordForward => self.delete_forward_word(),
+            KeymapAction::DeleteForward => self.delete_forward(1),
+            KeymapAction::KillToLineStart => self.kill_to_beginning_of_line(),
+            KeymapAction::KillToLineEnd => self.kill_to_end_of_line(),
+            KeymapAction::MoveWordLeft => self.set_cursor(self.beginning_of_previous_word()),
+            KeymapAction::MoveWordRight => self.set_cursor(self.end_of_next_word()),
+            KeymapAction::MoveLeft => self.move_cursor_left(),
+            KeymapAction::MoveRight => self.move_cursor_right(),
+            KeymapAction::MoveUp => self.move_cursor_up(),
+            KeymapAction::MoveDown => self.move_cursor_down(),
+            // Home stays on the current line; the other chords walk back
+            // through earlier lines when repeated.
+            KeymapAction::LineStart => self.move_cursor_to_beginning_of_line(code != KeyCode::Home),
+            KeymapAction::LineEnd => self.move_cursor_to_end_of_line(code != KeyCode::End),
+            _ => {}
         }
     }
 
@@ -648,6 +544,21 @@ impl TextArea {
         self.set_cursor(end);
     }
 
+    /// The byte ranges of the atomic elements, such as pasted placeholders.
+    pub(crate) fn element_ranges(&self) -> Vec<Range<usize>> {
+        self.elements.iter().map(|e| e.range.clone()).collect()
+    }
+
+    /// Replaces the text and its elements, e.g. to restore an earlier state.
+    pub(crate) fn set_text_with_elements(&mut self, text: &str, elements: &[Range<usize>]) {
+        self.set_text(text);
+        for range in elements {
+            if range.end <= self.text.len() {
+                self.add_element(range.clone());
+            }
+        }
+    }
+
     fn add_element(&mut self, range: Range<usize>) {
         let elem = TextElement { range };
         self.elements.push(elem);
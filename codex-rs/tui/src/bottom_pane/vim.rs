@@ -0,0 +1,1189 @@
+//! Opt-in vim-style modal editing for the composer's [`TextArea`].
+//!
+//! Insert mode leaves keys to the composer, so typing, submitting and
+//! pasting behave as usual. Normal and visual mode interpret printable keys
+//! as commands: motions (`h j k l w b e W B E 0 ^ $ gg G`), operators (`d c y`)
+//! applied to motions, repeated operators (`dd`) and text objects (`iw`,
+//! `a"`, `i(` ...), counts, `x X D C Y s S r ~ J p P`, and `u`/`Ctrl+R` for
+//! undo and redo. Keys vim does not know in normal mode, such as Enter, arrows
+//! and other Ctrl chords, are still left to the composer.
+
+use std::ops::Range;
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+
+use super::textarea::TextArea;
+
+/// Undo steps kept per prompt.
+const MAX_UNDO_STEPS: usize = 100;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum VimMode {
+    Insert,
+    Normal,
+    Visual,
+    VisualLine,
+}
+
+impl VimMode {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            VimMode::Insert => "-- INSERT --",
+            VimMode::Normal => "-- NORMAL --",
+            VimMode::Visual => "-- VISUAL --",
+            VimMode::VisualLine => "-- VISUAL LINE --",
+        }
+    }
+}
+
+/// Whether the composer should go on to handle a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum VimOutcome {
+    Handled,
+    PassThrough,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+/// The first keys of a command that needs more.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pending {
+    None,
+    /// `g`, waiting for the second `g`.
+    G,
+    /// `r`, waiting for the replacement character.
+    Replace,
+    /// An operator waiting for its motion or text object.
+    Operator {
+        op: Operator,
+        count: usize,
+    },
+    /// `g` after an operator.
+    OperatorG {
+        op: Operator,
+        count: usize,
+    },
+    /// `i` or `a` after an operator, waiting for the object.
+    TextObject {
+        op: Operator,
+        around: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MotionKind {
+    Exclusive,
+    Inclusive,
+    Linewise,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Motion {
+    target: usize,
+    kind: MotionKind,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Register {
+    text: String,
+    linewise: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Snapshot {
+    text: String,
+    /// Atomic elements of the text, such as pasted placeholders.
+    elements: Vec<Range<usize>>,
+    cursor: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Vim {
+    mode: VimMode,
+    count: Option<usize>,
+    pending: Pending,
+    /// Start of the selection in visual mode.
+    anchor: usize,
+    register: Register,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+impl Vim {
+    pub(crate) fn new(textarea: &TextArea) -> Self {
+        let mut vim = Self {
+            mode: VimMode::Insert,
+            count: None,
+            pending: Pending::None,
+            anchor: 0,
+            register: Register::default(),
+            undo: Vec::new(),
+            redo: Vec::new(),
+        };
+        vim.checkpoint(textarea);
+        vim
+    }
+
+    pub(crate) fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Starts over in insert mode, as after submitting a message.
+    pub(crate) fn reset(&mut self, textarea: &TextArea) {
+        *self = Self {
+            register: std::mem::take(&mut self.register),
+            ..Self::new(textarea)
+        };
+    }
+
+    /// Whether Esc is part of editing rather than a request to the rest of
+    /// the UI, such as backtracking or interrupting.
+    pub(crate) fn consumes_esc(&self) -> bool {
+        self.mode != VimMode::Normal || self.pending != Pending::None || self.count.is_some()
+    }
+
+    pub(crate) fn handle_key(&mut self, textarea: &mut TextArea, key: &KeyEvent) -> VimOutcome {
+        if self.mode == VimMode::Insert {
+            if key.code == KeyCode::Esc {
+                self.enter_normal(textarea);
+                return VimOutcome::Handled;
+            }
+            return VimOutcome::PassThrough;
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                let consumed = self.consumes_esc();
+                self.clear_pending();
+                if self.mode != VimMode::Normal {
+                    self.mode = VimMode::Normal;
+                    clamp_normal(textarea);
+                }
+                if consumed {
+                    VimOutcome::Handled
+                } else {
+                    VimOutcome::PassThrough
+                }
+            }
+            KeyCode::Char('r') if key.modifiers == KeyModifiers::CONTROL => {
+                let count = self.take_count();
+                self.clear_pending();
+                for _ in 0..count {
+                    if let Some(snapshot) = self.redo.pop() {
+                        self.undo.push(snapshot_of(textarea));
+                        restore(textarea, snapshot);
+                    }
+                }
+                VimOutcome::Handled
+            }
+            KeyCode::Backspace if self.pending == Pending::None => {
+                self.motion_key(textarea, 'h');
+                VimOutcome::Handled
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.char_key(textarea, c);
+                VimOutcome::Handled
+            }
+            _ => {
+                self.clear_pending();
+                VimOutcome::PassThrough
+            }
+        }
+    }
+
+    fn char_key(&mut self, textarea: &mut TextArea, c: char) {
+        match self.pending {
+            Pending::Replace => {
+                self.pending = Pending::None;
+                let count = self.take_count();
+                self.replace_chars(textarea, c, count);
+                return;
+            }
+            Pending::TextObject { op, around } => {
+                self.pending = Pending::None;
+                self.count = None;
+                if let Some(range) = text_object(textarea.text(), textarea.cursor(), c, around) {
+                    self.apply_charwise(textarea, op, range.0, range.1);
+                }
+                return;
+            }
+            _ => {}
+        }
+
+        if let Some(digit) = c.to_digit(10)
+            && (digit != 0 || self.count.is_some())
+        {
+            let count = self.count.unwrap_or(0);
+            self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+            return;
+        }
+
+        match self.pending {
+            Pending::Operator { op, count } => self.operator_key(textarea, op, count, c),
+            Pending::OperatorG { op, count } => {
+                self.pending = Pending::None;
+                let explicit = self.count.is_some() || count > 1;
+                let line = self.take_count().saturating_mul(count);
+                if c == 'g' {
+                    let target = goto_line(textarea.text(), explicit.then_some(line), 1);
+                    self.apply_linewise(textarea, op, textarea.cursor(), target);
+                }
+            }
+            Pending::G => {
+                self.pending = Pending::None;
+                let explicit = self.count.is_some();
+                let line = self.take_count();
+                if c == 'g' {
+                    let target = goto_line(textarea.text(), explicit.then_some(line), 1);
+                    self.move_to(textarea, target);
+                }
+            }
+            _ if matches!(self.mode, VimMode::Visual | VimMode::VisualLine) => {
+                self.visual_key(textarea, c)
+            }
+            _ => self.normal_key(textarea, c),
+        }
+    }
+
+    fn normal_key(&mut self, textarea: &mut TextArea, c: char) {
+        let cursor = textarea.cursor();
+        let text = textarea.text();
+        let (bol, eol) = (line_start(text, cursor), line_end(text, cursor));
+        match c {
+            'i' => self.enter_insert(textarea, cursor),
+            'a' => self.enter_insert(textarea, next_char(text, cursor).min(eol)),
+            'I' => self.enter_insert(textarea, first_non_blank(text, bol)),
+            'A' => self.enter_insert(textarea, eol),
+            'o' => {
+                self.checkpoint(textarea);
+                textarea.replace_range(eol..eol, "\n");
+                self.mode = VimMode::Insert;
+                textarea.set_cursor(eol + 1);
+            }
+            'O' => {
+                self.checkpoint(textarea);
+                textarea.replace_range(bol..bol, "\n");
+                self.mode = VimMode::Insert;
+                textarea.set_cursor(bol);
+            }
+            'v' | 'V' => {
+                self.anchor = cursor;
+                self.mode = if c == 'v' {
+                    VimMode::Visual
+                } else {
+                    VimMode::VisualLine
+                };
+            }
+            'x' => {
+                let count = self.take_count();
+                let end = advance(text, cursor, count).min(eol);
+                self.apply_charwise(textarea, Operator::Delete, cursor, end);
+            }
+            'X' => {
+                let count = self.take_count();
+                let start = retreat(text, cursor, count).max(bol);
+                self.apply_charwise(textarea, Operator::Delete, start, cursor);
+            }
+            's' => {
+                let count = self.take_count();
+                let end = advance(text, cursor, count).min(eol);
+                self.apply_charwise(textarea, Operator::Change, cursor, end);
+            }
+            'D' | 'C' => {
+                let count = self.take_count();
+                let end = line_end(text, nth_line_below(text, cursor, count - 1));
+                let op = if c == 'D' {
+                    Operator::Delete
+                } else {
+                    Operator::Change
+                };
+                self.apply_charwise(textarea, op, cursor, end);
+            }
+            'S' | 'Y' => {
+                let count = self.take_count();
+                let last = nth_line_below(text, cursor, count - 1);
+                let op = if c == 'S' {
+                    Operator::Change
+                } else {
+                    Operator::Yank
+                };
+                self.apply_linewise(textarea, op, cursor, last);
+            }
+            'd' | 'c' | 'y' => {
+                let op = match c {
+                    'd' => Operator::Delete,
+                    'c' => Operator::Change,
+                    _ => Operator::Yank,
+                };
+                let count = self.take_count();
+                self.pending = Pending::Operator { op, count };
+            }
+            'p' | 'P' => {
+                let count = self.take_count();
+                self.put(textarea, c == 'p', count);
+            }
+            'u' => {
+                let count = self.take_count();
+                for _ in 0..count {
+                    let Some(snapshot) = self.undo.pop() else {
+                        break;
+                    };
+                    self.redo.push(snapshot_of(textarea));
+                    restore(textarea, snapshot);
+                }
+                clamp_normal(textarea);
+            }
+            'r' => self.pending = Pending::Replace,
+            'g' => self.pending = Pending::G,
+            '~' => {
+                let count = self.take_count();
+                let end = advance(text, cursor, count).min(eol);
+                if end > cursor {
+                    self.checkpoint(textarea);
+                    let toggled: String = textarea.text()[cursor..end]
+                        .chars()
+                        .map(toggle_case)
+                        .collect();
+                    textarea.replace_range(cursor..end, &toggled);
+                    textarea.set_cursor(end);
+                    clamp_normal(textarea);
+                }
+            }
+            'J' => {
+                let count = self.take_count().max(2);
+                self.join_lines(textarea, count - 1);
+            }
+            _ => self.motion_key(textarea, c),
+        }
+    }
+
+    fn visual_key(&mut self, textarea: &mut TextArea, c: char) {
+        let cursor = textarea.cursor();
+        let op = match c {
+            'd' | 'x' => Operator::Delete,
+            'c' | 's' => Operator::Change,
+            'y' => Operator::Yank,
+            'o' => {
+                textarea.set_cursor(self.anchor);
+                self.anchor = cursor;
+                return;
+            }
+            'v' | 'V' => {
+                let mode = if c == 'v' {
+                    VimMode::Visual
+                } else {
+                    VimMode::VisualLine
+                };
+                self.mode = if self.mode == mode {
+                    VimMode::Normal
+                } else {
+                    mode
+                };
+                return;
+            }
+            'g' => {
+                self.pending = Pending::G;
+                return;
+            }
+            _ => {
+                self.motion_key(textarea, c);
+                return;
+            }
+        };
+        self.count = None;
+        let (start, end) = (self.anchor.min(cursor), self.anchor.max(cursor));
+        let linewise = self.mode == VimMode::VisualLine;
+        self.mode = VimMode::Normal;
+        if linewise {
+            self.apply_linewise(textarea, op, start, end);
+        } else {
+            let end = next_char(textarea.text(), end);
+            self.apply_charwise(textarea, op, start, end);
+        }
+    }
+
+    fn operator_key(&mut self, textarea: &mut TextArea, op: Operator, count: usize, c: char) {
+        self.pending = Pending::None;
+        let text = textarea.text();
+        let cursor = textarea.cursor();
+        let doubled = matches!(
+            (op, c),
+            (Operator::Delete, 'd') | (Operator::Change, 'c') | (Operator::Yank, 'y')
+        );
+        if doubled {
+            let count = self.take_count().saturating_mul(count);
+            let last = nth_line_below(text, cursor, count - 1);
+            self.apply_linewise(textarea, op, cursor, last);
+            return;
+        }
+        match c {
+            'i' | 'a' => {
+                self.pending = Pending::TextObject {
+                    op,
+                    around: c == 'a',
+                };
+            }
+            'g' => self.pending = Pending::OperatorG { op, count },
+            _ => {
+                let explicit = self.count.is_some() || count > 1;
+                let total = self.take_count().saturating_mul(count);
+                // `cw` changes to the end of the word, like `ce`, but
+                // starting from the word under the cursor.
+                let change_word =
+                    op == Operator::Change && matches!(c, 'w' | 'W') && !is_blank_at(text, cursor);
+                let motion = if change_word {
+                    let big_word = c == 'W';
+                    Some(Motion {
+                        target: repeat(run_end(text, cursor, big_word), total - 1, |pos| {
+                            word_end(text, pos, big_word)
+                        }),
+                        kind: MotionKind::Inclusive,
+                    })
+                } else {
+                    motion(text, cursor, c, total, explicit)
+                };
+                let Some(mut motion) = motion else {
+                    return;
+                };
+                // A word motion that crosses into the next line stops at the
+                // end of the current one.
+                if matches!(c, 'w' | 'W')
+                    && !change_word
+                    && text[cursor..motion.target.max(cursor)].contains('\n')
+                {
+                    motion.target = line_end(text, cursor);
+                }
+                match motion.kind {
+                    MotionKind::Linewise => {
+                        self.apply_linewise(textarea, op, cursor, motion.target)
+                    }
+                    MotionKind::Exclusive => {
+                        let (start, end) = order(cursor, motion.target);
+                        self.apply_charwise(textarea, op, start, end);
+                    }
+                    MotionKind::Inclusive => {
+                        let (start, end) = order(cursor, motion.target);
+                        let end = next_char(text, end);
+                        self.apply_charwise(textarea, op, start, end);
+                    }
+                }
+            }
+        }
+    }
+
+    fn motion_key(&mut self, textarea: &mut TextArea, c: char) {
+        let explicit = self.count.is_some();
+        let count = self.take_count();
+        if let Some(motion) = motion(textarea.text(), textarea.cursor(), c, count, explicit) {
+            self.move_to(textarea, motion.target);
+        }
+    }
+
+    fn move_to(&mut self, textarea: &mut TextArea, target: usize) {
+        textarea.set_cursor(target);
+        if self.mode == VimMode::Normal {
+            clamp_normal(textarea);
+        }
+    }
+
+    fn apply_charwise(&mut self, textarea: &mut TextArea, op: Operator, start: usize, end: usize) {
+        let text = textarea.text();
+        let end = end.min(text.len());
+        if start >= end && op != Operator::Change {
+            return;
+        }
+        self.register = Register {
+            text: text[start..end].to_string(),
+            linewise: false,
+        };
+        match op {
+            Operator::Yank => textarea.set_cursor(start),
+            Operator::Delete => {
+                self.checkpoint(textarea);
+                textarea.replace_range(start..end, "");
+                textarea.set_cursor(start);
+                clamp_normal(textarea);
+            }
+            Operator::Change => {
+                self.checkpoint(textarea);
+                textarea.replace_range(start..end, "");
+                textarea.set_cursor(start);
+                self.mode = VimMode::Insert;
+            }
+        }
+    }
+
+    /// Applies `op` to the whole lines from the one containing `from` to the
+    /// one containing `to`.
+    fn apply_linewise(&mut self, textarea: &mut TextArea, op: Operator, from: usize, to: usize) {
+        let text = textarea.text();
+        let (from, to) = order(from, to);
+        let (start, end) = (line_start(text, from), line_end(text, to));
+        self.register = Register {
+            text: format!("{}\n", &text[start..end]),
+            linewise: true,
+        };
+        match op {
+            Operator::Yank => textarea.set_cursor(start.max(line_start(text, textarea.cursor()))),
+            Operator::Delete => {
+                self.checkpoint(textarea);
+                let range = if end < text.len() {
+                    start..end + 1
+                } else {
+                    start.saturating_sub(1)..end
+                };
+                textarea.replace_range(range.clone(), "");
+                let text = textarea.text();
+                let cursor = line_start(text, range.start.min(text.len()));
+                textarea.set_cursor(first_non_blank(text, cursor));
+            }
+            Operator::Change => {
+                self.checkpoint(textarea);
+                textarea.replace_range(start..end, "");
+                textarea.set_cursor(start);
+                self.mode = VimMode::Insert;
+            }
+        }
+    }
+
+    fn put(&mut self, textarea: &mut TextArea, after: bool, count: usize) {
+        if self.register.text.is_empty() {
+            return;
+        }
+        self.checkpoint(textarea);
+        let text = textarea.text();
+        let cursor = textarea.cursor();
+        let contents = self.register.text.repeat(count);
+        if self.register.linewise {
+            let (at, inserted) = if after {
+                let eol = line_end(text, cursor);
+                let body = contents.strip_suffix('\n').unwrap_or(&contents);
+                (eol, format!("\n{body}"))
+            } else {
+                (line_start(text, cursor), contents)
+            };
+            textarea.replace_range(at..at, &inserted);
+            let line = if after { at + 1 } else { at };
+            textarea.set_cursor(first_non_blank(textarea.text(), line));
+        } else {
+            let at = if after && cursor < line_end(text, cursor) {
+                next_char(text, cursor)
+            } else {
+                cursor
+            };
+            textarea.replace_range(at..at, &contents);
+            textarea.set_cursor(retreat(textarea.text(), at + contents.len(), 1).max(at));
+        }
+    }
+
+    fn replace_chars(&mut self, textarea: &mut TextArea, c: char, count: usize) {
+        let text = textarea.text();
+        let cursor = textarea.cursor();
+        let eol = line_end(text, cursor);
+        if text[cursor..eol].chars().count() < count {
+            return;
+        }
+        self.checkpoint(textarea);
+        let end = advance(text, cursor, count);
+        let replacement = c.to_string().repeat(count);
+        textarea.replace_range(cursor..end, &replacement);
+        textarea.set_cursor(cursor + replacement.len() - c.len_utf8());
+    }
+
+    fn join_lines(&mut self, textarea: &mut TextArea, joins: usize) {
+        self.checkpoint(textarea);
+        for _ in 0..joins {
+            let text = textarea.text();
+            let eol = line_end(text, textarea.cursor());
+            if eol == text.len() {
+                break;
+            }
+            let next = first_non_blank(text, eol + 1);
+            let separator = if next == line_end(text, eol + 1) {
+                ""
+            } else {
+                " "
+            };
+            textarea.replace_range(eol..next, separator);
+            textarea.set_cursor(eol);
+        }
+    }
+
+    fn enter_insert(&mut self, textarea: &mut TextArea, cursor: usize) {
+        self.count = None;
+        self.checkpoint(textarea);
+        textarea.set_cursor(cursor);
+        self.mode = VimMode::Insert;
+    }
+
+    fn enter_normal(&mut self, textarea: &mut TextArea) {
+        self.mode = VimMode::Normal;
+        // Drop the checkpoint taken when insert mode began if nothing was
+        // typed, so that `u` undoes an actual change.
+        if self.undo.last() == Some(&snapshot_of(textarea)) {
+            self.undo.pop();
+        }
+        let cursor = textarea.cursor();
+        if cursor > line_start(textarea.text(), cursor) {
+            textarea.set_cursor(retreat(textarea.text(), cursor, 1));
+        }
+    }
+
+    fn checkpoint(&mut self, textarea: &TextArea) {
+        if self.undo.len() == MAX_UNDO_STEPS {
+            self.undo.remove(0);
+        }
+        self.undo.push(snapshot_of(textarea));
+        self.redo.clear();
+    }
+
+    fn take_count(&mut self) -> usize {
+        self.count.take().unwrap_or(1).max(1)
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending = Pending::None;
+        self.count = None;
+    }
+}
+
+fn snapshot_of(textarea: &TextArea) -> Snapshot {
+    Snapshot {
+        text: textarea.text().to_string(),
+        elements: textarea.element_ranges(),
+        cursor: textarea.cursor(),
+    }
+}
+
+fn restore(textarea: &mut TextArea, snapshot: Snapshot) {
+    textarea.set_text_with_elements(&snapshot.text, &snapshot.elements);
+    textarea.set_cursor(snapshot.cursor.min(snapshot.text.len()));
+}
+
+/// Keeps the cursor on a character, as normal mode has no position past the
+/// end of a line.
+fn clamp_normal(textarea: &mut TextArea) {
+    let text = textarea.text();
+    let cursor = textarea.cursor();
+    if cursor == line_end(text, cursor) && cursor > line_start(text, cursor) {
+        textarea.set_cursor(retreat(text, cursor, 1));
+    }
+}
+
+fn motion(text: &str, cursor: usize, c: char, count: usize, explicit: bool) -> Option<Motion> {
+    let bol = line_start(text, cursor);
+    let eol = line_end(text, cursor);
+    let (target, kind) = match c {
+        'h' => (retreat(text, cursor, count).max(bol), MotionKind::Exclusive),
+        'l' | ' ' => (advance(text, cursor, count).min(eol), MotionKind::Exclusive),
+        'j' => (
+            line_at_column(text, nth_line_below(text, cursor, count), cursor),
+            MotionKind::Linewise,
+        ),
+        'k' => (
+            line_at_column(text, nth_line_above(text, cursor, count), cursor),
+            MotionKind::Linewise,
+        ),
+        '0' => (bol, MotionKind::Exclusive),
+        '^' => (first_non_blank(text, bol), MotionKind::Exclusive),
+        '$' => {
+            let eol = line_end(text, nth_line_below(text, cursor, count - 1));
+            (
+                retreat(text, eol, 1).max(line_start(text, eol)),
+                MotionKind::Inclusive,
+            )
+        }
+        'G' => (
+            goto_line(text, explicit.then_some(count), usize::MAX),
+            MotionKind::Linewise,
+        ),
+        'w' | 'W' => (
+            repeat(cursor, count, |pos| word_forward(text, pos, c == 'W')),
+            MotionKind::Exclusive,
+        ),
+        'b' | 'B' => (
+            repeat(cursor, count, |pos| word_backward(text, pos, c == 'B')),
+            MotionKind::Exclusive,
+        ),
+        'e' | 'E' => (
+            repeat(cursor, count, |pos| word_end(text, pos, c == 'E')),
+            MotionKind::Inclusive,
+        ),
+        _ => return None,
+    };
+    Some(Motion { target, kind })
+}
+
+/// Applies `step` up to `count` times, stopping early once it no longer
+/// moves so that huge counts stay cheap.
+fn repeat(start: usize, count: usize, step: impl Fn(usize) -> usize) -> usize {
+    let mut pos = start;
+    for _ in 0..count {
+        let next = step(pos);
+        if next == pos {
+            break;
+        }
+        pos = next;
+    }
+    pos
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn class(c: char, big_word: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Blank
+    } else if big_word || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+fn class_at(text: &str, pos: usize, big_word: bool) -> CharClass {
+    text[pos..]
+        .chars()
+        .next()
+        .map_or(CharClass::Blank, |c| class(c, big_word))
+}
+
+fn is_blank_at(text: &str, pos: usize) -> bool {
+    class_at(text, pos, false) == CharClass::Blank
+}
+
+fn word_forward(text: &str, pos: usize, big_word: bool) -> usize {
+    let mut pos = pos;
+    let start_class = class_at(text, pos, big_word);
+    while pos < text.len() && start_class != CharClass::Blank {
+        if class_at(text, pos, big_word) != start_class {
+            break;
+        }
+        pos = next_char(text, pos);
+    }
+    while pos < text.len() && class_at(text, pos, big_word) == CharClass::Blank {
+        pos = next_char(text, pos);
+    }
+    pos
+}
+
+fn word_end(text: &str, pos: usize, big_word: bool) -> usize {
+    let mut pos = next_char(text, pos);
+    while pos < text.len() && class_at(text, pos, big_word) == CharClass::Blank {
+        pos = next_char(text, pos);
+    }
+    if pos >= text.len() {
+        return retreat(text, text.len(), 1);
+    }
+    run_end(text, pos, big_word)
+}
+
+fn word_backward(text: &str, pos: usize, big_word: bool) -> usize {
+    let mut pos = retreat(text, pos, 1);
+    while pos > 0 && class_at(text, pos, big_word) == CharClass::Blank {
+        pos = retreat(text, pos, 1);
+    }
+    run_start(text, pos, big_word)
+}
+
+/// The first character of the run of same-class characters at `pos`.
+fn run_start(text: &str, pos: usize, big_word: bool) -> usize {
+    let class = class_at(text, pos, big_word);
+    let mut start = pos;
+    while start > 0 {
+        let prev = retreat(text, start, 1);
+        if class_at(text, prev, big_word) != class {
+            break;
+        }
+        start = prev;
+    }
+    start
+}
+
+/// The last character of the run of same-class characters at `pos`.
+fn run_end(text: &str, pos: usize, big_word: bool) -> usize {
+    let class = class_at(text, pos, big_word);
+    let mut end = pos;
+    loop {
+        let next = next_char(text, end);
+        if next >= text.len() || class_at(text, next, big_word) != class {
+            return end;
+        }
+        end = next;
+    }
+}
+
+/// The byte range selected by a text object such as `iw` or `a(`.
+fn text_object(text: &str, cursor: usize, object: char, around: bool) -> Option<(usize, usize)> {
+    match object {
+        'w' | 'W' => Some(word_object(text, cursor, object == 'W', around)),
+        '"' | '\'' | '`' => quote_object(text, cursor, object, around),
+        '(' | ')' | 'b' => bracket_object(text, cursor, '(', ')', around),
+        '{' | '}' | 'B' => bracket_object(text, cursor, '{', '}', around),
+        '[' | ']' => bracket_object(text, cursor, '[', ']', around),
+        '<' | '>' => bracket_object(text, cursor, '<', '>', around),
+        _ => None,
+    }
+}
+
+fn word_object(text: &str, cursor: usize, big_word: bool, around: bool) -> (usize, usize) {
+    let start = run_start(text, cursor, big_word);
+    let end = next_char(text, run_end(text, cursor, big_word));
+    if !around {
+        return (start, end);
+    }
+    let eol = line_end(text, cursor);
+    let mut trailing = end;
+    while trailing < eol && is_blank_at(text, trailing) {
+        trailing = next_char(text, trailing);
+    }
+    if trailing > end || is_blank_at(text, cursor) {
+        return (start, trailing);
+    }
+    // Without trailing blanks, `aw` takes the blanks before the word.
+    let bol = line_start(text, cursor);
+    let mut leading = start;
+    while leading > bol && is_blank_at(text, retreat(text, leading, 1)) {
+        leading = retreat(text, leading, 1);
+    }
+    (leading, end)
+}
+
+fn quote_object(text: &str, cursor: usize, quote: char, around: bool) -> Option<(usize, usize)> {
+    let (bol, eol) = (line_start(text, cursor), line_end(text, cursor));
+    let quotes: Vec<usize> = text[bol..eol]
+        .match_indices(quote)
+        .map(|(i, _)| bol + i)
+        .collect();
+    let (open, close) = quotes
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .find(|&(_, close)| close >= cursor)?;
+    if around {
+        Some((open, close + quote.len_utf8()))
+    } else {
+        Some((open + quote.len_utf8(), close))
+    }
+}
+
+fn bracket_object(
+    text: &str,
+    cursor: usize,
+    open: char,
+    close: char,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let at_cursor = text[cursor..].chars().next();
+    let mut depth = 0usize;
+    let mut start = None;
+    let end_of_search = if at_cursor == Some(open) {
+        next_char(text, cursor)
+    } else {
+        cursor
+    };
+    for (i, c) in text[..end_of_search].char_indices().rev() {
+        if c == close {
+            depth += 1;
+        } else if c == open {
+            if depth == 0 {
+                start = Some(i);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let start = start?;
+    let mut depth = 0usize;
+    let inner = start + open.len_utf8();
+    let end = text[inner..].char_indices().find_map(|(i, c)| {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            if depth == 0 {
+                return Some(inner + i);
+            }
+            depth -= 1;
+        }
+        None
+    })?;
+    if around {
+        Some((start, end + close.len_utf8()))
+    } else {
+        Some((inner, end))
+    }
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i)
+}
+
+fn first_non_blank(text: &str, pos: usize) -> usize {
+    let bol = line_start(text, pos);
+    let eol = line_end(text, pos);
+    text[bol..eol]
+        .find(|c: char| c != ' ' && c != '\t')
+        .map_or(eol, |i| bol + i)
+}
+
+fn next_char(text: &str, pos: usize) -> usize {
+    text[pos..]
+        .chars()
+        .next()
+        .map_or(pos, |c| pos + c.len_utf8())
+}
+
+fn advance(text: &str, pos: usize, count: usize) -> usize {
+    repeat(pos, count, |pos| next_char(text, pos))
+}
+
+fn retreat(text: &str, pos: usize, count: usize) -> usize {
+    repeat(pos, count, |pos| {
+        text[..pos]
+            .chars()
+            .next_back()
+            .map_or(pos, |c| pos - c.len_utf8())
+    })
+}
+
+/// The start of the line `count` lines below the one containing `pos`,
+/// stopping at the last line.
+fn nth_line_below(text: &str, pos: usize, count: usize) -> usize {
+    let mut bol = line_start(text, pos);
+    for _ in 0..count {
+        let eol = line_end(text, bol);
+        if eol == text.len() {
+            break;
+        }
+        bol = eol + 1;
+    }
+    bol
+}
+
+/// The start of the line `count` lines above the one containing `pos`,
+/// stopping at the first line.
+fn nth_line_above(text: &str, pos: usize, count: usize) -> usize {
+    let mut bol = line_start(text, pos);
+    for _ in 0..count {
+        if bol == 0 {
+            break;
+        }
+        bol = line_start(text, bol - 1);
+    }
+    bol
+}
+
+/// The position on the line starting at `bol` in the same column as `cursor`.
+fn line_at_column(text: &str, bol: usize, cursor: usize) -> usize {
+    let column = text[line_start(text, cursor)..cursor].chars().count();
+    advance(text, bol, column).min(line_end(text, bol))
+}
+
+/// The first non-blank character of the 1-based `line`, or of `default` when
+/// no count was given. Lines past the end select the last line.
+fn goto_line(text: &str, line: Option<usize>, default: usize) -> usize {
+    let line = line.unwrap_or(default).max(1);
+    first_non_blank(text, nth_line_below(text, 0, line - 1))
+}
+
+fn order(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
+
+fn toggle_case(c: char) -> char {
+    if c.is_uppercase() {
+        c.to_lowercase().next().unwrap_or(c)
+    } else {
+        c.to_uppercase().next().unwrap_or(c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Starts in normal mode on `text` with the cursor at `cursor`, then
+    /// types `keys`, where `<esc>` stands for Esc.
+    fn run(text: &str, cursor: usize, keys: &str) -> (String, usize, VimMode) {
+        let mut textarea = TextArea::new();
+        textarea.set_text(text);
+        textarea.set_cursor(cursor);
+        let mut vim = Vim::new(&textarea);
+        vim.mode = VimMode::Normal;
+        for key in keys.split("<esc>").enumerate().flat_map(|(i, chunk)| {
+            let esc = (i > 0).then_some(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+            esc.into_iter().chain(
+                chunk
+                    .chars()
+                    .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)),
+            )
+        }) {
+            if vim.handle_key(&mut textarea, &key) == VimOutcome::PassThrough {
+                textarea.input(key);
+            }
+        }
+        (textarea.text().to_string(), textarea.cursor(), vim.mode())
+    }
+
+    #[test]
+    fn motions_move_the_cursor() {
+        let text = "foo bar.baz qux\n  second line";
+        assert_eq!(run(text, 0, "w").1, 4);
+        assert_eq!(run(text, 0, "2w").1, 7);
+        assert_eq!(run(text, 0, "W").1, 4);
+        assert_eq!(run(text, 0, "2W").1, 12);
+        assert_eq!(run(text, 0, "e").1, 2);
+        assert_eq!(run(text, 12, "b").1, 8);
+        assert_eq!(run(text, 12, "B").1, 4);
+        assert_eq!(run(text, 5, "$").1, 14);
+        assert_eq!(run(text, 5, "0").1, 0);
+        assert_eq!(run(text, 0, "G").1, 18);
+        assert_eq!(run(text, 20, "gg").1, 0);
+        assert_eq!(run(text, 5, "j").1, 21);
+        assert_eq!(run(text, 21, "^").1, 18);
+        assert_eq!(run(text, 0, "10l").1, 10);
+        assert_eq!(run(text, 0, "999999999j").1, 16);
+    }
+
+    #[test]
+    fn operators_apply_to_motions_and_lines() {
+        assert_eq!(run("foo bar baz", 0, "dw").0, "bar baz");
+        assert_eq!(run("foo bar baz", 0, "d2w").0, "baz");
+        assert_eq!(run("foo bar baz", 4, "d$").0, "foo ");
+        assert_eq!(run("foo bar\nbaz", 4, "dw").0, "foo \nbaz");
+        assert_eq!(
+            run("one\ntwo\nthree", 4, "dd"),
+            ("one\nthree".into(), 4, VimMode::Normal)
+        );
+        assert_eq!(run("one\ntwo\nthree", 8, "dd").0, "one\ntwo");
+        assert_eq!(run("one\ntwo\nthree", 0, "2dd").0, "three");
+        assert_eq!(run("one\ntwo\nthree", 0, "dj").0, "three");
+        assert_eq!(
+            run("foo bar", 0, "cwnew<esc>"),
+            ("new bar".into(), 2, VimMode::Normal)
+        );
+        assert_eq!(run("foo bar", 0, "ccnew").0, "new");
+        assert_eq!(run("foo bar", 0, "ywP").0, "foo foo bar");
+        assert_eq!(run("one\ntwo", 0, "yyp").0, "one\none\ntwo");
+        assert_eq!(run("one\ntwo", 4, "ddP").0, "two\none");
+    }
+
+    #[test]
+    fn text_objects_select_words_quotes_and_brackets() {
+        assert_eq!(run("foo bar baz", 5, "diw").0, "foo  baz");
+        assert_eq!(run("foo bar baz", 5, "daw").0, "foo baz");
+        assert_eq!(run(r#"say "hi there" ok"#, 7, "ci\"yo").0, r#"say "yo" ok"#);
+        assert_eq!(run(r#"say "hi there" ok"#, 7, "da\"").0, "say  ok");
+        assert_eq!(run("f(a, (b), c)", 5, "di(").0, "f(a, (), c)");
+        assert_eq!(run("f(a, (b), c)", 3, "dab").0, "f");
+        assert_eq!(run("{ x }", 2, "yi{P").0, "{ x  x }");
+    }
+
+    #[test]
+    fn counts_and_simple_edits() {
+        assert_eq!(run("abcdef", 1, "3x").0, "aef");
+        assert_eq!(run("abcdef", 3, "X").0, "abdef");
+        assert_eq!(run("abcdef", 0, "2rz").0, "zzcdef");
+        assert_eq!(run("abc", 0, "~~").0, "ABc");
+        assert_eq!(run("one\n  two", 0, "J").0, "one two");
+        assert_eq!(run("abc def", 4, "D").0, "abc ");
+        assert_eq!(
+            run("abc", 1, "ax<esc>"),
+            ("abxc".into(), 2, VimMode::Normal)
+        );
+        assert_eq!(run("abc", 1, "Ax").0, "abcx");
+        assert_eq!(run("  abc", 4, "Ix").0, "  xabc");
+        assert_eq!(run("one\ntwo", 0, "onew").0, "one\nnew\ntwo");
+        assert_eq!(run("one\ntwo", 4, "Onew").0, "one\nnew\ntwo");
+        // Counts on both the operator and the motion multiply without
+        // overflowing, and an undo count stops at the oldest change.
+        assert_eq!(run("one\ntwo", 0, "99999999999d99999999999d").0, "");
+        assert_eq!(run("one two", 0, "99999999999d99999999999w").0, "");
+        assert_eq!(run("one\ntwo", 0, "99999999999d99999999999gg").0, "");
+        assert_eq!(run("abc", 0, "x99999999999u").0, "abc");
+    }
+
+    #[test]
+    fn visual_mode_selects_before_operating() {
+        assert_eq!(run("foo bar baz", 4, "ved").0, "foo  baz");
+        assert_eq!(run("foo bar baz", 6, "vbd").0, "foo  baz");
+        assert_eq!(run("one\ntwo\nthree", 0, "Vjd").0, "three");
+        assert_eq!(run("foo bar", 0, "veyP").0, "foofoo bar");
+        assert_eq!(run("foo bar", 0, "v<esc>").2, VimMode::Normal);
+    }
+
+    #[test]
+    fn undo_and_redo_restore_text() {
+        let mut textarea = TextArea::new();
+        textarea.set_text("foo bar baz");
+        let mut vim = Vim::new(&textarea);
+        vim.mode = VimMode::Normal;
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        for c in ['d', 'w', 'x'] {
+            vim.handle_key(&mut textarea, &key(c));
+        }
+        assert_eq!(textarea.text(), "ar baz");
+
+        vim.handle_key(&mut textarea, &key('u'));
+        assert_eq!(textarea.text(), "bar baz");
+        vim.handle_key(&mut textarea, &key('u'));
+        assert_eq!(textarea.text(), "foo bar baz");
+        vim.handle_key(
+            &mut textarea,
+            &KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+        );
+        assert_eq!(textarea.text(), "bar baz");
+    }
+
+    #[test]
+    fn undo_restores_elements() {
+        let mut textarea = TextArea::new();
+        textarea.insert_element("[Pasted Content 1000 chars]");
+        let elements = textarea.element_ranges();
+        let mut vim = Vim::new(&textarea);
+        vim.mode = VimMode::Normal;
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        vim.handle_key(&mut textarea, &key('d'));
+        vim.handle_key(&mut textarea, &key('d'));
+        assert_eq!(textarea.text(), "");
+
+        vim.handle_key(&mut textarea, &key('u'));
+        assert_eq!(textarea.text(), "[Pasted Content 1000 chars]");
+        assert_eq!(textarea.element_ranges(), elements);
+    }
+
+    #[test]
+    fn esc_in_normal_mode_is_left_to_the_composer() {
+        let mut textarea = TextArea::new();
+        let mut vim = Vim::new(&textarea);
+        let esc = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+        assert!(vim.consumes_esc());
+        assert_eq!(vim.handle_key(&mut textarea, &esc), VimOutcome::Handled);
+        assert!(!vim.consumes_esc());
+        assert_eq!(vim.handle_key(&mut textarea, &esc), VimOutcome::PassThrough);
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            vim.handle_key(&mut textarea, &enter),
+            VimOutcome::PassThrough
+        );
+    }
+}
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::config::Config;
+use codex_core::config_keymap::KeymapAction;
 use codex_core::config_types::Notifications;
 use codex_core::git_info::current_branch_name;
 use codex_core::git_info::local_git_branches;
@@ -28,7 +29,10 @@ use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::NO_TURN_TO_STEER_MESSAGE;
 use codex_core::protocol::Op;
+use codex_core::protocol::OverlayDecision;
+use codex_core::protocol::OverlayResolvedEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
@@ -43,10 +47,8 @@ use codex_core::protocol::WebSearchBeginEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::parse_command::ParsedCommand;
-use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
-use crossterm::event::KeyModifiers;
 use rand::Rng;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint;
@@ -63,6 +65,7 @@ use crate::bottom_pane::ApprovalRequest;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::HistorySearchView;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
@@ -80,6 +83,7 @@ use crate::history_cell::AgentMessageCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::McpToolCallCell;
 use crate::history_cell::PatchEventType;
+use crate::keymap::keymap;
 use crate::markdown::append_markdown;
 use crate::slash_command::SlashCommand;
 use crate::status::RateLimitSnapshotDisplay;
@@ -90,6 +94,8 @@ use self::interrupts::InterruptManager;
 mod agent;
 use self::agent::spawn_agent;
 use self::agent::spawn_agent_from_existing;
+#[cfg(unix)]
+use self::agent::spawn_attached_agent;
 mod session_header;
 use self::session_header::SessionHeader;
 use crate::streaming::controller::StreamController;
@@ -247,6 +253,9 @@ pub(crate) struct ChatWidget {
     suppress_session_configured_redraw: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Messages sent as steers since the last turn started, which Codex
+    // rejects if the turn ended before it received them
+    steered_user_messages: Vec<UserMessage>,
     // Pending notification to show when unfocused on next Draw
     pending_notification: Option<Notification>,
     // Simple review mode flag; used to adjust layout and banners.
@@ -254,10 +263,14 @@ pub(crate) struct ChatWidget {
     // List of ghost commits corresponding to each turn.
     ghost_snapshots: Vec<GhostCommit>,
     ghost_snapshots_disabled: bool,
+    // Diff of the overlay layer against the workspace, offered for commit
+    // when the turn completes.
+    overlay_diff: Option<String>,
     // Whether to add a final message separator after the last message
     needs_final_message_separator: bool,
 }
 
+#[derive(Clone)]
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
@@ -280,6 +293,20 @@ fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Optio
     }
 }
 
+/// The input items for a message's text and attached images.
+fn input_items(text: &str, image_paths: Vec<PathBuf>) -> Vec<InputItem> {
+    let mut items: Vec<InputItem> = Vec::new();
+    if !text.is_empty() {
+        items.push(InputItem::Text {
+            text: text.to_string(),
+        });
+    }
+    for path in image_paths {
+        items.push(InputItem::LocalImage { path });
+    }
+    items
+}
+
 impl ChatWidget {
     fn flush_answer_stream_with_separator(&mut self) {
         if let Some(mut controller) = self.stream_controller.take()
@@ -370,6 +397,7 @@ impl ChatWidget {
     // Raw reasoning uses the same flow as summarized reasoning
 
     fn on_task_started(&mut self) {
+        self.steered_user_messages.clear();
         self.bottom_pane.clear_ctrl_c_quit_hint();
         self.bottom_pane.set_task_running(true);
         self.full_reasoning_buffer.clear();
@@ -385,6 +413,11 @@ impl ChatWidget {
         self.running_commands.clear();
         self.request_redraw();
 
+        if let Some(diff) = self.overlay_diff.take()
+            && self.queued_user_messages.is_empty()
+        {
+            self.open_overlay_popup(diff);
+        }
         // If there is a queued user message, send exactly one now to begin the next turn.
         self.maybe_send_next_queued_input();
         // Emit a notification when the turn completes (suppressed if focused).
@@ -442,6 +475,16 @@ impl ChatWidget {
     }
 
     fn on_error(&mut self, message: String) {
+        // The turn ended before the steers reached it: send them as the next
+        // message instead.
+        if message == NO_TURN_TO_STEER_MESSAGE && !self.steered_user_messages.is_empty() {
+            for user_message in self.steered_user_messages.drain(..).rev() {
+                self.queued_user_messages.push_front(user_message);
+            }
+            self.maybe_send_next_queued_input();
+            self.request_redraw();
+            return;
+        }
         self.finalize_turn();
         self.add_to_history(history_cell::new_error_event(message));
         self.request_redraw();
@@ -587,6 +630,32 @@ impl ChatWidget {
 
     fn on_turn_diff(&mut self, unified_diff: String) {
         debug!("TurnDiffEvent: {unified_diff}");
+        if matches!(self.config.sandbox_policy, SandboxPolicy::Overlay { .. }) {
+            self.overlay_diff = Some(unified_diff);
+        }
+    }
+
+    fn on_overlay_resolved(&mut self, ev: OverlayResolvedEvent) {
+        let files = match ev.changed_files {
+            1 => "1 file".to_string(),
+            n => format!("{n} files"),
+        };
+        let message = match ev.decision {
+            OverlayDecision::Commit => format!("Committed changes to {files} to the workspace"),
+            OverlayDecision::Discard => format!("Discarded changes to {files}"),
+        };
+        self.add_info_message(message, None);
+    }
+
+    /// Core stopped the task at a configured turn limit. The model's summary
+    /// has already streamed in and `TaskComplete` follows, so only note why
+    /// the turn ended.
+    fn on_turn_limit_reached(&mut self) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_warning_event(
+            "Turn stopped after reaching a configured turn limit".to_owned(),
+        ));
+        self.request_redraw();
     }
 
     fn on_background_event(&mut self, message: String) {
@@ -755,6 +824,8 @@ impl ChatWidget {
             id,
             reason: ev.reason,
             grant_root: ev.grant_root,
+            changes: ev.changes.clone(),
+            cwd: self.config.cwd.clone(),
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -862,58 +933,12 @@ impl ChatWidget {
         common: ChatWidgetInit,
         conversation_manager: Arc<ConversationManager>,
     ) -> Self {
-        let ChatWidgetInit {
-            config,
-            frame_requester,
-            app_event_tx,
-            initial_prompt,
-            initial_images,
-            enhanced_keys_supported,
-            auth_manager,
-        } = common;
-        let mut rng = rand::rng();
-        let placeholder = EXAMPLE_PROMPTS[rng.random_range(0..EXAMPLE_PROMPTS.len())].to_string();
-        let codex_op_tx = spawn_agent(config.clone(), app_event_tx.clone(), conversation_manager);
-
-        Self {
-            app_event_tx: app_event_tx.clone(),
-            frame_requester: frame_requester.clone(),
-            codex_op_tx,
-            bottom_pane: BottomPane::new(BottomPaneParams {
-                frame_requester,
-                app_event_tx,
-                has_input_focus: true,
-                enhanced_keys_supported,
-                placeholder_text: placeholder,
-                disable_paste_burst: config.disable_paste_burst,
-            }),
-            active_cell: None,
-            config: config.clone(),
-            auth_manager,
-            session_header: SessionHeader::new(config.model),
-            initial_user_message: create_initial_user_message(
-                initial_prompt.unwrap_or_default(),
-                initial_images,
-            ),
-            token_info: None,
-            rate_limit_snapshot: None,
-            rate_limit_warnings: RateLimitWarningState::default(),
-            stream_controller: None,
-            running_commands: HashMap::new(),
-            task_complete_pending: false,
-            interrupts: InterruptManager::new(),
-            reasoning_buffer: String::new(),
-            full_reasoning_buffer: String::new(),
-            conversation_id: None,
-            queued_user_messages: VecDeque::new(),
-            show_welcome_banner: true,
-            suppress_session_configured_redraw: false,
-            pending_notification: None,
-            is_review_mode: false,
-            ghost_snapshots: Vec::new(),
-            ghost_snapshots_disabled: true,
-            needs_final_message_separator: false,
-        }
+        let codex_op_tx = spawn_agent(
+            common.config.clone(),
+            common.app_event_tx.clone(),
+            conversation_manager,
+        );
+        Self::with_agent(common, codex_op_tx, false)
     }
 
     /// Create a ChatWidget attached to an existing conversation (e.g., a fork).
@@ -921,6 +946,34 @@ impl ChatWidget {
         common: ChatWidgetInit,
         conversation: std::sync::Arc<codex_core::CodexConversation>,
         session_configured: codex_core::protocol::SessionConfiguredEvent,
+    ) -> Self {
+        let codex_op_tx = spawn_agent_from_existing(
+            conversation,
+            session_configured,
+            common.config.codex_home.clone(),
+            common.app_event_tx.clone(),
+        );
+        Self::with_agent(common, codex_op_tx, true)
+    }
+
+    /// Create a ChatWidget that mirrors a conversation hosted by another TUI
+    /// (`codex attach`).
+    #[cfg(unix)]
+    pub(crate) fn new_attached(
+        common: ChatWidgetInit,
+        stream: tokio::net::UnixStream,
+        read_only: bool,
+    ) -> Self {
+        let codex_op_tx = spawn_attached_agent(stream, read_only, common.app_event_tx.clone());
+        Self::with_agent(common, codex_op_tx, true)
+    }
+
+    /// The widget state shared by every constructor; `codex_op_tx` reaches
+    /// whichever agent loop drives the conversation.
+    fn with_agent(
+        common: ChatWidgetInit,
+        codex_op_tx: UnboundedSender<Op>,
+        suppress_session_configured_redraw: bool,
     ) -> Self {
         let ChatWidgetInit {
             config,
@@ -934,9 +987,6 @@ impl ChatWidget {
         let mut rng = rand::rng();
         let placeholder = EXAMPLE_PROMPTS[rng.random_range(0..EXAMPLE_PROMPTS.len())].to_string();
 
-        let codex_op_tx =
-            spawn_agent_from_existing(conversation, session_configured, app_event_tx.clone());
-
         Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
@@ -948,6 +998,7 @@ impl ChatWidget {
                 enhanced_keys_supported,
                 placeholder_text: placeholder,
                 disable_paste_burst: config.disable_paste_burst,
+                vim_mode: config.tui_vim_mode,
             }),
             active_cell: None,
             config: config.clone(),
@@ -968,12 +1019,14 @@ impl ChatWidget {
             full_reasoning_buffer: String::new(),
             conversation_id: None,
             queued_user_messages: VecDeque::new(),
+            steered_user_messages: Vec::new(),
             show_welcome_banner: true,
-            suppress_session_configured_redraw: true,
+            suppress_session_configured_redraw,
             pending_notification: None,
             is_review_mode: false,
             ghost_snapshots: Vec::new(),
             ghost_snapshots_disabled: true,
+            overlay_diff: None,
             needs_final_message_separator: false,
         }
     }
@@ -987,22 +1040,19 @@ impl ChatWidget {
     }
 
     pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) {
+        let keymap = keymap();
         match key_event {
             KeyEvent {
-                code: KeyCode::Char('c'),
-                modifiers: crossterm::event::KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if keymap.matches(KeymapAction::Interrupt, &key_event) => {
                 self.on_ctrl_c();
                 return;
             }
             KeyEvent {
-                code: KeyCode::Char('v'),
-                modifiers: KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } => {
+            } if keymap.matches(KeymapAction::PasteImage, &key_event) => {
                 if let Ok((path, info)) = paste_image_to_temp_png() {
                     self.attach_image(path, info.width, info.height, info.encoded_format.label());
                 }
@@ -1016,11 +1066,11 @@ impl ChatWidget {
 
         match key_event {
             KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::ALT,
                 kind: KeyEventKind::Press,
                 ..
-            } if !self.queued_user_messages.is_empty() => {
+            } if keymap.matches(KeymapAction::EditQueuedMessage, &key_event)
+                && !self.queued_user_messages.is_empty() =>
+            {
                 // Prefer the most recently queued item.
                 if let Some(user_message) = self.queued_user_messages.pop_back() {
                     self.bottom_pane.set_composer_text(user_message.text);
@@ -1028,6 +1078,15 @@ impl ChatWidget {
                     self.request_redraw();
                 }
             }
+            KeyEvent {
+                kind: KeyEventKind::Press,
+                ..
+            } if keymap.matches(KeymapAction::SteerTurn, &key_event)
+                && self.bottom_pane.is_task_running()
+                && !self.queued_user_messages.is_empty() =>
+            {
+                self.steer_queued_messages();
+            }
             _ => {
                 match self.bottom_pane.handle_key_event(key_event) {
                     InputResult::Submitted(text) => {
@@ -1130,6 +1189,9 @@ impl ChatWidget {
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
+            SlashCommand::Edit => {
+                self.app_event_tx.send(AppEvent::OpenExternalEditor);
+            }
             SlashCommand::Status => {
                 self.add_status_output();
             }
@@ -1227,16 +1289,7 @@ impl ChatWidget {
 
         self.capture_ghost_snapshot();
 
-        let mut items: Vec<InputItem> = Vec::new();
-
-        if !text.is_empty() {
-            items.push(InputItem::Text { text: text.clone() });
-        }
-
-        for path in image_paths {
-            items.push(InputItem::LocalImage { path });
-        }
-
+        let items = input_items(&text, image_paths);
         self.codex_op_tx
             .send(Op::UserInput { items })
             .unwrap_or_else(|e| {
@@ -1259,6 +1312,27 @@ impl ChatWidget {
         self.needs_final_message_separator = false;
     }
 
+    /// Send the queued messages to the running turn, which sees them at its
+    /// next tool-call boundary instead of after it ends.
+    fn steer_queued_messages(&mut self) {
+        while let Some(user_message) = self.queued_user_messages.pop_front() {
+            if user_message.text.is_empty() && user_message.image_paths.is_empty() {
+                continue;
+            }
+            self.steered_user_messages.push(user_message.clone());
+            let UserMessage { text, image_paths } = user_message;
+            self.submit_op(Op::Steer {
+                items: input_items(&text, image_paths),
+            });
+            if !text.is_empty() {
+                self.submit_op(Op::AddToHistory { text: text.clone() });
+                self.add_to_history(history_cell::new_steer_note(text));
+            }
+        }
+        self.refresh_queued_user_messages();
+        self.request_redraw();
+    }
+
     fn capture_ghost_snapshot(&mut self) {
         if self.ghost_snapshots_disabled {
             return;
@@ -1317,7 +1391,8 @@ impl ChatWidget {
     /// distinguish replayed events from live ones.
     fn replay_initial_messages(&mut self, events: Vec<EventMsg>) {
         for msg in events {
-            if matches!(msg, EventMsg::SessionConfigured(_)) {
+            // A recorded turn diff may no longer match the files on disk.
+            if matches!(msg, EventMsg::SessionConfigured(_) | EventMsg::TurnDiff(_)) {
                 continue;
             }
             // `id: None` indicates a synthetic/fake id coming from replay.
@@ -1380,6 +1455,7 @@ impl ChatWidget {
                 TurnAbortReason::ReviewEnded => {
                     self.on_interrupted_turn(ev.reason);
                 }
+                TurnAbortReason::LimitReached => self.on_turn_limit_reached(),
             },
             EventMsg::PlanUpdate(update) => self.on_plan_update(update),
             EventMsg::ExecApprovalRequest(ev) => {
@@ -1399,6 +1475,7 @@ impl ChatWidget {
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
+            EventMsg::SearchHistoryResponse(ev) => self.bottom_pane.on_history_search_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
@@ -1420,6 +1497,7 @@ impl ChatWidget {
                 self.on_entered_review_mode(review_request)
             }
             EventMsg::ExitedReviewMode(review) => self.on_exited_review_mode(review),
+            EventMsg::OverlayResolved(ev) => self.on_overlay_resolved(ev),
         }
     }
 
@@ -1634,10 +1712,12 @@ impl ChatWidget {
         let mut items: Vec<SelectionItem> = Vec::new();
         let presets: Vec<ApprovalPreset> = builtin_approval_presets();
         for preset in presets.into_iter() {
-            let is_current =
-                current_approval == preset.approval && current_sandbox == preset.sandbox;
             let approval = preset.approval;
-            let sandbox = preset.sandbox.clone();
+            let sandbox = preset
+                .sandbox
+                .clone()
+                .with_deny_read(self.config.sandbox_deny_read.clone());
+            let is_current = current_approval == approval && current_sandbox == sandbox;
             let name = preset.label.to_string();
             let description = Some(preset.description.to_string());
             let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
@@ -1670,6 +1750,59 @@ impl ChatWidget {
         });
     }
 
+    /// Offer to commit or discard the changes the turn left in the overlay
+    /// layer.
+    fn open_overlay_popup(&mut self, diff: String) {
+        let resolve = |decision: OverlayDecision| -> Vec<SelectionAction> {
+            vec![Box::new(move |tx| {
+                tx.send(AppEvent::CodexOp(Op::ResolveOverlay { decision }));
+            })]
+        };
+        let items = vec![
+            SelectionItem {
+                name: "View changes".to_string(),
+                description: None,
+                is_current: false,
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::DiffResult(diff.clone()));
+                })],
+                dismiss_on_select: false,
+                search_value: None,
+            },
+            SelectionItem {
+                name: "Commit changes to the workspace".to_string(),
+                description: None,
+                is_current: false,
+                actions: resolve(OverlayDecision::Commit),
+                dismiss_on_select: true,
+                search_value: None,
+            },
+            SelectionItem {
+                name: "Discard changes".to_string(),
+                description: None,
+                is_current: false,
+                actions: resolve(OverlayDecision::Discard),
+                dismiss_on_select: true,
+                search_value: None,
+            },
+            SelectionItem {
+                name: "Keep changes in the overlay".to_string(),
+                description: Some("decide after a later turn".to_string()),
+                is_current: false,
+                actions: Vec::new(),
+                dismiss_on_select: true,
+                search_value: None,
+            },
+        ];
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "The turn's changes are in the overlay".to_string(),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
+
     /// Set the approval policy in the widget's config copy.
     pub(crate) fn set_approval_policy(&mut self, policy: AskForApproval) {
         self.config.approval_policy = policy;
@@ -1744,6 +1877,14 @@ impl ChatWidget {
         self.bottom_pane.insert_str(text);
     }
 
+    pub(crate) fn editor_draft(&self) -> String {
+        self.bottom_pane.editor_draft()
+    }
+
+    pub(crate) fn apply_editor_draft(&mut self, text: &str) {
+        self.bottom_pane.apply_editor_draft(text);
+    }
+
     /// Replace the composer content with the provided text and reset cursor.
     pub(crate) fn set_composer_text(&mut self, text: String) {
         self.bottom_pane.set_composer_text(text);
@@ -1946,6 +2087,23 @@ impl ChatWidget {
         self.bottom_pane.show_view(Box::new(view));
     }
 
+    /// Close the approval prompt for `id`, which another terminal attached to
+    /// this session already answered.
+    pub(crate) fn dismiss_approval_request(&mut self, id: &str) {
+        self.interrupts.remove_approval(id);
+        self.bottom_pane.dismiss_approval_request(id);
+    }
+
+    /// Open the Ctrl+R search over the messages sent from every session.
+    pub(crate) fn open_history_search(&mut self) {
+        let view = HistorySearchView::new(
+            self.app_event_tx.clone(),
+            self.config.cwd.clone(),
+            self.conversation_id,
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
     /// Programmatically submit a user text message as if typed in the
     /// composer. The text will be added to conversation history and sent to
     /// the agent.
@@ -1,3 +1,5 @@
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::CodexConversation;
@@ -5,11 +7,17 @@ use codex_core::ConversationManager;
 use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::protocol::Op;
+use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::unbounded_channel;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::attach::AttachHost;
+#[cfg(unix)]
+use crate::attach::HostMessage;
+#[cfg(unix)]
+use crate::history_cell;
 
 /// Spawn the agent bootstrapper and op forwarding loop, returning the
 /// `UnboundedSender<Op>` used by the UI to submit operations.
@@ -18,10 +26,11 @@ pub(crate) fn spawn_agent(
     app_event_tx: AppEventSender,
     server: Arc<ConversationManager>,
 ) -> UnboundedSender<Op> {
-    let (codex_op_tx, mut codex_op_rx) = unbounded_channel::<Op>();
+    let (codex_op_tx, codex_op_rx) = unbounded_channel::<Op>();
 
     let app_event_tx_clone = app_event_tx;
     tokio::spawn(async move {
+        let codex_home = config.codex_home.clone();
         let NewConversation {
             conversation_id: _,
             conversation,
@@ -35,27 +44,14 @@ pub(crate) fn spawn_agent(
             }
         };
 
-        // Forward the captured `SessionConfigured` event so it can be rendered in the UI.
-        let ev = codex_core::protocol::Event {
-            // The `id` does not matter for rendering, so we can use a fake value.
-            id: "".to_string(),
-            msg: codex_core::protocol::EventMsg::SessionConfigured(session_configured),
-        };
-        app_event_tx_clone.send(AppEvent::CodexEvent(ev));
-
-        let conversation_clone = conversation.clone();
-        tokio::spawn(async move {
-            while let Some(op) = codex_op_rx.recv().await {
-                let id = conversation_clone.submit(op).await;
-                if let Err(e) = id {
-                    tracing::error!("failed to submit op: {e}");
-                }
-            }
-        });
-
-        while let Ok(event) = conversation.next_event().await {
-            app_event_tx_clone.send(AppEvent::CodexEvent(event));
-        }
+        forward_conversation(
+            conversation,
+            session_configured,
+            &codex_home,
+            app_event_tx_clone,
+            codex_op_rx,
+        )
+        .await;
     });
 
     codex_op_tx
@@ -67,31 +63,142 @@ pub(crate) fn spawn_agent(
 pub(crate) fn spawn_agent_from_existing(
     conversation: std::sync::Arc<CodexConversation>,
     session_configured: codex_core::protocol::SessionConfiguredEvent,
+    codex_home: PathBuf,
     app_event_tx: AppEventSender,
 ) -> UnboundedSender<Op> {
+    let (codex_op_tx, codex_op_rx) = unbounded_channel::<Op>();
+
+    tokio::spawn(async move {
+        forward_conversation(
+            conversation,
+            session_configured,
+            &codex_home,
+            app_event_tx,
+            codex_op_rx,
+        )
+        .await;
+    });
+
+    codex_op_tx
+}
+
+/// Forward `session_configured` and every later event to the UI, submit Ops
+/// from the UI, and let other terminals attach to the conversation meanwhile.
+async fn forward_conversation(
+    conversation: Arc<CodexConversation>,
+    session_configured: codex_core::protocol::SessionConfiguredEvent,
+    codex_home: &Path,
+    app_event_tx: AppEventSender,
+    mut codex_op_rx: UnboundedReceiver<Op>,
+) {
+    let attach_host = match AttachHost::start(
+        codex_home,
+        session_configured.session_id,
+        conversation.clone(),
+        app_event_tx.clone(),
+    ) {
+        Ok(host) => Some(Arc::new(host)),
+        Err(e) => {
+            tracing::warn!("other terminals cannot attach to this session: {e}");
+            None
+        }
+    };
+
+    // Forward the captured `SessionConfigured` event so it can be rendered in the UI.
+    let ev = codex_core::protocol::Event {
+        // The `id` does not matter for rendering, so we can use a fake value.
+        id: "".to_string(),
+        msg: codex_core::protocol::EventMsg::SessionConfigured(session_configured),
+    };
+    if let Some(host) = &attach_host {
+        host.publish_event(&ev);
+    }
+    app_event_tx.send(AppEvent::CodexEvent(ev));
+
+    let conversation_clone = conversation.clone();
+    let op_attach_host = attach_host.clone();
+    tokio::spawn(async move {
+        while let Some(op) = codex_op_rx.recv().await {
+            if let Some(host) = &op_attach_host {
+                host.observe_local_op(&op);
+            }
+            let id = conversation_clone.submit(op).await;
+            if let Err(e) = id {
+                tracing::error!("failed to submit op: {e}");
+            }
+        }
+    });
+
+    while let Ok(event) = conversation.next_event().await {
+        if let Some(host) = &attach_host {
+            host.publish_event(&event);
+        }
+        app_event_tx.send(AppEvent::CodexEvent(event));
+    }
+}
+
+/// Spawn loops for a conversation hosted by another TUI (`codex attach`).
+/// Events arrive over `stream`; Ops are sent back unless `read_only`.
+#[cfg(unix)]
+pub(crate) fn spawn_attached_agent(
+    stream: tokio::net::UnixStream,
+    read_only: bool,
+    app_event_tx: AppEventSender,
+) -> UnboundedSender<Op> {
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+
     let (codex_op_tx, mut codex_op_rx) = unbounded_channel::<Op>();
+    let (read_half, mut write_half) = stream.into_split();
 
-    let app_event_tx_clone = app_event_tx;
+    let app_event_tx_clone = app_event_tx.clone();
     tokio::spawn(async move {
-        // Forward the captured `SessionConfigured` event so it can be rendered in the UI.
-        let ev = codex_core::protocol::Event {
-            id: "".to_string(),
-            msg: codex_core::protocol::EventMsg::SessionConfigured(session_configured),
-        };
-        app_event_tx_clone.send(AppEvent::CodexEvent(ev));
-
-        let conversation_clone = conversation.clone();
-        tokio::spawn(async move {
-            while let Some(op) = codex_op_rx.recv().await {
-                let id = conversation_clone.submit(op).await;
-                if let Err(e) = id {
-                    tracing::error!("failed to submit op: {e}");
+        let mut lines = tokio::io::BufReader::new(read_half).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            match serde_json::from_str::<HostMessage>(&line) {
+                Ok(HostMessage::Event { event }) => {
+                    app_event_tx_clone.send(AppEvent::CodexEvent(event));
+                }
+                Ok(HostMessage::UserPrompt { text }) => {
+                    app_event_tx_clone.send(AppEvent::InsertHistoryCell(Box::new(
+                        history_cell::new_user_prompt(text),
+                    )));
                 }
+                Ok(HostMessage::ApprovalResolved { id }) => {
+                    app_event_tx_clone.send(AppEvent::ApprovalResolved(id));
+                }
+                Err(e) => tracing::warn!("ignoring invalid message from host: {e}"),
             }
-        });
+        }
+        // The hosting TUI exited.
+        app_event_tx_clone.send(AppEvent::ExitRequest);
+    });
 
-        while let Ok(event) = conversation.next_event().await {
-            app_event_tx_clone.send(AppEvent::CodexEvent(event));
+    tokio::spawn(async move {
+        while let Some(op) = codex_op_rx.recv().await {
+            // Quitting only detaches; the session keeps running in its host.
+            if matches!(op, Op::Shutdown) {
+                app_event_tx.send(AppEvent::ExitRequest);
+                continue;
+            }
+            if read_only {
+                if matches!(op, Op::UserInput { .. }) {
+                    app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                        history_cell::new_error_event(
+                            "Attached read-only; the message was not sent.".to_string(),
+                        ),
+                    )));
+                }
+                continue;
+            }
+            let Ok(mut line) = serde_json::to_string(&op) else {
+                continue;
+            };
+            line.push('\n');
+            if let Err(e) = write_half.write_all(line.as_bytes()).await {
+                tracing::error!("failed to send op to host: {e}");
+                break;
+            }
         }
     });
 
@@ -71,6 +71,18 @@ impl InterruptManager {
         self.queue.push_back(QueuedInterrupt::PatchEnd(ev));
     }
 
+    /// Drop a queued approval request that was answered elsewhere.
+    pub(crate) fn remove_approval(&mut self, id: &str) {
+        self.queue.retain(|q| {
+            !matches!(
+                q,
+                QueuedInterrupt::ExecApproval(queued_id, _)
+                    | QueuedInterrupt::ApplyPatchApproval(queued_id, _)
+                    if queued_id == id
+            )
+        });
+    }
+
     pub(crate) fn flush_all(&mut self, chat: &mut ChatWidget) {
         while let Some(q) = self.queue.pop_front() {
             match q {
@@ -22,6 +22,7 @@ use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
@@ -311,6 +312,7 @@ fn make_chatwidget_manual() -> (
         enhanced_keys_supported: false,
         placeholder_text: "Ask Codex to do anything".to_string(),
         disable_paste_burst: false,
+        vim_mode: false,
     });
     let auth_manager = AuthManager::from_auth_for_testing(CodexAuth::from_api_key("test"));
     let widget = ChatWidget {
@@ -335,11 +337,13 @@ fn make_chatwidget_manual() -> (
         frame_requester: FrameRequester::test_dummy(),
         show_welcome_banner: true,
         queued_user_messages: VecDeque::new(),
+        steered_user_messages: Vec::new(),
         suppress_session_configured_redraw: false,
         pending_notification: None,
         is_review_mode: false,
         ghost_snapshots: Vec::new(),
         ghost_snapshots_disabled: false,
+        overlay_diff: None,
         needs_final_message_separator: false,
     };
     (widget, rx, op_rx)
@@ -645,6 +649,65 @@ fn alt_up_edits_most_recent_queued_message() {
     );
 }
 
+#[test]
+fn alt_s_steers_the_running_turn_with_queued_messages() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    chat.queued_user_messages
+        .push_back(UserMessage::from("use the staging database".to_string()));
+    chat.refresh_queued_user_messages();
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT));
+
+    assert!(chat.queued_user_messages.is_empty());
+    match op_rx.try_recv() {
+        Ok(Op::Steer { items }) => assert_eq!(
+            items,
+            vec![InputItem::Text {
+                text: "use the staging database".to_string(),
+            }]
+        ),
+        other => panic!("expected Op::Steer, got {other:?}"),
+    }
+    let cells = drain_insert_history(&mut rx);
+    let text = lines_to_single_string(cells.last().expect("a steer note"));
+    assert!(text.contains("steered the running turn"), "{text}");
+    assert!(text.contains("use the staging database"), "{text}");
+}
+
+#[test]
+fn steers_rejected_after_the_turn_ended_are_sent_as_the_next_message() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    chat.queued_user_messages
+        .push_back(UserMessage::from("use the staging database".to_string()));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::ALT));
+    while op_rx.try_recv().is_ok() {}
+
+    chat.handle_codex_event(Event {
+        id: "sub-1".into(),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "sub-2".into(),
+        msg: EventMsg::Error(ErrorEvent {
+            message: NO_TURN_TO_STEER_MESSAGE.to_string(),
+        }),
+    });
+
+    match op_rx.try_recv() {
+        Ok(Op::UserInput { items }) => assert_eq!(
+            items,
+            vec![InputItem::Text {
+                text: "use the staging database".to_string(),
+            }]
+        ),
+        other => panic!("expected Op::UserInput, got {other:?}"),
+    }
+}
+
 #[test]
 fn exec_history_cell_shows_working_then_completed() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -699,6 +762,33 @@ fn exec_history_cell_shows_working_then_failed() {
     assert!(blob.to_lowercase().contains("bloop"), "expected error text");
 }
 
+#[test]
+fn concurrent_exec_calls_share_one_cell() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    // The second command starts before the first one finishes.
+    begin_exec(&mut chat, "call-1", "echo one");
+    begin_exec(&mut chat, "call-2", "echo two");
+    assert!(
+        drain_insert_history(&mut rx).is_empty(),
+        "running commands must not be flushed"
+    );
+
+    // Finish out of order; the cell flushes once both are done.
+    end_exec(&mut chat, "call-2", "two", "", 0);
+    assert!(drain_insert_history(&mut rx).is_empty());
+    let blob = active_blob(&chat);
+    assert!(blob.contains("Running echo one"), "{blob:?}");
+    assert!(blob.contains("Ran echo two"), "{blob:?}");
+
+    end_exec(&mut chat, "call-1", "one", "", 0);
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one finalized exec cell");
+    let blob = lines_to_single_string(&cells[0]);
+    assert!(blob.contains("• Ran echo one"), "{blob:?}");
+    assert!(blob.contains("• Ran echo two"), "{blob:?}");
+}
+
 /// Selecting the custom prompt option from the review popup sends
 /// OpenReviewCustomPrompt to the app event channel.
 #[test]
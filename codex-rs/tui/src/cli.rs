@@ -27,6 +27,14 @@ pub struct Cli {
     #[clap(skip)]
     pub resume_session_id: Option<String>,
 
+    /// Internal: attach to a session running in another TUI. Set by the
+    /// top-level `codex attach <SESSION_ID>` wrapper; not exposed as a public flag.
+    #[clap(skip)]
+    pub attach_session_id: Option<String>,
+
+    #[clap(skip)]
+    pub attach_read_only: bool,
+
     /// Model the agent should use.
     #[arg(long, short = 'm')]
     pub model: Option<String>,
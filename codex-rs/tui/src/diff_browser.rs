@@ -0,0 +1,762 @@
+//! Files and hunks of a diff, laid out for the full-screen diff browser in
+//! `pager_overlay.rs`.
+//!
+//! Diffs come either from `git diff` output (`/diff`) or from the changes of
+//! a patch waiting for approval. They are laid out as a unified diff or, on
+//! wide terminals, side by side, and a removed line paired with the added
+//! line that replaced it has the words that changed emphasized.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::protocol::FileChange;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use similar::ChangeTag;
+use similar::TextDiff;
+
+use crate::diff_render::display_path_for;
+use crate::diff_render::split_spans_at;
+use crate::render::highlight::Language;
+use crate::render::highlight::highlight_line;
+use crate::render::highlight::language_for_path;
+
+/// Narrowest content width at which diffs are shown side by side.
+pub(crate) const SIDE_BY_SIDE_MIN_WIDTH: u16 = 120;
+
+/// Paired lines less similar than this are shown as a whole removal and
+/// addition, since emphasizing nearly every word would only add noise.
+const MIN_WORD_DIFF_RATIO: f32 = 0.4;
+
+const LINE_NUMBER_WIDTH: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiffFile {
+    pub(crate) path: String,
+    /// The path before a rename.
+    pub(crate) old_path: Option<String>,
+    pub(crate) hunks: Vec<DiffHunk>,
+    /// Shown instead of hunks, e.g. for binary files.
+    pub(crate) note: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DiffHunk {
+    pub(crate) old_start: usize,
+    pub(crate) new_start: usize,
+    pub(crate) lines: Vec<DiffLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum DiffLine {
+    Context(String),
+    Delete(String),
+    Insert(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DiffLayout {
+    Unified,
+    SideBySide,
+}
+
+impl DiffLayout {
+    pub(crate) fn for_width(width: u16) -> Self {
+        if width >= SIDE_BY_SIDE_MIN_WIDTH {
+            DiffLayout::SideBySide
+        } else {
+            DiffLayout::Unified
+        }
+    }
+
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            DiffLayout::Unified => DiffLayout::SideBySide,
+            DiffLayout::SideBySide => DiffLayout::Unified,
+        }
+    }
+}
+
+impl DiffFile {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            old_path: None,
+            hunks: Vec::new(),
+            note: None,
+        }
+    }
+
+    /// Lines added and removed.
+    pub(crate) fn counts(&self) -> (usize, usize) {
+        self.hunks.iter().flat_map(|hunk| hunk.lines.iter()).fold(
+            (0, 0),
+            |(added, removed), line| match line {
+                DiffLine::Insert(_) => (added + 1, removed),
+                DiffLine::Delete(_) => (added, removed + 1),
+                DiffLine::Context(_) => (added, removed),
+            },
+        )
+    }
+
+    fn language(&self) -> Option<&'static Language> {
+        language_for_path(Path::new(&self.path))
+    }
+}
+
+impl DiffHunk {
+    fn new(old_start: usize, new_start: usize) -> Self {
+        Self {
+            old_start,
+            new_start,
+            lines: Vec::new(),
+        }
+    }
+
+    pub(crate) fn header(&self) -> String {
+        let old_len = self
+            .lines
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Insert(_)))
+            .count();
+        let new_len = self
+            .lines
+            .iter()
+            .filter(|line| !matches!(line, DiffLine::Delete(_)))
+            .count();
+        format!(
+            "@@ -{},{old_len} +{},{new_len} @@",
+            self.old_start, self.new_start
+        )
+    }
+}
+
+/// The files of a patch, in path order.
+pub(crate) fn files_from_changes(
+    changes: &HashMap<PathBuf, FileChange>,
+    cwd: &Path,
+) -> Vec<DiffFile> {
+    let mut changes: Vec<(&PathBuf, &FileChange)> = changes.iter().collect();
+    changes.sort_by_key(|(path, _)| *path);
+    changes
+        .into_iter()
+        .map(|(path, change)| file_from_change(path, change, cwd))
+        .collect()
+}
+
+/// One file of a patch. Added and deleted files get a single hunk.
+pub(crate) fn file_from_change(path: &Path, change: &FileChange, cwd: &Path) -> DiffFile {
+    let mut file = DiffFile::new(display_path_for(path, cwd));
+    match change {
+        FileChange::Add { content } => {
+            let mut hunk = DiffHunk::new(0, 1);
+            hunk.lines = content
+                .lines()
+                .map(|line| DiffLine::Insert(line.to_string()))
+                .collect();
+            file.hunks.push(hunk);
+        }
+        FileChange::Delete { content } => {
+            let mut hunk = DiffHunk::new(1, 0);
+            hunk.lines = content
+                .lines()
+                .map(|line| DiffLine::Delete(line.to_string()))
+                .collect();
+            file.hunks.push(hunk);
+        }
+        FileChange::Update {
+            unified_diff,
+            move_path,
+        } => {
+            file.hunks = files_from_unified_diff(unified_diff)
+                .into_iter()
+                .flat_map(|parsed| parsed.hunks)
+                .collect();
+            if let Some(move_path) = move_path {
+                file.old_path = Some(file.path.clone());
+                file.path = display_path_for(move_path, cwd);
+            }
+        }
+    }
+    file
+}
+
+/// Parse `git diff` output, or any unified diff, into its files. Hunks that
+/// are not preceded by file headers are put in a file without a path.
+pub(crate) fn files_from_unified_diff(text: &str) -> Vec<DiffFile> {
+    let mut files: Vec<DiffFile> = Vec::new();
+    // Lines still expected on the old and new side of the current hunk.
+    let mut remaining = (0usize, 0usize);
+
+    for line in text.lines() {
+        if remaining.0 > 0 || remaining.1 > 0 {
+            // `\ No newline at end of file` is about the line before it.
+            if line.starts_with('\\') {
+                continue;
+            }
+            let parsed = match line.chars().next() {
+                Some('+') => Some(DiffLine::Insert(line[1..].to_string())),
+                Some('-') => Some(DiffLine::Delete(line[1..].to_string())),
+                Some(' ') => Some(DiffLine::Context(line[1..].to_string())),
+                // Some tools strip the space of empty context lines.
+                None => Some(DiffLine::Context(String::new())),
+                _ => None,
+            };
+            if let Some(parsed) = parsed
+                && let Some(hunk) = files.last_mut().and_then(|file| file.hunks.last_mut())
+            {
+                match parsed {
+                    DiffLine::Insert(_) => remaining.1 = remaining.1.saturating_sub(1),
+                    DiffLine::Delete(_) => remaining.0 = remaining.0.saturating_sub(1),
+                    DiffLine::Context(_) => {
+                        remaining.0 = remaining.0.saturating_sub(1);
+                        remaining.1 = remaining.1.saturating_sub(1);
+                    }
+                }
+                hunk.lines.push(parsed);
+                continue;
+            }
+            remaining = (0, 0);
+        }
+
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths
+                .rsplit_once(" b/")
+                .map_or(paths, |(_, new)| new)
+                .to_string();
+            files.push(DiffFile::new(path));
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            let Some((old_start, old_len, new_start, new_len)) = parse_hunk_header(header) else {
+                continue;
+            };
+            if files.is_empty() {
+                files.push(DiffFile::new(String::new()));
+            }
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(DiffHunk::new(old_start, new_start));
+            }
+            remaining = (old_len, new_len);
+        } else if let Some(old) = line.strip_prefix("--- ") {
+            // A diff without `diff --git` headers starts a file here.
+            let starts_file = files
+                .last()
+                .is_none_or(|file| !file.hunks.is_empty() || file.note.is_some());
+            if starts_file {
+                files.push(DiffFile::new(strip_side_prefix(old, "a/")));
+            }
+        } else if let Some(new) = line.strip_prefix("+++ ")
+            && let Some(file) = files.last_mut()
+        {
+            let new = strip_side_prefix(new, "b/");
+            if new != "/dev/null" {
+                file.path = new;
+            }
+        } else if let Some(file) = files.last_mut() {
+            if let Some(old) = line.strip_prefix("rename from ") {
+                file.old_path = Some(old.to_string());
+            } else if let Some(new) = line.strip_prefix("rename to ") {
+                file.path = new.to_string();
+            } else if line.starts_with("Binary files ") {
+                file.note = Some("Binary file changed".to_string());
+            }
+        }
+    }
+
+    for file in &mut files {
+        if file.hunks.is_empty() && file.note.is_none() && file.old_path.is_none() {
+            file.note = Some("No content changes".to_string());
+        }
+    }
+    files
+}
+
+fn strip_side_prefix(path: &str, prefix: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path);
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// `-12,5 +12,7 @@ fn main()` into the starts and lengths of both sides.
+fn parse_hunk_header(header: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut parts = header.split_whitespace();
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_len) = range(old)?;
+    let (new_start, new_len) = range(new)?;
+    Some((old_start, old_len, new_start, new_len))
+}
+
+/// The heading of a file: its path and how many lines changed.
+pub(crate) fn file_header_lines(file: &DiffFile, first: bool) -> Vec<Line<'static>> {
+    let (added, removed) = file.counts();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    if let Some(old_path) = &file.old_path {
+        spans.push(format!("{old_path} → ").bold());
+    }
+    spans.push(file.path.clone().bold());
+    // Counts would only be noise next to a note such as "Binary file changed".
+    if !file.hunks.is_empty() || file.note.is_none() {
+        spans.push(" (".into());
+        spans.push(format!("+{added}").green());
+        spans.push(" ".into());
+        spans.push(format!("-{removed}").red());
+        spans.push(")".into());
+    }
+
+    let mut lines = Vec::new();
+    if !first {
+        lines.push(Line::default());
+    }
+    lines.push(Line::from(spans));
+    if let Some(note) = &file.note {
+        lines.push(Line::from(note.clone().dim().italic()));
+    }
+    lines
+}
+
+/// One side of a diff row: a line number and its content.
+struct Side {
+    line_number: usize,
+    sign: char,
+    spans: Vec<Span<'static>>,
+}
+
+/// A hunk laid out to `width` columns.
+pub(crate) fn hunk_lines(
+    file: &DiffFile,
+    hunk: &DiffHunk,
+    width: u16,
+    layout: DiffLayout,
+) -> Vec<Line<'static>> {
+    let width = width as usize;
+    let mut lines = vec![Line::from(hunk.header().dim())];
+    let rows = hunk_rows(hunk, file.language());
+    match layout {
+        DiffLayout::Unified => {
+            for (old, new) in rows {
+                // Removals come before the additions they pair with.
+                let (deleted, rest) = match (old, new) {
+                    (Some(old), Some(new)) if old.sign == ' ' => (None, Some((Some(old), new))),
+                    (Some(old), Some(new)) => (Some(old), Some((None, new))),
+                    (Some(old), None) => (Some(old), None),
+                    (None, Some(new)) => (None, Some((None, new))),
+                    (None, None) => (None, None),
+                };
+                if let Some(old) = deleted {
+                    lines.extend(unified_line(Some(old.line_number), None, old, width));
+                }
+                if let Some((context, new)) = rest {
+                    let old_number = context.map(|old| old.line_number);
+                    lines.extend(unified_line(old_number, Some(new.line_number), new, width));
+                }
+            }
+        }
+        DiffLayout::SideBySide => {
+            let left_width = width.saturating_sub(1) / 2;
+            let right_width = width.saturating_sub(1 + left_width);
+            for (old, new) in rows {
+                let left = side_rows(old, left_width);
+                let right = side_rows(new, right_width);
+                for i in 0..left.len().max(right.len()) {
+                    let mut spans = pad_to(left.get(i).cloned().unwrap_or_default(), left_width);
+                    spans.push("│".dim());
+                    spans.extend(right.get(i).cloned().unwrap_or_default());
+                    lines.push(Line::from(spans));
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// The rows of a hunk as (old, new) pairs: context lines on both sides, and
+/// each run of removals paired with the run of additions that follows it.
+fn hunk_rows(
+    hunk: &DiffHunk,
+    language: Option<&'static Language>,
+) -> Vec<(Option<Side>, Option<Side>)> {
+    let mut rows = Vec::new();
+    let mut old_number = hunk.old_start;
+    let mut new_number = hunk.new_start;
+    let mut i = 0;
+    while i < hunk.lines.len() {
+        if let DiffLine::Context(text) = &hunk.lines[i] {
+            let text = expand_tabs(text);
+            let spans = match language {
+                Some(language) => highlight_line(language, &text),
+                None => vec![Span::from(text)],
+            };
+            rows.push((
+                Some(Side {
+                    line_number: old_number,
+                    sign: ' ',
+                    spans: spans.clone(),
+                }),
+                Some(Side {
+                    line_number: new_number,
+                    sign: ' ',
+                    spans,
+                }),
+            ));
+            old_number += 1;
+            new_number += 1;
+            i += 1;
+            continue;
+        }
+
+        let deleted: Vec<String> = hunk.lines[i..]
+            .iter()
+            .map_while(|line| match line {
+                DiffLine::Delete(text) => Some(expand_tabs(text)),
+                _ => None,
+            })
+            .collect();
+        i += deleted.len();
+        let inserted: Vec<String> = hunk.lines[i..]
+            .iter()
+            .map_while(|line| match line {
+                DiffLine::Insert(text) => Some(expand_tabs(text)),
+                _ => None,
+            })
+            .collect();
+        i += inserted.len();
+
+        for row in 0..deleted.len().max(inserted.len()) {
+            let (old_spans, new_spans) = match (deleted.get(row), inserted.get(row)) {
+                (Some(old), Some(new)) => match word_diff(old, new) {
+                    Some((old_parts, new_parts)) => (
+                        Some(changed_spans(&old_parts, style_del())),
+                        Some(changed_spans(&new_parts, style_add())),
+                    ),
+                    None => (
+                        Some(vec![Span::styled(old.clone(), style_del())]),
+                        Some(vec![Span::styled(new.clone(), style_add())]),
+                    ),
+                },
+                (old, new) => (
+                    old.map(|old| vec![Span::styled(old.clone(), style_del())]),
+                    new.map(|new| vec![Span::styled(new.clone(), style_add())]),
+                ),
+            };
+            let old = old_spans.map(|spans| {
+                old_number += 1;
+                Side {
+                    line_number: old_number - 1,
+                    sign: '-',
+                    spans,
+                }
+            });
+            let new = new_spans.map(|spans| {
+                new_number += 1;
+                Side {
+                    line_number: new_number - 1,
+                    sign: '+',
+                    spans,
+                }
+            });
+            rows.push((old, new));
+        }
+        if deleted.is_empty() && inserted.is_empty() {
+            // Unreachable with the three line kinds, but never loop forever.
+            i += 1;
+        }
+    }
+    rows
+}
+
+/// A unified diff line: old and new line numbers, the sign and the content,
+/// wrapped to `width`.
+fn unified_line(
+    old_number: Option<usize>,
+    new_number: Option<usize>,
+    side: Side,
+    width: usize,
+) -> Vec<Line<'static>> {
+    let number = |n: Option<usize>| n.map_or_else(String::new, |n| n.to_string());
+    let gutter = format!(
+        "{:>w$} {:>w$} ",
+        number(old_number),
+        number(new_number),
+        w = LINE_NUMBER_WIDTH
+    );
+    let prefix_width = gutter.len() + 1;
+    let sign_style = sign_style(side.sign);
+    wrap_spans(side.spans, width.saturating_sub(prefix_width))
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut spans = if i == 0 {
+                vec![
+                    Span::styled(gutter.clone(), Style::default().dim()),
+                    Span::styled(side.sign.to_string(), sign_style),
+                ]
+            } else {
+                vec![Span::from(" ".repeat(prefix_width))]
+            };
+            spans.extend(chunk);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// One side of a side-by-side row, wrapped to `width`. Rows without a line on
+/// this side are left blank.
+fn side_rows(side: Option<Side>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let Some(side) = side else {
+        return Vec::new();
+    };
+    let gutter = format!("{:>w$} ", side.line_number, w = LINE_NUMBER_WIDTH);
+    let prefix_width = gutter.len() + 1;
+    let sign_style = sign_style(side.sign);
+    wrap_spans(side.spans, width.saturating_sub(prefix_width))
+        .into_iter()
+        .enumerate()
+        .map(|(i, chunk)| {
+            let mut spans = if i == 0 {
+                vec![
+                    Span::styled(gutter.clone(), Style::default().dim()),
+                    Span::styled(side.sign.to_string(), sign_style),
+                ]
+            } else {
+                vec![Span::from(" ".repeat(prefix_width))]
+            };
+            spans.extend(chunk);
+            spans
+        })
+        .collect()
+}
+
+/// Split `spans` into rows of at most `width` characters. Empty content
+/// still takes one row.
+fn wrap_spans(spans: Vec<Span<'static>>, width: usize) -> Vec<Vec<Span<'static>>> {
+    let mut rows = Vec::new();
+    let mut remaining = spans;
+    loop {
+        let (row, rest) = split_spans_at(remaining, width.max(1));
+        rows.push(row);
+        if rest.iter().all(|span| span.content.is_empty()) {
+            return rows;
+        }
+        remaining = rest;
+    }
+}
+
+fn pad_to(mut spans: Vec<Span<'static>>, width: usize) -> Vec<Span<'static>> {
+    let used = Line::from(spans.clone()).width();
+    if used < width {
+        spans.push(Span::from(" ".repeat(width - used)));
+    }
+    spans
+}
+
+/// The words of `old` and `new`, each flagged when it is not in the other
+/// line, or `None` when the lines have too little in common.
+#[allow(clippy::type_complexity)]
+fn word_diff(old: &str, new: &str) -> Option<(Vec<(String, bool)>, Vec<(String, bool)>)> {
+    let diff = TextDiff::from_words(old, new);
+    if diff.ratio() < MIN_WORD_DIFF_RATIO {
+        return None;
+    }
+    let mut old_parts: Vec<(String, bool)> = Vec::new();
+    let mut new_parts: Vec<(String, bool)> = Vec::new();
+    let push = |parts: &mut Vec<(String, bool)>, text: &str, changed: bool| match parts.last_mut() {
+        Some((last, last_changed)) if *last_changed == changed => last.push_str(text),
+        _ => parts.push((text.to_string(), changed)),
+    };
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Equal => {
+                push(&mut old_parts, change.value(), false);
+                push(&mut new_parts, change.value(), false);
+            }
+            ChangeTag::Delete => push(&mut old_parts, change.value(), true),
+            ChangeTag::Insert => push(&mut new_parts, change.value(), true),
+        }
+    }
+    Some((old_parts, new_parts))
+}
+
+fn changed_spans(parts: &[(String, bool)], style: Style) -> Vec<Span<'static>> {
+    parts
+        .iter()
+        .map(|(text, changed)| {
+            let style = if *changed {
+                style.add_modifier(Modifier::REVERSED)
+            } else {
+                style
+            };
+            Span::styled(text.clone(), style)
+        })
+        .collect()
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', "    ")
+}
+
+fn sign_style(sign: char) -> Style {
+    match sign {
+        '+' => style_add(),
+        '-' => style_del(),
+        _ => Style::default(),
+    }
+}
+
+fn style_add() -> Style {
+    Style::default().fg(Color::Green)
+}
+
+fn style_del() -> Style {
+    Style::default().fg(Color::Red)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    const GIT_DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    let total = compute(1, 2);
++    let total = compute(1, 3);
+ }
+diff --git a/old name.txt b/new name.txt
+similarity index 100%
+rename from old name.txt
+rename to new name.txt
+diff --git a/logo.png b/logo.png
+new file mode 100644
+Binary files /dev/null and b/logo.png differ
+";
+
+    fn text(lines: &[Line<'static>]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_git_diff_files() {
+        let files = files_from_unified_diff(GIT_DIFF);
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].counts(), (1, 1));
+        assert_eq!(
+            files[0].hunks[0].lines[1],
+            DiffLine::Delete("    let total = compute(1, 2);".to_string())
+        );
+        assert_eq!(files[1].path, "new name.txt");
+        assert_eq!(files[1].old_path.as_deref(), Some("old name.txt"));
+        assert_eq!(files[1].note, None);
+        assert_eq!(files[2].path, "logo.png");
+        assert_eq!(files[2].note.as_deref(), Some("Binary file changed"));
+    }
+
+    #[test]
+    fn keeps_hunk_lines_after_a_no_newline_marker() {
+        let diff = "\
+--- a/notes.txt
++++ b/notes.txt
+@@ -1,2 +1,3 @@
+-last
+\\ No newline at end of file
++last
++more
+ kept
+";
+        let files = files_from_unified_diff(diff);
+        assert_eq!(
+            files[0].hunks[0].lines,
+            vec![
+                DiffLine::Delete("last".to_string()),
+                DiffLine::Insert("last".to_string()),
+                DiffLine::Insert("more".to_string()),
+                DiffLine::Context("kept".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn lays_out_hunks_unified_and_side_by_side() {
+        let files = files_from_unified_diff(GIT_DIFF);
+        let file = &files[0];
+        let hunk = &file.hunks[0];
+
+        assert_eq!(
+            text(&hunk_lines(file, hunk, 60, DiffLayout::Unified)),
+            vec![
+                "@@ -1,3 +1,3 @@",
+                "   1    1  fn main() {",
+                "   2      -    let total = compute(1, 2);",
+                "        2 +    let total = compute(1, 3);",
+                "   3    3  }",
+            ]
+        );
+        assert_eq!(
+            text(&hunk_lines(file, hunk, 81, DiffLayout::SideBySide)),
+            vec![
+                "@@ -1,3 +1,3 @@",
+                "   1  fn main() {                       │   1  fn main() {",
+                "   2 -    let total = compute(1, 2);    │   2 +    let total = compute(1, 3);",
+                "   3  }                                 │   3  }",
+            ]
+        );
+    }
+
+    #[test]
+    fn emphasizes_changed_words_of_paired_lines() {
+        let files = files_from_unified_diff(GIT_DIFF);
+        let lines = hunk_lines(&files[0], &files[0].hunks[0], 60, DiffLayout::Unified);
+        let emphasized = |line: &Line<'static>| -> Vec<String> {
+            line.spans
+                .iter()
+                .filter(|span| span.style.add_modifier.contains(Modifier::REVERSED))
+                .map(|span| span.content.to_string())
+                .collect()
+        };
+        assert_eq!(emphasized(&lines[2]), vec!["2);"]);
+        assert_eq!(emphasized(&lines[3]), vec!["3);"]);
+
+        // Lines with little in common are not emphasized word by word.
+        assert_eq!(word_diff("let a = 1;", "return something_else()"), None);
+    }
+
+    #[test]
+    fn wraps_long_lines_within_each_side() {
+        let diff = "@@ -1 +1 @@\n-short\n+a line that is much too long for one half\n";
+        let files = files_from_unified_diff(diff);
+        let lines = hunk_lines(&files[0], &files[0].hunks[0], 41, DiffLayout::SideBySide);
+        assert_eq!(
+            text(&lines),
+            vec![
+                "@@ -1,1 +1,1 @@",
+                "   1 -short         │   1 +a line that is",
+                "                    │       much too long",
+                "                    │       for one half",
+            ]
+        );
+    }
+}
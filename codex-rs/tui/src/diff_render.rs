@@ -11,6 +11,11 @@ use std::path::PathBuf;
 
 use crate::exec_command::relativize_to_home;
 use crate::history_cell::PatchEventType;
+use crate::render::highlight::Language;
+use crate::render::highlight::highlight_line;
+use crate::render::highlight::language_for_path;
+use crate::style::diff_line_bg;
+use crate::terminal_palette::default_bg;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
 
@@ -23,6 +28,29 @@ enum DiffLineType {
     Context,
 }
 
+/// How the content of diff lines is colored.
+#[derive(Clone, Copy, Default)]
+struct DiffHighlight {
+    /// Language of the file, for syntax highlighting.
+    language: Option<&'static Language>,
+    /// Tinted backgrounds for added and removed lines. Without them, those
+    /// lines are colored green and red instead of being syntax highlighted,
+    /// so that they stay distinguishable.
+    add_bg: Option<Color>,
+    del_bg: Option<Color>,
+}
+
+impl DiffHighlight {
+    fn for_path(path: &Path) -> Self {
+        let bg = default_bg();
+        Self {
+            language: language_for_path(path),
+            add_bg: bg.and_then(|bg| diff_line_bg(bg, true)),
+            del_bg: bg.and_then(|bg| diff_line_bg(bg, false)),
+        }
+    }
+}
+
 pub(crate) fn create_diff_summary(
     changes: &HashMap<PathBuf, FileChange>,
     event_type: PatchEventType,
@@ -190,6 +218,7 @@ fn render_changes_block(
             out.push(RtLine::from(header));
         }
 
+        let highlight = DiffHighlight::for_path(r.move_path.as_deref().unwrap_or(&r.path));
         match r.change {
             FileChange::Add { content } => {
                 for (i, raw) in content.lines().enumerate() {
@@ -198,6 +227,7 @@ fn render_changes_block(
                         DiffLineType::Insert,
                         raw,
                         term_cols,
+                        highlight,
                     ));
                 }
             }
@@ -208,6 +238,7 @@ fn render_changes_block(
                         DiffLineType::Delete,
                         raw,
                         term_cols,
+                        highlight,
                     ));
                 }
             }
@@ -231,6 +262,7 @@ fn render_changes_block(
                                         DiffLineType::Insert,
                                         s,
                                         term_cols,
+                                        highlight,
                                     ));
                                     new_ln += 1;
                                 }
@@ -241,6 +273,7 @@ fn render_changes_block(
                                         DiffLineType::Delete,
                                         s,
                                         term_cols,
+                                        highlight,
                                     ));
                                     old_ln += 1;
                                 }
@@ -251,6 +284,7 @@ fn render_changes_block(
                                         DiffLineType::Context,
                                         s,
                                         term_cols,
+                                        highlight,
                                     ));
                                     old_ln += 1;
                                     new_ln += 1;
@@ -301,10 +335,10 @@ fn push_wrapped_diff_line(
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+    highlight: DiffHighlight,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -313,52 +347,88 @@ fn push_wrapped_diff_line(
     let gap_after_ln = SPACES_AFTER_LINE_NUMBER.saturating_sub(ln_str.len());
     let prefix_cols = indent.len() + ln_str.len() + gap_after_ln;
 
-    let mut first = true;
-    let (sign_char, line_style) = match kind {
-        DiffLineType::Insert => ('+', style_add()),
-        DiffLineType::Delete => ('-', style_del()),
-        DiffLineType::Context => (' ', style_context()),
+    let (sign_char, sign_style, bg) = match kind {
+        DiffLineType::Insert => ('+', style_add(), highlight.add_bg),
+        DiffLineType::Delete => ('-', style_del(), highlight.del_bg),
+        DiffLineType::Context => (' ', style_context(), None),
+    };
+    let (sign_style, content_style) = match bg {
+        Some(bg) => (sign_style.bg(bg), Style::default().bg(bg)),
+        None => (sign_style, sign_style),
     };
+    let syntax = highlight
+        .language
+        .filter(|_| bg.is_some() || matches!(kind, DiffLineType::Context));
+    let mut remaining: Vec<RtSpan<'static>> = match syntax {
+        Some(language) => highlight_line(language, text)
+            .into_iter()
+            .map(|span| span.patch_style(content_style))
+            .collect(),
+        None => vec![RtSpan::styled(text.to_string(), content_style)],
+    };
+
+    let mut first = true;
     let mut lines: Vec<RtLine<'static>> = Vec::new();
 
     loop {
-        // Fit the content for the current terminal row:
-        // compute how many columns are available after the prefix, then split
-        // at a UTF-8 character boundary so this row's chunk fits exactly.
+        // Fit the content for the current terminal row: take as many
+        // characters as there are columns after the prefix.
         let available_content_cols = term_cols.saturating_sub(prefix_cols + 1).max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
-
-        if first {
-            // Build gutter (indent + line number + spacing) as a dimmed span
+        let (chunk, rest) = split_spans_at(remaining, available_content_cols);
+        remaining = rest;
+
+        let mut spans = if first {
+            // Build gutter (indent + line number + spacing) as a dimmed span,
+            // followed by the sign ('+'/'-'/' ') styled per diff kind.
             let gutter = format!("{indent}{ln_str}{}", " ".repeat(gap_after_ln));
-            // Content with a sign ('+'/'-'/' ') styled per diff kind
-            let content = format!("{sign_char}{chunk}");
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(content, line_style),
-            ]));
             first = false;
+            vec![
+                RtSpan::styled(gutter, style_gutter()),
+                RtSpan::styled(sign_char.to_string(), sign_style),
+            ]
         } else {
             // Continuation lines keep a space for the sign column so content aligns
             let gutter = format!("{indent}{} ", " ".repeat(ln_str.len() + gap_after_ln));
-            lines.push(RtLine::from(vec![
-                RtSpan::styled(gutter, style_gutter()),
-                RtSpan::styled(chunk.to_string(), line_style),
-            ]));
-        }
-        if remaining_text.is_empty() {
+            vec![RtSpan::styled(gutter, style_gutter())]
+        };
+        spans.extend(chunk);
+        lines.push(RtLine::from(spans));
+        if remaining.is_empty() {
             break;
         }
     }
     lines
 }
 
+/// Split `spans` after `max_chars` characters, keeping each part's style.
+pub(crate) fn split_spans_at(
+    spans: Vec<RtSpan<'static>>,
+    max_chars: usize,
+) -> (Vec<RtSpan<'static>>, Vec<RtSpan<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut budget = max_chars;
+    for span in spans {
+        if !tail.is_empty() || budget == 0 {
+            tail.push(span);
+            continue;
+        }
+        match span.content.char_indices().nth(budget) {
+            Some((split_at, _)) => {
+                let (left, right) = span.content.split_at(split_at);
+                head.push(RtSpan::styled(left.to_string(), span.style));
+                tail.push(RtSpan::styled(right.to_string(), span.style));
+                budget = 0;
+            }
+            None => {
+                budget -= span.content.chars().count();
+                head.push(span);
+            }
+        }
+    }
+    (head, tail)
+}
+
 fn style_gutter() -> Style {
     Style::default().add_modifier(Modifier::DIM)
 }
@@ -463,12 +533,76 @@ mod tests {
         let long_line = "this is a very long line that should wrap across multiple terminal columns and continue";
 
         // Call the wrapping function directly so we can precisely control the width
-        let lines = push_wrapped_diff_line(1, DiffLineType::Insert, long_line, 80);
+        let lines = push_wrapped_diff_line(
+            1,
+            DiffLineType::Insert,
+            long_line,
+            80,
+            DiffHighlight::default(),
+        );
 
         // Render into a small terminal to capture the visual layout
         snapshot_lines("wrap_behavior_insert", lines, 90, 8);
     }
 
+    #[test]
+    fn highlights_diff_lines_by_file_extension() {
+        let content = |lines: &[RtLine<'static>]| -> Vec<(String, Style)> {
+            lines
+                .iter()
+                .flat_map(|line| line.spans.iter().skip(1))
+                .map(|span| (span.content.to_string(), span.style))
+                .collect()
+        };
+        let highlight = DiffHighlight {
+            language: language_for_path(Path::new("src/lib.rs")),
+            ..DiffHighlight::default()
+        };
+
+        // Context lines are syntax highlighted.
+        let lines = push_wrapped_diff_line(3, DiffLineType::Context, "let x = 1;", 80, highlight);
+        assert_eq!(
+            content(&lines),
+            vec![
+                (" ".to_string(), style_context()),
+                ("let".to_string(), Style::default().fg(Color::Magenta)),
+                (" x = ".to_string(), Style::default()),
+                ("1".to_string(), Style::default().fg(Color::Cyan)),
+                (";".to_string(), Style::default()),
+            ]
+        );
+
+        // Without a tinted background, added lines stay green.
+        let lines = push_wrapped_diff_line(3, DiffLineType::Insert, "let x = 1;", 80, highlight);
+        assert_eq!(
+            content(&lines),
+            vec![
+                ("+".to_string(), style_add()),
+                ("let x = 1;".to_string(), style_add()),
+            ]
+        );
+
+        // With one, they are highlighted over it, and wrap between spans.
+        let tinted = DiffHighlight {
+            add_bg: Some(Color::DarkGray),
+            ..highlight
+        };
+        let lines = push_wrapped_diff_line(3, DiffLineType::Insert, "let x = 1;", 16, tinted);
+        let bg = Style::default().bg(Color::DarkGray);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            content(&lines),
+            vec![
+                ("+".to_string(), style_add().bg(Color::DarkGray)),
+                ("let".to_string(), bg.fg(Color::Magenta)),
+                (" x".to_string(), bg),
+                (" = ".to_string(), bg),
+                ("1".to_string(), bg.fg(Color::Cyan)),
+                (";".to_string(), bg),
+            ]
+        );
+    }
+
     #[test]
     fn ui_snapshot_single_line_replacement_counts() {
         // Reproduce: one deleted line replaced by one inserted line, no extra context
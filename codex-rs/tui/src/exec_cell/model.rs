@@ -45,7 +45,9 @@ impl ExecCell {
             start_time: Some(Instant::now()),
             duration: None,
         };
-        if self.is_exploring_cell() && Self::is_exploring_call(&call) {
+        // Calls that start while this cell is still running (the model asked
+        // for several commands at once) are shown together with it.
+        if (self.is_exploring_cell() && Self::is_exploring_call(&call)) || self.is_active() {
             Some(Self {
                 calls: [self.calls.clone(), vec![call]].concat(),
             })
@@ -0,0 +1,74 @@
+//! Editing the composer draft in the user's `$VISUAL` or `$EDITOR`.
+//!
+//! The draft is written to a temporary file, the editor is run on it with
+//! the terminal handed over, and the file's contents are read back once the
+//! editor exits successfully.
+
+use std::io;
+use std::io::Write;
+
+use tempfile::Builder;
+use tokio::process::Command;
+
+/// Runs the user's editor on `draft` and returns the edited text, without
+/// the trailing newline editors add. The caller must have released the
+/// terminal.
+pub(crate) async fn edit_draft(draft: &str) -> io::Result<String> {
+    let command = editor_command(
+        std::env::var("VISUAL").ok().as_deref(),
+        std::env::var("EDITOR").ok().as_deref(),
+    )
+    .ok_or_else(|| io::Error::other("set $VISUAL or $EDITOR to edit drafts in an editor"))?;
+
+    let mut file = Builder::new()
+        .prefix("codex-draft-")
+        .suffix(".md")
+        .tempfile()?;
+    file.write_all(draft.as_bytes())?;
+    file.flush()?;
+
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(file.path())
+        .status()
+        .await
+        .map_err(|err| io::Error::new(err.kind(), format!("`{}`: {err}", command[0])))?;
+    if !status.success() {
+        return Err(io::Error::other(format!(
+            "`{}` exited with {status}",
+            command.join(" ")
+        )));
+    }
+
+    let edited = std::fs::read_to_string(file.path())?;
+    Ok(edited.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// The editor to run, split into program and arguments, preferring
+/// `$VISUAL` over `$EDITOR` as other tools do.
+fn editor_command(visual: Option<&str>, editor: Option<&str>) -> Option<Vec<String>> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .filter(|value| !value.trim().is_empty())
+        .find_map(|value| shlex::split(value).filter(|words| !words.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn prefers_visual_and_splits_arguments() {
+        assert_eq!(
+            editor_command(Some("code --wait"), Some("vi")),
+            Some(vec!["code".to_string(), "--wait".to_string()])
+        );
+        assert_eq!(
+            editor_command(Some(""), Some("'/opt/my editor/bin/ed' -x")),
+            Some(vec!["/opt/my editor/bin/ed".to_string(), "-x".to_string()])
+        );
+        assert_eq!(editor_command(None, Some("  ")), None);
+    }
+}
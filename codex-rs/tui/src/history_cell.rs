@@ -440,6 +440,21 @@ pub(crate) fn new_user_prompt(message: String) -> UserHistoryCell {
     UserHistoryCell { message }
 }
 
+/// A message the user sent to the running turn, shown where it joined the
+/// turn rather than as a new prompt.
+pub(crate) fn new_steer_note(message: String) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec!["↳ steered the running turn".dim().into()];
+    lines.extend(prefix_lines(
+        message
+            .lines()
+            .map(|line| Line::from(line.to_string().italic()))
+            .collect(),
+        "  ".into(),
+        "  ".into(),
+    ));
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_user_approval_decision(lines: Vec<Line<'static>>) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
@@ -1,7 +1,8 @@
+use codex_core::config_keymap::Key;
+use codex_core::config_keymap::KeyChord;
 use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Span;
-use std::fmt::Display;
 
 #[cfg(test)]
 const ALT_PREFIX: &str = "⌥";
@@ -28,22 +29,43 @@ fn key_hint_style() -> Style {
     Style::default().bold()
 }
 
-fn modifier_span(prefix: &str, key: impl Display) -> Span<'static> {
-    Span::styled(format!("{prefix}{key}"), key_hint_style())
+/// The label of a key chord, like `⌃T` or `Shift+⏎`. Letters are shown in
+/// upper case when they are combined with Ctrl or Alt.
+pub(crate) fn chord_label(chord: &KeyChord) -> String {
+    let mut label = String::new();
+    if chord.ctrl {
+        label.push_str(CTRL_PREFIX);
+    }
+    if chord.alt {
+        label.push_str(ALT_PREFIX);
+    }
+    if chord.shift {
+        label.push_str(SHIFT_PREFIX);
+    }
+    let key = match chord.key {
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(c) if chord.ctrl || chord.alt => c.to_uppercase().to_string(),
+        Key::Char(c) => c.to_string(),
+        Key::Enter => "⏎".to_string(),
+        Key::Esc => "Esc".to_string(),
+        Key::Tab => "Tab".to_string(),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Delete => "Del".to_string(),
+        Key::Insert => "Ins".to_string(),
+        Key::Up => "↑".to_string(),
+        Key::Down => "↓".to_string(),
+        Key::Left => "←".to_string(),
+        Key::Right => "→".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::PageUp => "PgUp".to_string(),
+        Key::PageDown => "PgDn".to_string(),
+        Key::F(n) => format!("F{n}"),
+    };
+    label.push_str(&key);
+    label
 }
 
-pub(crate) fn ctrl(key: impl Display) -> Span<'static> {
-    modifier_span(CTRL_PREFIX, key)
-}
-
-pub(crate) fn alt(key: impl Display) -> Span<'static> {
-    modifier_span(ALT_PREFIX, key)
-}
-
-pub(crate) fn shift(key: impl Display) -> Span<'static> {
-    modifier_span(SHIFT_PREFIX, key)
-}
-
-pub(crate) fn plain(key: impl Display) -> Span<'static> {
-    Span::styled(format!("{key}"), key_hint_style())
+pub(crate) fn chord(chord: &KeyChord) -> Span<'static> {
+    Span::styled(chord_label(chord), key_hint_style())
 }
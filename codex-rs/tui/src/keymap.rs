@@ -0,0 +1,296 @@
+//! Key bindings of the TUI, resolved from the `[tui.keymap]` config table.
+//!
+//! The keymap is installed once at startup with [`init`] and read anywhere
+//! keys are handled or hinted through [`keymap`], which falls back to the
+//! default bindings when nothing was installed (as in tests).
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use codex_core::config_keymap::Key;
+use codex_core::config_keymap::KeyChord;
+use codex_core::config_keymap::Keymap as KeymapConfig;
+use codex_core::config_keymap::KeymapAction;
+use codex_core::config_keymap::KeymapPreset;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::text::Span;
+
+use crate::key_hint;
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// Installs the keymap described by `config`. Only the first call has an
+/// effect.
+pub(crate) fn init(config: &KeymapConfig) {
+    let _ = KEYMAP.set(Keymap::new(config));
+}
+
+pub(crate) fn keymap() -> &'static Keymap {
+    KEYMAP.get_or_init(|| Keymap::new(&KeymapConfig::default()))
+}
+
+#[derive(Debug)]
+pub(crate) struct Keymap {
+    preset: KeymapPreset,
+    overrides: HashMap<KeymapAction, Vec<KeyChord>>,
+}
+
+impl Keymap {
+    pub(crate) fn new(config: &KeymapConfig) -> Self {
+        Self {
+            preset: config.preset,
+            overrides: config
+                .bindings
+                .iter()
+                .map(|(action, chords)| (*action, chords.0.clone()))
+                .collect(),
+        }
+    }
+
+    /// The chords bound to `action`, in the order they should be hinted.
+    pub(crate) fn chords(&self, action: KeymapAction) -> Vec<KeyChord> {
+        match self.overrides.get(&action) {
+            Some(chords) => chords.clone(),
+            None => {
+                let mut chords = default_chords(action);
+                chords.extend(preset_chords(self.preset, action));
+                chords
+            }
+        }
+    }
+
+    pub(crate) fn matches(&self, action: KeymapAction, event: &KeyEvent) -> bool {
+        self.chords(action)
+            .iter()
+            .any(|chord| chord_matches(chord, event))
+    }
+
+    /// The first of `actions` bound to the key of `event`.
+    pub(crate) fn action_for(
+        &self,
+        actions: &[KeymapAction],
+        event: &KeyEvent,
+    ) -> Option<KeymapAction> {
+        actions
+            .iter()
+            .copied()
+            .find(|action| self.matches(*action, event))
+    }
+
+    /// A hint for the first chord bound to `action`, if it is bound.
+    pub(crate) fn hint(&self, action: KeymapAction) -> Option<Span<'static>> {
+        self.chords(action).first().map(key_hint::chord)
+    }
+
+    /// The label of the first chord bound to `action`, if it is bound.
+    pub(crate) fn label(&self, action: KeymapAction) -> Option<String> {
+        self.chords(action).first().map(key_hint::chord_label)
+    }
+}
+
+fn chord_matches(chord: &KeyChord, event: &KeyEvent) -> bool {
+    let modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+    let mut expected = KeyModifiers::NONE;
+    expected.set(KeyModifiers::CONTROL, chord.ctrl);
+    expected.set(KeyModifiers::ALT, chord.alt);
+    if modifiers != expected {
+        return false;
+    }
+    let shift = event.modifiers.contains(KeyModifiers::SHIFT);
+    match (chord.key, event.code) {
+        // The character already reflects Shift.
+        (Key::Char(expected), KeyCode::Char(c)) => c == expected,
+        (Key::Tab, KeyCode::BackTab) => chord.shift,
+        (key, code) => key_code(key) == code && shift == chord.shift,
+    }
+}
+
+fn key_code(key: Key) -> KeyCode {
+    match key {
+        Key::Char(c) => KeyCode::Char(c),
+        Key::Enter => KeyCode::Enter,
+        Key::Esc => KeyCode::Esc,
+        Key::Tab => KeyCode::Tab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Insert => KeyCode::Insert,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        Key::F(n) => KeyCode::F(n),
+    }
+}
+
+const fn plain(c: char) -> KeyChord {
+    KeyChord::plain(Key::Char(c))
+}
+
+const fn ctrl(c: char) -> KeyChord {
+    KeyChord::ctrl(Key::Char(c))
+}
+
+const fn alt(c: char) -> KeyChord {
+    KeyChord::alt(Key::Char(c))
+}
+
+fn default_chords(action: KeymapAction) -> Vec<KeyChord> {
+    use KeymapAction::*;
+    match action {
+        Submit => vec![KeyChord::plain(Key::Enter)],
+        Newline => vec![KeyChord::shift(Key::Enter), ctrl('j'), ctrl('m')],
+        HistoryPrev => vec![KeyChord::plain(Key::Up)],
+        HistoryNext => vec![KeyChord::plain(Key::Down)],
+        OpenTranscript => vec![ctrl('t')],
+        Backtrack => vec![KeyChord::plain(Key::Esc)],
+        Interrupt => vec![ctrl('c')],
+        Quit => vec![ctrl('d')],
+        PasteImage => vec![ctrl('v')],
+        EditQueuedMessage => vec![KeyChord::alt(Key::Up)],
+        SteerTurn => vec![alt('s')],
+        OpenEditor => vec![ctrl('g')],
+        SearchHistory => vec![ctrl('r')],
+        AcceptApproval => vec![plain('y')],
+        AcceptApprovalForSession => vec![plain('a')],
+        DeclineApproval => vec![plain('n')],
+        EditApprovalCommand => vec![plain('e')],
+        ReviewChanges => vec![plain('d')],
+        SelectPatchHunks => vec![plain('h')],
+        ClosePager => vec![plain('q')],
+        ScrollUp => vec![KeyChord::plain(Key::Up)],
+        ScrollDown => vec![KeyChord::plain(Key::Down)],
+        PageUp => vec![KeyChord::plain(Key::PageUp)],
+        PageDown => vec![KeyChord::plain(Key::PageDown), plain(' ')],
+        ScrollToTop => vec![KeyChord::plain(Key::Home)],
+        ScrollToBottom => vec![KeyChord::plain(Key::End)],
+        NextHunk => vec![plain('n'), plain(']')],
+        PrevHunk => vec![plain('p'), plain('[')],
+        NextFile => vec![KeyChord::plain(Key::Tab)],
+        PrevFile => vec![KeyChord::shift(Key::Tab)],
+        ToggleDiffLayout => vec![plain('s')],
+        MoveLeft => vec![KeyChord::plain(Key::Left), ctrl('b')],
+        MoveRight => vec![KeyChord::plain(Key::Right), ctrl('f')],
+        MoveUp => vec![KeyChord::plain(Key::Up)],
+        MoveDown => vec![KeyChord::plain(Key::Down)],
+        MoveWordLeft => vec![
+            alt('b'),
+            KeyChord::alt(Key::Left),
+            KeyChord::ctrl(Key::Left),
+        ],
+        MoveWordRight => vec![
+            alt('f'),
+            KeyChord::alt(Key::Right),
+            KeyChord::ctrl(Key::Right),
+        ],
+        LineStart => vec![KeyChord::plain(Key::Home), ctrl('a')],
+        LineEnd => vec![KeyChord::plain(Key::End), ctrl('e')],
+        DeleteBackward => vec![KeyChord::plain(Key::Backspace), ctrl('h')],
+        DeleteForward => vec![KeyChord::plain(Key::Delete), ctrl('d')],
+        DeleteWordBackward => vec![
+            KeyChord::alt(Key::Backspace),
+            KeyChord {
+                key: Key::Char('h'),
+                ctrl: true,
+                alt: true,
+                shift: false,
+            },
+            ctrl('w'),
+        ],
+        DeleteWordForward => vec![KeyChord::alt(Key::Delete)],
+        KillToLineStart => vec![ctrl('u')],
+        KillToLineEnd => vec![ctrl('k')],
+    }
+}
+
+/// Chords a preset adds to the defaults.
+fn preset_chords(preset: KeymapPreset, action: KeymapAction) -> Vec<KeyChord> {
+    use KeymapAction::*;
+    match (preset, action) {
+        (KeymapPreset::Emacs, HistoryPrev | MoveUp | ScrollUp) => vec![ctrl('p')],
+        (KeymapPreset::Emacs, HistoryNext | MoveDown | ScrollDown) => vec![ctrl('n')],
+        (KeymapPreset::Emacs, PageUp) => vec![alt('v')],
+        (KeymapPreset::Emacs, PageDown) => vec![ctrl('v')],
+        (KeymapPreset::Emacs, ScrollToTop) => vec![alt('<')],
+        (KeymapPreset::Emacs, ScrollToBottom) => vec![alt('>')],
+        (KeymapPreset::Vi, ScrollUp) => vec![plain('k')],
+        (KeymapPreset::Vi, ScrollDown) => vec![plain('j')],
+        (KeymapPreset::Vi, PageUp) => vec![ctrl('b')],
+        (KeymapPreset::Vi, PageDown) => vec![ctrl('f')],
+        (KeymapPreset::Vi, ScrollToTop) => vec![plain('g')],
+        (KeymapPreset::Vi, ScrollToBottom) => vec![plain('G')],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config_keymap::KeyChords;
+    use pretty_assertions::assert_eq;
+    use std::collections::BTreeMap;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn default_keymap_matches_existing_keys() {
+        let keymap = Keymap::new(&KeymapConfig::default());
+        assert!(keymap.matches(
+            KeymapAction::OpenTranscript,
+            &key(KeyCode::Char('t'), KeyModifiers::CONTROL)
+        ));
+        assert!(keymap.matches(
+            KeymapAction::Newline,
+            &key(KeyCode::Enter, KeyModifiers::SHIFT)
+        ));
+        assert!(!keymap.matches(
+            KeymapAction::Submit,
+            &key(KeyCode::Enter, KeyModifiers::SHIFT)
+        ));
+        assert!(!keymap.matches(
+            KeymapAction::ClosePager,
+            &key(KeyCode::Char('q'), KeyModifiers::CONTROL)
+        ));
+    }
+
+    #[test]
+    fn overrides_replace_defaults_and_presets_add_to_them() {
+        let keymap = Keymap::new(&KeymapConfig {
+            preset: KeymapPreset::Vi,
+            bindings: BTreeMap::from([
+                (KeymapAction::OpenTranscript, KeyChords(vec![ctrl('o')])),
+                (KeymapAction::PasteImage, KeyChords(Vec::new())),
+            ]),
+        });
+
+        assert!(keymap.matches(
+            KeymapAction::OpenTranscript,
+            &key(KeyCode::Char('o'), KeyModifiers::CONTROL)
+        ));
+        assert!(!keymap.matches(
+            KeymapAction::OpenTranscript,
+            &key(KeyCode::Char('t'), KeyModifiers::CONTROL)
+        ));
+        assert_eq!(keymap.hint(KeymapAction::PasteImage), None);
+        // Shifted letters arrive uppercase, with or without the modifier.
+        assert!(keymap.matches(
+            KeymapAction::ScrollToBottom,
+            &key(KeyCode::Char('G'), KeyModifiers::SHIFT)
+        ));
+        assert!(keymap.matches(
+            KeymapAction::ScrollToBottom,
+            &key(KeyCode::End, KeyModifiers::NONE)
+        ));
+        assert_eq!(
+            keymap.label(KeymapAction::OpenTranscript),
+            Some("⌃O".to_string())
+        );
+    }
+}
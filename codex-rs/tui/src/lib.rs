@@ -32,6 +32,7 @@ mod app_backtrack;
 mod app_event;
 mod app_event_sender;
 mod ascii_animation;
+mod attach;
 mod bottom_pane;
 mod chatwidget;
 mod citation_regex;
@@ -39,15 +40,18 @@ mod cli;
 mod clipboard_paste;
 mod color;
 pub mod custom_terminal;
+mod diff_browser;
 mod diff_render;
 mod exec_cell;
 mod exec_command;
+mod external_editor;
 mod file_search;
 mod frames;
 mod get_git_diff;
 mod history_cell;
 pub mod insert_history;
 mod key_hint;
+mod keymap;
 pub mod live_wrap;
 mod markdown;
 mod markdown_render;
@@ -254,6 +258,7 @@ async fn run_ratatui_app(
 ) -> color_eyre::Result<AppExitInfo> {
     let mut config = config;
     color_eyre::install()?;
+    keymap::init(&config.tui_keymap);
 
     // Forward panic reports through tracing so they appear in the UI status
     // line, but do not swallow the default/color-eyre panic handler.
@@ -336,12 +341,18 @@ async fn run_ratatui_app(
         .await?;
         if let Some(TrustDirectorySelection::Trust) = directory_trust_decision {
             config.approval_policy = AskForApproval::OnRequest;
-            config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+            config.sandbox_policy = SandboxPolicy::new_workspace_write_policy()
+                .with_deny_read(config.sandbox_deny_read.clone());
         }
     }
 
-    // Determine resume behavior: explicit id, then resume last, then picker.
-    let resume_selection = if let Some(id_str) = cli.resume_session_id.as_deref() {
+    // Determine resume behavior: attach, explicit id, then resume last, then picker.
+    let resume_selection = if let Some(session_id) = cli.attach_session_id.clone() {
+        resume_picker::ResumeSelection::Attach {
+            session_id,
+            read_only: cli.attach_read_only,
+        }
+    } else if let Some(id_str) = cli.resume_session_id.as_deref() {
         match find_conversation_path_by_id_str(&config.codex_home, id_str).await? {
             Some(path) => resume_picker::ResumeSelection::Resume(path),
             None => {
@@ -458,7 +469,8 @@ fn determine_repo_trust_state(
         // if the current cwd project is trusted and no config has been set
         // skip the trust flow and set the approval policy and sandbox mode
         config.approval_policy = AskForApproval::OnRequest;
-        config.sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+        config.sandbox_policy = SandboxPolicy::new_workspace_write_policy()
+            .with_deny_read(config.sandbox_deny_read.clone());
         Ok(false)
     } else {
         // if none of the above conditions are met, show the trust screen
@@ -1,4 +1,7 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::render::highlight::Language;
+use crate::render::highlight::highlight_code_to_lines;
+use crate::render::highlight::language_for_info_string;
 use pulldown_cmark::CodeBlockKind;
 use pulldown_cmark::CowStr;
 use pulldown_cmark::Event;
@@ -71,6 +74,10 @@ where
     scheme: Option<String>,
     cwd: Option<std::path::PathBuf>,
     in_code_block: bool,
+    /// Text of a fenced code block in a language that is highlighted, which
+    /// is buffered until the block ends so that comments and strings can span
+    /// lines.
+    highlighted_code: Option<(&'static Language, String)>,
 }
 
 impl<'a, I> Writer<'a, I>
@@ -91,6 +98,7 @@ where
             scheme,
             cwd,
             in_code_block: false,
+            highlighted_code: None,
         }
     }
 
@@ -237,6 +245,10 @@ where
             self.push_line(Line::default());
         }
         self.pending_marker_line = false;
+        if let Some((_, code)) = self.highlighted_code.as_mut() {
+            code.push_str(&text);
+            return;
+        }
         if self.in_code_block
             && !self.needs_newline
             && self
@@ -350,11 +362,15 @@ where
         self.needs_newline = false;
     }
 
-    fn start_codeblock(&mut self, _lang: Option<String>, indent: Option<Span<'static>>) {
+    fn start_codeblock(&mut self, lang: Option<String>, indent: Option<Span<'static>>) {
         if !self.text.lines.is_empty() {
             self.push_blank_line();
         }
         self.in_code_block = true;
+        self.highlighted_code = lang
+            .as_deref()
+            .and_then(language_for_info_string)
+            .map(|language| (language, String::new()));
         self.indent_stack.push(IndentContext::new(
             vec![indent.unwrap_or_default()],
             None,
@@ -369,6 +385,15 @@ where
     }
 
     fn end_codeblock(&mut self) {
+        if let Some((language, code)) = self.highlighted_code.take() {
+            let code = code.strip_suffix('\n').unwrap_or(&code);
+            for line in highlight_code_to_lines(language, code) {
+                self.push_line(Line::default());
+                for span in line.spans {
+                    self.push_span(span);
+                }
+            }
+        }
         // self.push_line("```".into());
         self.needs_newline = true;
         self.in_code_block = false;
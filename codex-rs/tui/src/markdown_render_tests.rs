@@ -652,9 +652,27 @@ fn link() {
 }
 
 #[test]
-fn code_block_unhighlighted() {
-    let text = render_markdown_text("```rust\nfn main() {}\n```\n");
-    let expected = Text::from_iter([Line::from_iter(["", "fn main() {}"])]);
+fn code_block_highlighted_by_language() {
+    let text = render_markdown_text("```rust\n// entry\nfn main() { let s = \"hi\"; }\n```\n");
+    let expected = Text::from_iter([
+        Line::from_iter(["".into(), "// entry".dim().italic()]),
+        Line::from_iter([
+            "".into(),
+            "fn".magenta(),
+            " main() { ".into(),
+            "let".magenta(),
+            " s = ".into(),
+            "\"hi\"".green(),
+            "; }".into(),
+        ]),
+    ]);
+    assert_eq!(text, expected);
+}
+
+#[test]
+fn code_block_unknown_language_unhighlighted() {
+    let text = render_markdown_text("```brainfuck\n+[->+<]\n```\n");
+    let expected = Text::from_iter([Line::from_iter(["", "+[->+<]"])]);
     assert_eq!(text, expected);
 }
 
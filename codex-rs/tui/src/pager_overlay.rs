@@ -2,11 +2,16 @@ use std::io::Result;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::diff_browser::DiffFile;
+use crate::diff_browser::DiffLayout;
+use crate::diff_browser::file_header_lines;
+use crate::diff_browser::hunk_lines;
 use crate::history_cell::HistoryCell;
+use crate::keymap::keymap;
 use crate::render::line_utils::push_owned_lines;
 use crate::tui;
 use crate::tui::TuiEvent;
-use crossterm::event::KeyCode;
+use codex_core::config_keymap::KeymapAction;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use ratatui::buffer::Buffer;
@@ -26,6 +31,7 @@ use ratatui::widgets::WidgetRef;
 pub(crate) enum Overlay {
     Transcript(TranscriptOverlay),
     Static(StaticOverlay),
+    Diff(DiffOverlay),
 }
 
 impl Overlay {
@@ -37,10 +43,15 @@ impl Overlay {
         Self::Static(StaticOverlay::with_title(lines, title))
     }
 
+    pub(crate) fn new_diff(files: Vec<DiffFile>, title: String) -> Self {
+        Self::Diff(DiffOverlay::new(files, title))
+    }
+
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match self {
             Overlay::Transcript(o) => o.handle_event(tui, event),
             Overlay::Static(o) => o.handle_event(tui, event),
+            Overlay::Diff(o) => o.handle_event(tui, event),
         }
     }
 
@@ -48,19 +59,59 @@ impl Overlay {
         match self {
             Overlay::Transcript(o) => o.is_done(),
             Overlay::Static(o) => o.is_done(),
+            Overlay::Diff(o) => o.is_done(),
         }
     }
 }
 
-// Common pager navigation hints rendered on the first line
-const PAGER_KEY_HINTS: &[(&str, &str)] = &[
-    ("↑/↓", "scroll"),
-    ("PgUp/PgDn", "page"),
-    ("Home/End", "jump"),
+/// Actions that move through a pager, in the order their chords are tried.
+const SCROLL_ACTIONS: &[KeymapAction] = &[
+    KeymapAction::ScrollUp,
+    KeymapAction::ScrollDown,
+    KeymapAction::PageUp,
+    KeymapAction::PageDown,
+    KeymapAction::ScrollToTop,
+    KeymapAction::ScrollToBottom,
 ];
 
+// Common pager navigation hints rendered on the first line
+fn pager_key_hints() -> Vec<(String, &'static str)> {
+    [
+        pair_hint(KeymapAction::ScrollUp, KeymapAction::ScrollDown, "scroll"),
+        pair_hint(KeymapAction::PageUp, KeymapAction::PageDown, "page"),
+        pair_hint(
+            KeymapAction::ScrollToTop,
+            KeymapAction::ScrollToBottom,
+            "jump",
+        ),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// The hint for a pair of actions moving back and forward, if either is bound.
+fn pair_hint(
+    back: KeymapAction,
+    forward: KeymapAction,
+    desc: &'static str,
+) -> Option<(String, &'static str)> {
+    let keymap = keymap();
+    let keys = match (keymap.label(back), keymap.label(forward)) {
+        (Some(back), Some(forward)) => format!("{back}/{forward}"),
+        (Some(key), None) | (None, Some(key)) => key,
+        (None, None) => return None,
+    };
+    Some((keys, desc))
+}
+
+/// The hint for `action`, if it is bound.
+fn action_hint(action: KeymapAction, desc: &'static str) -> Option<(String, &'static str)> {
+    keymap().label(action).map(|key| (key, desc))
+}
+
 // Render a single line of key hints from (key, description) pairs.
-fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
+fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(String, &str)]) {
     let key_hint_style = Style::default().fg(Color::Cyan);
     let mut spans: Vec<Span<'static>> = vec![" ".into()];
     let mut first = true;
@@ -190,53 +241,32 @@ impl PagerView {
     }
 
     fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> Result<()> {
-        match key_event {
-            KeyEvent {
-                code: KeyCode::Up,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            return Ok(());
+        }
+        let Some(action) = keymap().action_for(SCROLL_ACTIONS, &key_event) else {
+            return Ok(());
+        };
+        match action {
+            KeymapAction::ScrollUp => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
             }
-            KeyEvent {
-                code: KeyCode::Down,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            KeymapAction::ScrollDown => {
                 self.scroll_offset = self.scroll_offset.saturating_add(1);
             }
-            KeyEvent {
-                code: KeyCode::PageUp,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            KeymapAction::PageUp => {
                 let area = self.scroll_area(tui.terminal.viewport_area);
                 self.scroll_offset = self.scroll_offset.saturating_sub(area.height as usize);
             }
-            KeyEvent {
-                code: KeyCode::PageDown | KeyCode::Char(' '),
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            KeymapAction::PageDown => {
                 let area = self.scroll_area(tui.terminal.viewport_area);
                 self.scroll_offset = self.scroll_offset.saturating_add(area.height as usize);
             }
-            KeyEvent {
-                code: KeyCode::Home,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
+            KeymapAction::ScrollToTop => {
                 self.scroll_offset = 0;
             }
-            KeyEvent {
-                code: KeyCode::End,
-                kind: KeyEventKind::Press | KeyEventKind::Repeat,
-                ..
-            } => {
-                self.scroll_offset = usize::MAX;
-            }
             _ => {
-                return Ok(());
+                self.scroll_offset = usize::MAX;
             }
         }
         tui.frame_requester()
@@ -426,10 +456,16 @@ impl TranscriptOverlay {
     fn render_hints(&self, area: Rect, buf: &mut Buffer) {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
-        render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let mut pairs: Vec<(&str, &str)> = vec![("q", "quit"), ("Esc", "edit prev")];
+        render_key_hints(line1, buf, &pager_key_hints());
+        let mut pairs: Vec<(String, &str)> = [
+            action_hint(KeymapAction::ClosePager, "quit"),
+            action_hint(KeymapAction::Backtrack, "edit prev"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
         if self.highlight_cell.is_some() {
-            pairs.push(("⏎", "edit message"));
+            pairs.push(("⏎".to_string(), "edit message"));
         }
         render_key_hints(line2, buf, &pairs);
     }
@@ -448,22 +484,19 @@ impl TranscriptOverlay {
         match event {
             TuiEvent::Key(key_event) => match key_event {
                 KeyEvent {
-                    code: KeyCode::Char('q'),
-                    kind: KeyEventKind::Press,
-                    ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('t'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
                     kind: KeyEventKind::Press,
                     ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                } => {
+                } if keymap()
+                    .action_for(
+                        &[
+                            KeymapAction::ClosePager,
+                            KeymapAction::OpenTranscript,
+                            KeymapAction::Interrupt,
+                        ],
+                        &key_event,
+                    )
+                    .is_some() =>
+                {
                     self.is_done = true;
                     Ok(())
                 }
@@ -499,8 +532,10 @@ impl StaticOverlay {
     fn render_hints(&self, area: Rect, buf: &mut Buffer) {
         let line1 = Rect::new(area.x, area.y, area.width, 1);
         let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
-        render_key_hints(line1, buf, PAGER_KEY_HINTS);
-        let pairs = [("q", "quit")];
+        render_key_hints(line1, buf, &pager_key_hints());
+        let pairs: Vec<_> = action_hint(KeymapAction::ClosePager, "quit")
+            .into_iter()
+            .collect();
         render_key_hints(line2, buf, &pairs);
     }
 
@@ -518,16 +553,15 @@ impl StaticOverlay {
         match event {
             TuiEvent::Key(key_event) => match key_event {
                 KeyEvent {
-                    code: KeyCode::Char('q'),
                     kind: KeyEventKind::Press,
                     ..
-                }
-                | KeyEvent {
-                    code: KeyCode::Char('c'),
-                    modifiers: crossterm::event::KeyModifiers::CONTROL,
-                    kind: KeyEventKind::Press,
-                    ..
-                } => {
+                } if keymap()
+                    .action_for(
+                        &[KeymapAction::ClosePager, KeymapAction::Interrupt],
+                        &key_event,
+                    )
+                    .is_some() =>
+                {
                     self.is_done = true;
                     Ok(())
                 }
@@ -547,6 +581,273 @@ impl StaticOverlay {
     }
 }
 
+/// Narrowest terminal at which the diff browser lists the files in a sidebar.
+const DIFF_SIDEBAR_MIN_WIDTH: u16 = 100;
+
+/// Actions of the diff browser on top of the pager's own.
+const DIFF_ACTIONS: &[KeymapAction] = &[
+    KeymapAction::NextHunk,
+    KeymapAction::PrevHunk,
+    KeymapAction::NextFile,
+    KeymapAction::PrevFile,
+    KeymapAction::ToggleDiffLayout,
+];
+
+/// Full-screen browser for the files of a diff, with jumps between hunks and
+/// files and a side-by-side layout on wide terminals.
+pub(crate) struct DiffOverlay {
+    view: PagerView,
+    files: Vec<DiffFile>,
+    /// For each chunk of the view, the file it belongs to and whether it is a
+    /// hunk rather than the file's header.
+    chunks: Vec<(usize, bool)>,
+    /// The layout picked by the user, overriding the one for the width.
+    layout: Option<DiffLayout>,
+    /// The width and layout the chunks were laid out for.
+    built_for: Option<(u16, DiffLayout)>,
+    is_done: bool,
+}
+
+impl DiffOverlay {
+    pub(crate) fn new(files: Vec<DiffFile>, title: String) -> Self {
+        Self {
+            view: PagerView::new(Vec::new(), title, 0),
+            files,
+            chunks: Vec::new(),
+            layout: None,
+            built_for: None,
+            is_done: false,
+        }
+    }
+
+    fn layout_for(&self, width: u16) -> DiffLayout {
+        self.layout.unwrap_or_else(|| DiffLayout::for_width(width))
+    }
+
+    /// Lay the files out again when the width or layout changed, keeping the
+    /// chunk at the top of the view in place.
+    fn rebuild(&mut self, width: u16) {
+        let layout = self.layout_for(width);
+        if self.built_for == Some((width, layout)) {
+            return;
+        }
+        let top = self.top_chunk();
+        let mut texts = Vec::new();
+        let mut chunks = Vec::new();
+        for (idx, file) in self.files.iter().enumerate() {
+            texts.push(Text::from(file_header_lines(file, idx == 0)));
+            chunks.push((idx, false));
+            for hunk in &file.hunks {
+                texts.push(Text::from(hunk_lines(file, hunk, width, layout)));
+                chunks.push((idx, true));
+            }
+        }
+        self.view.texts = texts;
+        self.view.wrap_cache = None;
+        self.chunks = chunks;
+        self.built_for = Some((width, layout));
+        if let Some(top) = top {
+            self.view.ensure_wrapped(width);
+            if let Some(range) = self
+                .view
+                .wrap_cache
+                .as_ref()
+                .and_then(|cache| cache.chunk_ranges.get(top))
+            {
+                self.view.scroll_offset = range.start;
+            }
+        }
+    }
+
+    /// The first wrapped line in view.
+    fn top_line(&self) -> usize {
+        let total = self.view.cached().len();
+        let height = self.view.last_content_height.unwrap_or(0);
+        self.view.scroll_offset.min(total.saturating_sub(height))
+    }
+
+    /// The chunk at the top of the view.
+    fn top_chunk(&self) -> Option<usize> {
+        let cache = self.view.wrap_cache.as_ref()?;
+        let top = self.top_line();
+        cache
+            .chunk_ranges
+            .iter()
+            .rposition(|range| range.start <= top)
+    }
+
+    fn current_file(&self) -> Option<usize> {
+        self.top_chunk()
+            .and_then(|chunk| self.chunks.get(chunk))
+            .map(|(file, _)| *file)
+    }
+
+    /// Scroll to the start of the next or previous hunk or file.
+    fn jump(&mut self, action: KeymapAction) {
+        let Some(cache) = self.view.wrap_cache.as_ref() else {
+            return;
+        };
+        let hunks = matches!(action, KeymapAction::NextHunk | KeymapAction::PrevHunk);
+        let forward = matches!(action, KeymapAction::NextHunk | KeymapAction::NextFile);
+        let top = self.top_line();
+        let mut starts = self
+            .chunks
+            .iter()
+            .zip(&cache.chunk_ranges)
+            .filter(|((_, is_hunk), _)| *is_hunk == hunks)
+            .map(|(_, range)| range.start);
+        let target = if forward {
+            starts.find(|start| *start > top)
+        } else {
+            starts.rfind(|start| *start < top)
+        };
+        if let Some(target) = target {
+            self.view.scroll_offset = target;
+        }
+    }
+
+    fn sidebar_width(&self, width: u16) -> u16 {
+        if width < DIFF_SIDEBAR_MIN_WIDTH || self.files.len() < 2 {
+            return 0;
+        }
+        let longest = self
+            .files
+            .iter()
+            .map(|file| file.path.chars().count())
+            .max()
+            .unwrap_or(0);
+        // A leading space and the separator column.
+        (longest.min(u16::MAX as usize) as u16 + 2).clamp(16, width / 4)
+    }
+
+    /// The list of files, with the one at the top of the view highlighted.
+    /// Its header and bottom rows line up with those of the pager.
+    fn render_sidebar(&self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        if area.height < 2 {
+            return;
+        }
+        Span::from("/ ".repeat(area.width as usize / 2))
+            .dim()
+            .render_ref(Rect::new(area.x, area.y, area.width, 1), buf);
+        Span::from("─".repeat(area.width as usize))
+            .dim()
+            .render_ref(Rect::new(area.x, area.bottom() - 1, area.width, 1), buf);
+
+        let rows = (area.height - 2) as usize;
+        let name_width = area.width.saturating_sub(2) as usize;
+        let current = self.current_file().unwrap_or(0);
+        let start = (current + 1).saturating_sub(rows);
+        for row in 0..rows {
+            let y = area.y + 1 + row as u16;
+            Span::from("│")
+                .dim()
+                .render_ref(Rect::new(area.right() - 1, y, 1, 1), buf);
+            let Some(file) = self.files.get(start + row) else {
+                continue;
+            };
+            let name = format!(" {}", truncate_start(&file.path, name_width));
+            let name = if start + row == current {
+                name.cyan().bold()
+            } else {
+                name.into()
+            };
+            name.render_ref(Rect::new(area.x, y, area.width - 1, 1), buf);
+        }
+    }
+
+    fn render_hints(&self, area: Rect, buf: &mut Buffer) {
+        let line1 = Rect::new(area.x, area.y, area.width, 1);
+        let line2 = Rect::new(area.x, area.y.saturating_add(1), area.width, 1);
+        render_key_hints(line1, buf, &pager_key_hints());
+        let layout = match self.built_for {
+            Some((_, DiffLayout::SideBySide)) => "unified",
+            _ => "side by side",
+        };
+        let pairs: Vec<_> = [
+            pair_hint(KeymapAction::PrevHunk, KeymapAction::NextHunk, "hunk"),
+            pair_hint(KeymapAction::PrevFile, KeymapAction::NextFile, "file"),
+            action_hint(KeymapAction::ToggleDiffLayout, layout),
+            action_hint(KeymapAction::ClosePager, "quit"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        render_key_hints(line2, buf, &pairs);
+    }
+
+    pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let top_h = area.height.saturating_sub(3);
+        let sidebar_w = self.sidebar_width(area.width);
+        let content = Rect::new(area.x + sidebar_w, area.y, area.width - sidebar_w, top_h);
+        let bottom = Rect::new(area.x, area.y + top_h, area.width, 3);
+        self.rebuild(content.width.max(1));
+        self.view.render(content, buf);
+        if sidebar_w > 0 {
+            self.render_sidebar(Rect::new(area.x, area.y, sidebar_w, top_h), buf);
+        }
+        self.render_hints(bottom, buf);
+    }
+}
+
+impl DiffOverlay {
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) => {
+                let keymap = keymap();
+                let pressed = matches!(key_event.kind, KeyEventKind::Press);
+                let repeated = matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat);
+                if pressed
+                    && keymap
+                        .action_for(
+                            &[KeymapAction::ClosePager, KeymapAction::Interrupt],
+                            &key_event,
+                        )
+                        .is_some()
+                {
+                    self.is_done = true;
+                } else if repeated && let Some(action) = keymap.action_for(DIFF_ACTIONS, &key_event)
+                {
+                    if action == KeymapAction::ToggleDiffLayout {
+                        if let Some((width, layout)) = self.built_for {
+                            self.layout = Some(layout.toggled());
+                            self.rebuild(width);
+                        }
+                    } else {
+                        self.jump(action);
+                    }
+                    tui.frame_requester()
+                        .schedule_frame_in(Duration::from_millis(16));
+                } else {
+                    self.view.handle_key_event(tui, key_event)?;
+                }
+                Ok(())
+            }
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+}
+
+/// `text` shortened to `width` characters by dropping its start.
+fn truncate_start(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len <= width {
+        return text.to_string();
+    }
+    let keep = width.saturating_sub(1);
+    let tail: String = text.chars().skip(len - keep).collect();
+    format!("…{tail}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -782,6 +1083,64 @@ mod tests {
         assert_snapshot!(term.backend());
     }
 
+    fn diff_overlay_files() -> Vec<DiffFile> {
+        crate::diff_browser::files_from_unified_diff(
+            "\
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"hello\");
++    println!(\"hello, world\");
+ }
+@@ -10,2 +10,3 @@
+ fn helper() {}
++fn other() {}
+ // end
+diff --git a/README.md b/README.md
+--- a/README.md
++++ b/README.md
+@@ -1 +1 @@
+-# Title
++# New title
+",
+        )
+    }
+
+    #[test]
+    fn diff_overlay_side_by_side_with_sidebar_snapshot() {
+        let mut overlay = DiffOverlay::new(diff_overlay_files(), "D I F F".to_string());
+        let mut term = Terminal::new(TestBackend::new(140, 14)).expect("term");
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert_snapshot!(term.backend());
+    }
+
+    #[test]
+    fn diff_overlay_jumps_between_hunks_and_files() {
+        let mut overlay = DiffOverlay::new(diff_overlay_files(), "D I F F".to_string());
+        let area = Rect::new(0, 0, 60, 8);
+        let mut buf = Buffer::empty(area);
+        overlay.render(area, &mut buf);
+        assert_eq!(overlay.current_file(), Some(0));
+
+        overlay.jump(KeymapAction::NextHunk);
+        overlay.jump(KeymapAction::NextHunk);
+        overlay.render(area, &mut buf);
+        let top = buffer_to_text(&buf, Rect::new(0, 1, 60, 1));
+        assert!(top.contains("@@ -10,2 +10,3 @@"), "got {top:?}");
+
+        overlay.jump(KeymapAction::NextFile);
+        overlay.render(area, &mut buf);
+        assert_eq!(overlay.current_file(), Some(1));
+
+        overlay.jump(KeymapAction::PrevFile);
+        overlay.render(area, &mut buf);
+        assert_eq!(overlay.current_file(), Some(0));
+        assert_eq!(overlay.top_line(), 0);
+    }
+
     #[test]
     fn pager_wrap_cache_reuses_for_same_width_and_rebuilds_on_change() {
         let long = "This is a long line that should wrap multiple times to ensure non-empty wrapped output.";
@@ -1,8 +1,16 @@
+use std::path::Path;
+
 use codex_core::bash::try_parse_bash;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
 use ratatui::text::Span;
 
+use crate::color::is_light;
+use crate::terminal_palette::default_bg;
+
 /// Convert the full bash script into per-line styled content by first
 /// computing operator-dimmed spans across the entire script, then splitting
 /// by newlines and dimming heredoc body lines. Performs a single parse and
@@ -78,6 +86,587 @@ pub(crate) fn highlight_bash_to_lines(script: &str) -> Vec<Line<'static>> {
     lines
 }
 
+/// Lexical rules for one language, enough to pick out keywords, types,
+/// literals and comments. This is deliberately not a parser: it only has to
+/// make code blocks and diffs easier to scan.
+pub(crate) struct Language {
+    /// Fenced code block info strings that select this language.
+    names: &'static [&'static str],
+    /// File extensions, without the dot, that select this language.
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// String delimiters, tried in order, and whether the string may span
+    /// lines.
+    strings: &'static [(&'static str, bool)],
+    /// `'x'` is a character literal, but a lone `'` (a Rust lifetime, say) is
+    /// not a string.
+    char_literals: bool,
+    keywords: &'static [&'static str],
+    /// Built-in types and constants such as `true` or `None`.
+    builtins: &'static [&'static str],
+    /// Identifiers starting with an uppercase letter are types.
+    capitalized_types: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum TokenKind {
+    Plain,
+    Keyword,
+    Builtin,
+    String,
+    Number,
+    Comment,
+}
+
+/// Styles for each kind of token, picked for the terminal's background.
+struct SyntaxTheme {
+    keyword: Style,
+    builtin: Style,
+    string: Style,
+    number: Style,
+    comment: Style,
+}
+
+impl SyntaxTheme {
+    /// Uses the bright ANSI colors on dark backgrounds, where the normal ones
+    /// can be hard to read, and the normal ones otherwise.
+    fn for_background(bg: Option<(u8, u8, u8)>) -> Self {
+        let dark = bg.is_some_and(|bg| !is_light(bg));
+        let (magenta, cyan, green) = if dark {
+            (Color::LightMagenta, Color::LightCyan, Color::LightGreen)
+        } else {
+            (Color::Magenta, Color::Cyan, Color::Green)
+        };
+        Self {
+            keyword: Style::default().fg(magenta),
+            builtin: Style::default().fg(cyan),
+            string: Style::default().fg(green),
+            number: Style::default().fg(cyan),
+            comment: Style::default().add_modifier(Modifier::DIM | Modifier::ITALIC),
+        }
+    }
+
+    fn style(&self, kind: TokenKind) -> Style {
+        match kind {
+            TokenKind::Plain => Style::default(),
+            TokenKind::Keyword => self.keyword,
+            TokenKind::Builtin => self.builtin,
+            TokenKind::String => self.string,
+            TokenKind::Number => self.number,
+            TokenKind::Comment => self.comment,
+        }
+    }
+}
+
+/// The language named by a fenced code block's info string, e.g. `rust` or
+/// `py {.numberLines}`.
+pub(crate) fn language_for_info_string(info: &str) -> Option<&'static Language> {
+    let name = info
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '{')
+        .next()?
+        .trim_start_matches('.')
+        .to_ascii_lowercase();
+    if name.is_empty() {
+        return None;
+    }
+    LANGUAGES.iter().find(|lang| {
+        lang.names.contains(&name.as_str()) || lang.extensions.contains(&name.as_str())
+    })
+}
+
+/// The language of a file, going by its extension.
+pub(crate) fn language_for_path(path: &Path) -> Option<&'static Language> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|lang| lang.extensions.contains(&ext.as_str()))
+}
+
+/// Highlight a whole code block. Comments and strings may span lines.
+pub(crate) fn highlight_code_to_lines(lang: &Language, code: &str) -> Vec<Line<'static>> {
+    let theme = SyntaxTheme::for_background(default_bg());
+    let mut lines = vec![Line::default()];
+    for (kind, token) in tokenize(lang, code) {
+        for (i, part) in token.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            if !part.is_empty()
+                && let Some(line) = lines.last_mut()
+            {
+                line.spans
+                    .push(Span::styled(part.to_string(), theme.style(kind)));
+            }
+        }
+    }
+    lines
+}
+
+/// Highlight a single line, such as one line of a diff hunk, without any
+/// context from the lines around it.
+pub(crate) fn highlight_line(lang: &Language, line: &str) -> Vec<Span<'static>> {
+    highlight_code_to_lines(lang, line)
+        .into_iter()
+        .flat_map(|line| line.spans)
+        .collect()
+}
+
+fn tokenize<'a>(lang: &Language, code: &'a str) -> Vec<(TokenKind, &'a str)> {
+    // Byte ranges of the tokens, with runs of plain text merged.
+    let mut ranges: Vec<(TokenKind, usize, usize)> = Vec::new();
+    let mut push = |kind: TokenKind, start: usize, len: usize| match ranges.last_mut() {
+        Some((TokenKind::Plain, _, end)) if kind == TokenKind::Plain && *end == start => {
+            *end = start + len;
+        }
+        _ => ranges.push((kind, start, start + len)),
+    };
+
+    let mut i = 0;
+    'outer: while i < code.len() {
+        let rest = &code[i..];
+        let prev = code[..i].chars().next_back();
+
+        if let Some((open, close)) = lang.block_comment
+            && rest.starts_with(open)
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |pos| open.len() + pos + close.len());
+            push(TokenKind::Comment, i, end);
+            i += end;
+            continue;
+        }
+
+        for marker in lang.line_comments {
+            // `#` only starts a comment at the start of a word, so that `$#`
+            // and `a#b` in shell stay as they are.
+            let at_word_start = *marker != "#" || prev.is_none_or(char::is_whitespace);
+            if rest.starts_with(marker) && at_word_start {
+                let end = rest.find('\n').unwrap_or(rest.len());
+                push(TokenKind::Comment, i, end);
+                i += end;
+                continue 'outer;
+            }
+        }
+
+        for (delim, multiline) in lang.strings {
+            if rest.starts_with(delim) {
+                let end = string_end(rest, delim, *multiline);
+                push(TokenKind::String, i, end);
+                i += end;
+                continue 'outer;
+            }
+        }
+
+        if lang.char_literals
+            && rest.starts_with('\'')
+            && let Some(end) = char_literal_end(rest)
+        {
+            push(TokenKind::String, i, end);
+            i += end;
+            continue;
+        }
+
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+        if c.is_ascii_digit() && !prev.is_some_and(is_ident_char) {
+            let mut end = 0;
+            for (pos, ch) in rest.char_indices() {
+                let decimal_point =
+                    ch == '.' && rest[pos + 1..].starts_with(|next: char| next.is_ascii_digit());
+                if !(is_ident_char(ch) || decimal_point) {
+                    break;
+                }
+                end = pos + ch.len_utf8();
+            }
+            push(TokenKind::Number, i, end);
+            i += end;
+        } else if is_ident_start(c) {
+            let end = rest
+                .char_indices()
+                .find(|(_, ch)| !is_ident_char(*ch))
+                .map_or(rest.len(), |(pos, _)| pos);
+            let word = &rest[..end];
+            let kind = if lang.keywords.contains(&word) {
+                TokenKind::Keyword
+            } else if lang.builtins.contains(&word)
+                || (lang.capitalized_types && word.starts_with(|ch: char| ch.is_ascii_uppercase()))
+            {
+                TokenKind::Builtin
+            } else {
+                TokenKind::Plain
+            };
+            push(kind, i, end);
+            i += end;
+        } else {
+            let len = c.len_utf8();
+            push(TokenKind::Plain, i, len);
+            i += len;
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(kind, start, end)| (kind, &code[start..end]))
+        .collect()
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Length of the string starting at the beginning of `rest`, including its
+/// delimiters. Unterminated strings run to the end of the line, or of the
+/// text when strings may span lines.
+fn string_end(rest: &str, delim: &str, multiline: bool) -> usize {
+    let mut chars = rest.char_indices().skip(delim.chars().count());
+    while let Some((pos, ch)) = chars.next() {
+        if ch == '\\' {
+            chars.next();
+        } else if ch == '\n' && !multiline {
+            return pos;
+        } else if rest[pos..].starts_with(delim) {
+            return pos + delim.len();
+        }
+    }
+    rest.len()
+}
+
+/// Length of a character literal such as `'a'` or `'\n'` at the beginning of
+/// `rest`, if there is one.
+fn char_literal_end(rest: &str) -> Option<usize> {
+    let mut chars = rest.char_indices().skip(1);
+    let (_, first) = chars.next()?;
+    if first == '\\' {
+        // An escape: allow `'\u{1F600}'` and the like.
+        return chars
+            .take(12)
+            .find(|(_, ch)| *ch == '\'' || *ch == '\n')
+            .filter(|(_, ch)| *ch == '\'')
+            .map(|(pos, _)| pos + 1);
+    }
+    match chars.next()? {
+        (pos, '\'') if first != '\'' => Some(pos + 1),
+        _ => None,
+    }
+}
+
+const C_LIKE_STRINGS: &[(&str, bool)] = &[("\"", false)];
+
+static LANGUAGES: &[Language] = &[
+    Language {
+        names: &["rust"],
+        extensions: &["rs"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &[("\"", true)],
+        char_literals: true,
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+            "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "type",
+            "unsafe", "use", "where", "while", "yield",
+        ],
+        builtins: &[
+            "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+            "u16", "u32", "u64", "u128", "usize", "true", "false",
+        ],
+        capitalized_types: true,
+    },
+    Language {
+        names: &["python", "python3"],
+        extensions: &["py", "pyi"],
+        line_comments: &["#"],
+        block_comment: None,
+        strings: &[("\"\"\"", true), ("'''", true), ("\"", false), ("'", false)],
+        char_literals: false,
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in",
+            "is", "lambda", "match", "nonlocal", "not", "or", "pass", "raise", "return", "try",
+            "while", "with", "yield",
+        ],
+        builtins: &["True", "False", "None", "self", "cls"],
+        capitalized_types: true,
+    },
+    Language {
+        names: &["javascript", "js", "jsx", "typescript", "ts", "tsx"],
+        extensions: &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &[("`", true), ("\"", false), ("'", false)],
+        char_literals: false,
+        keywords: &[
+            "as",
+            "async",
+            "await",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "export",
+            "extends",
+            "finally",
+            "for",
+            "from",
+            "function",
+            "if",
+            "implements",
+            "import",
+            "in",
+            "instanceof",
+            "interface",
+            "let",
+            "new",
+            "of",
+            "return",
+            "satisfies",
+            "static",
+            "switch",
+            "this",
+            "throw",
+            "try",
+            "type",
+            "typeof",
+            "var",
+            "void",
+            "while",
+            "yield",
+        ],
+        builtins: &[
+            "any",
+            "boolean",
+            "false",
+            "null",
+            "number",
+            "string",
+            "true",
+            "undefined",
+            "unknown",
+        ],
+        capitalized_types: true,
+    },
+    Language {
+        names: &["go", "golang"],
+        extensions: &["go"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &[("`", true), ("\"", false)],
+        char_literals: true,
+        keywords: &[
+            "break",
+            "case",
+            "chan",
+            "const",
+            "continue",
+            "default",
+            "defer",
+            "else",
+            "fallthrough",
+            "for",
+            "func",
+            "go",
+            "goto",
+            "if",
+            "import",
+            "interface",
+            "map",
+            "package",
+            "range",
+            "return",
+            "select",
+            "struct",
+            "switch",
+            "type",
+            "var",
+        ],
+        builtins: &[
+            "bool", "byte", "error", "false", "float32", "float64", "int", "int8", "int16",
+            "int32", "int64", "iota", "nil", "rune", "string", "true", "uint", "uint8", "uint16",
+            "uint32", "uint64", "uintptr",
+        ],
+        capitalized_types: false,
+    },
+    Language {
+        names: &["c", "cpp", "c++", "cxx", "objc"],
+        extensions: &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx", "m", "mm"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: C_LIKE_STRINGS,
+        char_literals: true,
+        keywords: &[
+            "auto",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "constexpr",
+            "continue",
+            "default",
+            "delete",
+            "do",
+            "else",
+            "enum",
+            "extern",
+            "for",
+            "goto",
+            "if",
+            "inline",
+            "namespace",
+            "new",
+            "operator",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "sizeof",
+            "static",
+            "struct",
+            "switch",
+            "template",
+            "this",
+            "throw",
+            "try",
+            "typedef",
+            "typename",
+            "union",
+            "using",
+            "virtual",
+            "volatile",
+            "while",
+        ],
+        builtins: &[
+            "bool", "char", "double", "false", "float", "int", "long", "nullptr", "short",
+            "signed", "size_t", "true", "unsigned", "void", "NULL",
+        ],
+        capitalized_types: false,
+    },
+    Language {
+        names: &[
+            "java", "kotlin", "kt", "csharp", "cs", "c#", "swift", "scala",
+        ],
+        extensions: &["java", "kt", "kts", "cs", "swift", "scala"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: &[("\"\"\"", true), ("\"", false)],
+        char_literals: true,
+        keywords: &[
+            "abstract",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "extends",
+            "final",
+            "finally",
+            "for",
+            "fun",
+            "func",
+            "if",
+            "implements",
+            "import",
+            "interface",
+            "let",
+            "namespace",
+            "new",
+            "override",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "struct",
+            "super",
+            "switch",
+            "this",
+            "throw",
+            "throws",
+            "try",
+            "using",
+            "val",
+            "var",
+            "when",
+            "while",
+        ],
+        builtins: &[
+            "boolean", "byte", "char", "double", "false", "float", "int", "long", "nil", "null",
+            "short", "string", "true", "void",
+        ],
+        capitalized_types: true,
+    },
+    Language {
+        names: &["ruby", "rb"],
+        extensions: &["rb", "rake", "gemspec"],
+        line_comments: &["#"],
+        block_comment: None,
+        strings: &[("\"", true), ("'", true)],
+        char_literals: false,
+        keywords: &[
+            "alias", "and", "begin", "break", "case", "class", "def", "do", "else", "elsif", "end",
+            "ensure", "for", "if", "in", "module", "next", "not", "or", "redo", "rescue", "retry",
+            "return", "self", "super", "then", "unless", "until", "when", "while", "yield",
+        ],
+        builtins: &["true", "false", "nil"],
+        capitalized_types: true,
+    },
+    Language {
+        names: &["bash", "sh", "shell", "zsh", "console", "shellscript"],
+        extensions: &["sh", "bash", "zsh"],
+        line_comments: &["#"],
+        block_comment: None,
+        strings: &[("\"", true), ("'", true)],
+        char_literals: false,
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "select", "then", "until", "while",
+        ],
+        builtins: &[],
+        capitalized_types: false,
+    },
+    Language {
+        names: &["json", "jsonc", "json5"],
+        extensions: &["json", "jsonc", "json5", "jsonl"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        strings: C_LIKE_STRINGS,
+        char_literals: false,
+        keywords: &[],
+        builtins: &["true", "false", "null"],
+        capitalized_types: false,
+    },
+    Language {
+        names: &["toml", "yaml", "yml", "ini"],
+        extensions: &["toml", "yaml", "yml", "ini", "cfg"],
+        line_comments: &["#"],
+        block_comment: None,
+        strings: &[("\"\"\"", true), ("\"", false), ("'", false)],
+        char_literals: false,
+        keywords: &[],
+        builtins: &["true", "false", "null", "yes", "no"],
+        capitalized_types: false,
+    },
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -142,4 +731,76 @@ mod tests {
         assert!(!dimmed.contains(&"\"".to_string()));
         assert!(!dimmed.contains(&"'".to_string()));
     }
+
+    fn tokens<'a>(lang: &str, code: &'a str) -> Vec<(TokenKind, &'a str)> {
+        let lang = language_for_info_string(lang).expect("known language");
+        tokenize(lang, code)
+            .into_iter()
+            .filter(|(kind, _)| *kind != TokenKind::Plain)
+            .collect()
+    }
+
+    #[test]
+    fn finds_languages_by_info_string_and_extension() {
+        let name = |lang: Option<&Language>| lang.map(|lang| lang.names[0]);
+        assert_eq!(
+            name(language_for_info_string("py {.numberLines}")),
+            Some("python")
+        );
+        assert_eq!(name(language_for_info_string("TSX")), Some("javascript"));
+        assert_eq!(name(language_for_info_string("brainfuck")), None);
+        assert_eq!(
+            name(language_for_path(Path::new("src/main.rs"))),
+            Some("rust")
+        );
+        assert_eq!(name(language_for_path(Path::new("Makefile"))), None);
+    }
+
+    #[test]
+    fn tokenizes_comments_strings_and_literals() {
+        use TokenKind::*;
+        assert_eq!(
+            tokens(
+                "rust",
+                "fn f<'a>(c: char) -> &'a str { /* a\nb */ 'x'; \"s\\\"\" }"
+            ),
+            vec![
+                (Keyword, "fn"),
+                (Builtin, "char"),
+                (Builtin, "str"),
+                (Comment, "/* a\nb */"),
+                (String, "'x'"),
+                (String, "\"s\\\"\""),
+            ]
+        );
+        assert_eq!(
+            tokens(
+                "python",
+                "def f():\n    \"\"\"doc\n    more\"\"\"\n    return None  # done"
+            ),
+            vec![
+                (Keyword, "def"),
+                (String, "\"\"\"doc\n    more\"\"\""),
+                (Keyword, "return"),
+                (Builtin, "None"),
+                (Comment, "# done"),
+            ]
+        );
+        assert_eq!(
+            tokens("bash", "echo $# 0x1f 1.5 # note"),
+            vec![(Number, "0x1f"), (Number, "1.5"), (Comment, "# note")]
+        );
+    }
+
+    #[test]
+    fn splits_highlighted_code_into_lines() {
+        let lang = language_for_info_string("go").expect("go");
+        let lines = highlight_code_to_lines(lang, "/* one\ntwo */\nx := 1");
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|sp| sp.content.clone()).collect())
+            .collect();
+        assert_eq!(text, vec!["/* one", "two */", "x := 1"]);
+        assert!(lines[1].spans[0].style.add_modifier.contains(Modifier::DIM));
+    }
 }
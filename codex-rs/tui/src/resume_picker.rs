@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
@@ -5,6 +6,7 @@ use std::sync::Arc;
 
 use chrono::DateTime;
 use chrono::Utc;
+use codex_core::BranchSummary;
 use codex_core::ConversationItem;
 use codex_core::ConversationsPage;
 use codex_core::Cursor;
@@ -23,12 +25,17 @@ use tokio::sync::mpsc;
 use tokio_stream::StreamExt;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
+use crate::diff_browser::DiffFile;
+use crate::diff_browser::files_from_unified_diff;
+use crate::pager_overlay::Overlay;
 use crate::text_formatting::truncate_text;
 use crate::tui::FrameRequester;
 use crate::tui::Tui;
 use crate::tui::TuiEvent;
+use codex_protocol::mcp_protocol::ConversationId;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::ForkOrigin;
 use codex_protocol::protocol::InputMessageKind;
 use codex_protocol::protocol::USER_MESSAGE_BEGIN;
 
@@ -39,6 +46,11 @@ const LOAD_NEAR_THRESHOLD: usize = 5;
 pub enum ResumeSelection {
     StartFresh,
     Resume(PathBuf),
+    /// Mirror a session that is running in another TUI.
+    Attach {
+        session_id: String,
+        read_only: bool,
+    },
     Exit,
 }
 
@@ -52,17 +64,27 @@ struct PageLoadRequest {
 
 type PageLoader = Arc<dyn Fn(PageLoadRequest) + Send + Sync>;
 
+/// Loads the message a fork starts with, given its rollout and the user
+/// message it branched off at.
+type BranchLoader = Arc<dyn Fn(PathBuf, usize) + Send + Sync>;
+
 enum BackgroundEvent {
     PageLoaded {
         request_token: usize,
         search_token: Option<usize>,
         page: std::io::Result<ConversationsPage>,
     },
+    BranchLoaded {
+        path: PathBuf,
+        first_message: Option<String>,
+    },
 }
 
 /// Interactive session picker that lists recorded rollout files with simple
 /// search and pagination. Shows the first user input as the preview, relative
-/// time (e.g., "5 seconds ago"), and the absolute path.
+/// time (e.g., "5 seconds ago"), and the absolute path. Forked conversations
+/// are listed under the conversation they were forked from, where the user
+/// can switch between sibling branches and compare the changes they made.
 pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<ResumeSelection> {
     let alt = AltScreenGuard::enter(tui);
     let (bg_tx, bg_rx) = mpsc::unbounded_channel();
@@ -85,11 +107,28 @@ pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<Resum
         });
     });
 
+    let branch_tx = bg_tx.clone();
+    let branch_loader: BranchLoader = Arc::new(move |path: PathBuf, nth_user_message: usize| {
+        let tx = branch_tx.clone();
+        tokio::spawn(async move {
+            // A fork that can't be read keeps the preview of its parent.
+            let first_message = RolloutRecorder::summarize_branch(&path, nth_user_message)
+                .await
+                .ok()
+                .and_then(|summary| summary.first_message);
+            let _ = tx.send(BackgroundEvent::BranchLoaded {
+                path,
+                first_message,
+            });
+        });
+    });
+
     let mut state = PickerState::new(
         codex_home.to_path_buf(),
         alt.tui.frame_requester(),
         page_loader,
-    );
+    )
+    .with_branch_loader(branch_loader);
     state.load_initial_page().await?;
     state.request_frame();
 
@@ -99,6 +138,14 @@ pub async fn run_resume_picker(tui: &mut Tui, codex_home: &Path) -> Result<Resum
     loop {
         tokio::select! {
             Some(ev) = tui_events.next() => {
+                if let Some(overlay) = state.overlay.as_mut() {
+                    overlay.handle_event(alt.tui, ev)?;
+                    if overlay.is_done() {
+                        state.overlay = None;
+                        state.request_frame();
+                    }
+                    continue;
+                }
                 match ev {
                     TuiEvent::Key(key) => {
                         if matches!(key.kind, KeyEventKind::Release) {
@@ -162,7 +209,10 @@ struct PickerState {
     next_request_token: usize,
     next_search_token: usize,
     page_loader: PageLoader,
+    branch_loader: Option<BranchLoader>,
     view_rows: Option<usize>,
+    /// The comparison of branches, while it is open.
+    overlay: Option<Overlay>,
 }
 
 struct PaginationState {
@@ -219,6 +269,19 @@ struct Row {
     path: PathBuf,
     preview: String,
     ts: Option<DateTime<Utc>>,
+    id: Option<ConversationId>,
+    forked_from: Option<ForkOrigin>,
+    /// For forks, the message they start with once it is loaded; their
+    /// preview is the first message of the conversation they came from.
+    branch_message: Option<String>,
+    /// How many forks deep the row is listed.
+    depth: usize,
+}
+
+impl Row {
+    fn label(&self) -> &str {
+        self.branch_message.as_deref().unwrap_or(&self.preview)
+    }
 }
 
 impl PickerState {
@@ -242,10 +305,17 @@ impl PickerState {
             next_request_token: 0,
             next_search_token: 0,
             page_loader,
+            branch_loader: None,
             view_rows: None,
+            overlay: None,
         }
     }
 
+    fn with_branch_loader(mut self, branch_loader: BranchLoader) -> Self {
+        self.branch_loader = Some(branch_loader);
+        self
+    }
+
     fn request_frame(&self) {
         self.requester.schedule_frame();
     }
@@ -280,6 +350,20 @@ impl PickerState {
                 self.maybe_load_more_for_scroll();
                 self.request_frame();
             }
+            KeyCode::Left | KeyCode::Right => {
+                let group = self.branch_group();
+                if let Some(pos) = group.iter().position(|idx| *idx == self.selected) {
+                    let next = if key.code == KeyCode::Right {
+                        (pos + 1) % group.len()
+                    } else {
+                        (pos + group.len() - 1) % group.len()
+                    };
+                    self.selected = group[next];
+                    self.ensure_selected_visible();
+                    self.request_frame();
+                }
+            }
+            KeyCode::Tab => self.compare_branches().await,
             KeyCode::PageUp => {
                 let step = self.view_rows.unwrap_or(10).max(1);
                 if self.selected > 0 {
@@ -352,10 +436,89 @@ impl PickerState {
                 let completed_token = pending.search_token.or(search_token);
                 self.continue_search_if_token_matches(completed_token);
             }
+            BackgroundEvent::BranchLoaded {
+                path,
+                first_message,
+            } => {
+                let Some(message) = first_message
+                    .map(|message| strip_ide_context(message).trim().to_string())
+                    .filter(|message| !message.is_empty())
+                else {
+                    return Ok(());
+                };
+                if let Some(row) = self.all_rows.iter_mut().find(|row| row.path == path) {
+                    row.branch_message = Some(message);
+                    self.apply_filter();
+                }
+            }
         }
         Ok(())
     }
 
+    /// The selected row and its sibling branches, in display order: the
+    /// conversation they were forked from, then its forks. Empty when the
+    /// selected row has no siblings.
+    fn branch_group(&self) -> Vec<usize> {
+        let rows = &self.filtered_rows;
+        let Some(row) = rows.get(self.selected) else {
+            return Vec::new();
+        };
+        let base = row
+            .forked_from
+            .as_ref()
+            .and_then(|origin| {
+                rows.iter()
+                    .position(|candidate| candidate.id == Some(origin.conversation_id))
+            })
+            .unwrap_or(self.selected);
+        let Some(base_id) = rows.get(base).and_then(|row| row.id) else {
+            return Vec::new();
+        };
+        let forks: Vec<usize> = rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| {
+                row.forked_from
+                    .as_ref()
+                    .is_some_and(|origin| origin.conversation_id == base_id)
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        if forks.is_empty() {
+            return Vec::new();
+        }
+        std::iter::once(base).chain(forks).collect()
+    }
+
+    /// Opens the changes each branch of the selected row's group made since
+    /// the earliest point one of them branched off at.
+    async fn compare_branches(&mut self) {
+        let rows: Vec<&Row> = self
+            .branch_group()
+            .into_iter()
+            .filter_map(|idx| self.filtered_rows.get(idx))
+            .collect();
+        let Some(nth_user_message) = rows
+            .iter()
+            .skip(1)
+            .filter_map(|row| row.forked_from.as_ref())
+            .map(|origin| origin.nth_user_message)
+            .min()
+        else {
+            return;
+        };
+        let paths: Vec<PathBuf> = rows.iter().map(|row| row.path.clone()).collect();
+        let mut branches = Vec::new();
+        for path in paths {
+            branches.push(RolloutRecorder::summarize_branch(&path, nth_user_message).await);
+        }
+        self.overlay = Some(Overlay::new_diff(
+            branch_comparison_files(&branches),
+            "B R A N C H E S".to_string(),
+        ));
+        self.request_frame();
+    }
+
     fn reset_pagination(&mut self) {
         self.pagination.next_cursor = None;
         self.pagination.num_scanned_files = 0;
@@ -377,25 +540,41 @@ impl PickerState {
             self.pagination.reached_scan_cap = true;
         }
 
+        let selected_path = self
+            .filtered_rows
+            .get(self.selected)
+            .map(|row| row.path.clone());
         let rows = rows_from_items(page.items);
         for row in rows {
             if self.seen_paths.insert(row.path.clone()) {
+                if let (Some(loader), Some(origin)) = (&self.branch_loader, &row.forked_from) {
+                    loader(row.path.clone(), origin.nth_user_message);
+                }
                 self.all_rows.push(row);
             }
         }
 
         self.apply_filter();
+        // A page can add the parent of a listed fork above the selected row.
+        if let Some(path) = selected_path
+            && let Some(idx) = self.filtered_rows.iter().position(|row| row.path == path)
+        {
+            self.selected = idx;
+            self.ensure_selected_visible();
+        }
     }
 
     fn apply_filter(&mut self) {
         if self.query.is_empty() {
-            self.filtered_rows = self.all_rows.clone();
+            self.filtered_rows = tree_order(&self.all_rows);
         } else {
             let q = self.query.to_lowercase();
             self.filtered_rows = self
                 .all_rows
                 .iter()
-                .filter(|r| r.preview.to_lowercase().contains(&q))
+                .filter(|r| {
+                    r.preview.to_lowercase().contains(&q) || r.label().to_lowercase().contains(&q)
+                })
                 .cloned()
                 .collect();
         }
@@ -563,6 +742,113 @@ fn rows_from_items(items: Vec<ConversationItem>) -> Vec<Row> {
     items.into_iter().map(|item| head_to_row(&item)).collect()
 }
 
+/// `rows` with each fork listed under the conversation it was forked from.
+/// A tree of branches is listed where the first of its rows was, so the one
+/// with the most recent branch comes first.
+fn tree_order(rows: &[Row]) -> Vec<Row> {
+    let by_id: HashMap<ConversationId, usize> = rows
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, row)| row.id.map(|id| (id, idx)))
+        .collect();
+    let parent = |idx: usize| {
+        let origin = rows[idx].forked_from.as_ref()?;
+        by_id
+            .get(&origin.conversation_id)
+            .copied()
+            .filter(|parent| *parent != idx)
+    };
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); rows.len()];
+    for idx in 0..rows.len() {
+        if let Some(parent) = parent(idx) {
+            children[parent].push(idx);
+        }
+    }
+
+    let mut placed = vec![false; rows.len()];
+    let mut ordered = Vec::with_capacity(rows.len());
+    for idx in 0..rows.len() {
+        // Bounded, in case forks somehow form a cycle.
+        let mut root = idx;
+        for _ in 0..rows.len() {
+            match parent(root) {
+                Some(parent) => root = parent,
+                None => break,
+            }
+        }
+        push_subtree(rows, &children, root, 0, &mut placed, &mut ordered);
+        push_subtree(rows, &children, idx, 0, &mut placed, &mut ordered);
+    }
+    ordered
+}
+
+fn push_subtree(
+    rows: &[Row],
+    children: &[Vec<usize>],
+    idx: usize,
+    depth: usize,
+    placed: &mut [bool],
+    ordered: &mut Vec<Row>,
+) {
+    if placed[idx] {
+        return;
+    }
+    placed[idx] = true;
+    let mut row = rows[idx].clone();
+    row.depth = depth;
+    ordered.push(row);
+    for child in &children[idx] {
+        push_subtree(rows, children, *child, depth + 1, placed, ordered);
+    }
+}
+
+/// The changes of each branch for the diff browser: a heading per branch
+/// with the message it starts with, followed by the files it changed, named
+/// with the branch's number. A file changed in several turns gets the hunks
+/// of each turn, in order.
+fn branch_comparison_files(branches: &[std::io::Result<BranchSummary>]) -> Vec<DiffFile> {
+    let mut files = Vec::new();
+    for (idx, branch) in branches.iter().enumerate() {
+        let number = idx + 1;
+        let mut changed: Vec<DiffFile> = Vec::new();
+        let note = match branch {
+            Ok(summary) => {
+                for diff in &summary.turn_diffs {
+                    for file in files_from_unified_diff(diff) {
+                        match changed.iter_mut().find(|known| known.path == file.path) {
+                            Some(known) => known.hunks.extend(file.hunks),
+                            None => changed.push(file),
+                        }
+                    }
+                }
+                let message = summary
+                    .first_message
+                    .clone()
+                    .map(strip_ide_context)
+                    .unwrap_or_else(|| "(no message yet)".to_string());
+                let changes = match changed.len() {
+                    0 => "no file changes".to_string(),
+                    1 => "1 file changed".to_string(),
+                    n => format!("{n} files changed"),
+                };
+                format!("{} · {changes}", message.trim())
+            }
+            Err(err) => format!("Could not read this branch: {err}"),
+        };
+        files.push(DiffFile {
+            path: format!("Branch {number}"),
+            old_path: None,
+            hunks: Vec::new(),
+            note: Some(note),
+        });
+        files.extend(changed.into_iter().map(|mut file| {
+            file.path = format!("[{number}] {}", file.path);
+            file
+        }));
+    }
+    files
+}
+
 fn head_to_row(item: &ConversationItem) -> Row {
     let mut ts: Option<DateTime<Utc>> = None;
     if let Some(first) = item.head.first()
@@ -571,6 +857,11 @@ fn head_to_row(item: &ConversationItem) -> Row {
     {
         ts = Some(parsed.with_timezone(&Utc));
     }
+    // The first record is the session meta.
+    let meta_field = |name: &str| item.head.first().and_then(|first| first.get(name)).cloned();
+    let id = meta_field("id").and_then(|id| serde_json::from_value(id).ok());
+    let forked_from =
+        meta_field("forked_from").and_then(|origin| serde_json::from_value(origin).ok());
 
     let preview = preview_from_head(&item.head)
         .map(|s| s.trim().to_string())
@@ -581,6 +872,18 @@ fn head_to_row(item: &ConversationItem) -> Row {
         path: item.path.clone(),
         preview,
         ts,
+        id,
+        forked_from,
+        branch_message: None,
+        depth: 0,
+    }
+}
+
+/// `text` without the IDE context that may precede the user's message.
+fn strip_ide_context(text: String) -> String {
+    match text.find(USER_MESSAGE_BEGIN) {
+        Some(idx) => text[idx + USER_MESSAGE_BEGIN.len()..].trim().to_string(),
+        None => text,
     }
 }
 
@@ -599,14 +902,7 @@ fn preview_from_head(head: &[serde_json::Value]) -> Option<String> {
                                 InputMessageKind::Plain
                             ) =>
                         {
-                            // Strip ide context.
-                            let text = match text.find(USER_MESSAGE_BEGIN) {
-                                Some(idx) => {
-                                    text[idx + USER_MESSAGE_BEGIN.len()..].trim().to_string()
-                                }
-                                None => text,
-                            };
-                            Some(text)
+                            Some(strip_ide_context(text))
                         }
                         _ => None,
                     })
@@ -653,7 +949,7 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
         render_list(frame, list, state);
 
         // Hint line
-        let hint_line: Line = vec![
+        let mut hint_spans = vec![
             "Enter".bold(),
             " to resume ".into(),
             "• ".dim(),
@@ -665,8 +961,18 @@ fn draw_picker(tui: &mut Tui, state: &PickerState) -> std::io::Result<()> {
             "• ".dim(),
             "↑/↓".into(),
             " to browse".dim(),
-        ]
-        .into();
+        ];
+        if !state.branch_group().is_empty() {
+            hint_spans.extend([
+                " • ".dim(),
+                "←/→".into(),
+                " to switch branch ".dim(),
+                "• ".dim(),
+                "Tab".into(),
+                " to compare".dim(),
+            ]);
+        }
+        let hint_line: Line = hint_spans.into();
         frame.render_widget_ref(hint_line, hint);
     })
 }
@@ -696,10 +1002,14 @@ fn render_list(frame: &mut crate::custom_terminal::Frame, area: Rect, state: &Pi
             .map(human_time_ago)
             .unwrap_or_else(|| "".to_string())
             .dim();
+        let indent = match row.depth {
+            0 => String::new(),
+            depth => format!("{}└ ", "  ".repeat(depth - 1)),
+        };
         let max_cols = area.width.saturating_sub(6) as usize;
-        let preview = truncate_text(&row.preview, max_cols);
+        let preview = truncate_text(row.label(), max_cols.saturating_sub(indent.chars().count()));
 
-        let line: Line = vec![marker, ts, "  ".into(), preview.into()].into();
+        let line: Line = vec![marker, ts, "  ".into(), indent.dim(), preview.into()].into();
         let rect = Rect::new(area.x, y, area.width, 1);
         frame.render_widget_ref(line, rect);
         y = y.saturating_add(1);
@@ -740,7 +1050,7 @@ fn render_empty_state_line(state: &PickerState) -> Line<'static> {
     vec!["No sessions yet".italic().dim()].into()
 }
 
-fn human_time_ago(ts: DateTime<Utc>) -> String {
+pub(crate) fn human_time_ago(ts: DateTime<Utc>) -> String {
     let now = Utc::now();
     let delta = now - ts;
     let secs = delta.num_seconds();
@@ -807,6 +1117,23 @@ mod tests {
         }
     }
 
+    fn make_conversation(
+        path: &str,
+        id: &str,
+        forked_from: Option<(&str, usize)>,
+        preview: &str,
+    ) -> ConversationItem {
+        let mut item = make_item(path, "2025-01-01T00:00:00Z", preview);
+        item.head[0]["id"] = json!(id);
+        if let Some((parent, nth_user_message)) = forked_from {
+            item.head[0]["forked_from"] = json!({
+                "conversation_id": parent,
+                "nth_user_message": nth_user_message,
+            });
+        }
+        item
+    }
+
     fn cursor_from_str(repr: &str) -> Cursor {
         serde_json::from_str::<Cursor>(&format!("\"{repr}\""))
             .expect("cursor format should deserialize")
@@ -857,6 +1184,109 @@ mod tests {
         assert_eq!(preview.as_deref(), Some("real question"));
     }
 
+    #[test]
+    fn forks_are_listed_under_their_parent_and_cycle_between_siblings() {
+        const BASE: &str = "00000000-0000-0000-0000-00000000000a";
+        const FIRST_FORK: &str = "00000000-0000-0000-0000-00000000000b";
+        let loader: PageLoader = Arc::new(|_| {});
+        let mut state =
+            PickerState::new(PathBuf::from("/tmp"), FrameRequester::test_dummy(), loader);
+        state.reset_pagination();
+        // Newest first, as listed by the backend.
+        state.ingest_page(page(
+            vec![
+                make_conversation("/tmp/fork-1.jsonl", FIRST_FORK, Some((BASE, 1)), "plan"),
+                make_conversation(
+                    "/tmp/other.jsonl",
+                    "00000000-0000-0000-0000-00000000000c",
+                    None,
+                    "other",
+                ),
+                make_conversation(
+                    "/tmp/fork-2.jsonl",
+                    "00000000-0000-0000-0000-00000000000d",
+                    Some((BASE, 1)),
+                    "plan",
+                ),
+                make_conversation(
+                    "/tmp/nested.jsonl",
+                    "00000000-0000-0000-0000-00000000000e",
+                    Some((FIRST_FORK, 2)),
+                    "plan",
+                ),
+                make_conversation("/tmp/base.jsonl", BASE, None, "plan"),
+            ],
+            None,
+            5,
+            false,
+        ));
+
+        let listed: Vec<(&str, usize)> = state
+            .filtered_rows
+            .iter()
+            .map(|row| (row.path.to_str().unwrap_or_default(), row.depth))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("/tmp/base.jsonl", 0),
+                ("/tmp/fork-1.jsonl", 1),
+                ("/tmp/nested.jsonl", 2),
+                ("/tmp/fork-2.jsonl", 1),
+                ("/tmp/other.jsonl", 0),
+            ]
+        );
+
+        // Siblings are the base conversation and its forks.
+        state.selected = 1;
+        let mut visited = Vec::new();
+        for code in [KeyCode::Right, KeyCode::Right, KeyCode::Left] {
+            block_on_future(async {
+                state
+                    .handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+                    .await
+                    .unwrap();
+            });
+            visited.push(state.selected);
+        }
+        assert_eq!(visited, vec![3, 0, 3]);
+
+        // Rows without siblings stay put.
+        state.selected = 4;
+        assert!(state.branch_group().is_empty());
+    }
+
+    #[test]
+    fn branch_comparison_lists_the_files_of_each_branch() {
+        let branches = vec![
+            Ok(BranchSummary {
+                first_message: Some("use a cache".to_string()),
+                turn_diffs: vec![
+                    "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n-a\n+b\n".to_string(),
+                    "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -9 +9 @@\n-c\n+d\n".to_string(),
+                ],
+            }),
+            Ok(BranchSummary {
+                first_message: None,
+                turn_diffs: Vec::new(),
+            }),
+        ];
+
+        let files = branch_comparison_files(&branches);
+        let listed: Vec<(&str, usize, Option<&str>)> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file.hunks.len(), file.note.as_deref()))
+            .collect();
+        assert_eq!(
+            listed,
+            vec![
+                ("Branch 1", 0, Some("use a cache · 1 file changed")),
+                ("[1] src/lib.rs", 2, None),
+                ("Branch 2", 0, Some("(no message yet) · no file changes")),
+            ]
+        );
+    }
+
     #[test]
     fn rows_from_items_preserves_backend_order() {
         // Construct two items with different timestamps and real user text.
@@ -21,6 +21,7 @@ pub enum SlashCommand {
     Undo,
     Diff,
     Mention,
+    Edit,
     Status,
     Mcp,
     Logout,
@@ -41,6 +42,7 @@ impl SlashCommand {
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
             SlashCommand::Mention => "mention a file",
+            SlashCommand::Edit => "write the message in $VISUAL or $EDITOR",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
@@ -70,6 +72,7 @@ impl SlashCommand {
             | SlashCommand::Logout => false,
             SlashCommand::Diff
             | SlashCommand::Mention
+            | SlashCommand::Edit
             | SlashCommand::Status
             | SlashCommand::Mcp
             | SlashCommand::Quit => true,
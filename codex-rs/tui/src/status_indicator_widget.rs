@@ -4,17 +4,19 @@
 use std::time::Duration;
 use std::time::Instant;
 
+use codex_core::config_keymap::KeymapAction;
 use codex_core::protocol::Op;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::WidgetRef;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
-use crate::key_hint;
+use crate::keymap::keymap;
 use crate::shimmer::shimmer_spans;
 use crate::tui::FrameRequester;
 use crate::ui_consts::LIVE_PREFIX_COLS;
@@ -189,8 +191,20 @@ impl WidgetRef for StatusIndicatorWidget {
             }
         }
         if !self.queued_messages.is_empty() {
-            let shortcut = key_hint::alt("↑");
-            lines.push(Line::from(vec!["   ".into(), shortcut, " edit".into()]).dim());
+            let keymap = keymap();
+            let mut hint: Vec<Span<'static>> = vec!["   ".into()];
+            if let Some(shortcut) = keymap.hint(KeymapAction::EditQueuedMessage) {
+                hint.extend([shortcut, " edit".into()]);
+            }
+            if let Some(shortcut) = keymap.hint(KeymapAction::SteerTurn) {
+                if hint.len() > 1 {
+                    hint.push("   ".into());
+                }
+                hint.extend([shortcut, " send now".into()]);
+            }
+            if hint.len() > 1 {
+                lines.push(Line::from(hint).dim());
+            }
         }
 
         let paragraph = Paragraph::new(lines);
@@ -13,19 +13,34 @@ pub fn user_message_style(terminal_bg: Option<(u8, u8, u8)>) -> Style {
     }
 }
 
-#[allow(clippy::disallowed_methods)]
 pub fn user_message_bg(terminal_bg: (u8, u8, u8)) -> Color {
     let top = if is_light(terminal_bg) {
         (0, 0, 0)
     } else {
         (255, 255, 255)
     };
-    let bottom = terminal_bg;
+    closest_supported_color(blend(top, terminal_bg, 0.1))
+}
+
+/// Background for added or removed diff lines: the terminal background tinted
+/// towards green or red, or `None` when the terminal can't show it.
+pub fn diff_line_bg(terminal_bg: (u8, u8, u8), added: bool) -> Option<Color> {
+    let tint = if added { (40, 170, 60) } else { (200, 50, 50) };
+    let alpha = if is_light(terminal_bg) { 0.18 } else { 0.25 };
+    match closest_supported_color(blend(tint, terminal_bg, alpha)) {
+        Color::Reset => None,
+        color => Some(color),
+    }
+}
+
+/// `target` in the terminal's color space: itself with true color, the
+/// nearest palette entry with 256 colors, and `Color::Reset` otherwise.
+#[allow(clippy::disallowed_methods)]
+fn closest_supported_color(target: (u8, u8, u8)) -> Color {
     let Some(color_level) = supports_color::on_cached(supports_color::Stream::Stdout) else {
         return Color::default();
     };
 
-    let target = blend(top, bottom, 0.1);
     if color_level.has_16m {
         let (r, g, b) = target;
         Color::Rgb(r, g, b)
@@ -4,12 +4,16 @@ use std::io::Stdout;
 use std::io::stdout;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 #[cfg(unix)]
 use std::sync::atomic::AtomicU8;
 #[cfg(unix)]
 use std::sync::atomic::AtomicU16;
 use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Waker;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -22,6 +26,7 @@ use crossterm::event::DisableFocusChange;
 use crossterm::event::EnableBracketedPaste;
 use crossterm::event::EnableFocusChange;
 use crossterm::event::Event;
+use crossterm::event::EventStream;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyboardEnhancementFlags;
 use crossterm::event::PopKeyboardEnhancementFlags;
@@ -166,6 +171,41 @@ pub struct Tui {
     // True when terminal/tab is focused; updated internally from crossterm events
     terminal_focused: Arc<AtomicBool>,
     enhanced_keys_supported: bool,
+    crossterm_events: Arc<Mutex<CrosstermEvents>>,
+}
+
+/// Terminal events from crossterm, which can be paused while a foreground
+/// program owns the terminal. As long as crossterm's stream exists, a thread
+/// of its own reads the terminal and would take the program's input, so
+/// pausing drops the stream.
+#[derive(Default)]
+struct CrosstermEvents {
+    paused: bool,
+    stream: Option<EventStream>,
+    /// The task waiting for events while paused.
+    waker: Option<Waker>,
+}
+
+impl CrosstermEvents {
+    fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Event>>> {
+        if self.paused {
+            self.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        Pin::new(self.stream.get_or_insert_with(EventStream::new)).poll_next(cx)
+    }
+
+    fn pause(&mut self) {
+        self.paused = true;
+        self.stream = None;
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
 }
 
 #[cfg(unix)]
@@ -293,6 +333,7 @@ impl Tui {
             alt_screen_active: Arc::new(AtomicBool::new(false)),
             terminal_focused: Arc::new(AtomicBool::new(true)),
             enhanced_keys_supported,
+            crossterm_events: Arc::default(),
         }
     }
 
@@ -307,8 +348,7 @@ impl Tui {
     }
 
     pub fn event_stream(&self) -> Pin<Box<dyn Stream<Item = TuiEvent> + Send + 'static>> {
-        use tokio_stream::StreamExt;
-        let mut crossterm_events = crossterm::event::EventStream::new();
+        let crossterm_events = self.crossterm_events.clone();
         let mut draw_rx = self.draw_tx.subscribe();
         #[cfg(unix)]
         let resume_pending = self.resume_pending.clone();
@@ -320,7 +360,10 @@ impl Tui {
         let event_stream = async_stream::stream! {
             loop {
                 select! {
-                    Some(Ok(event)) = crossterm_events.next() => {
+                    Some(Ok(event)) = std::future::poll_fn(|cx| match crossterm_events.lock() {
+                        Ok(mut events) => events.poll_next(cx),
+                        Err(_) => Poll::Ready(None),
+                    }) => {
                         match event {
                             crossterm::event::Event::Key(key_event) => {
                                 #[cfg(unix)]
@@ -447,6 +490,38 @@ impl Tui {
         Ok(())
     }
 
+    /// Hands the terminal to a foreground program, such as an external
+    /// editor, while `f` runs, then takes it back and repaints the viewport.
+    /// Terminal events are paused meanwhile, so that the program gets its
+    /// input.
+    pub async fn with_restored<F: Future>(&mut self, f: impl FnOnce() -> F) -> Result<F::Output> {
+        // Start the program where the viewport is so that anything it leaves
+        // on the screen is covered by the next draw.
+        let top = self.terminal.viewport_area.top();
+        let _ = execute!(stdout(), crossterm::cursor::MoveTo(0, top));
+        self.set_events_paused(true);
+        if let Err(err) = restore() {
+            self.set_events_paused(false);
+            return Err(err);
+        }
+        let result = f().await;
+        self.set_events_paused(false);
+        set_modes()?;
+        self.terminal.clear()?;
+        self.frame_requester().schedule_frame();
+        Ok(result)
+    }
+
+    fn set_events_paused(&self, paused: bool) {
+        if let Ok(mut events) = self.crossterm_events.lock() {
+            if paused {
+                events.pause();
+            } else {
+                events.resume();
+            }
+        }
+    }
+
     /// Enter alternate screen and expand the viewport to full terminal size, saving the current
     /// inline viewport for restoration when leaving.
     pub fn enter_alt_screen(&mut self) -> Result<()> {